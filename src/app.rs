@@ -1,11 +1,13 @@
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashMap, HashSet},
     io,
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
+use chrono::NaiveDate;
 use crossterm::{
-    event::{self, Event},
+    event::{self, DisableBracketedPaste, EnableBracketedPaste, Event},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
@@ -13,26 +15,91 @@ use ratatui::{Terminal, backend::CrosstermBackend, layout::Rect};
 
 use crate::{
     constants::{BLINK_SETTINGS, FACE_SETTINGS, TIME_SETTINGS},
-    domain::{CategoryId, ReportPeriod, TimeTracker},
+    domain::{
+        self, CategoryId, CategoryLogEntry, CategorySuggestionIndex, KarmaReportSummary,
+        KeptOverlaps, PeriodLabelStyle, ReportPeriod, Session, SessionOverlap, TimeTracker,
+    },
+    duration::parse_duration,
+    idle,
     sand::SandEngine,
     storage,
 };
 
+mod active_session;
+mod category_groups;
 mod category_modal_view;
 mod category_state;
 mod event_handlers;
+mod focus_contract;
+mod goal_line;
+mod help_view;
+mod history_view;
+mod idle_prompt;
+mod migration_view;
+mod modal_input;
+mod plan_view;
+mod pomodoro;
 mod render_views;
 mod report_modal_view;
 mod report_state;
+mod save_queue;
+mod screensaver;
+mod settings_view;
+mod split_view;
+mod stats_view;
+mod strata_view;
+mod tag_editor;
+mod theme;
 mod time_format;
+mod timeline_view;
+mod totals_strip;
 mod ui_helpers;
+mod undo;
+mod view_cache;
 mod view_style;
+mod zen;
+
+pub use pomodoro::PomodoroSettings;
+
+use focus_contract::ContractPrompt;
+use save_queue::SaveQueue;
+use strata_view::StrataLayer;
+use theme::Theme;
+use view_cache::ViewCache;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum UiMode {
     Main,
     CategoryModal,
     KarmaModal,
+    HelpModal,
+    HistoryModal,
+    MigrationModal,
+    StatsView,
+    SettingsModal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Visualization {
+    Sand,
+    Strata,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BulkAction {
+    Delete,
+    Move(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ReportCacheKey {
+    revision: u64,
+    period: ReportPeriod,
+    range: Option<(NaiveDate, NaiveDate)>,
+    day: NaiveDate,
+    live: Option<(CategoryId, usize)>,
+    /// Pairs are only ever added, so the count changes with the set.
+    kept_overlaps: usize,
 }
 
 struct App {
@@ -42,20 +109,111 @@ struct App {
     ui_mode: UiMode,
     selected_index: usize,
     new_category_name: String,
+    renaming_category: Option<String>,
+    editing_color: Option<String>,
+    category_filter: Option<String>,
+    confirm_delete_category: bool,
     color_index: usize,
     modal_description: String,
+    /// Char position typing goes to once the field has been edited.
+    modal_text_cursor: Option<usize>,
     category_tags: storage::CategoryTagsState,
+    category_groups: storage::CategoryGroupsState,
+    modal_group: Option<String>,
+    editing_group: Option<String>,
+    tag_editor: Option<tag_editor::TagEditor>,
     modal_tag_index: Option<usize>,
     report_selected_index: usize,
     report_period: ReportPeriod,
+    /// Custom dates that replace `report_period` while set.
+    report_range: Option<report_state::ReportRange>,
+    period_label_style: PeriodLabelStyle,
     report_logs_category_id: Option<CategoryId>,
     report_log_selected_index: usize,
     report_show_help: bool,
+    report_chart: bool,
+    report_overlap: Option<SessionOverlap>,
+    kept_overlaps: KeptOverlaps,
+    report_marked: HashSet<usize>,
+    report_mark_anchor: Option<usize>,
+    report_bulk: Option<BulkAction>,
+    stopwatch_mode: bool,
+    pomodoro: Option<pomodoro::Pomodoro>,
+    grain_log_path: Option<PathBuf>,
+    usage_events: Option<Vec<&'static str>>,
+    save_queue: SaveQueue,
+    pending_trash: Vec<Session>,
+    pending_resize: Option<(u16, u16, Instant)>,
+    idle_paused: bool,
+    /// System idle time that stops the running session, from `idle_stop`.
+    idle_stop_seconds: Option<u64>,
+    hooks: storage::HooksConfig,
+    keys: storage::KeyBindings,
+    vim_pending: Option<char>,
+    vim_insert: bool,
+    confirm_reset_idle: bool,
+    recent_categories: Vec<CategoryId>,
+    focus_contract: storage::FocusContractState,
+    contract_prompt: Option<ContractPrompt>,
+    plan: storage::PlanState,
+    plan_selected: Option<usize>,
+    undo_stack: Vec<undo::UndoAction>,
+    history_selected: usize,
+    history_filter: Option<CategoryId>,
+    history_page_rows: usize,
+    history_edit: Option<history_view::SessionEdit>,
+    history_confirm_delete: Option<usize>,
+    migration: Option<migration_view::Migration>,
+    status_message: Option<(String, Instant)>,
+    prompt_focus_rating: bool,
+    focus_prompt_session: Option<usize>,
+    suggestion_index: CategorySuggestionIndex,
+    visualization: Visualization,
+    daily_goal_seconds: Option<usize>,
+    goal_reached: Option<bool>,
+    goal_sparkle_frames: u16,
+    strata_layers_cache: ViewCache<(u64, i64), Vec<StrataLayer>>,
+    show_timeline: bool,
+    show_totals: bool,
+    zen: bool,
+    /// Keeps the karma report beside the sand on wide terminals.
+    split_pane: bool,
+    sand_day: NaiveDate,
+    themes: Vec<Theme>,
+    theme_index: usize,
+    timeline_cache: ViewCache<(u64, NaiveDate), Vec<domain::TimelineEntry>>,
+    category_budgets: HashMap<CategoryId, usize>,
+    carry_over: Option<domain::CarryOverRules>,
+    goal_ledger: Vec<domain::GoalLedgerEntry>,
+    goal_ledger_week: Option<NaiveDate>,
+    budget_notified: HashSet<(NaiveDate, CategoryId)>,
+    notifications: storage::NotificationsConfig,
+    none_reminded: bool,
+    idle_prompt_after: Option<usize>,
+    last_key_at: Instant,
+    idle_prompt: Option<idle_prompt::IdlePrompt>,
+    settings_selected: usize,
+    physics_ms: u64,
+    show_seconds: bool,
+    autosave_seconds: usize,
+    /// Settings changed in the TUI, waiting to be written to `config.json`.
+    config_edits: BTreeMap<String, String>,
+    /// What `active_session.json` holds as far as the TUI knows.
+    shared_session: Option<crate::cli::ActiveSession>,
+    /// The daemon socket, answered by the TUI while it holds the instance lock.
+    cli_listener: Option<crate::daemon::Listener>,
+    /// Set for `strata pomodoro --tui`, which gives the instance lock back
+    /// once started: sessions from this index on are appended under a short
+    /// lock instead of rewriting `time_log.csv`.
+    append_sessions_from: Option<usize>,
+    report_rows_cache: ViewCache<ReportCacheKey, KarmaReportSummary>,
+    report_logs_cache: ViewCache<(ReportCacheKey, CategoryId), Vec<CategoryLogEntry>>,
+    karma_header_cache: ViewCache<(u64, NaiveDate, Option<CategoryId>), isize>,
     render_needed: bool,
 }
 
 impl App {
-    fn new(width: u16, height: u16) -> Self {
+    fn new(width: u16, height: u16, adopt_shared_session: bool) -> Self {
         let mut tracker = TimeTracker::new();
         let data_dir = storage::get_data_dir();
         let categories_path = data_dir.join("categories.csv");
@@ -71,38 +229,176 @@ impl App {
             loaded_sessions.next_session_id,
         );
 
+        let suggestion_index = CategorySuggestionIndex::from_sessions(&tracker.sessions);
+
         let mut category_tags = storage::load_category_tags(&storage::get_category_tags_path());
         let valid_category_ids: HashSet<u64> = tracker
-            .categories_for_storage()
-            .into_iter()
+            .categories_ordered()
             .map(|category| category.id.0)
             .collect();
         category_tags
             .tags_by_category
             .retain(|category_id, _| valid_category_ids.contains(category_id));
 
+        let config = storage::load_config(&storage::get_config_path());
+        domain::set_week_start(storage::week_start_setting(&config));
+        domain::set_period_label_style(storage::period_label_style());
+        tracker.set_switch_grace(Duration::from_secs(
+            config
+                .switch_grace
+                .as_deref()
+                .and_then(parse_duration)
+                .unwrap_or(0) as u64,
+        ));
+
+        let carry_over = storage::carry_over_rules(&config);
+        let themes = Theme::load_all(&config);
+        let theme_index = theme::configured_theme_index(&themes, &config);
+        let physics_ms = storage::sand_speed_setting(&config);
+        let show_seconds = storage::time_format_seconds(&config);
+        let autosave_seconds = storage::autosave_seconds(&config);
+
+        let category_budgets =
+            storage::load_category_budgets(&storage::get_category_budgets_path())
+                .daily_seconds_by_category
+                .into_iter()
+                .map(|(category_id, seconds)| (CategoryId::new(category_id), seconds))
+                .collect();
+
         let mut app = Self {
             time_tracker: tracker,
-            sand_engine: SandEngine::new(width, height),
+            sand_engine: SandEngine::new(width.saturating_sub(2), height.saturating_sub(2))
+                .with_spawn_strategy(storage::spawn_strategy_setting(&config)),
             blink_state: 0,
             ui_mode: UiMode::Main,
             selected_index: 0,
             new_category_name: String::new(),
+            renaming_category: None,
+            editing_color: None,
+            category_filter: None,
+            confirm_delete_category: false,
             color_index: 0,
             modal_description: String::new(),
+            modal_text_cursor: None,
             category_tags,
+            category_groups: storage::load_category_groups(&storage::get_category_groups_path()),
+            modal_group: None,
+            editing_group: None,
+            tag_editor: None,
             modal_tag_index: None,
             report_selected_index: 0,
             report_period: ReportPeriod::Today,
+            report_range: None,
+            period_label_style: storage::period_label_style(),
             report_logs_category_id: None,
             report_log_selected_index: 0,
             report_show_help: false,
+            report_chart: false,
+            report_overlap: None,
+            kept_overlaps: storage::load_kept_overlaps(&storage::get_kept_overlaps_path()),
+            report_marked: HashSet::new(),
+            report_mark_anchor: None,
+            report_bulk: None,
+            stopwatch_mode: false,
+            pomodoro: None,
+            grain_log_path: storage::grain_log_enabled().then(storage::get_grain_log_path),
+            usage_events: config.metrics.then(|| vec!["tui"]),
+            save_queue: SaveQueue::new(),
+            pending_trash: Vec::new(),
+            pending_resize: None,
+            idle_paused: false,
+            idle_stop_seconds: storage::idle_stop_seconds(&config),
+            hooks: config.hooks,
+            keys: config.keys,
+            vim_pending: None,
+            vim_insert: false,
+            confirm_reset_idle: false,
+            recent_categories: Vec::new(),
+            focus_contract: storage::load_focus_contract(&storage::get_focus_contract_path()),
+            contract_prompt: None,
+            plan: storage::PlanState::default(),
+            plan_selected: None,
+            undo_stack: Vec::new(),
+            history_selected: 0,
+            history_filter: None,
+            history_page_rows: 0,
+            history_edit: None,
+            history_confirm_delete: None,
+            migration: None,
+            status_message: None,
+            prompt_focus_rating: config.prompt_focus_rating,
+            focus_prompt_session: None,
+            suggestion_index,
+            visualization: Visualization::Sand,
+            daily_goal_seconds: config
+                .daily_goal
+                .as_deref()
+                .and_then(parse_duration)
+                .filter(|seconds| *seconds > 0),
+            goal_reached: None,
+            goal_sparkle_frames: 0,
+            strata_layers_cache: ViewCache::new(),
+            show_timeline: false,
+            show_totals: false,
+            zen: false,
+            split_pane: false,
+            sand_day: domain::operational_day_key_now(),
+            themes,
+            theme_index,
+            timeline_cache: ViewCache::new(),
+            category_budgets,
+            carry_over,
+            goal_ledger: Vec::new(),
+            goal_ledger_week: None,
+            budget_notified: HashSet::new(),
+            notifications: config.notifications,
+            none_reminded: false,
+            idle_prompt_after: config
+                .idle_prompt
+                .as_deref()
+                .and_then(parse_duration)
+                .filter(|seconds| *seconds > 0),
+            last_key_at: Instant::now(),
+            idle_prompt: None,
+            settings_selected: 0,
+            physics_ms,
+            show_seconds,
+            autosave_seconds,
+            config_edits: BTreeMap::new(),
+            shared_session: None,
+            cli_listener: None,
+            append_sessions_from: None,
+            report_rows_cache: ViewCache::new(),
+            report_logs_cache: ViewCache::new(),
+            karma_header_cache: ViewCache::new(),
             render_needed: true,
         };
 
+        if let Ok(cwd) = std::env::current_dir()
+            && let Some(stray) = storage::stray_local_sessions(&data_dir, &cwd)
+        {
+            app.show_status(format!(
+                "{} is not used; data is in {}",
+                stray.display(),
+                data_dir.display()
+            ));
+        }
+        if app.idle_stop_seconds.is_some()
+            && let Err(reason) = idle::system_idle_seconds()
+        {
+            app.show_status(format!(
+                "idle_stop is set but idle time is unknown: {}",
+                reason
+            ));
+        }
         app.persist_category_tags();
+        app.restore_focus_contract();
+        app.reload_plan();
+        app.sync_goal_ledger();
 
-        app.time_tracker.start_session();
+        if !(adopt_shared_session && app.adopt_shared_session()) {
+            app.start_session();
+        }
         if app.time_tracker.active_category_index() == Some(0) {
             app.blink_state = app.next_blink_interval();
         }
@@ -111,10 +407,14 @@ impl App {
     }
 
     fn open_modal(&mut self) {
+        self.record_usage("tui categories");
         self.ui_mode = UiMode::CategoryModal;
         self.selected_index = self.time_tracker.active_category_index().unwrap_or(0);
+        self.reveal_modal_selection();
         self.new_category_name = String::new();
         self.color_index = 0;
+        self.vim_insert = false;
+        self.sync_goal_ledger();
         self.sync_modal_description_from_selection();
         self.render_needed = true;
     }
@@ -122,17 +422,42 @@ impl App {
     fn close_modal(&mut self) {
         self.ui_mode = UiMode::Main;
         self.modal_description = String::new();
+        self.modal_text_cursor = None;
         self.modal_tag_index = None;
+        self.renaming_category = None;
+        self.editing_color = None;
+        self.editing_group = None;
+        self.tag_editor = None;
+        self.modal_group = None;
+        self.category_filter = None;
+        self.confirm_delete_category = false;
         self.render_needed = true;
     }
 
+    fn record_usage(&mut self, event: &'static str) {
+        if let Some(events) = self.usage_events.as_mut() {
+            events.push(event);
+        }
+    }
+
+    fn flush_usage(&mut self) -> Result<(), String> {
+        match self.usage_events.take() {
+            Some(events) => storage::record_usage(events),
+            None => Ok(()),
+        }
+    }
+
     fn open_report_modal(&mut self) {
+        self.record_usage("tui report");
         self.ui_mode = UiMode::KarmaModal;
         self.report_selected_index = 0;
         self.report_period = ReportPeriod::Today;
+        self.report_range = None;
         self.report_logs_category_id = None;
         self.report_log_selected_index = 0;
         self.report_show_help = false;
+        self.report_chart = false;
+        self.report_overlap = None;
         self.render_needed = true;
     }
 
@@ -141,9 +466,31 @@ impl App {
         self.report_logs_category_id = None;
         self.report_log_selected_index = 0;
         self.report_show_help = false;
+        self.report_overlap = None;
+        self.clear_report_marks();
         self.render_needed = true;
     }
 
+    fn queue_resize(&mut self, width: u16, height: u16) {
+        self.pending_resize = Some((width, height, Instant::now()));
+    }
+
+    fn apply_pending_resize(&mut self) -> bool {
+        let Some((width, height, queued_at)) = self.pending_resize else {
+            return false;
+        };
+        if queued_at.elapsed() < Duration::from_millis(TIME_SETTINGS.resize_debounce_ms) {
+            return false;
+        }
+
+        self.pending_resize = None;
+        self.sand_engine
+            .resize(self.sand_cols(width), self.sand_rows(height));
+        self.refill_hourglass();
+        self.render_needed = true;
+        true
+    }
+
     fn in_category_modal(&self) -> bool {
         matches!(self.ui_mode, UiMode::CategoryModal)
     }
@@ -152,6 +499,26 @@ impl App {
         matches!(self.ui_mode, UiMode::KarmaModal)
     }
 
+    fn in_help_modal(&self) -> bool {
+        matches!(self.ui_mode, UiMode::HelpModal)
+    }
+
+    fn in_history_modal(&self) -> bool {
+        matches!(self.ui_mode, UiMode::HistoryModal)
+    }
+
+    fn in_migration_modal(&self) -> bool {
+        matches!(self.ui_mode, UiMode::MigrationModal)
+    }
+
+    fn in_stats_view(&self) -> bool {
+        matches!(self.ui_mode, UiMode::StatsView)
+    }
+
+    fn in_settings_modal(&self) -> bool {
+        matches!(self.ui_mode, UiMode::SettingsModal)
+    }
+
     fn modal_rect(&self, terminal_size: Rect) -> Rect {
         self.modal_rect_ratio(terminal_size, 1, 3)
     }
@@ -181,7 +548,7 @@ impl App {
         let compact = self.modal_rect(terminal_size);
         let inner_width = compact.width.saturating_sub(2) as usize;
         let inner_height = compact.height.saturating_sub(2);
-        let footer_height = if self.report_show_help { 1 } else { 0 };
+        let footer_height = if self.report_footer_visible() { 1 } else { 0 };
         let visible_rows = inner_height.saturating_sub(footer_height) as usize;
 
         let breathing_room = 2usize;
@@ -199,8 +566,8 @@ impl App {
     fn get_idle_face(&self) -> String {
         let idle_seconds = self
             .time_tracker
-            .current_session_start
-            .map_or(0, |s| s.elapsed().as_secs() as usize);
+            .session_elapsed()
+            .map_or(0, |elapsed| elapsed.as_secs() as usize);
 
         if self.blink_state < 0 {
             "(-_-)".to_string()
@@ -244,43 +611,102 @@ impl App {
     }
 }
 
+enum LaunchMode {
+    Normal,
+    Stopwatch,
+    Pomodoro(PomodoroSettings),
+}
+
 pub fn run_ui() -> Result<(), io::Error> {
+    run_terminal(LaunchMode::Normal)
+}
+
+pub fn run_stopwatch_ui() -> Result<(), io::Error> {
+    run_terminal(LaunchMode::Stopwatch)
+}
+
+pub fn run_pomodoro_ui(settings: PomodoroSettings) -> Result<(), io::Error> {
+    run_terminal(LaunchMode::Pomodoro(settings))
+}
+
+pub fn run_screensaver_ui() -> Result<(), io::Error> {
+    screensaver::run()
+}
+
+fn run_terminal(mode: LaunchMode) -> Result<(), io::Error> {
+    let mut lock = Some(
+        storage::acquire_instance_lock(&storage::get_instance_lock_path())
+            .map_err(io::Error::other)?,
+    );
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
 
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     let size = terminal.size()?;
-    let mut app = App::new(size.width, size.height);
-    app.restore_sand_state();
+    let pomodoro = matches!(mode, LaunchMode::Pomodoro(_));
+    let mut app = App::new(size.width, size.height, !pomodoro);
+    match mode {
+        LaunchMode::Normal => {
+            app.restore_sand_state();
+            app.check_migration();
+        }
+        LaunchMode::Stopwatch => app.enter_stopwatch_mode(),
+        LaunchMode::Pomodoro(settings) => app.enter_pomodoro_mode(settings),
+    }
+    if pomodoro {
+        // A pomodoro can run for an hour; it only adds its intervals, so it
+        // lets other writers in between them.
+        app.persist_sessions();
+        app.flush_saves(true);
+        app.append_sessions_from = Some(app.time_tracker.sessions.len());
+        drop(lock.take());
+    } else {
+        app.cli_listener = crate::daemon::listen().ok();
+    }
 
-    let physics_rate = Duration::from_millis(TIME_SETTINGS.physics_ms);
     let tick_rate = Duration::from_millis(TIME_SETTINGS.tick_ms);
     let render_rate = Duration::from_millis(1000 / TIME_SETTINGS.target_fps);
-    let save_rate = Duration::from_secs(60);
+    let idle_check_rate = Duration::from_secs(30);
     let mut last_spawn = Instant::now();
     let mut last_physics = Instant::now();
     let mut last_render = Instant::now();
     let mut last_save = Instant::now();
+    let mut last_idle_check = Instant::now();
 
     loop {
         if last_spawn.elapsed() >= tick_rate {
+            app.serve_cli_requests();
+            app.sync_shared_session();
             let should_spawn = app.time_tracker.current_session_start.is_some()
-                && app.time_tracker.active_category_index().is_some();
-
+                && !app.time_tracker.is_paused()
+                && app.time_tracker.active_category_index().is_some()
+                && app.pomodoro.is_none();
+
+            app.check_contract();
+            app.check_none_reminder();
+            app.check_idle_prompt();
+            app.expire_status();
+            app.tick_pomodoro();
             if should_spawn {
+                app.check_budget();
                 let cat_id = app.time_tracker.active_category_id();
-                app.sand_engine.spawn(cat_id);
+                if let Some(x) = app.sand_engine.spawn(cat_id) {
+                    app.record_grain_event(cat_id, x);
+                }
+                app.check_goal_line();
                 app.render_needed = true;
             }
 
             last_spawn = Instant::now();
         }
 
-        if last_physics.elapsed() >= physics_rate {
+        if last_physics.elapsed() >= Duration::from_millis(app.physics_ms) {
             app.sand_engine.update();
+            app.tick_goal_sparkle();
             app.render_needed = true;
             if app.time_tracker.active_category_index() == Some(0) {
                 app.update_blink();
@@ -288,12 +714,22 @@ pub fn run_ui() -> Result<(), io::Error> {
             last_physics = Instant::now();
         }
 
-        if last_save.elapsed() >= save_rate {
+        if last_save.elapsed() >= Duration::from_secs(app.autosave_seconds as u64) {
             app.persist_sessions();
+            app.checkpoint_sand();
             last_save = Instant::now();
         }
 
-        if last_render.elapsed() >= render_rate && app.render_needed {
+        if last_idle_check.elapsed() >= idle_check_rate {
+            app.check_idle();
+            last_idle_check = Instant::now();
+        }
+
+        app.flush_saves(false);
+        app.step_migration();
+
+        let resized = app.apply_pending_resize();
+        if resized || (last_render.elapsed() >= render_rate && app.render_needed) {
             terminal.draw(|f| {
                 app.draw_frame(f);
             })?;
@@ -301,21 +737,51 @@ pub fn run_ui() -> Result<(), io::Error> {
             last_render = Instant::now();
         }
 
-        if event::poll(Duration::from_millis(1))?
-            && let Event::Key(key) = event::read()?
-            && app.handle_key(key)
-        {
-            break;
+        if event::poll(Duration::from_millis(1))? {
+            match event::read()? {
+                Event::Key(key) => {
+                    app.resume_from_idle();
+                    if app.handle_key(key) {
+                        break;
+                    }
+                }
+                Event::Paste(text) => {
+                    app.resume_from_idle();
+                    app.handle_paste(&text);
+                }
+                Event::Resize(width, height) => app.queue_resize(width, height),
+                _ => {}
+            }
         }
     }
 
-    app.time_tracker.end_session();
+    app.end_or_hand_off_session();
     app.persist_sessions();
-    app.persist_sand_state();
+    if !app.stopwatch_mode && app.pomodoro.is_none() {
+        app.persist_sand_state();
+    }
+    let usage = app.flush_usage();
+    app.flush_saves(true);
 
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        DisableBracketedPaste,
+        LeaveAlternateScreen
+    )?;
     terminal.show_cursor()?;
 
+    // Only now, out of the alternate screen, does the warning stay visible.
+    if let Err(e) = usage {
+        eprintln!("Warning: Could not save usage metrics: {}", e);
+    }
+    if let Some(failure) = app.save_queue.failure() {
+        return Err(io::Error::other(format!(
+            "Unsaved changes were lost: could not write {}: {}",
+            failure.path.display(),
+            failure.error
+        )));
+    }
+
     Ok(())
 }
@@ -0,0 +1,285 @@
+use std::time::Instant;
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+
+use crate::{
+    cli::{self, ActiveSession, StopOutput},
+    daemon::{DaemonRequest, DaemonResponse},
+    domain::{self, CategoryId, TimeTracker},
+    hooks::HookEvent,
+    storage,
+};
+
+use super::{App, save_queue::SaveTarget};
+
+/// Readings of the same start through `Instant` and the wall clock differ by
+/// a few milliseconds; anything within this is the same session.
+const START_TOLERANCE_SECONDS: i64 = 1;
+
+fn same_session(a: Option<&ActiveSession>, b: Option<&ActiveSession>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => {
+            a.category_id == b.category_id
+                && a.description == b.description
+                && a.project == b.project
+                && (a.start_time - b.start_time).num_seconds().abs() <= START_TOLERANCE_SECONDS
+        }
+        _ => false,
+    }
+}
+
+fn read_shared_session() -> Option<ActiveSession> {
+    let path = storage::get_active_session_path();
+    if !storage::file_exists(&path) {
+        return None;
+    }
+    storage::read_json(&path).ok()
+}
+
+/// The session `tracker` is timing, paused or not, as `strata start` would
+/// record it in `active_session.json`, and when its tracked time ends: now,
+/// or where it was paused. None counts as nothing.
+fn timed_session(
+    tracker: &TimeTracker,
+    now: DateTime<Utc>,
+) -> Option<(ActiveSession, DateTime<Utc>)> {
+    let category_id = tracker.active_category_id();
+    if category_id == CategoryId::new(0) {
+        return None;
+    }
+    let elapsed = ChronoDuration::from_std(tracker.session_elapsed()?).ok()?;
+    let paused_for = tracker
+        .paused_for()
+        .and_then(|paused_for| ChronoDuration::from_std(paused_for).ok())
+        .unwrap_or_default();
+    let tracked_until = now - paused_for;
+    let session = ActiveSession {
+        project: tracker.session_project().to_string(),
+        description: tracker.session_description().to_string(),
+        category_id: category_id.0,
+        category_name: tracker
+            .category_name_by_id(category_id)
+            .unwrap_or("unknown")
+            .to_string(),
+        start_time: tracked_until - elapsed,
+    };
+    Some((session, tracked_until))
+}
+
+impl App {
+    /// The running session as `strata start` would record it in
+    /// `active_session.json`; none and a paused session count as nothing.
+    fn tracked_session(&self) -> Option<ActiveSession> {
+        if self.time_tracker.is_paused() {
+            return None;
+        }
+        timed_session(&self.time_tracker, Utc::now()).map(|(session, _)| session)
+    }
+
+    /// Continues the session `strata start` left running, if any, instead of
+    /// starting on none. Returns whether there was one.
+    pub(super) fn adopt_shared_session(&mut self) -> bool {
+        let Some(session) = read_shared_session() else {
+            return false;
+        };
+        self.take_over_session(session);
+        true
+    }
+
+    /// Records what the TUI was tracking and carries on with `session`.
+    fn take_over_session(&mut self, session: ActiveSession) {
+        if self.end_session().is_some() {
+            self.persist_sessions();
+        }
+        let index = self
+            .time_tracker
+            .categories_ordered()
+            .position(|category| category.id.0 == session.category_id)
+            .unwrap_or(0);
+        let _ = self.time_tracker.set_active_category_by_index(index);
+        self.time_tracker
+            .set_session_description(session.description.clone());
+        self.time_tracker
+            .set_session_project(session.project.clone());
+        // The CLI already ran the start hook for it.
+        let _ = self.time_tracker.start_session();
+        let now = Instant::now();
+        let elapsed = (Utc::now() - session.start_time)
+            .to_std()
+            .unwrap_or_default();
+        self.time_tracker.current_session_start = Some(now.checked_sub(elapsed).unwrap_or(now));
+        self.shared_session = Some(session);
+        self.render_needed = true;
+    }
+
+    /// Keeps `active_session.json` and the TUI agreeing on the running
+    /// session: a change made by the CLI since the last look is taken over,
+    /// otherwise the TUI's own session is written out.
+    pub(super) fn sync_shared_session(&mut self) {
+        if self.append_sessions_from.is_some() {
+            return;
+        }
+        if !self.save_queue.is_pending(SaveTarget::ActiveSession) {
+            let on_disk = read_shared_session();
+            if !same_session(on_disk.as_ref(), self.shared_session.as_ref()) {
+                match on_disk {
+                    Some(session) => {
+                        let name = session.category_name.clone();
+                        self.take_over_session(session);
+                        self.show_status(format!("picked up {} from the CLI", name));
+                    }
+                    None => {
+                        // `strata stop` recorded it already.
+                        self.time_tracker.discard_session();
+                        self.shared_session = None;
+                        let _ = self.time_tracker.set_active_category_by_index(0);
+                        self.start_session();
+                        self.show_status("stopped from the CLI".to_string());
+                    }
+                }
+            }
+        }
+        self.publish_shared_session();
+    }
+
+    /// Answers `strata start`, `stop` and `status` sent to the daemon socket,
+    /// which the CLI uses instead of the instance lock while the TUI runs.
+    pub(super) fn serve_cli_requests(&mut self) {
+        let Some(listener) = self.cli_listener.take() else {
+            return;
+        };
+        listener.serve_pending(|request| self.handle_cli_request(request));
+        self.cli_listener = Some(listener);
+    }
+
+    fn handle_cli_request(&mut self, request: DaemonRequest) -> DaemonResponse {
+        match request {
+            DaemonRequest::Start {
+                project,
+                description,
+                category,
+                start_time,
+            } => {
+                match cli::new_active_session(project, description, category.as_deref(), start_time)
+                {
+                    Ok(session) => {
+                        cli::run_session_hook(HookEvent::Start, &session, None);
+                        let output = cli::start_output(&session);
+                        let name = session.category_name.clone();
+                        self.take_over_session(session);
+                        self.save(SaveTarget::ActiveSession);
+                        self.show_status(format!("started {} from the CLI", name));
+                        DaemonResponse::Started(output)
+                    }
+                    Err(e) => DaemonResponse::Error(e),
+                }
+            }
+            DaemonRequest::Stop { end_time, discard } => {
+                match self.stop_for_cli(end_time, discard) {
+                    Ok(output) => DaemonResponse::Stopped(output),
+                    Err(e) => DaemonResponse::Error(e),
+                }
+            }
+            DaemonRequest::Status => {
+                DaemonResponse::Status(cli::status_output_for(self.tracked_session().as_ref()))
+            }
+        }
+    }
+
+    fn stop_for_cli(
+        &mut self,
+        end_time: Option<DateTime<Utc>>,
+        discard: bool,
+    ) -> Result<StopOutput, String> {
+        // A paused session is stopped too, with the time up to the pause.
+        let (session, tracked_until) = timed_session(&self.time_tracker, Utc::now())
+            .ok_or_else(|| "No active session to stop".to_string())?;
+        let output = if discard {
+            self.time_tracker.discard_session();
+            cli::discard_session(session)
+        } else if let Some(end_time) = end_time {
+            domain::validate_session_end(session.start_time, end_time)?;
+            let early = (tracked_until - end_time).num_seconds().max(0) as usize;
+            let elapsed = self.time_tracker.end_session_idle(early).unwrap_or(0);
+            self.index_latest_session();
+            cli::run_session_hook(HookEvent::Stop, &session, Some(elapsed));
+            cli::recorded_stop_output(session, elapsed)
+        } else {
+            let elapsed = self.end_session().unwrap_or(0);
+            cli::recorded_stop_output(session, elapsed)
+        };
+        self.persist_sessions();
+        self.shared_session = None;
+        self.save(SaveTarget::ActiveSession);
+        let _ = self.time_tracker.set_active_category_by_index(0);
+        self.start_session();
+        self.show_status("stopped from the CLI".to_string());
+        Ok(output)
+    }
+
+    fn publish_shared_session(&mut self) {
+        // Without the instance lock the file belongs to whoever holds it.
+        if self.append_sessions_from.is_some() {
+            return;
+        }
+        let tracked = self.tracked_session();
+        if !same_session(tracked.as_ref(), self.shared_session.as_ref()) {
+            self.shared_session = tracked;
+            self.save(SaveTarget::ActiveSession);
+        }
+    }
+
+    /// On quit a running category session stays in `active_session.json`
+    /// for `strata stop` or the next TUI to finish; anything else, and every
+    /// session in stopwatch and pomodoro mode, ends here as before.
+    pub(super) fn end_or_hand_off_session(&mut self) {
+        let hand_off =
+            !self.stopwatch_mode && self.pomodoro.is_none() && self.tracked_session().is_some();
+        if !hand_off {
+            self.end_session();
+        }
+        self.publish_shared_session();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn working_tracker(elapsed_seconds: u64) -> TimeTracker {
+        let mut tracker = TimeTracker::new();
+        let _ = tracker.add_category("Work".to_string(), String::new(), Some(0));
+        assert!(tracker.set_active_category_by_index(1));
+        let _ = tracker.start_session();
+        tracker.current_session_start =
+            Instant::now().checked_sub(std::time::Duration::from_secs(elapsed_seconds));
+        tracker
+    }
+
+    #[test]
+    fn test_a_paused_session_is_stopped_with_the_time_up_to_the_pause() {
+        let mut tracker = working_tracker(100);
+        assert!(tracker.toggle_pause());
+
+        let now = Utc::now();
+        let (session, tracked_until) = timed_session(&tracker, now).expect("paused session");
+        assert_eq!(session.category_name, "Work");
+        assert!((tracked_until - session.start_time).num_seconds() >= 100);
+        assert!(tracked_until <= now);
+
+        let elapsed = tracker.end_session().expect("recorded");
+        assert!((100..102).contains(&elapsed));
+        assert_eq!(
+            tracker.sessions.last().map(|s| s.elapsed_seconds),
+            Some(elapsed)
+        );
+    }
+
+    #[test]
+    fn test_no_session_is_timed_on_none() {
+        let mut tracker = TimeTracker::new();
+        let _ = tracker.start_session();
+        assert!(timed_session(&tracker, Utc::now()).is_none());
+    }
+}
@@ -0,0 +1,128 @@
+use crate::domain::CategoryId;
+
+use super::{
+    App,
+    save_queue::SaveTarget,
+    ui_helpers::{self, ModalRow},
+};
+
+impl App {
+    pub(super) fn modal_rows(&self) -> Vec<ModalRow> {
+        self.modal_rows_with(|name| self.category_groups.is_collapsed(name))
+    }
+
+    /// Category indexes in modal order, folded groups included.
+    pub(super) fn modal_category_order(&self) -> Vec<usize> {
+        self.modal_rows_with(|_| false)
+            .into_iter()
+            .filter_map(|row| match row {
+                ModalRow::Category(index) => Some(index),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn modal_rows_with(&self, is_collapsed: impl Fn(&str) -> bool) -> Vec<ModalRow> {
+        let groups: Vec<Option<&str>> = self
+            .time_tracker
+            .categories_ordered()
+            .map(|category| {
+                (category.id != CategoryId::new(0))
+                    .then(|| self.category_groups.group_of(category.id))
+                    .flatten()
+            })
+            .collect();
+        let order = self
+            .category_groups
+            .ordered_groups(self.time_tracker.categories_ordered());
+        ui_helpers::grouped_modal_rows(&groups, &order, is_collapsed)
+    }
+
+    pub(super) fn modal_cursor(&self, rows: &[ModalRow]) -> usize {
+        rows.iter()
+            .position(|row| match row {
+                ModalRow::Group { name, .. } => self.modal_group.as_deref() == Some(name),
+                ModalRow::Category(index) => {
+                    self.modal_group.is_none() && *index == self.selected_index
+                }
+                ModalRow::Insert => self.modal_group.is_none() && self.is_on_insert_space(),
+            })
+            .unwrap_or(0)
+    }
+
+    pub(super) fn move_modal_cursor(&mut self, forward: bool) {
+        let rows = self.modal_rows();
+        let current = self.modal_cursor(&rows);
+        let next = if forward {
+            ui_helpers::wrap_next_index(current, rows.len())
+        } else {
+            ui_helpers::wrap_prev_index(current, rows.len())
+        };
+        match &rows[next] {
+            ModalRow::Group { name, .. } => self.modal_group = Some(name.clone()),
+            ModalRow::Category(index) => {
+                self.modal_group = None;
+                self.selected_index = *index;
+                self.sync_modal_description_from_selection();
+            }
+            ModalRow::Insert => {
+                self.modal_group = None;
+                self.selected_index = self.time_tracker.category_count();
+                self.sync_modal_description_from_selection();
+            }
+        }
+    }
+
+    /// Puts the cursor on the group header when the selected category is
+    /// folded away.
+    pub(super) fn reveal_modal_selection(&mut self) {
+        self.modal_group = self
+            .time_tracker
+            .category_by_index(self.selected_index)
+            .filter(|category| category.id != CategoryId::new(0))
+            .and_then(|category| self.category_groups.group_of(category.id))
+            .filter(|group| self.category_groups.is_collapsed(group))
+            .map(str::to_string);
+    }
+
+    pub(super) fn set_group_collapsed(&mut self, group: &str, collapsed: bool) {
+        if self.category_groups.set_collapsed(group, collapsed) {
+            self.save(SaveTarget::CategoryGroups);
+        }
+    }
+
+    /// Tracked today across the group's categories.
+    pub(super) fn group_spent_today(&self, group: &str) -> usize {
+        self.time_tracker
+            .categories_ordered()
+            .filter(|category| self.category_groups.group_of(category.id) == Some(group))
+            .map(|category| self.category_spent_today(category.id))
+            .sum()
+    }
+
+    pub(super) fn start_group_edit(&mut self) {
+        if self.selected_index == 0 {
+            return;
+        }
+        self.editing_group = self.selected_category_id().map(|id| {
+            self.category_groups
+                .group_of(id)
+                .unwrap_or_default()
+                .to_string()
+        });
+    }
+
+    /// An empty name ungroups the category; its new group opens so the
+    /// cursor stays on it.
+    pub(super) fn finish_group_edit(&mut self) {
+        let (Some(name), Some(id)) = (self.editing_group.take(), self.selected_category_id())
+        else {
+            return;
+        };
+        self.category_groups.set_group(id, &name);
+        if let Some(group) = self.category_groups.group_of(id).map(str::to_string) {
+            self.category_groups.set_collapsed(&group, false);
+        }
+        self.save(SaveTarget::CategoryGroups);
+    }
+}
@@ -2,81 +2,184 @@ use ratatui::prelude::{Line, Span};
 use ratatui::{
     Frame,
     layout::Rect,
-    style::{Color, Style, Stylize},
+    style::{Color, Modifier, Style, Stylize},
     widgets::{Block, BorderType, Borders, List, ListItem, ListState},
 };
 
 use crate::constants::COLORS;
 
-use super::{App, view_style};
+use super::{App, ui_helpers::ModalRow, view_style};
 
 impl App {
     pub(super) fn render_modal(&self, f: &mut Frame, terminal_size: Rect) {
         let modal_rect = self.modal_rect(terminal_size);
 
         let border_color = self.get_selected_color();
-        let categories = self.time_tracker.categories_ordered();
-
-        let items: Vec<ListItem> = categories
+        let suggestion = self.modal_category_suggestion().and_then(|index| {
+            self.time_tracker
+                .category_by_index(index)
+                .map(|category| format!("  tab → {}", category.name))
+        });
+        let categories: Vec<_> = self.time_tracker.categories_ordered().collect();
+        let rows: Vec<ModalRow> = if self.category_filter.is_some() {
+            self.filtered_category_indices()
+                .into_iter()
+                .map(ModalRow::Category)
+                .collect()
+        } else {
+            self.modal_rows()
+        };
+        let cursor = if self.category_filter.is_some() {
+            rows.iter()
+                .position(|row| *row == ModalRow::Category(self.selected_index))
+        } else {
+            Some(self.modal_cursor(&rows))
+        };
+        let items: Vec<ListItem> = rows
             .iter()
             .enumerate()
-            .map(|(i, cat)| {
-                let is_selected = i == self.selected_index;
-                let dot = if cat.karma_effect < 0 { "◯ " } else { "● " };
+            .map(|(row, modal_row)| match modal_row {
+                ModalRow::Category(i) => {
+                    let cat = categories[*i];
+                    let is_selected = Some(row) == cursor && self.modal_group.is_none();
+                    let dot = if cat.karma_effect < 0 { "◯ " } else { "● " };
+                    let color = self.theme().category_color(cat.color);
 
-                if is_selected {
-                    let text_color = view_style::text_color_for_bg(cat.color);
-                    let description_text = if self.modal_description.is_empty() {
-                        Span::raw("")
+                    if is_selected && let Some(hex) = self.editing_color.as_deref() {
+                        let preview = self.color_edit_preview().unwrap_or(self.theme().muted);
+                        ListItem::new(Line::from(vec![
+                            Span::raw(dot).fg(preview),
+                            Span::styled(
+                                format!("#{}_", hex),
+                                Style::default()
+                                    .fg(self.theme().text)
+                                    .add_modifier(Modifier::UNDERLINED),
+                            ),
+                        ]))
+                    } else if is_selected && let Some(name) = self.renaming_category.as_deref() {
+                        let text_color = view_style::text_color_for_bg(color);
+                        ListItem::new(Line::from(vec![
+                            Span::raw(dot).fg(color),
+                            Span::styled(
+                                format!("{}_", name),
+                                Style::default().add_modifier(Modifier::UNDERLINED),
+                            ),
+                        ]))
+                        .style(Style::default().fg(text_color).bg(color))
+                    } else if is_selected && let Some(group) = self.editing_group.as_deref() {
+                        let text_color = view_style::text_color_for_bg(color);
+                        ListItem::new(Line::from(vec![
+                            Span::raw(dot).fg(color),
+                            Span::raw(&cat.name),
+                            Span::raw("  group: "),
+                            Span::styled(
+                                format!("{}_", group),
+                                Style::default().add_modifier(Modifier::UNDERLINED),
+                            ),
+                        ]))
+                        .style(Style::default().fg(text_color).bg(color))
+                    } else if is_selected {
+                        let text_color = view_style::text_color_for_bg(color);
+                        let mut description_text = vec![Span::raw(
+                            if self.modal_description.is_empty() && !self.is_editing_modal_text() {
+                                ""
+                            } else {
+                                " "
+                            },
+                        )];
+                        description_text.extend(self.modal_input_spans(
+                            &self.modal_description,
+                            Style::default().add_modifier(Modifier::ITALIC),
+                        ));
+                        let suggestion_text = match suggestion.as_deref() {
+                            Some(text) => {
+                                Span::styled(text, Style::default().add_modifier(Modifier::DIM))
+                            }
+                            None => Span::raw(""),
+                        };
+                        let mut spans = vec![
+                            Span::raw(dot).fg(color),
+                            Span::raw(&cat.name).fg(text_color),
+                        ];
+                        spans.extend(description_text);
+                        spans.push(self.row_tags_span(cat.id));
+                        spans.push(suggestion_text);
+                        ListItem::new(Line::from(spans))
+                            .style(Style::default().fg(text_color).bg(color))
                     } else {
-                        Span::styled(
-                            format!(" {}", self.modal_description),
-                            Style::default().add_modifier(ratatui::style::Modifier::ITALIC),
-                        )
-                    };
-                    ListItem::new(Line::from(vec![
-                        Span::raw(dot).fg(cat.color),
-                        Span::raw(&cat.name).fg(text_color),
-                        description_text,
-                    ]))
-                    .style(Style::default().fg(text_color).bg(cat.color))
-                } else {
-                    ListItem::new(Line::from(vec![
-                        Span::raw(dot).fg(cat.color),
-                        Span::raw(&cat.name).fg(Color::White),
-                    ]))
+                        let name_color = if self.is_over_budget(cat.id) {
+                            Color::Red
+                        } else {
+                            self.theme().text
+                        };
+                        ListItem::new(Line::from(vec![
+                            Span::raw(dot).fg(color),
+                            Span::raw(&cat.name).fg(name_color),
+                            self.row_tags_span(cat.id).fg(self.theme().muted),
+                        ]))
+                    }
                 }
-            })
-            .chain(std::iter::once({
-                let is_selected = self.is_on_insert_space();
-                let cycling_color = COLORS[self.color_index];
+                ModalRow::Group {
+                    name,
+                    collapsed,
+                    count,
+                } => self.group_header_item(name, *collapsed, *count, Some(row) == cursor),
+                ModalRow::Insert => {
+                    let is_selected = self.is_on_insert_space();
+                    let cycling_color = self.theme().category_color(COLORS[self.color_index]);
 
-                if is_selected {
-                    ListItem::new(Line::from(vec![
-                        Span::raw("● ").fg(cycling_color),
-                        Span::raw(if self.new_category_name.is_empty() {
-                            "+ Add new..."
+                    if is_selected {
+                        let mut spans = vec![Span::raw("● ").fg(cycling_color)];
+                        if self.new_category_name.is_empty() && !self.is_editing_modal_text() {
+                            spans.push(Span::raw("+ Add new..."));
                         } else {
-                            &self.new_category_name
-                        }),
-                    ]))
-                    .style(Style::default().fg(Color::Black).bg(Color::White))
-                } else {
-                    ListItem::new(Line::from(vec![
-                        Span::raw("● ").fg(cycling_color),
-                        Span::raw(if self.new_category_name.is_empty() {
-                            "+ Add new..."
-                        } else {
-                            &self.new_category_name
-                        })
-                        .fg(Color::White),
-                    ]))
+                            spans.extend(
+                                self.modal_input_spans(&self.new_category_name, Style::default()),
+                            );
+                        }
+                        ListItem::new(Line::from(spans))
+                            .style(Style::default().fg(Color::Black).bg(self.theme().text))
+                    } else {
+                        ListItem::new(Line::from(vec![
+                            Span::raw("● ").fg(cycling_color),
+                            Span::raw(if self.new_category_name.is_empty() {
+                                "+ Add new..."
+                            } else {
+                                &self.new_category_name
+                            })
+                            .fg(self.theme().text),
+                        ]))
+                    }
                 }
-            }))
+            })
             .collect();
 
         let mut list_state = ListState::default();
-        list_state.select(Some(self.selected_index));
+        list_state.select(cursor);
+
+        let title = match self.category_filter.as_deref() {
+            Some(query) => format!("/{}_", query),
+            None if self.vim_insert => "strata -- insert --".to_string(),
+            None => "strata".to_string(),
+        };
+        let footer = if self.category_filter.is_some() {
+            format!("{} matches · enter switch · esc back", rows.len())
+        } else if self.renaming_category.is_some() {
+            "enter rename · esc cancel".to_string()
+        } else if self.editing_color.is_some() {
+            "#rrggbb · enter set color · esc cancel".to_string()
+        } else if self.editing_group.is_some() {
+            "enter set group · empty to ungroup · esc cancel".to_string()
+        } else if self.modal_group.is_some() {
+            "← fold · → unfold · enter toggle".to_string()
+        } else if self.is_on_insert_space() {
+            String::new()
+        } else {
+            self.time_tracker
+                .category_by_index(self.selected_index)
+                .map(|category| self.category_quick_stats(category.id))
+                .unwrap_or_default()
+        };
 
         let list = List::new(items)
             .block(
@@ -84,15 +187,121 @@ impl App {
                     .borders(Borders::ALL)
                     .border_type(BorderType::Rounded)
                     .title(Line::from(Span::styled(
-                        "strata",
-                        Style::default().fg(Color::White),
+                        title,
+                        Style::default().fg(self.theme().text),
                     )))
                     .title_alignment(ratatui::layout::Alignment::Center)
+                    .title_bottom(
+                        Line::from(Span::styled(
+                            footer,
+                            Style::default().add_modifier(Modifier::DIM),
+                        ))
+                        .alignment(ratatui::layout::Alignment::Center),
+                    )
                     .border_style(Style::default().fg(border_color)),
             )
             .highlight_style(Style::default());
 
         f.render_widget(ratatui::widgets::Clear, modal_rect);
         f.render_stateful_widget(list, modal_rect, &mut list_state);
+        if self.confirm_delete_category {
+            self.render_delete_confirm(f, modal_rect);
+        }
+        self.render_tag_editor(f, modal_rect);
+    }
+
+    fn group_header_item(
+        &self,
+        name: &str,
+        collapsed: bool,
+        count: usize,
+        is_selected: bool,
+    ) -> ListItem<'static> {
+        let arrow = if collapsed { "▸ " } else { "▾ " };
+        let mut spans = vec![
+            Span::raw(arrow),
+            Span::styled(
+                name.to_string(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" · {}", count),
+                Style::default().add_modifier(Modifier::DIM),
+            ),
+        ];
+        let spent = self.group_spent_today(name);
+        if spent > 0 {
+            spans.push(Span::styled(
+                format!(" · {} today", self.format_time(spent)),
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
+        let style = if is_selected {
+            Style::default().fg(Color::Black).bg(self.theme().text)
+        } else {
+            Style::default().fg(self.theme().text)
+        };
+        ListItem::new(Line::from(spans)).style(style)
+    }
+
+    fn render_delete_confirm(&self, f: &mut Frame, area: Rect) {
+        let Some(category) = self.time_tracker.category_by_index(self.selected_index) else {
+            return;
+        };
+        let sessions = delete_confirm_sessions_line(self.selected_category_session_count());
+        let lines = vec![
+            Line::from(vec![
+                Span::raw("delete "),
+                Span::styled(
+                    category.name.as_str(),
+                    Style::default()
+                        .fg(self.theme().category_color(category.color))
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("?"),
+            ]),
+            Line::from(Span::styled(
+                sessions,
+                Style::default().fg(self.theme().muted),
+            )),
+        ];
+
+        let width = lines.iter().map(Line::width).max().unwrap_or(0).max(18) as u16 + 4;
+        let width = width.min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let rect = Rect::new(
+            area.x + area.width.saturating_sub(width) / 2,
+            area.y + area.height.saturating_sub(height) / 2,
+            width,
+            height,
+        );
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title_bottom(
+                Line::from(Span::styled(
+                    " y delete · n keep ",
+                    Style::default().fg(self.theme().muted),
+                ))
+                .alignment(ratatui::layout::Alignment::Center),
+            )
+            .border_style(Style::default().fg(Color::Red));
+
+        f.render_widget(ratatui::widgets::Clear, rect);
+        f.render_widget(
+            ratatui::widgets::Paragraph::new(lines)
+                .alignment(ratatui::layout::Alignment::Center)
+                .block(block),
+            rect,
+        );
+    }
+}
+
+/// Second line of the delete confirmation, saying what happens to the category's sessions.
+pub(super) fn delete_confirm_sessions_line(count: usize) -> String {
+    match count {
+        0 => "no sessions use it".to_string(),
+        1 => "1 session moves to none".to_string(),
+        count => format!("{} sessions move to none", count),
     }
 }
@@ -1,31 +1,485 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, Utc};
 use ratatui::style::Color;
 
-use crate::{constants::COLORS, domain::CategoryId, storage};
+use crate::{
+    constants::COLORS,
+    domain::{
+        Category, CategoryId, ReportPeriod, Session, SessionQuery, forget_removed_overlaps,
+        goal_ledger_week, operational_day_key_now, parse_color, period_date_bounds, week_start,
+    },
+    hooks::{self, HookEvent, HookSession},
+    idle, notify,
+    sand::SandEngine,
+    storage,
+};
+
+use super::{
+    App, focus_contract::CategorySwitch, save_queue::SaveTarget, ui_helpers, undo::UndoAction,
+};
+
+/// Puts `id` first among the last two categories tracked; none isn't one.
+fn remember_recent(recent: &mut Vec<CategoryId>, id: CategoryId) {
+    if id == CategoryId::new(0) {
+        return;
+    }
+    recent.retain(|other| *other != id);
+    recent.insert(0, id);
+    recent.truncate(2);
+}
 
-use super::App;
+/// Index in `ordered` of the most recent category other than `active` that
+/// still exists.
+fn toggle_target(
+    recent: &[CategoryId],
+    active: CategoryId,
+    ordered: &[CategoryId],
+) -> Option<usize> {
+    recent
+        .iter()
+        .filter(|id| **id != active)
+        .find_map(|id| ordered.iter().position(|other| other == id))
+}
+
+/// Presses the pile into a layer for `sand_day` once `today` has moved past
+/// it, and starts the new day with no daily goal reached. Returns whether the
+/// day rolled over.
+fn roll_over_sand_day(
+    sand_engine: &mut SandEngine,
+    sand_day: &mut NaiveDate,
+    goal_reached: &mut Option<bool>,
+    today: NaiveDate,
+) -> bool {
+    if today == *sand_day {
+        return false;
+    }
+    let yesterday = std::mem::replace(sand_day, today);
+    sand_engine.compact_day(Some(yesterday));
+    *goal_reached = None;
+    true
+}
 
 impl App {
-    pub(super) fn persist_categories(&self) {
-        let categories = self.time_tracker.categories_for_storage();
-        let path = storage::get_data_dir().join("categories.csv");
-        let _ = storage::save_categories_to_csv(&path, &categories);
+    pub(super) fn persist_categories(&mut self) {
+        self.save(SaveTarget::Categories);
     }
 
-    pub(super) fn persist_sessions(&self) {
-        let categories = self.time_tracker.categories_for_storage();
-        let path = storage::get_data_dir().join("time_log.csv");
-        let _ = storage::save_sessions_to_csv(&path, &self.time_tracker.sessions, &categories);
+    pub(super) fn persist_sessions(&mut self) {
+        self.save(SaveTarget::Sessions);
+        if forget_removed_overlaps(&mut self.kept_overlaps, &self.time_tracker.sessions) {
+            self.save(SaveTarget::KeptOverlaps);
+        }
     }
 
-    pub(super) fn persist_sand_state(&self) {
-        let state = self.sand_engine.snapshot_state();
-        let path = storage::get_sand_state_path();
-        let _ = storage::save_sand_state(&path, &state);
+    pub(super) fn trash_sessions(&mut self, removed: Vec<Session>) {
+        if removed.is_empty() {
+            return;
+        }
+        self.pending_trash.extend(removed);
+        self.save(SaveTarget::Trash);
+    }
+
+    pub(super) fn start_session(&mut self) {
+        self.remember_recent_category();
+        if self.time_tracker.start_session() {
+            self.focus_prompt_session = None;
+            self.persist_sessions();
+            let name = self.category_name_for_id(self.time_tracker.active_category_id());
+            self.show_status(format!("merged back into {}", name));
+        }
+        let description = self.time_tracker.session_description().to_string();
+        self.run_session_hook(HookEvent::Start, &description, Local::now(), None);
+    }
+
+    /// Keeps the last two non-none categories, newest first, for `tab`.
+    fn remember_recent_category(&mut self) {
+        remember_recent(
+            &mut self.recent_categories,
+            self.time_tracker.active_category_id(),
+        );
+    }
+
+    /// Switches to the most recent non-none category that isn't active,
+    /// so repeated presses flip between the last two.
+    pub(super) fn toggle_recent_category(&mut self) {
+        let ordered: Vec<CategoryId> = self
+            .time_tracker
+            .categories_ordered()
+            .map(|category| category.id)
+            .collect();
+        let Some(index) = toggle_target(
+            &self.recent_categories,
+            self.time_tracker.active_category_id(),
+            &ordered,
+        ) else {
+            self.show_status("no previous category yet".to_string());
+            return;
+        };
+        self.record_usage("tui toggle category");
+        self.request_category_switch(CategorySwitch {
+            index,
+            description: None,
+        });
+    }
+
+    /// Switches straight to the `n`th category in modal order, none aside.
+    pub(super) fn quick_switch_category(&mut self, n: usize) {
+        let Some(index) = self
+            .modal_category_order()
+            .into_iter()
+            .filter(|index| *index != 0)
+            .nth(n.saturating_sub(1))
+        else {
+            self.show_status(format!("no category {}", n));
+            return;
+        };
+        if self.time_tracker.active_category_index() == Some(index) {
+            return;
+        }
+        self.record_usage("tui quick switch");
+        self.request_category_switch(CategorySwitch {
+            index,
+            description: None,
+        });
+    }
+
+    pub(super) fn end_session(&mut self) -> Option<usize> {
+        let description = self.time_tracker.session_description().to_string();
+        let elapsed = self.time_tracker.end_session()?;
+        self.index_latest_session();
+        let started_at = Local::now() - ChronoDuration::seconds(elapsed as i64);
+        self.run_session_hook(HookEvent::Stop, &description, started_at, Some(elapsed));
+        if self.prompt_focus_rating
+            && elapsed > 0
+            && self.time_tracker.active_category_id() != CategoryId::new(0)
+        {
+            self.focus_prompt_session = self.time_tracker.sessions.last().map(|session| session.id);
+        }
+        Some(elapsed)
+    }
+
+    pub(super) fn reset_idle_today(&mut self) {
+        let sand = self.sand_engine.snapshot_state();
+        let session_start = self.time_tracker.current_session_start;
+        let removed = self.time_tracker.reset_none_counter_today();
+        self.push_undo(UndoAction::ResetIdle {
+            sessions: removed.clone(),
+            sand,
+            session_start,
+        });
+        self.trash_sessions(removed);
+        self.persist_sessions();
+        self.sand_engine.clear_category(CategoryId::new(0));
+        self.persist_sand_state();
+    }
+
+    pub(super) fn rate_session_focus(&mut self, session_id: usize, rating: u8) {
+        if self.time_tracker.set_session_focus(session_id, rating) {
+            self.persist_sessions();
+        }
+    }
+
+    pub(super) fn index_latest_session(&mut self) {
+        if let Some(session) = self.time_tracker.sessions.last() {
+            self.suggestion_index.add_session(session);
+        }
+    }
+
+    pub(super) fn modal_category_suggestion(&self) -> Option<usize> {
+        if self.is_on_insert_space() || self.modal_description.trim().is_empty() {
+            return None;
+        }
+        let category_id = self.suggestion_index.suggest(&self.modal_description)?;
+        let index = self
+            .time_tracker
+            .categories_ordered()
+            .position(|category| category.id == category_id)?;
+        (index != self.selected_index).then_some(index)
+    }
+
+    fn run_session_hook(
+        &self,
+        event: HookEvent,
+        description: &str,
+        started_at: DateTime<Local>,
+        elapsed_seconds: Option<usize>,
+    ) {
+        let category_id = self.time_tracker.active_category_id();
+        let category = self
+            .time_tracker
+            .category_name_by_id(category_id)
+            .unwrap_or_default();
+        hooks::run_hook(
+            &self.hooks,
+            event,
+            &HookSession {
+                category_id: category_id.0,
+                category,
+                project: "",
+                description,
+                started_at,
+                elapsed_seconds,
+            },
+        );
+    }
+
+    pub(super) fn category_spent_today(&self, category_id: CategoryId) -> usize {
+        let recorded = self.time_tracker.category_time_today(category_id);
+        let live = match self.time_tracker.session_elapsed() {
+            Some(elapsed) if self.time_tracker.active_category_id() == category_id => {
+                elapsed.as_secs() as usize
+            }
+            _ => 0,
+        };
+        recorded + live
+    }
+
+    pub(super) fn category_quick_stats(&self, category_id: CategoryId) -> String {
+        let today = self.category_spent_today(category_id);
+        let live = today.saturating_sub(self.time_tracker.category_time_today(category_id));
+        let today_key = operational_day_key_now().format("%Y-%m-%d").to_string();
+        let (week, last) = week_total_and_last_session(
+            &self.time_tracker.sessions,
+            category_id,
+            live,
+            period_date_bounds(ReportPeriod::Week),
+            &today_key,
+        );
+
+        let contracts = match self.contract_record(category_id) {
+            (_, 0) => String::new(),
+            (kept, total) => format!("· contracts {}/{} kept ", kept, total),
+        };
+        let goal = self
+            .week_goal_progress(category_id)
+            .map(|progress| format!("· goal {} ", progress))
+            .unwrap_or_default();
+        format!(
+            " today {} · week {} · last {} {}{}",
+            self.format_time(today),
+            self.format_time(week),
+            last,
+            goal,
+            contracts
+        )
+    }
+
+    /// Closes finished goal weeks once per week while carry-over is on.
+    pub(super) fn sync_goal_ledger(&mut self) {
+        let Some(rules) = self.carry_over else {
+            return;
+        };
+        let current_week = goal_ledger_week(operational_day_key_now(), week_start());
+        if self.goal_ledger_week == Some(current_week) {
+            return;
+        }
+        let goals: HashMap<CategoryId, usize> = self
+            .category_budgets
+            .iter()
+            .map(|(category_id, daily)| (*category_id, daily * 7))
+            .collect();
+        match storage::sync_goal_ledger(
+            &storage::get_goal_ledger_path(),
+            &self.time_tracker.sessions,
+            &goals,
+            &rules,
+            current_week,
+        ) {
+            Ok(ledger) => {
+                self.goal_ledger = ledger;
+                self.goal_ledger_week = Some(current_week);
+            }
+            Err(e) => self.show_status(format!("goal ledger not saved: {}", e)),
+        }
+    }
+
+    /// "tracked of goal" for this ledger week, the goal including carried
+    /// debt or credit; `None` without carry-over or a budget.
+    pub(super) fn week_goal_progress(&self, category_id: CategoryId) -> Option<String> {
+        let rules = self.carry_over?;
+        let week = self.goal_ledger_week?;
+        let daily = self.category_budgets.get(&category_id)?;
+        let carried = rules.carried_into(&self.goal_ledger, category_id, week);
+        let target = ((daily * 7) as i64 + carried).max(0) as usize;
+
+        let today = operational_day_key_now();
+        let live = self
+            .category_spent_today(category_id)
+            .saturating_sub(self.time_tracker.category_time_today(category_id));
+        let tracked = SessionQuery::new()
+            .between(week, today)
+            .category(category_id)
+            .run(&self.time_tracker.sessions)
+            .sessions
+            .iter()
+            .map(|session| session.elapsed_seconds)
+            .sum::<usize>()
+            + live;
+
+        let balance = match carried {
+            0 => String::new(),
+            debt if debt > 0 => format!(" · {} debt", self.format_time(debt as usize)),
+            credit => format!(
+                " · {} credit",
+                self.format_time(credit.unsigned_abs() as usize)
+            ),
+        };
+        Some(format!(
+            "{} of {}{}",
+            self.format_time(tracked),
+            self.format_time(target),
+            balance
+        ))
+    }
+
+    /// A slim gauge of today's time against the active category's daily
+    /// budget, with the percentage; `None` without a budget.
+    pub(super) fn daily_goal_gauge(&self) -> Option<String> {
+        const GAUGE_WIDTH: usize = 8;
+        let category_id = self.time_tracker.active_category_id();
+        if category_id == CategoryId::new(0) {
+            return None;
+        }
+        let target = *self.category_budgets.get(&category_id)?;
+        let fraction = self.category_spent_today(category_id) as f64 / target.max(1) as f64;
+        let done = if fraction >= 1.0 { " ✓" } else { "" };
+        Some(format!(
+            "{} {}%{}",
+            ui_helpers::progress_gauge(fraction, GAUGE_WIDTH),
+            (fraction * 100.0) as usize,
+            done
+        ))
     }
 
-    pub(super) fn persist_category_tags(&self) {
-        let path = storage::get_category_tags_path();
-        let _ = storage::save_category_tags(&path, &self.category_tags);
+    pub(super) fn is_over_budget(&self, category_id: CategoryId) -> bool {
+        self.category_budgets
+            .get(&category_id)
+            .is_some_and(|budget| self.category_spent_today(category_id) > *budget)
+    }
+
+    pub(super) fn check_budget(&mut self) {
+        let category_id = self.time_tracker.active_category_id();
+        if !self.is_over_budget(category_id) {
+            return;
+        }
+
+        let key = (operational_day_key_now(), category_id);
+        if !self.budget_notified.insert(key) {
+            return;
+        }
+
+        if storage::budget_notifications_enabled(&self.notifications) {
+            let name = self
+                .time_tracker
+                .category_name_by_id(category_id)
+                .unwrap_or_default();
+            notify::desktop_notification(
+                "strata budget exceeded",
+                &format!("{} is over its daily budget", name),
+            );
+        }
+    }
+
+    /// Reminds once per stretch of "none" after `notifications.idle_none`.
+    pub(super) fn check_none_reminder(&mut self) {
+        if self.time_tracker.active_category_id() != CategoryId::new(0) {
+            self.none_reminded = false;
+            return;
+        }
+        let Some(limit) = storage::idle_none_reminder_seconds(&self.notifications) else {
+            return;
+        };
+        let idle = self
+            .time_tracker
+            .current_session_start
+            .map_or(0, |start| start.elapsed().as_secs() as usize);
+        if idle >= limit && !self.none_reminded {
+            self.none_reminded = true;
+            notify::desktop_notification(
+                "strata",
+                &format!("nothing tracked for {}", self.format_time(idle)),
+            );
+        }
+    }
+
+    pub(super) fn check_idle(&mut self) {
+        if self.time_tracker.active_category_index() == Some(0) || self.time_tracker.is_paused() {
+            return;
+        }
+
+        let idle_seconds = idle::idle_beyond(self.idle_stop_seconds);
+        if self.idle_paused {
+            if idle_seconds.is_none() {
+                self.resume_from_idle();
+            }
+            return;
+        }
+
+        let Some(idle_seconds) = idle_seconds else {
+            return;
+        };
+        let description = self.time_tracker.session_description().to_string();
+        let Some(elapsed) = self.time_tracker.end_session_idle(idle_seconds as usize) else {
+            return;
+        };
+        let started_at =
+            Local::now() - ChronoDuration::seconds((elapsed + idle_seconds as usize) as i64);
+        self.run_session_hook(HookEvent::Stop, &description, started_at, Some(elapsed));
+        self.index_latest_session();
+        self.idle_paused = true;
+        self.persist_sessions();
+        self.render_needed = true;
+    }
+
+    pub(super) fn resume_from_idle(&mut self) {
+        if !self.idle_paused {
+            return;
+        }
+        self.idle_paused = false;
+        if self.time_tracker.current_session_start.is_none() {
+            self.start_session();
+        }
+        self.render_needed = true;
+    }
+
+    pub(super) fn persist_sand_state(&mut self) {
+        self.save(SaveTarget::SandState);
+    }
+
+    /// Saves the pile with the periodic save, pressing it into a layer when
+    /// the operational day has rolled over so it only holds today.
+    pub(super) fn checkpoint_sand(&mut self) {
+        if self.stopwatch_mode || self.pomodoro.is_some() {
+            return;
+        }
+        if roll_over_sand_day(
+            &mut self.sand_engine,
+            &mut self.sand_day,
+            &mut self.goal_reached,
+            operational_day_key_now(),
+        ) {
+            self.render_needed = true;
+        }
+        self.persist_sand_state();
+    }
+
+    pub(super) fn persist_category_tags(&mut self) {
+        self.save(SaveTarget::CategoryTags);
+    }
+
+    pub(super) fn record_grain_event(&self, category_id: CategoryId, x: usize) {
+        let Some(path) = self.grain_log_path.as_ref() else {
+            return;
+        };
+
+        let event = storage::GrainEvent {
+            timestamp: Utc::now(),
+            category_id: category_id.0,
+            x,
+            grid_width: self.sand_engine.width as usize,
+        };
+        let _ = storage::append_grain_event(path, &event);
     }
 
     pub(super) fn restore_sand_state(&mut self) {
@@ -33,30 +487,71 @@ impl App {
         let Some(state) = storage::load_sand_state(&path) else {
             return;
         };
+        let stale_day = state.day.filter(|day| *day != self.sand_day);
 
         let valid_category_ids = self
             .time_tracker
-            .categories_for_storage()
-            .into_iter()
+            .categories_ordered()
             .map(|category| category.id)
             .collect::<std::collections::HashSet<_>>();
 
         self.sand_engine.restore_state(&state, &valid_category_ids);
+        if stale_day.is_some() {
+            self.sand_engine.compact_day(stale_day);
+            self.persist_sand_state();
+        }
+    }
+
+    pub(super) fn enter_stopwatch_mode(&mut self) {
+        const STOPWATCH_CATEGORY: &str = "stopwatch";
+
+        if self.active_contract().is_some() {
+            return;
+        }
+
+        let existing_index = self
+            .time_tracker
+            .categories_ordered()
+            .position(|category| category.name.eq_ignore_ascii_case(STOPWATCH_CATEGORY));
+
+        let index = match existing_index {
+            Some(index) => index,
+            None => {
+                if self
+                    .time_tracker
+                    .add_category(STOPWATCH_CATEGORY.to_string(), String::new(), None)
+                    .is_none()
+                {
+                    return;
+                }
+                self.persist_categories();
+                self.time_tracker.category_count().saturating_sub(1)
+            }
+        };
+
+        self.end_session();
+        let _ = self.time_tracker.set_active_category_by_index(index);
+        self.start_session();
+        self.stopwatch_mode = true;
+        self.blink_state = 0;
     }
 
     pub(super) fn sync_modal_description_from_selection(&mut self) {
         if self.is_on_insert_space() {
             self.modal_description.clear();
+        } else if self.time_tracker.active_category_index() == Some(self.selected_index) {
+            self.modal_description = self.time_tracker.session_description().to_string();
         } else {
             self.modal_description = self
                 .time_tracker
-                .category_description_by_index(self.selected_index)
+                .category_default_description_by_index(self.selected_index)
                 .unwrap_or_default();
         }
         self.modal_tag_index = None;
+        self.modal_text_cursor = None;
     }
 
-    fn selected_category_id(&self) -> Option<CategoryId> {
+    pub(super) fn selected_category_id(&self) -> Option<CategoryId> {
         if self.is_on_insert_space() {
             None
         } else {
@@ -71,21 +566,13 @@ impl App {
             return;
         };
 
-        let tag = self.modal_description.trim();
-        if tag.is_empty() {
+        if !self
+            .category_tags
+            .add_tag(category_id, &self.modal_description)
+        {
             return;
         }
 
-        let tags = self
-            .category_tags
-            .tags_by_category
-            .entry(category_id.0)
-            .or_default();
-        tags.retain(|existing| existing != tag);
-        tags.insert(0, tag.to_string());
-        const MAX_TAGS_PER_CATEGORY: usize = 24;
-        tags.truncate(MAX_TAGS_PER_CATEGORY);
-
         self.modal_tag_index = Some(0);
         self.persist_category_tags();
     }
@@ -135,6 +622,66 @@ impl App {
         self.modal_description = tags[next_index].clone();
     }
 
+    /// Category indices matching the modal filter, best match first.
+    pub(super) fn filtered_category_indices(&self) -> Vec<usize> {
+        let query = self.category_filter.as_deref().unwrap_or_default();
+        let mut matches: Vec<(i64, usize)> = self
+            .time_tracker
+            .categories_ordered()
+            .enumerate()
+            .filter_map(|(index, category)| {
+                ui_helpers::fuzzy_score(query, &category.name).map(|score| (score, index))
+            })
+            .collect();
+        matches.sort_by_key(|&(score, index)| (std::cmp::Reverse(score), index));
+        matches.into_iter().map(|(_, index)| index).collect()
+    }
+
+    pub(super) fn select_best_filtered_category(&mut self) {
+        if let Some(&index) = self.filtered_category_indices().first() {
+            self.selected_index = index;
+        }
+    }
+
+    pub(super) fn move_filtered_selection(&mut self, forward: bool) {
+        let matches = self.filtered_category_indices();
+        let Some(position) = matches
+            .iter()
+            .position(|&index| index == self.selected_index)
+        else {
+            self.select_best_filtered_category();
+            return;
+        };
+        let next = if forward {
+            ui_helpers::wrap_next_index(position, matches.len())
+        } else {
+            ui_helpers::wrap_prev_index(position, matches.len())
+        };
+        self.selected_index = matches[next];
+    }
+
+    /// Switches to the highlighted match (the best one unless moved) and
+    /// closes the modal.
+    pub(super) fn activate_filtered_category(&mut self) {
+        let matches = self.filtered_category_indices();
+        let Some(&index) = matches
+            .iter()
+            .find(|&&index| index == self.selected_index)
+            .or(matches.first())
+        else {
+            return;
+        };
+        self.selected_index = index;
+        self.sync_modal_description_from_selection();
+        if self.time_tracker.active_category_index() != Some(index) {
+            self.request_category_switch(CategorySwitch {
+                index,
+                description: Some(self.modal_description.clone()),
+            });
+        }
+        self.close_modal();
+    }
+
     pub(super) fn is_on_insert_space(&self) -> bool {
         self.selected_index == self.time_tracker.category_count()
     }
@@ -147,58 +694,373 @@ impl App {
                 Some(self.color_index),
             );
             if added.is_some() {
-                let index = self.time_tracker.category_count().saturating_sub(1);
-                let _ = self.time_tracker.set_active_category_by_index(index);
-                self.time_tracker.start_session();
+                // A running contract keeps the current category active.
+                if self.active_contract().is_none() {
+                    let index = self.time_tracker.category_count().saturating_sub(1);
+                    let _ = self.time_tracker.set_active_category_by_index(index);
+                    self.start_session();
+                }
                 self.persist_categories();
                 self.sync_modal_description_from_selection();
             }
         }
     }
 
+    /// The selected category unless it is "none", the insert row or locked
+    /// by a focus contract.
+    fn deletable_selected_category(&self) -> Option<&Category> {
+        if self.is_on_insert_space() || self.selected_index == 0 {
+            return None;
+        }
+        let category = self.time_tracker.category_by_index(self.selected_index)?;
+        let locked = self
+            .active_contract()
+            .map(|contract| CategoryId::new(contract.category_id));
+        (Some(category.id) != locked).then_some(category)
+    }
+
+    pub(super) fn request_delete_category(&mut self) {
+        if self.deletable_selected_category().is_some() {
+            self.confirm_delete_category = true;
+        }
+    }
+
+    /// Sessions that will show as "none" once the selected category is gone.
+    pub(super) fn selected_category_session_count(&self) -> usize {
+        self.deletable_selected_category().map_or(0, |category| {
+            category_session_count(&self.time_tracker.sessions, category.id)
+        })
+    }
+
     pub(super) fn delete_category(&mut self) {
+        let Some(removed) = self.deletable_selected_category().cloned() else {
+            return;
+        };
+        if !self.time_tracker.delete_category(self.selected_index) {
+            return;
+        }
+
+        let tags = self.category_tags.tags_by_category.remove(&removed.id.0);
+        self.persist_category_tags();
+        self.push_undo(UndoAction::DeleteCategory {
+            index: self.selected_index,
+            category: removed,
+            tags,
+        });
+
+        if self.selected_index > 0 && self.selected_index >= self.time_tracker.category_count() {
+            self.selected_index = self.time_tracker.category_count();
+        }
+        self.persist_categories();
+        self.sync_modal_description_from_selection();
+    }
+
+    pub(super) fn save_selected_default_description(&mut self) {
         if !self.is_on_insert_space()
-            && self.selected_index < self.time_tracker.category_count()
             && self.selected_index > 0
+            && self.time_tracker.set_category_default_description_by_index(
+                self.selected_index,
+                self.modal_description.trim().to_string(),
+            )
         {
-            let removed_id = self
-                .time_tracker
-                .category_by_index(self.selected_index)
-                .map(|category| category.id);
+            self.persist_categories();
+        }
+    }
 
-            if self.time_tracker.delete_category(self.selected_index) {
-                if let Some(category_id) = removed_id {
-                    self.category_tags.tags_by_category.remove(&category_id.0);
-                    self.persist_category_tags();
-                }
+    pub(super) fn start_category_rename(&mut self) {
+        if self.is_on_insert_space() || self.selected_index == 0 {
+            return;
+        }
+        self.renaming_category = self
+            .time_tracker
+            .category_by_index(self.selected_index)
+            .map(|category| category.name.clone());
+    }
 
-                if self.selected_index > 0
-                    && self.selected_index >= self.time_tracker.category_count()
-                {
-                    self.selected_index = self.time_tracker.category_count();
-                }
-                self.persist_categories();
-                self.sync_modal_description_from_selection();
-            }
+    pub(super) fn start_color_edit(&mut self) {
+        if self.is_on_insert_space() || self.selected_index == 0 {
+            return;
+        }
+        self.editing_color = Some(String::new());
+    }
+
+    /// The color being typed, once all six hex digits are in.
+    pub(super) fn color_edit_preview(&self) -> Option<Color> {
+        self.editing_color
+            .as_deref()
+            .filter(|hex| hex.len() == 6)
+            .and_then(|hex| parse_color(&format!("#{}", hex)))
+    }
+
+    /// Keeps the editor open until the hex is complete.
+    pub(super) fn finish_color_edit(&mut self) {
+        let Some(color) = self.color_edit_preview() else {
+            return;
+        };
+        self.editing_color = None;
+        if self
+            .time_tracker
+            .set_category_color_by_index(self.selected_index, color)
+        {
+            self.persist_categories();
+        }
+    }
+
+    /// Keeps the rename open when the name is empty or already taken.
+    pub(super) fn finish_category_rename(&mut self) {
+        let Some(name) = self.renaming_category.as_deref() else {
+            return;
+        };
+        let unchanged = self
+            .time_tracker
+            .category_by_index(self.selected_index)
+            .is_some_and(|category| category.name == name.trim());
+        if unchanged {
+            self.renaming_category = None;
+        } else if self
+            .time_tracker
+            .rename_category_by_index(self.selected_index, name)
+        {
+            self.renaming_category = None;
+            self.persist_categories();
+            // time_log.csv also carries the name next to the id.
+            self.persist_sessions();
+        }
+    }
+
+    pub(super) fn set_selected_karma(&mut self, karma_effect: i8) {
+        if !self.is_on_insert_space()
+            && self.selected_index > 0
+            && self.selected_index < self.time_tracker.category_count()
+            && self
+                .time_tracker
+                .set_category_karma_by_index(self.selected_index, karma_effect)
+        {
+            self.persist_categories();
         }
     }
 
     pub(super) fn get_selected_color(&self) -> Color {
-        if self.is_on_insert_space() {
-            COLORS[self.color_index]
-        } else if let Some(category) = self.time_tracker.category_by_index(self.selected_index) {
-            category.color
+        if let Some(color) = self.color_edit_preview() {
+            color
+        } else if self.is_on_insert_space() {
+            self.theme().category_color(COLORS[self.color_index])
+        } else if let Some(category) = self.time_tracker.category_by_index(self.selected_index)
+            && category.id != CategoryId::new(0)
+        {
+            self.theme().category_color(category.color)
         } else {
-            Color::White
+            self.theme().border
         }
     }
 
     pub(super) fn get_active_color(&self) -> Color {
         if let Some(idx) = self.time_tracker.active_category_index()
             && let Some(category) = self.time_tracker.category_by_index(idx)
+            && category.id != CategoryId::new(0)
         {
-            return category.color;
+            return self.theme().category_color(category.color);
         }
-        Color::White
+        self.theme().border
+    }
+}
+
+fn category_session_count(sessions: &[Session], category_id: CategoryId) -> usize {
+    sessions
+        .iter()
+        .filter(|session| session.category_id == category_id)
+        .count()
+}
+
+/// Week total (including the live run) and the "last" label for the category modal footer.
+fn week_total_and_last_session(
+    sessions: &[Session],
+    category_id: CategoryId,
+    live: usize,
+    (week_start, week_end): (NaiveDate, NaiveDate),
+    today_key: &str,
+) -> (usize, String) {
+    let category_sessions = || {
+        sessions
+            .iter()
+            .filter(move |session| session.category_id == category_id)
+    };
+    let week = category_sessions()
+        .filter(|session| {
+            NaiveDate::parse_from_str(&session.date, "%Y-%m-%d")
+                .is_ok_and(|date| date >= week_start && date <= week_end)
+        })
+        .map(|session| session.elapsed_seconds)
+        .sum::<usize>()
+        + live;
+
+    let last = if live > 0 {
+        "now".to_string()
+    } else {
+        match category_sessions().max_by(|a, b| (&a.date, &a.end_time).cmp(&(&b.date, &b.end_time)))
+        {
+            Some(session) if session.date == today_key => {
+                session.end_time.chars().take(5).collect()
+            }
+            Some(session) => session.date.clone(),
+            None => "never".to_string(),
+        }
+    };
+    (week, last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::category_modal_view::delete_confirm_sessions_line;
+
+    #[test]
+    fn test_footer_week_total_and_last_session() {
+        let (work, read) = (CategoryId::new(1), CategoryId::new(2));
+        let session = |date: &str, end: &str, category_id, elapsed_seconds| Session {
+            id: 0,
+            date: date.to_string(),
+            category_id,
+            description: String::new(),
+            project: String::new(),
+            start_time: "08:00:00".to_string(),
+            end_time: end.to_string(),
+            elapsed_seconds,
+            focus: None,
+        };
+        let week = (
+            NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 3, 8).unwrap(),
+        );
+        let sessions = vec![
+            session("2026-03-01", "09:00:00", work, 7200),
+            session("2026-03-03", "10:30:00", work, 1800),
+            session("2026-03-04", "17:15:00", work, 600),
+            session("2026-03-04", "18:00:00", read, 900),
+        ];
+
+        assert_eq!(
+            week_total_and_last_session(&sessions, work, 0, week, "2026-03-04"),
+            (2400, "17:15".to_string())
+        );
+        assert_eq!(
+            week_total_and_last_session(&sessions, work, 0, week, "2026-03-05"),
+            (2400, "2026-03-04".to_string())
+        );
+        assert_eq!(
+            week_total_and_last_session(&sessions, work, 120, week, "2026-03-04"),
+            (2520, "now".to_string())
+        );
+    }
+
+    #[test]
+    fn test_delete_confirmation_counts_only_that_categorys_sessions() {
+        let session = |id, category_id| Session {
+            id,
+            date: "2026-03-04".to_string(),
+            category_id: CategoryId::new(category_id),
+            description: String::new(),
+            project: String::new(),
+            start_time: "08:00:00".to_string(),
+            end_time: "09:00:00".to_string(),
+            elapsed_seconds: 3600,
+            focus: None,
+        };
+        let sessions = vec![session(1, 1), session(2, 2), session(3, 1)];
+
+        assert_eq!(category_session_count(&sessions, CategoryId::new(1)), 2);
+        assert_eq!(category_session_count(&sessions, CategoryId::new(3)), 0);
+        assert_eq!(delete_confirm_sessions_line(2), "2 sessions move to none");
+        assert_eq!(delete_confirm_sessions_line(1), "1 session moves to none");
+        assert_eq!(delete_confirm_sessions_line(0), "no sessions use it");
+    }
+
+    #[test]
+    fn test_footer_for_a_category_without_sessions() {
+        let week = (
+            NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 3, 8).unwrap(),
+        );
+        assert_eq!(
+            week_total_and_last_session(&[], CategoryId::new(1), 0, week, "2026-03-04"),
+            (0, "never".to_string())
+        );
+    }
+
+    #[test]
+    fn test_recent_categories_keep_the_last_two_newest_first() {
+        let (work, read, code) = (CategoryId::new(1), CategoryId::new(2), CategoryId::new(3));
+        let mut recent = Vec::new();
+
+        remember_recent(&mut recent, work);
+        remember_recent(&mut recent, CategoryId::new(0));
+        assert_eq!(recent, [work]);
+        remember_recent(&mut recent, read);
+        assert_eq!(recent, [read, work]);
+        remember_recent(&mut recent, work);
+        assert_eq!(recent, [work, read]);
+        remember_recent(&mut recent, code);
+        assert_eq!(recent, [code, work]);
+    }
+
+    #[test]
+    fn test_toggle_flips_back_to_the_previous_category() {
+        let (none, work, read) = (CategoryId::new(0), CategoryId::new(1), CategoryId::new(2));
+        let ordered = [none, work, read];
+        let mut recent = Vec::new();
+        assert_eq!(toggle_target(&recent, none, &ordered), None);
+
+        remember_recent(&mut recent, work);
+        assert_eq!(toggle_target(&recent, work, &ordered), None);
+        assert_eq!(toggle_target(&recent, none, &ordered), Some(1));
+
+        remember_recent(&mut recent, read);
+        assert_eq!(toggle_target(&recent, read, &ordered), Some(1));
+        remember_recent(&mut recent, work);
+        assert_eq!(toggle_target(&recent, work, &ordered), Some(2));
+
+        // A deleted category is skipped.
+        assert_eq!(toggle_target(&recent, none, &[none, read]), Some(1));
+    }
+
+    #[test]
+    fn test_a_new_day_presses_the_pile_and_resets_the_goal() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let yesterday = today.pred_opt().unwrap();
+        let work = CategoryId::new(1);
+        let mut sand = SandEngine::new(20, 20);
+        for _ in 0..5 {
+            sand.spawn(work);
+        }
+        let grains = sand.grain_count;
+        assert!(grains > 0);
+        let mut sand_day = yesterday;
+        let mut goal_reached = Some(true);
+
+        assert!(roll_over_sand_day(
+            &mut sand,
+            &mut sand_day,
+            &mut goal_reached,
+            today
+        ));
+        assert_eq!(sand_day, today);
+        assert_eq!(goal_reached, None);
+        assert_eq!(sand.grain_count, 0);
+        let layers = sand.snapshot_state().layers;
+        assert_eq!(layers.len(), 1);
+        assert_eq!(layers[0].day, Some(yesterday));
+        assert_eq!(layers[0].counts, [(work.0, grains)]);
+
+        goal_reached = Some(true);
+        sand.spawn(work);
+        assert!(!roll_over_sand_day(
+            &mut sand,
+            &mut sand_day,
+            &mut goal_reached,
+            today
+        ));
+        assert_eq!(goal_reached, Some(true));
+        assert_eq!(sand.grain_count, 1);
+        assert_eq!(sand.snapshot_state().layers.len(), 1);
     }
 }
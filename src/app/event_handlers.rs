@@ -1,30 +1,268 @@
+use std::time::Instant;
+
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 use crate::{
     constants::COLORS,
-    domain::{CategoryId, ReportPeriod},
+    domain::{CategoryId, OverlapResolution, ReportPeriod},
 };
 
-use super::{App, ui_helpers};
+use super::{
+    App, BulkAction, UiMode, Visualization, focus_contract::CategorySwitch, ui_helpers,
+    undo::UndoAction,
+};
 
 impl App {
+    /// Pasted text lands in the text field being edited as one line, so a
+    /// line break in it can't confirm anything. Anywhere else, including
+    /// views that browse with letter keys, it is dropped rather than run as
+    /// keys.
+    pub(super) fn handle_paste(&mut self, pasted: &str) {
+        let text = ui_helpers::paste_line(pasted);
+        self.last_key_at = Instant::now();
+        self.render_needed = true;
+        if self.stopwatch_mode || self.pomodoro.is_some() || self.idle_prompt.is_some() || self.zen
+        {
+            return;
+        }
+        match self.ui_mode {
+            UiMode::CategoryModal => self.paste_into_modal(&text),
+            UiMode::HistoryModal => self.paste_into_history_edit(&text),
+            UiMode::Main => self.paste_into_contract_prompt(&text),
+            _ => {}
+        }
+    }
+
+    fn paste_into_modal(&mut self, text: &str) {
+        if self.confirm_delete_category {
+            return;
+        }
+        if let Some(query) = self.category_filter.as_mut() {
+            query.push_str(text);
+            self.select_best_filtered_category();
+        } else if let Some(name) = self.renaming_category.as_mut() {
+            name.push_str(text);
+        } else if let Some(hex) = self.editing_color.as_mut() {
+            let digits = text.trim_start_matches('#').chars();
+            hex.extend(digits.filter(char::is_ascii_hexdigit).take(6 - hex.len()));
+            hex.make_ascii_lowercase();
+        } else if let Some(group) = self.editing_group.as_mut() {
+            group.push_str(text);
+        } else if self.tag_editor.is_some() {
+            self.paste_into_tag_editor(text);
+        } else if self.modal_group.is_none() && (!self.keys.vim || self.vim_insert) {
+            self.edit_modal_text(|field, at| ui_helpers::insert_str(field, at, text));
+        }
+    }
+
     pub(super) fn handle_key(&mut self, key: KeyEvent) -> bool {
-        if self.in_category_modal() {
+        self.last_key_at = Instant::now();
+        if self.stopwatch_mode || self.pomodoro.is_some() {
+            matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+        } else if self.idle_prompt.is_some() {
+            self.handle_idle_prompt_key(key);
+            false
+        } else if self.zen {
+            self.exit_zen();
+            false
+        } else if self.in_migration_modal() {
+            self.handle_migration_key(key);
+            false
+        } else if self.in_category_modal() {
             self.handle_modal_key(key);
             false
         } else if self.in_karma_modal() {
             self.handle_report_modal_key(key);
             false
+        } else if self.in_history_modal() {
+            self.handle_history_key(key);
+            false
+        } else if self.in_stats_view() {
+            self.handle_stats_key(key);
+            false
+        } else if self.in_settings_modal() {
+            self.handle_settings_key(key);
+            false
+        } else if self.in_help_modal() {
+            self.ui_mode = UiMode::Main;
+            self.render_needed = true;
+            false
+        } else if self.contract_prompt.is_some() {
+            self.handle_contract_prompt_key(key);
+            false
+        } else if self.plan_selected.is_some() {
+            self.handle_plan_key(key);
+            false
+        } else if self.confirm_reset_idle {
+            self.confirm_reset_idle = false;
+            self.render_needed = true;
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.reset_idle_today();
+            }
+            false
+        } else if let Some(session_id) = self.focus_prompt_session.take() {
+            self.render_needed = true;
+            match key.code {
+                KeyCode::Char(digit @ '1'..='5') => {
+                    self.rate_session_focus(session_id, digit as u8 - b'0');
+                    false
+                }
+                KeyCode::Esc => false,
+                _ => self.handle_normal_key(key),
+            }
         } else {
             self.handle_normal_key(key)
         }
     }
 
+    /// Applies `translate_vim_key` when vim keys are on in the config.
+    pub(super) fn vim_key(&mut self, key: KeyEvent) -> Option<KeyEvent> {
+        if self.keys.vim {
+            ui_helpers::translate_vim_key(&mut self.vim_pending, key)
+        } else {
+            Some(key)
+        }
+    }
+
     fn handle_modal_key(&mut self, key: KeyEvent) {
+        if self.confirm_delete_category {
+            self.confirm_delete_category = false;
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.delete_category();
+            }
+            return;
+        }
+
+        if let Some(query) = self.category_filter.as_mut() {
+            match key.code {
+                KeyCode::Esc => {
+                    self.category_filter = None;
+                    self.sync_modal_description_from_selection();
+                    self.reveal_modal_selection();
+                }
+                KeyCode::Enter => self.activate_filtered_category(),
+                KeyCode::Up => self.move_filtered_selection(false),
+                KeyCode::Down => self.move_filtered_selection(true),
+                KeyCode::Char(c) => {
+                    query.push(c);
+                    self.select_best_filtered_category();
+                }
+                KeyCode::Backspace => {
+                    if query.pop().is_none() {
+                        self.category_filter = None;
+                        self.sync_modal_description_from_selection();
+                        self.reveal_modal_selection();
+                    } else {
+                        self.select_best_filtered_category();
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        if let Some(name) = self.renaming_category.as_mut() {
+            match key.code {
+                KeyCode::Esc => self.renaming_category = None,
+                KeyCode::Enter => self.finish_category_rename(),
+                KeyCode::Char(c) => name.push(c),
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        if let Some(hex) = self.editing_color.as_mut() {
+            match key.code {
+                KeyCode::Esc => self.editing_color = None,
+                KeyCode::Enter => self.finish_color_edit(),
+                KeyCode::Char(c) if c.is_ascii_hexdigit() && hex.len() < 6 => {
+                    hex.push(c.to_ascii_lowercase());
+                }
+                KeyCode::Backspace => {
+                    hex.pop();
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        if self.tag_editor.is_some() {
+            self.handle_tag_editor_key(key);
+            return;
+        }
+
+        if let Some(group) = self.editing_group.as_mut() {
+            match key.code {
+                KeyCode::Esc => self.editing_group = None,
+                KeyCode::Enter => self.finish_group_edit(),
+                KeyCode::Char(c) => group.push(c),
+                KeyCode::Backspace => {
+                    group.pop();
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        // With vim keys the modal starts in normal mode; `i` types into the
+        // description or new name until `esc`.
+        let key = if self.vim_insert {
+            if key.code == KeyCode::Esc {
+                self.vim_insert = false;
+                return;
+            }
+            key
+        } else {
+            let Some(key) = self.vim_key(key) else {
+                return;
+            };
+            if self.keys.vim && key.code == KeyCode::Char('i') {
+                self.vim_insert = true;
+                return;
+            }
+            key
+        };
+        let typing = !self.keys.vim || self.vim_insert;
         let shift = key.modifiers.contains(KeyModifiers::SHIFT);
 
+        // On a group header, left folds it, right unfolds it and enter
+        // toggles; moving and closing work as on any row.
+        if let Some(group) = self.modal_group.clone() {
+            match key.code {
+                KeyCode::Left | KeyCode::Right => {
+                    self.set_group_collapsed(&group, key.code == KeyCode::Left);
+                    return;
+                }
+                KeyCode::Enter => {
+                    let collapsed = self.category_groups.is_collapsed(&group);
+                    self.set_group_collapsed(&group, !collapsed);
+                    return;
+                }
+                KeyCode::Up | KeyCode::Down if !shift => {}
+                KeyCode::Home | KeyCode::End | KeyCode::Esc => {}
+                _ => return,
+            }
+        }
+
         match key.code {
             KeyCode::Esc => self.close_modal(),
+            KeyCode::Left | KeyCode::Right | KeyCode::Home | KeyCode::End
+                if typing && !shift && self.is_editing_modal_text() =>
+            {
+                self.move_modal_text_cursor(key.code);
+            }
+            KeyCode::Home | KeyCode::End => {
+                self.modal_group = None;
+                self.selected_index = if key.code == KeyCode::Home {
+                    0
+                } else {
+                    self.time_tracker.category_count()
+                };
+                self.sync_modal_description_from_selection();
+            }
             KeyCode::Up => {
                 if shift {
                     if self.time_tracker.move_category_up(self.selected_index) {
@@ -32,12 +270,7 @@ impl App {
                         self.persist_categories();
                     }
                 } else {
-                    let total_rows = self.time_tracker.category_count() + 1;
-                    if total_rows > 0 {
-                        self.selected_index =
-                            ui_helpers::wrap_prev_index(self.selected_index, total_rows);
-                        self.sync_modal_description_from_selection();
-                    }
+                    self.move_modal_cursor(false);
                 }
             }
             KeyCode::Down => {
@@ -47,12 +280,7 @@ impl App {
                         self.persist_categories();
                     }
                 } else {
-                    let total_rows = self.time_tracker.category_count() + 1;
-                    if total_rows > 0 {
-                        self.selected_index =
-                            ui_helpers::wrap_next_index(self.selected_index, total_rows);
-                        self.sync_modal_description_from_selection();
-                    }
+                    self.move_modal_cursor(true);
                 }
             }
             KeyCode::Left => {
@@ -115,99 +343,125 @@ impl App {
                     }
                 } else {
                     if self.selected_index < self.time_tracker.category_count() {
-                        if self.time_tracker.set_category_description_by_index(
-                            self.selected_index,
-                            self.modal_description.clone(),
-                        ) {
-                            self.persist_categories();
-                        }
                         self.remember_selected_tag();
                     }
                     if self.time_tracker.active_category_index() != Some(self.selected_index) {
-                        self.time_tracker.end_session();
-                        self.persist_sessions();
-                        let _ = self
-                            .time_tracker
-                            .set_active_category_by_index(self.selected_index);
-                        self.time_tracker.start_session();
+                        self.request_category_switch(CategorySwitch {
+                            index: self.selected_index,
+                            description: Some(self.modal_description.clone()),
+                        });
+                    } else {
+                        self.time_tracker
+                            .set_session_description(self.modal_description.clone());
                     }
                     self.close_modal();
                 }
             }
-            KeyCode::Char('x') => {
-                if !self.is_on_insert_space() && self.selected_index > 0 {
-                    self.delete_category();
+            KeyCode::Tab => {
+                if let Some(index) = self.modal_category_suggestion() {
+                    self.selected_index = index;
+                    self.reveal_modal_selection();
+                } else {
+                    self.save_selected_default_description();
                 }
             }
-            KeyCode::Char('+') | KeyCode::Char('=') => {
-                if !self.is_on_insert_space()
-                    && self.selected_index > 0
-                    && self.selected_index < self.time_tracker.category_count()
-                    && self
-                        .time_tracker
-                        .set_category_karma_by_index(self.selected_index, 1)
-                {
-                    self.persist_categories();
-                }
+            KeyCode::F(2) => self.start_category_rename(),
+            KeyCode::F(3) => self.start_color_edit(),
+            KeyCode::F(4) => self.start_group_edit(),
+            KeyCode::F(5) => self.start_tag_edit(),
+            KeyCode::Char('t') if !typing => self.start_tag_edit(),
+            KeyCode::Char('w') if typing && key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.edit_modal_text(ui_helpers::delete_word_before);
             }
-            KeyCode::Char('-') | KeyCode::Char('_') => {
-                if !self.is_on_insert_space()
-                    && self.selected_index > 0
-                    && self.selected_index < self.time_tracker.category_count()
-                    && self
-                        .time_tracker
-                        .set_category_karma_by_index(self.selected_index, -1)
-                {
-                    self.persist_categories();
-                }
+            KeyCode::Char('/') => {
+                self.modal_group = None;
+                self.category_filter = Some(String::new());
+                self.select_best_filtered_category();
             }
-            KeyCode::Char(c) => {
-                if self.is_on_insert_space() {
-                    self.new_category_name.push(c);
-                } else if self.selected_index < self.time_tracker.category_count() {
-                    self.modal_tag_index = None;
-                    self.modal_description.push(c);
-                }
-            }
-            KeyCode::Backspace => {
-                if self.is_on_insert_space() {
-                    self.new_category_name.pop();
-                } else if self.selected_index < self.time_tracker.category_count() {
-                    self.modal_tag_index = None;
-                    self.modal_description.pop();
-                }
+            KeyCode::Char('x') | KeyCode::Delete => self.request_delete_category(),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.set_selected_karma(1),
+            KeyCode::Char('-') | KeyCode::Char('_') => self.set_selected_karma(-1),
+            KeyCode::Char(c) if typing => {
+                self.edit_modal_text(|text, at| ui_helpers::insert_char(text, at, c));
             }
+            KeyCode::Backspace if typing => self.edit_modal_text(ui_helpers::delete_char_before),
             _ => {}
         }
     }
 
     fn handle_report_modal_key(&mut self, key: KeyEvent) {
-        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
-
         let summary = self.report_rows();
         self.clamp_report_selection(summary.entries.len());
         let logs = self.report_current_logs();
         self.clamp_report_log_selection(logs.len());
         let in_logs_view = self.report_logs_category_id.is_some();
 
+        if self.report_overlap.is_some() {
+            match key.code {
+                KeyCode::Char('t') | KeyCode::Char('T') => {
+                    self.apply_overlap_resolution(OverlapResolution::Trim);
+                }
+                KeyCode::Char('m') | KeyCode::Char('M')
+                    if self
+                        .report_overlap
+                        .is_some_and(|overlap| self.overlap_merge_allowed(&overlap)) =>
+                {
+                    self.apply_overlap_resolution(OverlapResolution::Merge);
+                }
+                KeyCode::Char('k') | KeyCode::Char('K') => {
+                    self.apply_overlap_resolution(OverlapResolution::KeepBoth);
+                }
+                KeyCode::Esc => self.report_overlap = None,
+                _ => {}
+            }
+            self.render_needed = true;
+            return;
+        }
+
+        if let Some(action) = self.report_bulk {
+            match (action, key.code) {
+                (BulkAction::Delete, KeyCode::Char('y') | KeyCode::Char('Y'))
+                | (BulkAction::Move(_), KeyCode::Enter) => self.apply_bulk_action(),
+                (BulkAction::Move(_), KeyCode::Left) => self.cycle_bulk_move(false),
+                (BulkAction::Move(_), KeyCode::Right) => self.cycle_bulk_move(true),
+                _ => self.report_bulk = None,
+            }
+            self.render_needed = true;
+            return;
+        }
+
+        let Some(key) = self.vim_key(key) else {
+            return;
+        };
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let open_logs = |app: &mut Self| {
+            if let Some(entry) = summary.entries.get(app.report_selected_index)
+                && entry.category_id != CategoryId::new(0)
+            {
+                app.report_logs_category_id = Some(entry.category_id);
+                app.record_usage("tui report logs");
+                app.report_log_selected_index = 0;
+            }
+        };
+        let close_logs = |app: &mut Self| {
+            app.report_logs_category_id = None;
+            app.report_log_selected_index = 0;
+            app.clear_report_marks();
+        };
+
         match key.code {
             KeyCode::Esc => {
                 if in_logs_view {
-                    self.report_logs_category_id = None;
-                    self.report_log_selected_index = 0;
+                    close_logs(self);
                 } else {
                     self.close_report_modal();
                 }
             }
             KeyCode::Enter => {
                 if in_logs_view {
-                    self.report_logs_category_id = None;
-                    self.report_log_selected_index = 0;
-                } else if let Some(entry) = summary.entries.get(self.report_selected_index)
-                    && entry.category_id != CategoryId::new(0)
-                {
-                    self.report_logs_category_id = Some(entry.category_id);
-                    self.report_log_selected_index = 0;
+                    close_logs(self);
+                } else {
+                    open_logs(self);
                 }
             }
             KeyCode::Up => {
@@ -242,15 +496,69 @@ impl App {
             KeyCode::Right if shift => {
                 self.set_report_period(ui_helpers::report_period_next(self.report_period));
             }
+            KeyCode::Left if self.report_range.is_some() && !in_logs_view => {
+                self.shift_report_range(-1);
+            }
+            KeyCode::Right if self.report_range.is_some() && !in_logs_view => {
+                self.shift_report_range(1);
+            }
+            KeyCode::Tab if self.report_range.is_some() => self.toggle_report_range_side(),
+            KeyCode::Char('r') | KeyCode::Char('R') if !in_logs_view => {
+                self.toggle_report_range();
+            }
+            KeyCode::Left if in_logs_view => close_logs(self),
+            KeyCode::Right if !in_logs_view => open_logs(self),
+            KeyCode::Home | KeyCode::End => {
+                let last = if in_logs_view {
+                    logs.len()
+                } else {
+                    summary.entries.len()
+                }
+                .saturating_sub(1);
+                let index = if key.code == KeyCode::Home { 0 } else { last };
+                if in_logs_view {
+                    self.report_log_selected_index = index;
+                } else {
+                    self.report_selected_index = index;
+                }
+            }
             KeyCode::Char('d') | KeyCode::Char('D') => {
                 self.set_report_period(ReportPeriod::Today);
             }
+            KeyCode::Char('h') | KeyCode::Char('H') => {
+                self.set_report_period(ReportPeriod::Last24Hours);
+            }
             KeyCode::Char('w') | KeyCode::Char('W') => {
                 self.set_report_period(ReportPeriod::Week);
             }
             KeyCode::Char('m') | KeyCode::Char('M') => {
                 self.set_report_period(ReportPeriod::Month);
             }
+            KeyCode::Char('o') | KeyCode::Char('O') if in_logs_view => {
+                self.report_overlap = self.selected_log_overlap();
+            }
+            KeyCode::Char(' ') if in_logs_view => {
+                self.toggle_log_mark();
+            }
+            KeyCode::Char('V') if in_logs_view => {
+                self.mark_log_range();
+            }
+            KeyCode::Char('x') | KeyCode::Char('X') | KeyCode::Delete
+                if in_logs_view && !self.bulk_targets().is_empty() =>
+            {
+                self.report_bulk = Some(BulkAction::Delete);
+            }
+            KeyCode::Char('r') | KeyCode::Char('R')
+                if in_logs_view && !self.bulk_targets().is_empty() =>
+            {
+                self.start_bulk_move();
+            }
+            KeyCode::Char('c') | KeyCode::Char('C') if !in_logs_view => {
+                self.report_chart = !self.report_chart;
+                if self.report_chart {
+                    self.record_usage("tui report chart");
+                }
+            }
             KeyCode::Char('?') => {
                 self.report_show_help = !self.report_show_help;
             }
@@ -265,31 +573,111 @@ impl App {
 
         match key.code {
             KeyCode::Char('q') => true,
-            KeyCode::Char('c') | KeyCode::Char('C') => {
-                let is_shift_clear = shift || matches!(key.code, KeyCode::Char('C'));
-                if is_shift_clear {
+            KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&self.keys.clear_sand) => {
+                self.push_undo(UndoAction::ClearSand {
+                    sand: self.sand_engine.snapshot_state(),
+                });
+                if shift || ch.is_ascii_uppercase() {
                     self.sand_engine.clear_category(CategoryId::new(0));
                 } else {
                     self.sand_engine.clear();
-                    self.time_tracker.reset_none_counter_today();
-                    self.persist_sessions();
                 }
                 self.persist_sand_state();
                 false
             }
+            KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&self.keys.reset_idle) => {
+                self.confirm_reset_idle = true;
+                self.render_needed = true;
+                false
+            }
             KeyCode::Char('k') | KeyCode::Char('K') => {
                 self.open_report_modal();
                 false
             }
+            KeyCode::Char('p') | KeyCode::Char('P') => {
+                if self.time_tracker.active_category_index() != Some(0) {
+                    self.time_tracker.toggle_pause();
+                    self.render_needed = true;
+                }
+                false
+            }
+            KeyCode::Char('V') => {
+                self.toggle_split_pane();
+                false
+            }
+            KeyCode::Char('v') => {
+                self.visualization = match self.visualization {
+                    Visualization::Sand => Visualization::Strata,
+                    Visualization::Strata => Visualization::Sand,
+                };
+                if self.visualization == Visualization::Strata {
+                    self.record_usage("tui strata view");
+                }
+                self.render_needed = true;
+                false
+            }
             KeyCode::Enter => {
                 self.open_modal();
                 false
             }
             KeyCode::Esc => {
-                self.time_tracker.end_session();
-                self.persist_sessions();
-                let _ = self.time_tracker.set_active_category_by_index(0);
-                self.time_tracker.start_session();
+                self.request_category_switch(CategorySwitch {
+                    index: 0,
+                    description: None,
+                });
+                false
+            }
+            KeyCode::Tab => {
+                self.toggle_recent_category();
+                false
+            }
+            KeyCode::Char(digit @ '1'..='9') => {
+                self.quick_switch_category(digit as usize - '0' as usize);
+                false
+            }
+            KeyCode::Char('l') | KeyCode::Char('L') => {
+                self.open_contract_prompt();
+                false
+            }
+            KeyCode::Char('t') | KeyCode::Char('T') => {
+                self.open_plan();
+                false
+            }
+            KeyCode::Char('h') | KeyCode::Char('H') => {
+                self.open_history();
+                false
+            }
+            KeyCode::Char('s') | KeyCode::Char('S') => {
+                self.open_stats();
+                false
+            }
+            KeyCode::Char('d') | KeyCode::Char('D') => {
+                self.toggle_timeline();
+                false
+            }
+            KeyCode::Char('b') | KeyCode::Char('B') => {
+                self.toggle_totals_strip();
+                false
+            }
+            KeyCode::Char('e') | KeyCode::Char('E') => {
+                self.cycle_theme();
+                false
+            }
+            KeyCode::Char('o') | KeyCode::Char('O') => {
+                self.open_settings();
+                false
+            }
+            KeyCode::Char('z') | KeyCode::Char('Z') => {
+                self.enter_zen();
+                false
+            }
+            KeyCode::Char('u') | KeyCode::Char('U') => {
+                self.undo_last();
+                false
+            }
+            KeyCode::Char('?') => {
+                self.ui_mode = UiMode::HelpModal;
+                self.render_needed = true;
                 false
             }
             _ => false,
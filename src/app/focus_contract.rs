@@ -0,0 +1,191 @@
+use chrono::{DateTime, Utc};
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::{
+    constants::FOCUS_CONTRACT, domain::CategoryId, duration::parse_duration, storage::FocusContract,
+};
+
+use super::{App, save_queue::SaveTarget};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct CategorySwitch {
+    pub index: usize,
+    pub description: Option<String>,
+}
+
+/// Text prompts owned by the focus contract: choosing its length, and typing
+/// the break phrase before a switch away from the locked category.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum ContractPrompt {
+    Length(String),
+    Break {
+        switch: CategorySwitch,
+        typed: String,
+    },
+}
+
+impl App {
+    pub(super) fn active_contract(&self) -> Option<FocusContract> {
+        self.focus_contract.active_at(Utc::now())
+    }
+
+    pub(super) fn contract_remaining_seconds(&self) -> Option<usize> {
+        let contract = self.active_contract()?;
+        Some((contract.ends_at - Utc::now()).num_seconds().max(0) as usize)
+    }
+
+    pub(super) fn open_contract_prompt(&mut self) {
+        if self.time_tracker.active_category_index() == Some(0) || self.active_contract().is_some()
+        {
+            return;
+        }
+        self.contract_prompt = Some(ContractPrompt::Length(
+            FOCUS_CONTRACT.default_length.to_string(),
+        ));
+        self.render_needed = true;
+    }
+
+    /// Closes out a contract whose window has passed, counting it as kept.
+    pub(super) fn check_contract(&mut self) {
+        if self.focus_contract.expire(Utc::now()) {
+            self.save(SaveTarget::FocusContract);
+            self.render_needed = true;
+        }
+    }
+
+    /// Re-activates the locked category after a restart.
+    pub(super) fn restore_focus_contract(&mut self) {
+        self.check_contract();
+        let Some(contract) = self.active_contract() else {
+            return;
+        };
+        let index = self
+            .time_tracker
+            .categories_ordered()
+            .position(|category| category.id == CategoryId::new(contract.category_id));
+        match index {
+            Some(index) => {
+                let _ = self.time_tracker.set_active_category_by_index(index);
+            }
+            None => self.finish_contract(None),
+        }
+    }
+
+    /// Switches categories unless a contract on the current one forbids it,
+    /// in which case the break phrase prompt opens instead.
+    pub(super) fn request_category_switch(&mut self, switch: CategorySwitch) {
+        if self.active_contract().is_some()
+            && self.time_tracker.active_category_index() != Some(switch.index)
+        {
+            self.contract_prompt = Some(ContractPrompt::Break {
+                switch,
+                typed: String::new(),
+            });
+            self.render_needed = true;
+            return;
+        }
+        self.switch_category(switch);
+    }
+
+    fn switch_category(&mut self, switch: CategorySwitch) {
+        self.end_session();
+        self.persist_sessions();
+        let _ = self.time_tracker.set_active_category_by_index(switch.index);
+        if let Some(description) = switch.description {
+            self.time_tracker.set_session_description(description);
+        }
+        self.start_session();
+        self.render_needed = true;
+    }
+
+    pub(super) fn paste_into_contract_prompt(&mut self, text: &str) {
+        match self.contract_prompt.as_mut() {
+            Some(ContractPrompt::Length(input)) => input.push_str(text),
+            Some(ContractPrompt::Break { typed, .. }) => typed.push_str(text),
+            None => {}
+        }
+    }
+
+    pub(super) fn handle_contract_prompt_key(&mut self, key: KeyEvent) {
+        let Some(prompt) = self.contract_prompt.take() else {
+            return;
+        };
+        self.render_needed = true;
+
+        match (prompt, key.code) {
+            (_, KeyCode::Esc) => {}
+            (ContractPrompt::Length(input), KeyCode::Enter) => {
+                if let Some(seconds) = parse_duration(&input).filter(|seconds| *seconds > 0) {
+                    self.start_contract(seconds);
+                } else {
+                    self.contract_prompt = Some(ContractPrompt::Length(input));
+                }
+            }
+            (ContractPrompt::Break { switch, typed }, KeyCode::Enter) => {
+                if typed.trim() == FOCUS_CONTRACT.break_phrase {
+                    self.finish_contract(Some(Utc::now()));
+                    self.switch_category(switch);
+                } else {
+                    self.contract_prompt = Some(ContractPrompt::Break { switch, typed });
+                }
+            }
+            (mut prompt, code) => {
+                let text = match &mut prompt {
+                    ContractPrompt::Length(input) => input,
+                    ContractPrompt::Break { typed, .. } => typed,
+                };
+                match code {
+                    KeyCode::Char(c) => text.push(c),
+                    KeyCode::Backspace => {
+                        text.pop();
+                    }
+                    _ => {}
+                }
+                self.contract_prompt = Some(prompt);
+            }
+        }
+    }
+
+    fn start_contract(&mut self, seconds: usize) {
+        let category_id = self.time_tracker.active_category_id();
+        if category_id == CategoryId::new(0) {
+            return;
+        }
+        self.focus_contract
+            .start(category_id.0, Utc::now(), seconds);
+        self.save(SaveTarget::FocusContract);
+    }
+
+    fn finish_contract(&mut self, broken_at: Option<DateTime<Utc>>) {
+        if self.focus_contract.finish(broken_at) {
+            self.save(SaveTarget::FocusContract);
+            self.render_needed = true;
+        }
+    }
+
+    pub(super) fn contract_prompt_text(&self) -> Option<String> {
+        match self.contract_prompt.as_ref()? {
+            ContractPrompt::Length(input) => Some(format!(
+                "lock {} for: {}_ · enter/esc",
+                self.category_name_for_id(self.time_tracker.active_category_id()),
+                input
+            )),
+            ContractPrompt::Break { typed, .. } => Some(format!(
+                "locked · type '{}' to switch: {}_",
+                FOCUS_CONTRACT.break_phrase, typed
+            )),
+        }
+    }
+
+    /// Kept and total contracts for a category.
+    pub(super) fn contract_record(&self, category_id: CategoryId) -> (usize, usize) {
+        let records = self
+            .focus_contract
+            .history
+            .iter()
+            .filter(|record| record.category_id == category_id.0);
+        records.fold((0, 0), |(kept, total), record| {
+            (kept + usize::from(record.broken_at.is_none()), total + 1)
+        })
+    }
+}
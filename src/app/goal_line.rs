@@ -0,0 +1,88 @@
+use rand::Rng;
+use ratatui::{
+    prelude::{Line, Span},
+    style::{Color, Style},
+};
+
+use crate::{
+    constants::{GOAL_LINE, SAND_ENGINE, TIME_SETTINGS},
+    domain::CategoryId,
+    notify,
+};
+
+use super::App;
+
+impl App {
+    fn goal_grains(&self) -> Option<usize> {
+        self.daily_goal_seconds
+            .map(|seconds| seconds * 1000 / TIME_SETTINGS.tick_ms as usize)
+    }
+
+    /// Grains dropped while none ran; they fill the glass too but don't
+    /// count toward the goal.
+    fn idle_grains(&self) -> usize {
+        self.sand_engine.category_grains(CategoryId::new(0))
+    }
+
+    pub(super) fn check_goal_line(&mut self) {
+        let Some(goal_grains) = self.goal_grains() else {
+            return;
+        };
+        let tracked = self
+            .sand_engine
+            .grain_count
+            .saturating_sub(self.idle_grains());
+        let reached = tracked >= goal_grains;
+        if self.goal_reached == Some(false) && reached {
+            self.goal_sparkle_frames = GOAL_LINE.sparkle_frames;
+            if self.notifications.goals
+                && let Some(goal) = self.daily_goal_seconds
+            {
+                notify::desktop_notification(
+                    "strata goal reached",
+                    &format!("{} tracked today", self.format_time(goal)),
+                );
+            }
+        }
+        self.goal_reached = Some(reached);
+    }
+
+    pub(super) fn tick_goal_sparkle(&mut self) {
+        self.goal_sparkle_frames = self.goal_sparkle_frames.saturating_sub(1);
+    }
+
+    /// Marks the height the pile reaches once the goal is tracked, on top of
+    /// the idle grains already in it.
+    pub(super) fn overlay_goal_line(&self, lines: &mut [Line<'static>]) {
+        let Some(row) = self.goal_grains().and_then(|grains| {
+            self.sand_engine
+                .cell_row_for_grains(grains + self.idle_grains())
+        }) else {
+            return;
+        };
+        let Some(line) = lines.get_mut(row) else {
+            return;
+        };
+
+        let empty_cell = char::from_u32(SAND_ENGINE.braille_base).unwrap_or(' ');
+        let mut rng = rand::thread_rng();
+        for span in line.spans.iter_mut() {
+            if self.goal_sparkle_frames > 0 {
+                if rng.gen_ratio(1, 3) {
+                    let sparkle = GOAL_LINE.sparkles[rng.gen_range(0..GOAL_LINE.sparkles.len())];
+                    let color = if rng.gen_bool(0.5) {
+                        Color::Yellow
+                    } else {
+                        self.theme().text
+                    };
+                    *span = Span::styled(sparkle.to_string(), Style::default().fg(color));
+                }
+            } else if span.content.chars().all(|c| c == empty_cell) {
+                *span = Span::styled(
+                    GOAL_LINE.dash.to_string(),
+                    Style::default().fg(Color::DarkGray),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use ratatui::prelude::{Line, Span};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph},
+};
+
+use super::{App, theme::Theme};
+
+impl App {
+    fn help_sections(&self) -> Vec<(&'static str, Vec<(String, &'static str)>)> {
+        let key = |text: &str| text.to_string();
+        let mut sections = vec![
+            (
+                "main",
+                vec![
+                    (key("enter"), "choose category"),
+                    (key("esc"), "stop (back to none)"),
+                    (key("tab"), "back to the previous category"),
+                    (key("1-9"), "switch to the nth category"),
+                    (key("p"), "pause / resume"),
+                    (key("l"), "lock category (focus contract)"),
+                    (key("t"), "today's plan checklist"),
+                    (key("k"), "karma report"),
+                    (key("h"), "session history"),
+                    (key("s"), "weekly stats"),
+                    (key("d"), "today's timeline strip"),
+                    (key("b"), "today's totals strip"),
+                    (key("e"), "cycle color theme"),
+                    (key("o"), "settings"),
+                    (key("z"), "zen mode (any key returns)"),
+                    (key("v"), "sand / strata view"),
+                    (key("V"), "split view: sand + karma report"),
+                    (self.keys.clear_sand.to_string(), "clear sand"),
+                    (
+                        self.keys.clear_sand.to_ascii_uppercase().to_string(),
+                        "clear idle sand",
+                    ),
+                    (self.keys.reset_idle.to_string(), "reset today's idle"),
+                    (key("u"), "undo delete / clear / reset"),
+                    (key("?"), "this help"),
+                    (key("q"), "quit"),
+                ],
+            ),
+            (
+                "category modal",
+                vec![
+                    (key("up/down"), "select"),
+                    (key("shift+up/down"), "reorder"),
+                    (key("left/right"), "cycle tags"),
+                    (key("shift+left/right"), "change color"),
+                    (key("type"), "description / new name"),
+                    (key("ctrl+w"), "delete word (then ←/→ move)"),
+                    (key("tab"), "suggestion / save default"),
+                    (key("+ / -"), "karma"),
+                    (key("f2"), "rename category"),
+                    (key("f3"), "custom hex color"),
+                    (key("f4"), "set group (empty ungroups)"),
+                    (key("f5"), "edit tags"),
+                    (key("left/right"), "on a group: fold / unfold"),
+                    (key("/"), "fuzzy filter (enter switches)"),
+                    (key("x"), "delete category (y/n)"),
+                    (key("enter"), "switch"),
+                    (key("esc"), "close"),
+                ],
+            ),
+            (
+                "plan checklist",
+                vec![
+                    (key("up/down"), "select"),
+                    (key("enter"), "start tracking it"),
+                    (key("d / space"), "check off / reopen"),
+                    (key("esc / t"), "close"),
+                ],
+            ),
+            (
+                "session history",
+                vec![
+                    (key("left/right"), "filter category"),
+                    (key("enter"), "edit session"),
+                    (key("del"), "delete session (y/n)"),
+                    (key("tab"), "next field"),
+                    (key("esc / h"), "cancel / close"),
+                ],
+            ),
+            (
+                "karma report",
+                vec![
+                    (key("up/down"), "select"),
+                    (key("enter"), "session logs"),
+                    (key("c"), "per-day chart"),
+                    (key("d / h / w / m"), "day / 24h / week / month"),
+                    (key("shift+left/right"), "previous / next period"),
+                    (key("r"), "custom range (←/→ date, tab start/end)"),
+                    (key("?"), "report keys"),
+                    (key("esc / k"), "back / close"),
+                ],
+            ),
+        ];
+        if self.keys.vim {
+            sections.push((
+                "vim keys (modals, history, report)",
+                vec![
+                    (key("j / k"), "down / up"),
+                    (key("h / l"), "left / right"),
+                    (key("gg / G"), "first / last"),
+                    (key("dd"), "delete"),
+                    (key("i / esc"), "type in category modal / stop"),
+                ],
+            ));
+        }
+        sections
+    }
+
+    pub(super) fn render_help(&self, f: &mut Frame, terminal_size: Rect) {
+        let mut sections = self.help_sections();
+        let modal_sections = sections.split_off(1);
+        let main_lines = help_lines(sections, self.theme());
+        let modal_lines = help_lines(modal_sections, self.theme());
+
+        // Main keys on the left, modal keys on the right when they fit side
+        // by side; otherwise one column that may clip on short terminals.
+        let main_width = main_lines.iter().map(Line::width).max().unwrap_or(0);
+        let modal_width = modal_lines.iter().map(Line::width).max().unwrap_or(0);
+        let gap = 3;
+        let lines = if main_width + gap + modal_width + 4 <= terminal_size.width as usize {
+            let rows = main_lines.len().max(modal_lines.len());
+            let mut main_lines = main_lines.into_iter();
+            let mut modal_lines = modal_lines.into_iter();
+            (0..rows)
+                .map(|_| {
+                    let left = main_lines.next().unwrap_or_default();
+                    let padding = main_width + gap - left.width();
+                    let mut spans = left.spans;
+                    spans.push(Span::raw(" ".repeat(padding)));
+                    spans.extend(modal_lines.next().unwrap_or_default().spans);
+                    Line::from(spans)
+                })
+                .collect()
+        } else {
+            let mut lines = main_lines;
+            lines.push(Line::from(""));
+            lines.extend(modal_lines);
+            lines
+        };
+
+        let inner_width = lines.iter().map(Line::width).max().unwrap_or(0) as u16;
+        let width = (inner_width + 4).min(terminal_size.width.saturating_sub(2).max(1));
+        let height = (lines.len() as u16 + 2).min(terminal_size.height.saturating_sub(2).max(1));
+        let modal_rect = Rect::new(
+            terminal_size.width.saturating_sub(width) / 2,
+            terminal_size.height.saturating_sub(height) / 2,
+            width,
+            height,
+        );
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(
+                Line::from(Span::styled(
+                    "keys",
+                    Style::default()
+                        .fg(self.theme().text)
+                        .add_modifier(Modifier::BOLD),
+                ))
+                .alignment(Alignment::Center),
+            )
+            .title_bottom(
+                Line::from(Span::styled(
+                    "any key closes",
+                    Style::default().fg(self.theme().muted),
+                ))
+                .alignment(Alignment::Center),
+            )
+            .border_style(Style::default().fg(self.theme().border))
+            .padding(Padding::horizontal(1));
+
+        f.render_widget(Clear, modal_rect);
+        f.render_widget(Paragraph::new(lines).block(block), modal_rect);
+    }
+}
+
+fn help_lines(
+    sections: Vec<(&'static str, Vec<(String, &'static str)>)>,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let key_width = sections
+        .iter()
+        .flat_map(|(_, keys)| keys.iter())
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for (title, keys) in sections {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(
+            title,
+            Style::default().fg(theme.muted),
+        )));
+        for (key, action) in keys {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{:<width$}  ", key, width = key_width),
+                    Style::default().fg(theme.text).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(action, Style::default().fg(theme.text)),
+            ]));
+        }
+    }
+    lines
+}
@@ -0,0 +1,594 @@
+use std::collections::HashSet;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::{Line, Span};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use crate::{
+    domain::{CategoryId, Session, SessionQuery, remove_sessions},
+    duration::{format_duration, parse_duration},
+};
+
+use super::{App, UiMode};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum EditField {
+    Description,
+    Category,
+    Elapsed,
+}
+
+/// In-progress edit of one stored session from the history modal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct SessionEdit {
+    session_id: usize,
+    field: EditField,
+    description: String,
+    category_index: usize,
+    elapsed: String,
+    invalid: bool,
+}
+
+/// Where the history list is: the selected row clamped to the rows, and
+/// its page of `page_rows`.
+#[derive(Debug, PartialEq, Eq)]
+struct HistoryPage {
+    selected: usize,
+    index: usize,
+    count: usize,
+}
+
+fn history_page(selected: usize, rows: usize, page_rows: usize) -> HistoryPage {
+    let selected = selected.min(rows.saturating_sub(1));
+    HistoryPage {
+        selected,
+        index: selected.checked_div(page_rows).unwrap_or(0),
+        count: rows.div_ceil(page_rows.max(1)).max(1),
+    }
+}
+
+/// The selection after a navigation key, kept within `count` rows.
+fn moved_history_selection(selected: usize, key: KeyCode, count: usize, page_rows: usize) -> usize {
+    let page = page_rows.max(1);
+    let last = count.saturating_sub(1);
+    match key {
+        KeyCode::Up => selected.saturating_sub(1),
+        KeyCode::Down => (selected + 1).min(last),
+        KeyCode::PageUp => selected.saturating_sub(page),
+        KeyCode::PageDown => (selected + page).min(last),
+        KeyCode::Home => 0,
+        KeyCode::End => last,
+        _ => selected,
+    }
+}
+
+/// Stored sessions, newest first, narrowed to `filter`'s category.
+fn filtered_history(sessions: &[Session], filter: Option<CategoryId>) -> Vec<&Session> {
+    let query = match filter {
+        Some(category_id) => SessionQuery::new().category(category_id),
+        None => SessionQuery::new(),
+    };
+    query.run(sessions).sessions
+}
+
+/// The filter after `current`: all sessions, then each category in order,
+/// wrapping around.
+fn next_history_filter(
+    categories: &[CategoryId],
+    current: Option<CategoryId>,
+    forward: bool,
+) -> Option<CategoryId> {
+    let mut options: Vec<Option<CategoryId>> = vec![None];
+    options.extend(categories.iter().copied().map(Some));
+    let current = options
+        .iter()
+        .position(|option| *option == current)
+        .unwrap_or(0);
+    let next = if forward {
+        (current + 1) % options.len()
+    } else {
+        (current + options.len() - 1) % options.len()
+    };
+    options[next]
+}
+
+impl App {
+    pub(super) fn open_history(&mut self) {
+        self.record_usage("tui history");
+        self.ui_mode = UiMode::HistoryModal;
+        self.history_selected = 0;
+        self.history_filter = None;
+        self.history_edit = None;
+        self.history_confirm_delete = None;
+        self.render_needed = true;
+    }
+
+    /// Stored sessions, newest first, narrowed to the category filter.
+    pub(super) fn history_rows(&self) -> Vec<&Session> {
+        filtered_history(&self.time_tracker.sessions, self.history_filter)
+    }
+
+    fn cycle_history_filter(&mut self, forward: bool) {
+        let categories: Vec<CategoryId> = self
+            .time_tracker
+            .categories_ordered()
+            .map(|category| category.id)
+            .collect();
+        self.history_filter = next_history_filter(&categories, self.history_filter, forward);
+        self.history_selected = 0;
+    }
+
+    fn start_history_edit(&mut self) {
+        let rows = self.history_rows();
+        let Some(session) = rows.get(self.history_selected.min(rows.len().saturating_sub(1)))
+        else {
+            return;
+        };
+        let category_index = self
+            .time_tracker
+            .categories_ordered()
+            .position(|category| category.id == session.category_id)
+            .unwrap_or(0);
+        self.history_edit = Some(SessionEdit {
+            session_id: session.id,
+            field: EditField::Description,
+            description: session.description.clone(),
+            category_index,
+            elapsed: format_duration(session.elapsed_seconds),
+            invalid: false,
+        });
+    }
+
+    fn handle_history_edit_key(&mut self, key: KeyEvent) {
+        let Some(mut edit) = self.history_edit.take() else {
+            return;
+        };
+        let category_count = self.time_tracker.category_count().max(1);
+
+        match (edit.field, key.code) {
+            (_, KeyCode::Esc) => return,
+            (_, KeyCode::Enter) => {
+                let category_id = self
+                    .time_tracker
+                    .category_by_index(edit.category_index)
+                    .map(|category| category.id);
+                let elapsed = parse_duration(&edit.elapsed).filter(|seconds| *seconds > 0);
+                if let (Some(category_id), Some(elapsed)) = (category_id, elapsed)
+                    && self.time_tracker.edit_session(
+                        edit.session_id,
+                        category_id,
+                        &edit.description,
+                        elapsed,
+                    )
+                {
+                    self.persist_sessions();
+                    self.show_status("session updated".to_string());
+                    return;
+                }
+                edit.invalid = true;
+            }
+            (_, KeyCode::Tab | KeyCode::Down) => {
+                edit.field = match edit.field {
+                    EditField::Description => EditField::Category,
+                    EditField::Category => EditField::Elapsed,
+                    EditField::Elapsed => EditField::Description,
+                };
+            }
+            (_, KeyCode::BackTab | KeyCode::Up) => {
+                edit.field = match edit.field {
+                    EditField::Description => EditField::Elapsed,
+                    EditField::Category => EditField::Description,
+                    EditField::Elapsed => EditField::Category,
+                };
+            }
+            (EditField::Category, KeyCode::Left) => {
+                edit.category_index = (edit.category_index + category_count - 1) % category_count;
+            }
+            (EditField::Category, KeyCode::Right) => {
+                edit.category_index = (edit.category_index + 1) % category_count;
+            }
+            (EditField::Description, KeyCode::Char(c)) => edit.description.push(c),
+            (EditField::Description, KeyCode::Backspace) => {
+                edit.description.pop();
+            }
+            (EditField::Elapsed, KeyCode::Char(c)) => {
+                edit.elapsed.push(c);
+                edit.invalid = false;
+            }
+            (EditField::Elapsed, KeyCode::Backspace) => {
+                edit.elapsed.pop();
+                edit.invalid = false;
+            }
+            _ => {}
+        }
+        self.history_edit = Some(edit);
+    }
+
+    /// Only the edit form's description and duration take pasted text.
+    pub(super) fn paste_into_history_edit(&mut self, text: &str) {
+        let Some(edit) = self.history_edit.as_mut() else {
+            return;
+        };
+        match edit.field {
+            EditField::Description => edit.description.push_str(text),
+            EditField::Elapsed => {
+                edit.elapsed.push_str(text);
+                edit.invalid = false;
+            }
+            EditField::Category => {}
+        }
+    }
+
+    pub(super) fn handle_history_key(&mut self, key: KeyEvent) {
+        self.render_needed = true;
+        if self.history_edit.is_some() {
+            self.handle_history_edit_key(key);
+            return;
+        }
+
+        if let Some(session_id) = self.history_confirm_delete.take() {
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.delete_history_session(session_id);
+            }
+            return;
+        }
+
+        let Some(key) = self.vim_key(key) else {
+            return;
+        };
+        let count = self.history_rows().len();
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('h') | KeyCode::Char('H') | KeyCode::Char('q') => {
+                self.ui_mode = UiMode::Main;
+            }
+            KeyCode::Up
+            | KeyCode::Down
+            | KeyCode::PageUp
+            | KeyCode::PageDown
+            | KeyCode::Home
+            | KeyCode::End => {
+                self.history_selected = moved_history_selection(
+                    self.history_selected,
+                    key.code,
+                    count,
+                    self.history_page_rows,
+                );
+            }
+            KeyCode::Left => self.cycle_history_filter(false),
+            KeyCode::Right => self.cycle_history_filter(true),
+            KeyCode::Enter | KeyCode::Char('e') | KeyCode::Char('E') => self.start_history_edit(),
+            KeyCode::Delete => {
+                self.history_confirm_delete = self
+                    .history_rows()
+                    .get(self.history_selected)
+                    .map(|session| session.id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Moves one session to `trash.csv`, like a bulk delete in the report.
+    fn delete_history_session(&mut self, session_id: usize) {
+        let removed = remove_sessions(
+            self.time_tracker.sessions_mut(),
+            &HashSet::from([session_id]),
+        );
+        if removed.is_empty() {
+            return;
+        }
+        self.record_usage("tui history delete");
+        self.trash_sessions(removed);
+        self.persist_sessions();
+        self.history_selected = self
+            .history_selected
+            .min(self.history_rows().len().saturating_sub(1));
+        self.show_status("session moved to trash".to_string());
+    }
+
+    pub(super) fn render_history(&mut self, f: &mut Frame, terminal_size: Rect) {
+        let modal_rect = self.modal_rect_ratio(terminal_size, 2, 3);
+        let inner_width = modal_rect.width.saturating_sub(2) as usize;
+        let page_rows = modal_rect.height.saturating_sub(2) as usize;
+        self.history_page_rows = page_rows;
+
+        let rows = self.history_rows();
+        let page = history_page(self.history_selected, rows.len(), page_rows);
+        let (selected, page_index) = (page.selected, page.index);
+        let first = page_index * page_rows;
+
+        let name_width = 14usize;
+        let fixed_width = 2 + 16 + 2 + name_width + 2 + 2 + 8;
+        let description_width = inner_width.saturating_sub(fixed_width);
+        let lines: Vec<Line> = rows
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(page_rows)
+            .map(|(index, session)| {
+                let name: String = self
+                    .category_name_for_id(session.category_id)
+                    .chars()
+                    .take(name_width)
+                    .collect();
+                let description: String = session
+                    .description
+                    .chars()
+                    .take(description_width)
+                    .collect();
+                let start = session.start_time.get(..5).unwrap_or(&session.start_time);
+                let mut style = Style::default().fg(self.theme().text);
+                if index == selected {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                Line::from(vec![
+                    Span::styled("● ", self.category_color_for_id(session.category_id)),
+                    Span::styled(
+                        format!(
+                            "{} {}  {:name_width$}  {:description_width$}  {}",
+                            session.date,
+                            start,
+                            name,
+                            description,
+                            self.format_time(session.elapsed_seconds),
+                        ),
+                        style,
+                    ),
+                ])
+            })
+            .collect();
+
+        let filter = match self.history_filter {
+            Some(category_id) => self.category_name_for_id(category_id),
+            None => "all".to_string(),
+        };
+        let position = format!(
+            " {}/{} · page {}/{} ",
+            if rows.is_empty() { 0 } else { selected + 1 },
+            rows.len(),
+            page_index + 1,
+            page.count
+        );
+        let border_color = match self.history_filter {
+            Some(category_id) => self.category_color_for_id(category_id),
+            None => self.theme().border,
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(
+                Line::from(Span::styled(
+                    "history",
+                    Style::default()
+                        .fg(self.theme().text)
+                        .add_modifier(Modifier::BOLD),
+                ))
+                .alignment(Alignment::Center),
+            )
+            .title(
+                Line::from(Span::styled(
+                    format!(" ← {} → ", filter),
+                    Style::default().fg(self.theme().text),
+                ))
+                .alignment(Alignment::Left),
+            )
+            .title_bottom(
+                Line::from(Span::styled(
+                    position,
+                    Style::default().fg(self.theme().muted),
+                ))
+                .alignment(Alignment::Right),
+            )
+            .title_bottom(
+                Line::from(if self.history_confirm_delete.is_some() {
+                    Span::styled(
+                        " delete this session? y/n ",
+                        Style::default().fg(self.theme().text),
+                    )
+                } else {
+                    Span::styled(
+                        " enter edit · del delete · pgup/pgdn · home/end · esc ",
+                        Style::default().fg(Color::DarkGray),
+                    )
+                })
+                .alignment(Alignment::Left),
+            )
+            .border_style(Style::default().fg(border_color));
+
+        let body = if lines.is_empty() {
+            Paragraph::new(Line::from(Span::styled(
+                "no sessions",
+                Style::default().fg(self.theme().muted),
+            )))
+        } else {
+            Paragraph::new(lines)
+        };
+        f.render_widget(Clear, modal_rect);
+        f.render_widget(body.block(block), modal_rect);
+        self.render_history_edit(f, modal_rect);
+    }
+
+    fn render_history_edit(&self, f: &mut Frame, area: Rect) {
+        let Some(edit) = self.history_edit.as_ref() else {
+            return;
+        };
+        let category = self
+            .time_tracker
+            .category_by_index(edit.category_index)
+            .map(|category| {
+                (
+                    category.name.clone(),
+                    self.theme().category_color(category.color),
+                )
+            })
+            .unwrap_or_else(|| ("none".to_string(), self.theme().border));
+
+        let field_line = |field: EditField, label: &str, value: String| {
+            let active = edit.field == field;
+            let value_style = if active {
+                Style::default()
+                    .fg(self.theme().text)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(self.theme().muted)
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{:12}", label),
+                    Style::default().fg(self.theme().muted),
+                ),
+                Span::styled(value, value_style),
+                Span::styled(if active { "_" } else { "" }, value_style),
+            ])
+        };
+        let lines = vec![
+            field_line(
+                EditField::Description,
+                "description",
+                edit.description.clone(),
+            ),
+            field_line(
+                EditField::Category,
+                "category",
+                format!("← {} →", category.0),
+            ),
+            field_line(EditField::Elapsed, "duration", edit.elapsed.clone()),
+        ];
+
+        let width = area.width.saturating_sub(4).min(60);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let rect = Rect::new(
+            area.x + (area.width.saturating_sub(width)) / 2,
+            area.y + (area.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        );
+        let hint = if edit.invalid {
+            Span::styled(
+                " invalid duration, e.g. 25m or 1h30m ",
+                Style::default().fg(Color::Red),
+            )
+        } else {
+            Span::styled(
+                " tab next · enter save · esc cancel ",
+                Style::default().fg(Color::DarkGray),
+            )
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(
+                Line::from(Span::styled(
+                    format!(" edit session {} ", edit.session_id),
+                    Style::default().fg(self.theme().text),
+                ))
+                .alignment(Alignment::Center),
+            )
+            .title_bottom(Line::from(hint).alignment(Alignment::Center))
+            .border_style(Style::default().fg(category.1));
+
+        f.render_widget(Clear, rect);
+        f.render_widget(Paragraph::new(lines).block(block), rect);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: usize, category_id: u64, date: &str) -> Session {
+        Session {
+            id,
+            date: date.to_string(),
+            category_id: CategoryId::new(category_id),
+            description: String::new(),
+            project: String::new(),
+            start_time: "09:00:00".to_string(),
+            end_time: "10:00:00".to_string(),
+            elapsed_seconds: 3600,
+            focus: None,
+        }
+    }
+
+    #[test]
+    fn test_history_filter_narrows_to_a_category_and_cycles() {
+        let sessions = vec![
+            session(1, 1, "2026-03-01"),
+            session(2, 2, "2026-03-02"),
+            session(3, 1, "2026-03-03"),
+        ];
+        let ids = |rows: Vec<&Session>| rows.iter().map(|s| s.id).collect::<Vec<_>>();
+
+        assert_eq!(ids(filtered_history(&sessions, None)), [3, 2, 1]);
+        assert_eq!(
+            ids(filtered_history(&sessions, Some(CategoryId::new(1)))),
+            [3, 1]
+        );
+        assert!(filtered_history(&sessions, Some(CategoryId::new(9))).is_empty());
+
+        let categories = [CategoryId::new(1), CategoryId::new(2)];
+        assert_eq!(
+            next_history_filter(&categories, None, true),
+            Some(CategoryId::new(1))
+        );
+        assert_eq!(
+            next_history_filter(&categories, Some(CategoryId::new(2)), true),
+            None
+        );
+        assert_eq!(
+            next_history_filter(&categories, None, false),
+            Some(CategoryId::new(2))
+        );
+    }
+
+    #[test]
+    fn test_history_paging_stays_within_the_rows() {
+        assert_eq!(moved_history_selection(0, KeyCode::PageDown, 25, 10), 10);
+        assert_eq!(moved_history_selection(20, KeyCode::PageDown, 25, 10), 24);
+        assert_eq!(moved_history_selection(24, KeyCode::Down, 25, 10), 24);
+        assert_eq!(moved_history_selection(5, KeyCode::PageUp, 25, 10), 0);
+        assert_eq!(moved_history_selection(3, KeyCode::End, 25, 10), 24);
+        assert_eq!(moved_history_selection(3, KeyCode::Home, 25, 10), 0);
+        // Before the first draw the page size is unknown.
+        assert_eq!(moved_history_selection(0, KeyCode::PageDown, 25, 0), 1);
+
+        assert_eq!(
+            history_page(24, 25, 10),
+            HistoryPage {
+                selected: 24,
+                index: 2,
+                count: 3
+            }
+        );
+        // A selection left past the end, e.g. after narrowing the filter.
+        assert_eq!(
+            history_page(40, 12, 10),
+            HistoryPage {
+                selected: 11,
+                index: 1,
+                count: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_history_with_no_rows_has_one_empty_page() {
+        for key in [KeyCode::Down, KeyCode::PageDown, KeyCode::End, KeyCode::Up] {
+            assert_eq!(moved_history_selection(0, key, 0, 10), 0);
+        }
+        assert_eq!(
+            history_page(3, 0, 10),
+            HistoryPage {
+                selected: 0,
+                index: 0,
+                count: 1
+            }
+        );
+    }
+}
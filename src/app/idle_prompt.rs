@@ -0,0 +1,200 @@
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    prelude::{Line, Span},
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use crate::domain::CategoryId;
+
+use super::{App, ui_helpers};
+
+/// Asked after `idle_prompt` passes without a key press while a category
+/// is running. `reassign` holds the category list cursor once `r` is pressed.
+#[derive(Debug)]
+pub(super) struct IdlePrompt {
+    idle_since: Instant,
+    reassign: Option<usize>,
+}
+
+impl App {
+    pub(super) fn check_idle_prompt(&mut self) {
+        let Some(limit) = self.idle_prompt_after else {
+            return;
+        };
+        let running = self.time_tracker.current_session_start.is_some()
+            && !self.time_tracker.is_paused()
+            && self.time_tracker.active_category_id() != CategoryId::new(0);
+        if self.idle_prompt.is_some()
+            || !running
+            || self.stopwatch_mode
+            || self.pomodoro.is_some()
+            || self.last_key_at.elapsed().as_secs() < limit as u64
+        {
+            return;
+        }
+        self.idle_prompt = Some(IdlePrompt {
+            idle_since: self.last_key_at,
+            reassign: None,
+        });
+        self.render_needed = true;
+    }
+
+    fn idle_prompt_seconds(&self) -> usize {
+        self.idle_prompt
+            .as_ref()
+            .map_or(0, |prompt| prompt.idle_since.elapsed().as_secs() as usize)
+    }
+
+    /// Categories the idle stretch can go to: all but the running one.
+    fn reassign_targets(&self) -> Vec<CategoryId> {
+        let active = self.time_tracker.active_category_id();
+        self.time_tracker
+            .categories_ordered()
+            .map(|category| category.id)
+            .filter(|id| *id != active)
+            .collect()
+    }
+
+    pub(super) fn handle_idle_prompt_key(&mut self, key: KeyEvent) {
+        let targets = self.reassign_targets();
+        let Some(prompt) = self.idle_prompt.as_mut() else {
+            return;
+        };
+        self.render_needed = true;
+
+        if let Some(selected) = prompt.reassign {
+            match key.code {
+                KeyCode::Up => {
+                    prompt.reassign = Some(ui_helpers::wrap_prev_index(selected, targets.len()));
+                }
+                KeyCode::Down => {
+                    prompt.reassign = Some(ui_helpers::wrap_next_index(selected, targets.len()));
+                }
+                KeyCode::Enter => {
+                    if let Some(&target) = targets.get(selected) {
+                        self.resolve_idle(Some(target));
+                    }
+                }
+                KeyCode::Esc => prompt.reassign = None,
+                _ => {}
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('k') | KeyCode::Char('K') | KeyCode::Enter | KeyCode::Esc => {
+                self.idle_prompt = None;
+            }
+            KeyCode::Char('d') | KeyCode::Char('D') => self.resolve_idle(None),
+            KeyCode::Char('r') | KeyCode::Char('R') if !targets.is_empty() => {
+                prompt.reassign = Some(0);
+            }
+            _ => {}
+        }
+    }
+
+    /// Cuts the idle stretch off the running session; it is dropped, or
+    /// recorded for `target`, and the session carries on from now.
+    fn resolve_idle(&mut self, target: Option<CategoryId>) {
+        let idle = self.idle_prompt_seconds();
+        self.idle_prompt = None;
+        let before = self.time_tracker.sessions.len();
+        if self.time_tracker.split_idle_tail(idle, target).is_none() {
+            return;
+        }
+        for session in &self.time_tracker.sessions[before..] {
+            self.suggestion_index.add_session(session);
+        }
+        self.persist_sessions();
+
+        let message = match target {
+            Some(id) => format!(
+                "{} moved to {}",
+                self.format_time(idle),
+                self.category_name_for_id(id)
+            ),
+            None => format!("{} discarded", self.format_time(idle)),
+        };
+        self.show_status(message);
+    }
+
+    pub(super) fn render_idle_prompt(&self, f: &mut Frame, area: Rect) {
+        let Some(prompt) = self.idle_prompt.as_ref() else {
+            return;
+        };
+        let idle = self.format_time(self.idle_prompt_seconds());
+        let active = self.time_tracker.active_category_id();
+        let name = self.category_name_for_id(active);
+
+        let mut lines = vec![
+            Line::from(Span::styled(
+                format!("no key pressed for {}", idle),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Line::from(format!("keep the last {} on {}?", idle, name)),
+        ];
+        let footer = if let Some(selected) = prompt.reassign {
+            lines.push(Line::from(""));
+            for (index, id) in self.reassign_targets().into_iter().enumerate() {
+                let color = self
+                    .time_tracker
+                    .category_color_by_id(id)
+                    .map_or(self.theme().text, |color| {
+                        self.theme().category_color(color)
+                    });
+                let style = if index == selected {
+                    Style::default().fg(Color::Black).bg(color)
+                } else {
+                    Style::default().fg(color)
+                };
+                lines.push(Line::from(Span::styled(
+                    format!(" {} ", self.category_name_for_id(id)),
+                    style,
+                )));
+            }
+            " ↑/↓ choose · enter reassign · esc back "
+        } else {
+            " k keep · d discard · r reassign "
+        };
+
+        let width = lines
+            .iter()
+            .map(Line::width)
+            .chain([footer.chars().count()])
+            .max()
+            .unwrap_or(0) as u16
+            + 4;
+        let width = width.min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let rect = Rect::new(
+            area.x + area.width.saturating_sub(width) / 2,
+            area.y + area.height.saturating_sub(height) / 2,
+            width,
+            height,
+        );
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title_bottom(
+                Line::from(Span::styled(
+                    footer,
+                    Style::default().fg(self.theme().muted),
+                ))
+                .alignment(Alignment::Center),
+            )
+            .border_style(Style::default().fg(self.theme().text));
+
+        f.render_widget(Clear, rect);
+        f.render_widget(
+            Paragraph::new(lines)
+                .alignment(Alignment::Center)
+                .block(block),
+            rect,
+        );
+    }
+}
@@ -0,0 +1,410 @@
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::{Line, Span};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph, Wrap},
+};
+
+use crate::{
+    domain::{Category, Session},
+    storage::{self, CsvHeaderStatus},
+};
+
+use super::{App, UiMode};
+
+const PROGRESS_WIDTH: usize = 30;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MigrationTarget {
+    Categories,
+    Sessions,
+}
+
+impl MigrationTarget {
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Categories => "categories.csv",
+            Self::Sessions => "time_log.csv",
+        }
+    }
+
+    fn path(self) -> PathBuf {
+        storage::get_data_dir().join(self.file_name())
+    }
+
+    /// The file at `path` when it needs upgrading, with `rows` to rewrite.
+    fn inspect(self, path: &Path, rows: usize) -> Option<MigrationFile> {
+        let (status, added_columns) = match self {
+            Self::Categories => (
+                storage::categories_header_status(path),
+                storage::categories_added_columns(path),
+            ),
+            Self::Sessions => (
+                storage::sessions_header_status(path),
+                storage::sessions_added_columns(path),
+            ),
+        };
+        let status = status.ok()?;
+        matches!(
+            status,
+            CsvHeaderStatus::Legacy | CsvHeaderStatus::Positional
+        )
+        .then_some(MigrationFile {
+            target: self,
+            status,
+            added_columns,
+            rows,
+        })
+    }
+
+    /// Writes the loaded data back to `path` in the current schema.
+    fn rewrite(
+        self,
+        path: &Path,
+        categories: &[Category],
+        sessions: &[Session],
+    ) -> Result<(), String> {
+        match self {
+            Self::Categories => storage::save_categories_to_csv(path, categories),
+            Self::Sessions => storage::save_sessions_to_csv(path, sessions, categories),
+        }
+    }
+}
+
+/// A data file written by an older strata or with renamed headers.
+#[derive(Debug)]
+struct MigrationFile {
+    target: MigrationTarget,
+    status: CsvHeaderStatus,
+    added_columns: Vec<&'static str>,
+    rows: usize,
+}
+
+impl MigrationFile {
+    fn change(&self) -> String {
+        let mut change = match self.status {
+            CsvHeaderStatus::Positional => "restores the column names".to_string(),
+            _ => "upgrades to the current columns".to_string(),
+        };
+        if !self.added_columns.is_empty() {
+            change.push_str(&format!(", adds {}", self.added_columns.join(", ")));
+        }
+        change
+    }
+}
+
+/// Guided upgrade shown at startup: review, then a backup and a rewrite per
+/// file, one step per frame so the progress bar moves.
+#[derive(Debug)]
+pub(super) struct Migration {
+    files: Vec<MigrationFile>,
+    completed: Option<usize>,
+    results: Vec<(bool, String)>,
+}
+
+impl Migration {
+    fn total_steps(&self) -> usize {
+        self.files.len() * 2
+    }
+
+    fn is_running(&self) -> bool {
+        self.completed
+            .is_some_and(|completed| completed < self.total_steps())
+    }
+
+    fn is_finished(&self) -> bool {
+        self.completed == Some(self.total_steps())
+    }
+}
+
+impl App {
+    /// Opens the assistant when either CSV file needs upgrading.
+    pub(super) fn check_migration(&mut self) {
+        let candidates = [
+            (
+                MigrationTarget::Categories,
+                self.time_tracker.category_count().saturating_sub(1),
+            ),
+            (MigrationTarget::Sessions, self.time_tracker.sessions.len()),
+        ];
+        let files: Vec<MigrationFile> = candidates
+            .into_iter()
+            .filter_map(|(target, rows)| target.inspect(&target.path(), rows))
+            .collect();
+        if files.is_empty() {
+            return;
+        }
+
+        self.migration = Some(Migration {
+            files,
+            completed: None,
+            results: Vec::new(),
+        });
+        self.ui_mode = UiMode::MigrationModal;
+        self.render_needed = true;
+    }
+
+    pub(super) fn handle_migration_key(&mut self, key: KeyEvent) {
+        let Some(migration) = self.migration.as_mut() else {
+            self.ui_mode = UiMode::Main;
+            return;
+        };
+        self.render_needed = true;
+
+        if migration.is_finished() {
+            self.migration = None;
+            self.ui_mode = UiMode::Main;
+        } else if migration.completed.is_none() {
+            match key.code {
+                KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    migration.completed = Some(0);
+                }
+                KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Char('q') => {
+                    self.migration = None;
+                    self.ui_mode = UiMode::Main;
+                    self.show_status("migration skipped".to_string());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Runs the next backup or rewrite once the previous step has been drawn.
+    pub(super) fn step_migration(&mut self) {
+        if self.render_needed || !self.migration.as_ref().is_some_and(Migration::is_running) {
+            return;
+        }
+        let categories = self.time_tracker.categories_for_storage();
+        let Some(migration) = self.migration.as_mut() else {
+            return;
+        };
+        let Some(step) = migration.completed else {
+            return;
+        };
+        let file = &migration.files[step / 2];
+        let path = file.target.path();
+        let name = file.target.file_name();
+
+        if step % 2 == 0 {
+            match storage::create_backup(&path) {
+                Ok(()) => migration.completed = Some(step + 1),
+                Err(e) => {
+                    migration
+                        .results
+                        .push((false, format!("{}: backup failed, left as is: {}", name, e)));
+                    migration.completed = Some(step + 2);
+                }
+            }
+        } else {
+            let result = file
+                .target
+                .rewrite(&path, &categories, &self.time_tracker.sessions);
+            migration.results.push(match result {
+                Ok(()) => (
+                    true,
+                    format!("{}: backed up, {} rows rewritten", name, file.rows),
+                ),
+                Err(e) => (false, format!("{}: rewrite failed: {}", name, e)),
+            });
+            migration.completed = Some(step + 1);
+        }
+        self.render_needed = true;
+    }
+
+    pub(super) fn render_migration(&self, f: &mut Frame, terminal_size: Rect) {
+        let Some(migration) = self.migration.as_ref() else {
+            return;
+        };
+        let modal_rect = self.modal_rect_ratio(terminal_size, 2, 3);
+        let gray = Style::default().fg(self.theme().muted);
+        let white = Style::default().fg(self.theme().text);
+
+        let mut lines = vec![
+            Line::from(Span::styled(
+                format!(
+                    "Data in {} was written by an older strata:",
+                    storage::get_data_dir().display()
+                ),
+                white,
+            )),
+            Line::from(""),
+        ];
+        for file in &migration.files {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{:16}", file.target.file_name()),
+                    white.add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(format!("{} rows · {}", file.rows, file.change()), gray),
+            ]));
+        }
+        lines.push(Line::from(""));
+
+        let footer = match migration.completed {
+            None => {
+                lines.push(Line::from(Span::styled(
+                    "Each file is copied to backups/ before it is rewritten.",
+                    gray,
+                )));
+                " enter migrate · esc later "
+            }
+            Some(completed) => {
+                let total = migration.total_steps().max(1);
+                let filled = completed * PROGRESS_WIDTH / total;
+                let step = if migration.is_finished() {
+                    "done".to_string()
+                } else {
+                    let file = migration.files[completed / 2].target.file_name();
+                    if completed % 2 == 0 {
+                        format!("backing up {}", file)
+                    } else {
+                        format!("rewriting {}", file)
+                    }
+                };
+                lines.push(Line::from(vec![
+                    Span::styled("█".repeat(filled), white),
+                    Span::styled("░".repeat(PROGRESS_WIDTH - filled), gray),
+                    Span::styled(format!(" {}/{} {}", completed, total, step), gray),
+                ]));
+                if !migration.results.is_empty() {
+                    lines.push(Line::from(""));
+                }
+                for (ok, result) in &migration.results {
+                    let (mark, color) = if *ok {
+                        ("✓ ", Color::Green)
+                    } else {
+                        ("✗ ", Color::Red)
+                    };
+                    lines.push(Line::from(vec![
+                        Span::styled(mark, Style::default().fg(color)),
+                        Span::styled(result.clone(), white),
+                    ]));
+                }
+                if migration.is_finished() {
+                    " any key continues "
+                } else {
+                    " migrating… "
+                }
+            }
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(
+                Line::from(Span::styled(
+                    "migrate data",
+                    white.add_modifier(Modifier::BOLD),
+                ))
+                .alignment(Alignment::Center),
+            )
+            .title_bottom(Line::from(Span::styled(footer, gray)).alignment(Alignment::Center))
+            .border_style(Style::default().fg(self.theme().border))
+            .padding(Padding::horizontal(1));
+
+        f.render_widget(Clear, modal_rect);
+        f.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(block),
+            modal_rect,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::SystemTime};
+
+    use super::*;
+
+    fn unique_dir(prefix: &str) -> PathBuf {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let dir = PathBuf::from(format!("/tmp/{}_{}", prefix, now));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn backup_of(dir: &Path, file_name: &str) -> String {
+        let entry = fs::read_dir(dir.join("backups"))
+            .unwrap()
+            .flatten()
+            .find(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(&format!("{}.", file_name))
+            })
+            .expect("a backup");
+        fs::read_to_string(entry.path()).unwrap()
+    }
+
+    #[test]
+    fn test_a_legacy_dataset_is_backed_up_and_converted() {
+        let dir = unique_dir("strata_migration");
+        let categories_path = dir.join(MigrationTarget::Categories.file_name());
+        let sessions_path = dir.join(MigrationTarget::Sessions.file_name());
+        let legacy_categories = "id,name,description,color_index,karma_effect\n\
+                                 0,none,,0,1\n\
+                                 1,Work,deep work,2,1\n";
+        let legacy_sessions = "id,date,category_id,category_name,description,start_time,end_time,elapsed_seconds\n\
+                               1,2026-03-02,1,Work,spec,09:00:00,10:00:00,3600\n";
+        fs::write(&categories_path, legacy_categories).unwrap();
+        fs::write(&sessions_path, legacy_sessions).unwrap();
+
+        let categories = storage::load_categories_from_csv(&categories_path).categories;
+        let sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+        let files: Vec<MigrationFile> = [
+            (MigrationTarget::Categories, &categories_path, 1),
+            (MigrationTarget::Sessions, &sessions_path, sessions.len()),
+        ]
+        .into_iter()
+        .filter_map(|(target, path, rows)| target.inspect(path, rows))
+        .collect();
+        assert_eq!(files.len(), 2);
+        assert!(
+            files
+                .iter()
+                .all(|file| file.status == CsvHeaderStatus::Legacy)
+        );
+        assert_eq!(files[1].added_columns, ["project", "focus"]);
+
+        for (file, path) in files.iter().zip([&categories_path, &sessions_path]) {
+            storage::create_backup(path).unwrap();
+            file.target.rewrite(path, &categories, &sessions).unwrap();
+        }
+
+        assert_eq!(backup_of(&dir, "categories.csv"), legacy_categories);
+        assert_eq!(backup_of(&dir, "time_log.csv"), legacy_sessions);
+        assert!(
+            MigrationTarget::Categories
+                .inspect(&categories_path, 1)
+                .is_none()
+        );
+        assert!(
+            MigrationTarget::Sessions
+                .inspect(&sessions_path, 1)
+                .is_none()
+        );
+        assert_eq!(
+            storage::sessions_header_status(&sessions_path).unwrap(),
+            CsvHeaderStatus::Recognized
+        );
+
+        let migrated = storage::load_categories_from_csv(&categories_path).categories;
+        assert_eq!(migrated, categories);
+        let migrated = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+        assert_eq!(migrated.len(), 1);
+        assert_eq!(migrated[0].description, "spec");
+        assert_eq!(migrated[0].elapsed_seconds, 3600);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
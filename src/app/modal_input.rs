@@ -0,0 +1,72 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    prelude::Span,
+    style::{Modifier, Style},
+};
+
+use super::App;
+
+impl App {
+    /// The field the category modal types into: the new name on the insert
+    /// row, the description on a category row.
+    fn modal_text(&mut self) -> Option<&mut String> {
+        if self.is_on_insert_space() {
+            Some(&mut self.new_category_name)
+        } else if self.selected_index < self.time_tracker.category_count() {
+            Some(&mut self.modal_description)
+        } else {
+            None
+        }
+    }
+
+    /// Applies `edit` at the text cursor, which starts at the end of the
+    /// field, and keeps the cursor it returns.
+    pub(super) fn edit_modal_text(&mut self, edit: impl FnOnce(&mut String, usize) -> usize) {
+        let cursor = self.modal_text_cursor;
+        let Some(text) = self.modal_text() else {
+            return;
+        };
+        let at = cursor.unwrap_or(usize::MAX).min(text.chars().count());
+        let next = edit(text, at);
+        self.modal_text_cursor = Some(next);
+        self.modal_tag_index = None;
+    }
+
+    /// Left/right/home/end once editing has placed a text cursor.
+    pub(super) fn move_modal_text_cursor(&mut self, code: KeyCode) {
+        let Some(cursor) = self.modal_text_cursor else {
+            return;
+        };
+        let Some(text) = self.modal_text() else {
+            return;
+        };
+        let len = text.chars().count();
+        let at = cursor.min(len);
+        self.modal_text_cursor = Some(match code {
+            KeyCode::Left => at.saturating_sub(1),
+            KeyCode::Right => (at + 1).min(len),
+            KeyCode::Home => 0,
+            KeyCode::End => len,
+            _ => at,
+        });
+    }
+
+    pub(super) fn is_editing_modal_text(&self) -> bool {
+        self.modal_text_cursor.is_some()
+    }
+
+    /// `text` with the cursor drawn as a reversed cell while editing.
+    pub(super) fn modal_input_spans(&self, text: &str, style: Style) -> Vec<Span<'static>> {
+        let Some(cursor) = self.modal_text_cursor else {
+            return vec![Span::styled(text.to_string(), style)];
+        };
+        let mut chars = text.chars();
+        let before: String = chars.by_ref().take(cursor).collect();
+        let under = chars.next().map_or(" ".to_string(), String::from);
+        vec![
+            Span::styled(before, style),
+            Span::styled(under, style.add_modifier(Modifier::REVERSED)),
+            Span::styled(chars.collect::<String>(), style),
+        ]
+    }
+}
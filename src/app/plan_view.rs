@@ -0,0 +1,201 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::{Line, Span};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use crate::{
+    domain::{CategoryId, operational_day_key_now, planned_item_seconds},
+    duration::format_duration,
+    storage::{self, PlanItem},
+};
+
+use super::{App, focus_contract::CategorySwitch, save_queue::SaveTarget};
+
+impl App {
+    /// Picks up items pinned from the CLI since the TUI started.
+    pub(super) fn reload_plan(&mut self) {
+        self.plan = storage::load_plan(&storage::get_plan_path(), operational_day_key_now());
+    }
+
+    fn plan_items(&self) -> &[PlanItem] {
+        if self.plan.day == Some(operational_day_key_now()) {
+            &self.plan.items
+        } else {
+            &[]
+        }
+    }
+
+    pub(super) fn open_plan(&mut self) {
+        self.reload_plan();
+        let items = self.plan_items();
+        if items.is_empty() {
+            return;
+        }
+        let first_open = items.iter().position(|item| !item.is_done()).unwrap_or(0);
+        self.plan_selected = Some(first_open);
+        self.render_needed = true;
+    }
+
+    pub(super) fn handle_plan_key(&mut self, key: KeyEvent) {
+        let Some(selected) = self.plan_selected else {
+            return;
+        };
+        let count = self.plan_items().len();
+        if count == 0 {
+            self.plan_selected = None;
+            return;
+        }
+        let selected = selected.min(count - 1);
+        self.render_needed = true;
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('t') | KeyCode::Char('T') => self.plan_selected = None,
+            KeyCode::Up => self.plan_selected = Some((selected + count - 1) % count),
+            KeyCode::Down => self.plan_selected = Some((selected + 1) % count),
+            KeyCode::Enter => {
+                self.plan_selected = None;
+                self.start_plan_item(selected);
+            }
+            KeyCode::Char(' ') | KeyCode::Char('d') | KeyCode::Char('D') => {
+                self.toggle_plan_item(selected);
+            }
+            _ => {}
+        }
+    }
+
+    fn start_plan_item(&mut self, index: usize) {
+        let Some(item) = self.plan_items().get(index).cloned() else {
+            return;
+        };
+        let category_id = CategoryId::new(item.category_id);
+        let Some(category_index) = self
+            .time_tracker
+            .categories_ordered()
+            .position(|category| category.id == category_id)
+        else {
+            return;
+        };
+        let description = (!item.description.is_empty()).then_some(item.description);
+        if self.time_tracker.active_category_index() == Some(category_index) {
+            if let Some(description) = description {
+                self.time_tracker.set_session_description(description);
+            }
+            return;
+        }
+        self.request_category_switch(CategorySwitch {
+            index: category_index,
+            description,
+        });
+    }
+
+    /// Checks an item off with its tracked time, or reopens a done item.
+    fn toggle_plan_item(&mut self, index: usize) {
+        let Some(item) = self.plan_items().get(index) else {
+            return;
+        };
+        let actual = if item.is_done() {
+            None
+        } else {
+            Some(self.plan_item_tracked(item))
+        };
+        self.plan.items[index].actual_seconds = actual;
+        self.save(SaveTarget::Plan);
+    }
+
+    fn plan_item_tracked(&self, item: &PlanItem) -> usize {
+        if let Some(actual) = item.actual_seconds {
+            return actual;
+        }
+        let category_id = CategoryId::new(item.category_id);
+        let logged = planned_item_seconds(
+            &self.time_tracker.sessions,
+            operational_day_key_now(),
+            category_id,
+            &item.description,
+        );
+        let description_matches = item.description.is_empty()
+            || self
+                .time_tracker
+                .session_description()
+                .trim()
+                .eq_ignore_ascii_case(&item.description);
+        let running =
+            if self.time_tracker.active_category_id() == category_id && description_matches {
+                self.time_tracker
+                    .session_elapsed()
+                    .map_or(0, |elapsed| elapsed.as_secs() as usize)
+            } else {
+                0
+            };
+        logged + running
+    }
+
+    pub(super) fn render_plan(&self, f: &mut Frame, area: Rect) {
+        let items = self.plan_items();
+        if items.is_empty() || area.width < 34 || area.height < 5 {
+            return;
+        }
+
+        let width = (area.width / 2).clamp(32, 44);
+        let lines: Vec<Line> = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let tracked = format_duration(self.plan_item_tracked(item));
+                let time = match item.planned_seconds {
+                    Some(planned) => format!("{}/{}", tracked, format_duration(planned)),
+                    None => tracked,
+                };
+                let mut label = if item.description.is_empty() {
+                    self.category_name_for_id(CategoryId::new(item.category_id))
+                } else {
+                    item.description.clone()
+                };
+                if let Some(time) = item.starts_at {
+                    label = format!("{} {}", time.format("%H:%M"), label);
+                }
+                let check = if item.is_done() { "✓ " } else { "· " };
+                let label_width = (width as usize)
+                    .saturating_sub(4 + check.chars().count() + time.chars().count());
+                let label: String = label.chars().take(label_width).collect();
+                let padding = label_width.saturating_sub(label.chars().count());
+
+                let color = self.category_color_for_id(CategoryId::new(item.category_id));
+                let mut style = Style::default().fg(self.theme().text);
+                if item.is_done() {
+                    style = style
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::CROSSED_OUT);
+                }
+                if self.plan_selected == Some(index) {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                Line::from(vec![
+                    Span::styled(check, Style::default().fg(color)),
+                    Span::styled(format!("{}{} ", label, " ".repeat(padding)), style),
+                    Span::styled(time, Style::default().fg(self.theme().muted)),
+                ])
+            })
+            .collect();
+
+        let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+        let panel = Rect::new(area.x + area.width - width - 1, area.y + 1, width, height);
+        let title = if self.plan_selected.is_some() {
+            " today · enter start · d done "
+        } else {
+            " today · t "
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(Span::styled(title, Style::default().fg(self.theme().muted)))
+            .border_style(Style::default().fg(self.theme().muted));
+
+        f.render_widget(Clear, panel);
+        f.render_widget(Paragraph::new(lines).block(block), panel);
+    }
+}
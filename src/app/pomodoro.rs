@@ -0,0 +1,194 @@
+use std::time::Instant;
+
+use crate::{domain::CategoryId, notify};
+
+use super::App;
+
+/// What `strata pomodoro --tui` asks for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PomodoroSettings {
+    pub work_seconds: usize,
+    pub break_seconds: usize,
+    pub category_id: CategoryId,
+    pub cycles: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Phase {
+    Work,
+    Break,
+    Done,
+}
+
+/// The glass runs as an hourglass: it drains once per work interval, flips
+/// for the break and flips back for the next interval.
+#[derive(Debug)]
+pub(super) struct Pomodoro {
+    settings: PomodoroSettings,
+    phase: Phase,
+    phase_started: Instant,
+    phase_grains: usize,
+    completed: usize,
+}
+
+impl Pomodoro {
+    fn phase_seconds(&self) -> usize {
+        match self.phase {
+            Phase::Work => self.settings.work_seconds,
+            Phase::Break => self.settings.break_seconds,
+            Phase::Done => 0,
+        }
+    }
+
+    fn remaining_seconds(&self) -> usize {
+        self.phase_seconds()
+            .saturating_sub(self.phase_started.elapsed().as_secs() as usize)
+    }
+}
+
+impl App {
+    pub(super) fn enter_pomodoro_mode(&mut self, settings: PomodoroSettings) {
+        if self.active_contract().is_some() {
+            return;
+        }
+        let Some(index) = self
+            .time_tracker
+            .categories_ordered()
+            .position(|category| category.id == settings.category_id)
+        else {
+            return;
+        };
+
+        self.end_session();
+        let _ = self.time_tracker.set_active_category_by_index(index);
+        self.start_session();
+        self.pomodoro = Some(Pomodoro {
+            settings,
+            phase: Phase::Work,
+            phase_started: Instant::now(),
+            phase_grains: 0,
+            completed: 0,
+        });
+        self.blink_state = 0;
+        self.refill_hourglass();
+    }
+
+    /// Fills the hourglass for the time left in the phase, at the start and
+    /// whenever the glass is resized.
+    pub(super) fn refill_hourglass(&mut self) {
+        let Some(pomodoro) = self.pomodoro.as_mut() else {
+            return;
+        };
+        let total = self.sand_engine.hourglass_grains();
+        let top = match pomodoro.phase {
+            Phase::Done => 0,
+            _ => total * pomodoro.remaining_seconds() / pomodoro.phase_seconds().max(1),
+        };
+        self.sand_engine
+            .fill_hourglass(pomodoro.settings.category_id, total, top);
+        pomodoro.phase_grains = total;
+    }
+
+    /// Drips sand in step with the clock and moves on when the phase ends.
+    pub(super) fn tick_pomodoro(&mut self) {
+        let Some(pomodoro) = self.pomodoro.as_ref() else {
+            return;
+        };
+        if pomodoro.phase == Phase::Done {
+            return;
+        }
+        let length = pomodoro.phase_seconds().max(1) as f64;
+        let elapsed = pomodoro.phase_started.elapsed().as_secs_f64();
+        if elapsed >= length {
+            self.advance_pomodoro();
+            return;
+        }
+
+        let drained = (pomodoro.phase_grains as f64 * elapsed / length) as usize;
+        let keep = pomodoro.phase_grains.saturating_sub(drained);
+        while self.sand_engine.top_grains() > keep && self.sand_engine.drip() {}
+        self.render_needed = true;
+    }
+
+    fn advance_pomodoro(&mut self) {
+        let Some(mut pomodoro) = self.pomodoro.take() else {
+            return;
+        };
+        let category_index = self
+            .time_tracker
+            .categories_ordered()
+            .position(|category| category.id == pomodoro.settings.category_id)
+            .unwrap_or(0);
+
+        self.end_session();
+        let (phase, index, message) = match pomodoro.phase {
+            Phase::Work => {
+                pomodoro.completed += 1;
+                self.record_usage("tui pomodoro");
+                if pomodoro
+                    .settings
+                    .cycles
+                    .is_some_and(|cycles| pomodoro.completed >= cycles)
+                {
+                    (
+                        Phase::Done,
+                        0,
+                        format!("pomodoro done after {}", pomodoro.completed),
+                    )
+                } else if pomodoro.settings.break_seconds > 0 {
+                    (
+                        Phase::Break,
+                        0,
+                        format!(
+                            "pomodoro {} recorded · break {}",
+                            pomodoro.completed,
+                            self.format_time(pomodoro.settings.break_seconds)
+                        ),
+                    )
+                } else {
+                    (
+                        Phase::Work,
+                        category_index,
+                        format!("pomodoro {} recorded", pomodoro.completed),
+                    )
+                }
+            }
+            Phase::Break | Phase::Done => (
+                Phase::Work,
+                category_index,
+                format!("pomodoro {}: back to work", pomodoro.completed + 1),
+            ),
+        };
+        let _ = self.time_tracker.set_active_category_by_index(index);
+        self.start_session();
+        self.persist_sessions();
+
+        if phase != Phase::Done {
+            self.sand_engine.flip_hourglass();
+        }
+        pomodoro.phase = phase;
+        pomodoro.phase_started = Instant::now();
+        pomodoro.phase_grains = self.sand_engine.top_grains();
+        self.pomodoro = Some(pomodoro);
+
+        if self.notifications.pomodoro {
+            notify::desktop_notification("strata pomodoro", &message);
+        }
+        self.show_status(message);
+    }
+
+    /// " work 2 · 12:34 left " for the bottom border.
+    pub(super) fn pomodoro_label(&self) -> Option<String> {
+        let pomodoro = self.pomodoro.as_ref()?;
+        let phase = match pomodoro.phase {
+            Phase::Work => format!("work {}", pomodoro.completed + 1),
+            Phase::Break => "break".to_string(),
+            Phase::Done => return Some(format!(" {} done ", pomodoro.completed)),
+        };
+        Some(format!(
+            " {} · {} left ",
+            phase,
+            self.format_time(pomodoro.remaining_seconds())
+        ))
+    }
+}
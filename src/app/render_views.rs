@@ -2,63 +2,77 @@ use chrono::Local;
 use ratatui::prelude::{Line, Span};
 use ratatui::{
     Frame,
-    layout::Alignment,
-    style::{Color, Modifier, Style},
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
     widgets::{Block, BorderType, Borders, Paragraph},
 };
 
-use crate::constants::SAND_ENGINE;
-
-use super::App;
+use super::{App, UiMode, Visualization, timeline_view::TIMELINE_ROWS, totals_strip::TOTALS_ROWS};
 
 impl App {
     pub(super) fn draw_frame(&mut self, f: &mut Frame) {
         let size = f.size();
+        let (main_area, panel_area) = self.split_areas(size);
+        let sand_area = Rect {
+            height: main_area.height.saturating_sub(self.strip_rows()),
+            ..main_area
+        };
 
-        let inner_width = size.width.saturating_sub(2);
-        let inner_height = size.height.saturating_sub(2);
-
-        if self.sand_engine.width != inner_width * SAND_ENGINE.dot_width as u16
-            || self.sand_engine.height != inner_height * SAND_ENGINE.dot_height as u16
-        {
-            self.sand_engine.resize(inner_width, inner_height);
+        let sand = match self.visualization {
+            Visualization::Sand => {
+                let theme = self.theme();
+                let mut lines = self.sand_engine.render(
+                    self.time_tracker
+                        .categories_ordered()
+                        .map(|category| (category.id, theme.category_color(category.color))),
+                );
+                if self.pomodoro.is_none() && !self.zen {
+                    self.overlay_goal_line(&mut lines);
+                }
+                lines
+            }
+            Visualization::Strata => {
+                self.render_strata_bands(self.sand_cols(size.width), self.sand_rows(size.height))
+            }
+        };
+        if self.zen {
+            f.render_widget(Paragraph::new(sand), size);
+            self.render_idle_prompt(f, size);
+            self.render_save_warning(f, size);
+            return;
         }
-
-        let categories = self.time_tracker.categories_ordered();
-        let sand = self.sand_engine.render(&categories);
         let active_index = self.time_tracker.active_category_index();
 
         let category_name = if active_index == Some(0) {
             self.get_idle_face()
         } else if let Some(idx) = active_index {
-            categories
-                .get(idx)
+            self.time_tracker
+                .category_by_index(idx)
                 .map(|category| category.name.clone())
                 .unwrap_or_else(|| self.get_idle_face())
         } else {
             self.get_idle_face()
         };
 
-        let description = active_index
-            .and_then(|idx| {
-                categories
-                    .get(idx)
-                    .map(|category| category.description.clone())
-            })
-            .unwrap_or_default();
+        let description = self.time_tracker.session_description().to_string();
 
         let session_timer = if active_index == Some(0) {
             Local::now().format("%H:%M:%S").to_string()
-        } else if let Some(start) = self.time_tracker.current_session_start {
-            let elapsed = start.elapsed();
-            self.format_time(elapsed.as_secs() as usize)
+        } else if let Some(elapsed) = self.time_tracker.session_elapsed() {
+            let timer = self.format_time(elapsed.as_secs() as usize);
+            if self.time_tracker.is_paused() {
+                format!("paused {}", timer)
+            } else {
+                timer
+            }
         } else {
             Local::now().format("%H:%M:%S").to_string()
         };
 
         let effective_time_str = if self.in_category_modal() {
-            let cat_name = categories
-                .get(self.selected_index)
+            let cat_name = self
+                .time_tracker
+                .category_by_index(self.selected_index)
                 .map(|category| category.name.as_str())
                 .unwrap_or("none");
             let karma_time = if cat_name == "none" {
@@ -71,21 +85,26 @@ impl App {
             let karma_time = self.get_karma_adjusted_time();
             self.format_signed_time(karma_time)
         } else if let Some(idx) = active_index {
-            let cat_name = categories
-                .get(idx)
+            let cat_name = self
+                .time_tracker
+                .category_by_index(idx)
                 .map(|category| category.name.as_str())
                 .unwrap_or("none");
             let mut total = self.get_effective_time_for_category(cat_name);
-            if let Some(start) = self.time_tracker.current_session_start {
-                total += start.elapsed().as_secs() as usize;
+            if let Some(elapsed) = self.time_tracker.session_elapsed() {
+                total += elapsed.as_secs() as usize;
             }
             self.format_time(total)
         } else {
             self.format_time(self.get_effective_time_today())
         };
 
+        let goal_gauge = (self.pomodoro.is_none() && !self.stopwatch_mode)
+            .then(|| self.daily_goal_gauge())
+            .flatten();
+
         let border_color = self.get_active_color();
-        let block = Block::default()
+        let mut block = Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
             .title(
@@ -93,7 +112,7 @@ impl App {
                     Span::styled(
                         &category_name,
                         Style::default()
-                            .fg(Color::White)
+                            .fg(self.theme().text)
                             .add_modifier(Modifier::BOLD),
                     ),
                     if description.is_empty() {
@@ -102,7 +121,7 @@ impl App {
                         Span::styled(
                             format!(" {}", description),
                             Style::default()
-                                .fg(Color::White)
+                                .fg(self.theme().text)
                                 .add_modifier(Modifier::ITALIC),
                         )
                     },
@@ -110,27 +129,102 @@ impl App {
                 .alignment(Alignment::Left),
             )
             .title(
-                Line::from(Span::styled(
-                    session_timer.as_str(),
-                    Style::default().fg(Color::White),
-                ))
+                Line::from(vec![
+                    Span::styled(
+                        session_timer.as_str(),
+                        Style::default().fg(self.theme().text),
+                    ),
+                    match goal_gauge {
+                        Some(gauge) => Span::styled(
+                            format!("  {}", gauge),
+                            Style::default().fg(self.theme().text),
+                        ),
+                        None => Span::raw(""),
+                    },
+                ])
                 .alignment(Alignment::Center),
             )
             .title(
                 Line::from(Span::styled(
                     effective_time_str.as_str(),
-                    Style::default().fg(Color::White),
+                    Style::default().fg(self.theme().text),
                 ))
                 .alignment(Alignment::Right),
             )
             .border_style(Style::default().fg(border_color));
+        let prompt = if let Some(text) = self.contract_prompt_text() {
+            Some(text)
+        } else if self.confirm_reset_idle {
+            Some("delete today's idle time? y/n".to_string())
+        } else if self.focus_prompt_session.is_some() {
+            Some("focus? 1-5 · esc skip".to_string())
+        } else {
+            self.status_text().map(str::to_string)
+        };
+        if let Some(prompt) = prompt
+            && !self.in_category_modal()
+        {
+            block = block.title_bottom(
+                Line::from(Span::styled(prompt, Style::default().fg(self.theme().text)))
+                    .alignment(Alignment::Center),
+            );
+        }
+        if let Some(label) = self.pomodoro_label() {
+            block = block.title_bottom(
+                Line::from(Span::styled(label, Style::default().fg(self.theme().text)))
+                    .alignment(Alignment::Right),
+            );
+        }
+        if let Some(remaining) = self.contract_remaining_seconds() {
+            block = block.title_bottom(
+                Line::from(Span::styled(
+                    format!(" locked {} ", self.format_time(remaining)),
+                    Style::default().fg(self.theme().text),
+                ))
+                .alignment(Alignment::Right),
+            );
+        }
         let paragraph = Paragraph::new(sand).block(block);
-        f.render_widget(paragraph, size);
+        f.render_widget(paragraph, sand_area);
+        if let Some(panel) = panel_area {
+            self.render_report_panel(f, panel);
+        }
+        if self.show_timeline {
+            let strip = Rect {
+                y: sand_area.bottom(),
+                height: TIMELINE_ROWS.min(size.height),
+                ..main_area
+            };
+            self.render_timeline_strip(f, strip);
+        }
+        if self.show_totals {
+            let strip = Rect {
+                y: sand_area.bottom() + self.timeline_rows(),
+                height: TOTALS_ROWS.min(size.height),
+                ..main_area
+            };
+            self.render_totals_strip(f, strip);
+        }
+        if self.ui_mode == UiMode::Main {
+            self.render_plan(f, sand_area);
+        }
 
         if self.in_category_modal() {
             self.render_modal(f, size);
         } else if self.in_karma_modal() {
             self.render_report_modal(f, size);
+        } else if self.in_help_modal() {
+            self.render_help(f, size);
+        } else if self.in_history_modal() {
+            self.render_history(f, size);
+        } else if self.in_migration_modal() {
+            self.render_migration(f, size);
+        } else if self.in_stats_view() {
+            self.render_stats(f, size);
+        } else if self.in_settings_modal() {
+            self.render_settings(f, size);
         }
+        self.render_idle_prompt(f, size);
+        self.render_save_warning(f, size);
     }
 }
@@ -1,3 +1,4 @@
+use chrono::NaiveDate;
 use ratatui::prelude::{Line, Span};
 use ratatui::{
     Frame,
@@ -6,9 +7,14 @@ use ratatui::{
     widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph},
 };
 
-use crate::domain::{CategoryId, ReportPeriod};
+use crate::domain::{
+    CategoryId, KarmaReportSummary, PeriodLabelStyle, ReportPeriod, format_period_label,
+    period_date_bounds,
+};
+
+use super::{App, BulkAction, ui_helpers, view_style};
 
-use super::{App, ui_helpers, view_style};
+const CHART_ROWS: usize = 14;
 
 impl App {
     pub(super) fn render_report_modal(&self, f: &mut Frame, terminal_size: Rect) {
@@ -17,11 +23,18 @@ impl App {
             .report_logs_category_id
             .map(|category_id| self.report_logs_for_category(category_id));
 
-        let body_row_count = logs_for_view
-            .as_ref()
-            .map_or(summary.entries.len(), |logs| logs.len());
+        let chart_days =
+            (self.report_chart && logs_for_view.is_none()).then(|| self.report_chart_days());
 
-        let preferred_inner_width = if let Some(logs) = logs_for_view.as_ref() {
+        let body_row_count = match (logs_for_view.as_ref(), chart_days.as_ref()) {
+            (Some(logs), _) => logs.len(),
+            (None, Some(_)) => CHART_ROWS,
+            (None, None) => summary.entries.len(),
+        };
+
+        let preferred_inner_width = if let Some(days) = chart_days.as_ref() {
+            days.len() * 3
+        } else if let Some(logs) = logs_for_view.as_ref() {
             let max_detail = logs
                 .iter()
                 .map(|row| {
@@ -51,6 +64,12 @@ impl App {
 
             2 + max_name + 1 + 9
         };
+        // Both dates, the title and the total have to fit on the top border.
+        let preferred_inner_width = if self.report_range.is_some() {
+            preferred_inner_width.max(42)
+        } else {
+            preferred_inner_width
+        };
 
         let modal_rect =
             self.report_modal_rect(terminal_size, body_row_count, preferred_inner_width);
@@ -60,21 +79,52 @@ impl App {
             Some(self.report_selected_index.min(summary.entries.len() - 1))
         };
 
-        let interval_label = ui_helpers::format_report_interval_label(&summary.date);
+        let interval_label = match self.period_label_style {
+            PeriodLabelStyle::Range => ui_helpers::format_report_interval_label(&summary.date),
+            PeriodLabelStyle::Iso => {
+                let (start, end) = period_date_bounds(self.report_period);
+                format_period_label(self.report_period, start, end, self.period_label_style)
+            }
+        };
+        let period = self.report_range.is_none().then_some(self.report_period);
 
         let border_color = if let Some(category_id) = self.report_logs_category_id {
             self.category_color_for_id(category_id)
         } else {
             selected_summary_index
                 .and_then(|idx| summary.entries.get(idx))
-                .map(|entry| entry.color)
-                .unwrap_or(Color::White)
+                .map(|entry| self.theme().category_color(entry.color))
+                .unwrap_or(self.theme().text)
         };
 
-        let interval_title = Line::from(Span::styled(
-            interval_label,
-            Style::default().fg(Color::White),
-        ))
+        let interval_title = match self.report_range {
+            // The date that ←/→ move is shown reversed.
+            Some(range) => {
+                let date_style = |editing: bool| {
+                    let style = Style::default().fg(self.theme().text);
+                    if editing {
+                        style.add_modifier(Modifier::REVERSED)
+                    } else {
+                        style
+                    }
+                };
+                Line::from(vec![
+                    Span::styled(
+                        range.start.format("%Y-%m-%d").to_string(),
+                        date_style(!range.editing_end),
+                    ),
+                    Span::styled(" – ", Style::default().fg(self.theme().muted)),
+                    Span::styled(
+                        range.end.format("%Y-%m-%d").to_string(),
+                        date_style(range.editing_end),
+                    ),
+                ])
+            }
+            None => Line::from(Span::styled(
+                interval_label,
+                Style::default().fg(self.theme().text),
+            )),
+        }
         .alignment(Alignment::Left);
 
         let center_label = if let Some(category_id) = self.report_logs_category_id {
@@ -86,26 +136,27 @@ impl App {
         let center_title = Line::from(Span::styled(
             center_label,
             Style::default()
-                .fg(Color::White)
+                .fg(self.theme().text)
                 .add_modifier(Modifier::BOLD),
         ))
         .alignment(Alignment::Center);
 
         let total_title = Line::from(Span::styled(
             self.format_karma_time(summary.total_karma_seconds),
-            Style::default().fg(view_style::karma_color(summary.total_karma_seconds)),
+            Style::default().fg(self.theme().karma_color(summary.total_karma_seconds)),
         ))
         .alignment(Alignment::Right);
 
         let period_bottom_title = Line::from(vec![
-            view_style::report_period_label_span("day", self.report_period == ReportPeriod::Today),
-            Span::styled(" · ", Style::default().fg(Color::Gray)),
-            view_style::report_period_label_span("week", self.report_period == ReportPeriod::Week),
-            Span::styled(" · ", Style::default().fg(Color::Gray)),
-            view_style::report_period_label_span(
-                "month",
-                self.report_period == ReportPeriod::Month,
-            ),
+            view_style::report_period_label_span("day", period == Some(ReportPeriod::Today)),
+            Span::styled(" · ", Style::default().fg(self.theme().muted)),
+            view_style::report_period_label_span("24h", period == Some(ReportPeriod::Last24Hours)),
+            Span::styled(" · ", Style::default().fg(self.theme().muted)),
+            view_style::report_period_label_span("week", period == Some(ReportPeriod::Week)),
+            Span::styled(" · ", Style::default().fg(self.theme().muted)),
+            view_style::report_period_label_span("month", period == Some(ReportPeriod::Month)),
+            Span::styled(" · ", Style::default().fg(self.theme().muted)),
+            view_style::report_period_label_span("range", period.is_none()),
         ])
         .alignment(Alignment::Center);
 
@@ -122,7 +173,7 @@ impl App {
         f.render_widget(frame_block.clone(), modal_rect);
 
         let inner = frame_block.inner(modal_rect);
-        let footer_height = if self.report_show_help { 1 } else { 0 };
+        let footer_height = if self.report_footer_visible() { 1 } else { 0 };
         let vertical = Layout::default()
             .direction(Direction::Vertical)
             .constraints([Constraint::Min(3), Constraint::Length(footer_height)])
@@ -156,10 +207,25 @@ impl App {
                     );
                     let date_pad = date_width.saturating_sub(date.chars().count()) + 1;
 
+                    let is_marked = row
+                        .session_id
+                        .is_some_and(|id| self.report_marked.contains(&id));
+                    let mark_marker = if is_marked { "• " } else { "" };
+                    let overlap_marker = if row.overlaps { "! " } else { "" };
                     let detail_source = if row.description.trim().is_empty() {
-                        format!("{}-{}", row.start_time, row.end_time)
+                        format!(
+                            "{}{}{}-{}",
+                            mark_marker, overlap_marker, row.start_time, row.end_time
+                        )
                     } else {
-                        format!("{} · {}-{}", row.description, row.start_time, row.end_time)
+                        format!(
+                            "{}{}{} · {}-{}",
+                            mark_marker,
+                            overlap_marker,
+                            row.description,
+                            row.start_time,
+                            row.end_time
+                        )
                     };
                     let detail = self.truncate_label(&detail_source, detail_width);
                     let detail_pad = detail_width.saturating_sub(detail.chars().count()) + 1;
@@ -173,17 +239,11 @@ impl App {
                     };
 
                     let metric_color = if is_none_category {
-                        Color::Gray
+                        self.theme().muted
                     } else if row.karma_seconds == 0 {
-                        if row.karma_effect < 0 {
-                            Color::Red
-                        } else if row.karma_effect > 0 {
-                            Color::Green
-                        } else {
-                            Color::Gray
-                        }
+                        self.theme().karma_color(row.karma_effect as isize)
                     } else {
-                        view_style::karma_color(row.karma_seconds)
+                        self.theme().karma_color(row.karma_seconds)
                     };
 
                     if is_selected {
@@ -198,10 +258,16 @@ impl App {
                         .style(Style::default().fg(text_color).bg(border_color))
                     } else {
                         ListItem::new(Line::from(vec![
-                            Span::raw(date).fg(Color::Gray),
-                            Span::raw(" ".repeat(date_pad)).fg(Color::Gray),
-                            Span::raw(detail).fg(Color::White),
-                            Span::raw(" ".repeat(detail_pad)).fg(Color::White),
+                            Span::raw(date).fg(self.theme().muted),
+                            Span::raw(" ".repeat(date_pad)).fg(self.theme().muted),
+                            Span::raw(detail).fg(if is_marked {
+                                Color::Cyan
+                            } else if row.overlaps {
+                                Color::Yellow
+                            } else {
+                                self.theme().text
+                            }),
+                            Span::raw(" ".repeat(detail_pad)).fg(self.theme().text),
                             Span::raw(metric_value).fg(metric_color),
                         ]))
                     }
@@ -214,13 +280,15 @@ impl App {
             let list = if logs.is_empty() {
                 List::new(vec![ListItem::new(Line::from(vec![Span::styled(
                     "No logs for this category in this period.",
-                    Style::default().fg(Color::Gray),
+                    Style::default().fg(self.theme().muted),
                 )]))])
             } else {
                 List::new(items)
             };
 
             f.render_stateful_widget(list, vertical[0], &mut list_state);
+        } else if let Some(days) = chart_days.as_ref() {
+            self.render_report_chart(f, vertical[0], days, &summary);
         } else {
             let row_width = vertical[0].width as usize;
             let metric_width = 9;
@@ -250,33 +318,39 @@ impl App {
                         self.format_karma_time(entry.karma_seconds)
                     };
                     let metric_color = if is_none_row {
-                        Color::Gray
+                        self.theme().muted
                     } else if entry.karma_seconds == 0 {
-                        if entry.karma_effect < 0 {
-                            Color::Red
-                        } else if entry.karma_effect > 0 {
-                            Color::Green
-                        } else {
-                            Color::Gray
-                        }
+                        self.theme().karma_color(entry.karma_effect as isize)
                     } else {
-                        view_style::karma_color(entry.karma_seconds)
+                        self.theme().karma_color(entry.karma_seconds)
                     };
 
                     if is_selected {
-                        let text_color = view_style::text_color_for_bg(entry.color);
+                        let text_color =
+                            view_style::text_color_for_bg(self.theme().category_color(entry.color));
                         ListItem::new(Line::from(vec![
                             Span::raw(dot).fg(text_color),
                             Span::raw(name).fg(text_color),
                             Span::raw(" ".repeat(pad)).fg(text_color),
                             Span::raw(metric_value).fg(text_color),
                         ]))
-                        .style(Style::default().fg(text_color).bg(entry.color))
+                        .style(
+                            Style::default()
+                                .fg(text_color)
+                                .bg(self.theme().category_color(entry.color)),
+                        )
                     } else {
+                        let name_color = if period == Some(ReportPeriod::Today)
+                            && self.is_over_budget(entry.category_id)
+                        {
+                            Color::Red
+                        } else {
+                            self.theme().text
+                        };
                         ListItem::new(Line::from(vec![
-                            Span::raw(dot).fg(entry.color),
-                            Span::raw(name).fg(Color::White),
-                            Span::raw(" ".repeat(pad)).fg(Color::White),
+                            Span::raw(dot).fg(self.theme().category_color(entry.color)),
+                            Span::raw(name).fg(name_color),
+                            Span::raw(" ".repeat(pad)).fg(self.theme().text),
                             Span::raw(metric_value).fg(metric_color),
                         ]))
                     }
@@ -289,7 +363,7 @@ impl App {
             let list = if summary.entries.is_empty() {
                 List::new(vec![ListItem::new(Line::from(vec![Span::styled(
                     "No tracked sessions for this period.",
-                    Style::default().fg(Color::Gray),
+                    Style::default().fg(self.theme().muted),
                 )]))])
             } else {
                 List::new(items)
@@ -298,14 +372,152 @@ impl App {
             f.render_stateful_widget(list, vertical[0], &mut list_state);
         }
 
-        if self.report_show_help {
+        if let Some(overlap) = self.report_overlap.as_ref() {
+            let prompt = format!(
+                "overlaps {} ({}): {}{}  k keep both",
+                self.overlap_partner_label(overlap),
+                self.format_time(overlap.overlap_seconds),
+                self.overlap_trim_label(overlap),
+                if self.overlap_merge_allowed(overlap) {
+                    "  m merge"
+                } else {
+                    ""
+                }
+            );
+            let footer = Paragraph::new(Line::from(Span::raw(prompt).fg(Color::Yellow)));
+            f.render_widget(footer, vertical[1]);
+        } else if let Some(action) = self.report_bulk {
+            let count = self.bulk_targets().len();
+            let prompt = match action {
+                BulkAction::Delete => format!("delete {} session(s)? y/n", count),
+                BulkAction::Move(_) => format!(
+                    "move {} session(s) to < {} >? enter/esc",
+                    count,
+                    self.bulk_move_target()
+                        .map(|category| category.name)
+                        .unwrap_or_else(|| "unknown".to_string())
+                ),
+            };
+            let footer = Paragraph::new(Line::from(Span::raw(prompt).fg(Color::Yellow)));
+            f.render_widget(footer, vertical[1]);
+        } else if self.report_show_help {
             let help_text = if self.report_logs_category_id.is_some() {
-                "keys: up/down  shift+left/right  d/w/m  esc back  ?"
+                "keys: up/down  o overlap  space mark  V range  x delete  r move  d/w/m  esc back  ?"
+            } else if self.report_range.is_some() {
+                "keys: left/right date  tab start/end  r periods  enter logs  c chart  esc  ?"
             } else {
-                "keys: up/down  enter logs  shift+left/right  d/w/m  esc  ?"
+                "keys: up/down  enter logs  c chart  shift+left/right  d/w/m  r range  esc  ?"
             };
             let footer = Paragraph::new(Line::from(Span::raw(help_text).fg(Color::DarkGray)));
             f.render_widget(footer, vertical[1]);
         }
     }
+
+    /// Per-day stacked bars, one column per day, scaled to the busiest day.
+    fn render_report_chart(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+        days: &[(NaiveDate, Vec<(Color, usize)>)],
+        summary: &KarmaReportSummary,
+    ) {
+        let day_total = |segments: &[(Color, usize)]| -> usize {
+            segments.iter().map(|(_, seconds)| seconds).sum()
+        };
+        let peak = days
+            .iter()
+            .map(|(_, segments)| day_total(segments))
+            .max()
+            .unwrap_or(0);
+        if peak == 0 {
+            f.render_widget(
+                Paragraph::new(Line::from(Span::styled(
+                    "No tracked sessions for this period.",
+                    Style::default().fg(self.theme().muted),
+                ))),
+                area,
+            );
+            return;
+        }
+
+        let chart_rows = area.height.saturating_sub(3) as usize;
+        let column_width = (area.width as usize / days.len().max(1)).max(1);
+        let bar_width = if column_width > 2 {
+            column_width - 1
+        } else {
+            column_width
+        };
+        let eighths: Vec<Vec<usize>> = days
+            .iter()
+            .map(|(_, segments)| {
+                let seconds: Vec<usize> = segments.iter().map(|(_, seconds)| *seconds).collect();
+                ui_helpers::band_heights(&seconds, chart_rows * 8, peak)
+            })
+            .collect();
+
+        let mut lines = vec![Line::from(Span::styled(
+            format!("peak {}", self.format_time(peak)),
+            Style::default().fg(self.theme().muted),
+        ))];
+        for row in (0..chart_rows).rev() {
+            let spans: Vec<Span> = days
+                .iter()
+                .zip(&eighths)
+                .flat_map(|((_, segments), heights)| {
+                    let bar = match ui_helpers::stacked_bar_cell(heights, row) {
+                        Some((glyph, segment)) => Span::styled(
+                            glyph.to_string().repeat(bar_width),
+                            Style::default().fg(segments[segment].0),
+                        ),
+                        None => Span::raw(" ".repeat(bar_width)),
+                    };
+                    [bar, Span::raw(" ".repeat(column_width - bar_width))]
+                })
+                .collect();
+            lines.push(Line::from(spans));
+        }
+
+        let label_format = if days.len() <= 7 && column_width >= 4 {
+            "%a"
+        } else {
+            "%d"
+        };
+        let labels: String = days
+            .iter()
+            .map(|(day, _)| {
+                let label: String = if column_width >= 2 {
+                    day.format(label_format).to_string()
+                } else {
+                    String::new()
+                };
+                let label = self.truncate_label(&label, column_width);
+                format!("{:column_width$}", label)
+            })
+            .collect();
+        lines.push(Line::from(Span::styled(
+            labels,
+            Style::default().fg(self.theme().muted),
+        )));
+
+        let legend: Vec<Span> = summary
+            .entries
+            .iter()
+            .filter(|entry| entry.category_id != CategoryId::new(0) && entry.elapsed_seconds > 0)
+            .flat_map(|entry| {
+                [
+                    Span::styled(
+                        "■ ",
+                        Style::default().fg(self.theme().category_color(entry.color)),
+                    ),
+                    Span::styled(
+                        format!("{}  ", entry.category_name),
+                        Style::default().fg(self.theme().text),
+                    ),
+                ]
+            })
+            .collect();
+        lines.push(Line::from(legend));
+
+        f.render_widget(Paragraph::new(lines), area);
+    }
 }
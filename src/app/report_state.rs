@@ -1,12 +1,42 @@
-use chrono::Local;
+use std::collections::HashSet;
+
+use chrono::{Duration as ChronoDuration, Local, NaiveDate};
 use ratatui::style::Color;
 
 use crate::domain::{
-    CategoryId, CategoryLogEntry, KarmaReportSummary, LiveSessionPreview, ReportPeriod,
-    build_category_logs_for_period, build_period_karma_report_with_live,
+    Category, CategoryId, CategoryLogEntry, KarmaReportSummary, LiveSessionPreview,
+    OverlapResolution, ReportPeriod, SessionOverlap, build_category_logs_for_date_range,
+    build_category_logs_for_period, build_daily_reports, build_period_karma_report_with_live,
+    build_range_karma_report_with_live, operational_day_key_now, overlap_can_merge,
+    overlap_trim_removes, period_date_bounds, period_sessions, recategorize_sessions,
+    remove_sessions, resolve_session_overlap, unresolved_session_overlaps,
 };
 
-use super::App;
+use super::{App, BulkAction, ReportCacheKey, save_queue::SaveTarget, ui_helpers};
+
+/// Report dates picked a day at a time in the report modal; `editing_end`
+/// says which of the two `←`/`→` move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct ReportRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub editing_end: bool,
+}
+
+impl ReportRange {
+    /// Moves the edited date by `days`, keeping start <= end <= `today`.
+    fn shift(&mut self, days: i64, today: NaiveDate) {
+        let step = |date: NaiveDate| {
+            date.checked_add_signed(ChronoDuration::days(days))
+                .unwrap_or(date)
+        };
+        if self.editing_end {
+            self.end = step(self.end).clamp(self.start, today.max(self.start));
+        } else {
+            self.start = step(self.start).min(self.end);
+        }
+    }
+}
 
 impl App {
     pub(super) fn category_name_for_id(&self, category_id: CategoryId) -> String {
@@ -19,35 +49,82 @@ impl App {
     pub(super) fn category_color_for_id(&self, category_id: CategoryId) -> Color {
         self.time_tracker
             .category_color_by_id(category_id)
-            .unwrap_or(Color::White)
+            .map_or(self.theme().border, |color| {
+                self.theme().category_color(color)
+            })
+    }
+
+    fn report_cache_key(&self) -> ReportCacheKey {
+        let live = self.time_tracker.session_elapsed().map(|elapsed| {
+            (
+                self.time_tracker.active_category_id(),
+                elapsed.as_secs() as usize,
+            )
+        });
+
+        ReportCacheKey {
+            revision: self.time_tracker.revision(),
+            period: self.report_period,
+            range: self.report_range.map(|range| (range.start, range.end)),
+            day: operational_day_key_now(),
+            live,
+            kept_overlaps: self.kept_overlaps.len(),
+        }
     }
 
     pub(super) fn report_rows(&self) -> KarmaReportSummary {
-        let categories = self.time_tracker.categories_for_storage();
-        let live_preview = self.live_session_preview();
+        self.report_rows_cache
+            .get_or_compute(self.report_cache_key(), || {
+                let categories = self.time_tracker.categories_for_storage();
+                let live_preview = self.live_session_preview();
 
-        build_period_karma_report_with_live(
-            &self.time_tracker.sessions,
-            &categories,
-            self.report_period,
-            live_preview.as_ref(),
-        )
+                if let Some(range) = self.report_range {
+                    return build_range_karma_report_with_live(
+                        &self.time_tracker.sessions,
+                        &categories,
+                        range.start,
+                        range.end,
+                        live_preview.as_ref(),
+                    );
+                }
+                build_period_karma_report_with_live(
+                    &self.time_tracker.sessions,
+                    &categories,
+                    self.report_period,
+                    live_preview.as_ref(),
+                )
+            })
     }
 
     pub(super) fn report_logs_for_category(
         &self,
         category_id: CategoryId,
     ) -> Vec<CategoryLogEntry> {
-        let categories = self.time_tracker.categories_for_storage();
-        let live_preview = self.live_session_preview();
+        self.report_logs_cache
+            .get_or_compute((self.report_cache_key(), category_id), || {
+                let categories = self.time_tracker.categories_for_storage();
+                let live_preview = self.live_session_preview();
 
-        build_category_logs_for_period(
-            &self.time_tracker.sessions,
-            &categories,
-            category_id,
-            self.report_period,
-            live_preview.as_ref(),
-        )
+                if let Some(range) = self.report_range {
+                    return build_category_logs_for_date_range(
+                        &self.time_tracker.sessions,
+                        &self.kept_overlaps,
+                        &categories,
+                        category_id,
+                        range.start,
+                        range.end,
+                        live_preview.as_ref(),
+                    );
+                }
+                build_category_logs_for_period(
+                    &self.time_tracker.sessions,
+                    &self.kept_overlaps,
+                    &categories,
+                    category_id,
+                    self.report_period,
+                    live_preview.as_ref(),
+                )
+            })
     }
 
     pub(super) fn report_current_logs(&self) -> Vec<CategoryLogEntry> {
@@ -57,19 +134,59 @@ impl App {
         self.report_logs_for_category(category_id)
     }
 
+    /// Seconds per category for each day of the report period, oldest first
+    /// and stacked in category order, with the running session on today.
+    pub(super) fn report_chart_days(&self) -> Vec<(NaiveDate, Vec<(Color, usize)>)> {
+        let categories = self.time_tracker.categories_for_storage();
+        let (sessions, (start, end)) = match self.report_range {
+            Some(range) => (
+                self.time_tracker.sessions.as_slice().into(),
+                (range.start, range.end),
+            ),
+            None => (
+                period_sessions(&self.time_tracker.sessions, self.report_period),
+                period_date_bounds(self.report_period),
+            ),
+        };
+        let today = operational_day_key_now();
+        let live = self.live_session_preview();
+
+        build_daily_reports(&sessions, &categories, start, end)
+            .into_iter()
+            .zip(start.iter_days())
+            .map(|(report, day)| {
+                let segments = categories
+                    .iter()
+                    .filter(|category| category.id != CategoryId::new(0))
+                    .filter_map(|category| {
+                        let mut seconds = report
+                            .entries
+                            .iter()
+                            .find(|entry| entry.category_name == category.name)
+                            .map_or(0, |entry| entry.elapsed_seconds);
+                        if day == today
+                            && let Some(live) = live.as_ref()
+                            && live.category_id == category.id
+                        {
+                            seconds += live.elapsed_seconds;
+                        }
+                        (seconds > 0)
+                            .then_some((self.theme().category_color(category.color), seconds))
+                    })
+                    .collect();
+                (day, segments)
+            })
+            .collect()
+    }
+
     fn live_session_preview(&self) -> Option<LiveSessionPreview> {
-        let start = self.time_tracker.current_session_start?;
-        let elapsed_seconds = start.elapsed().as_secs() as usize;
+        let elapsed_seconds = self.time_tracker.session_elapsed()?.as_secs() as usize;
         if elapsed_seconds == 0 {
             return None;
         }
 
         let category_id = self.time_tracker.active_category_id();
-        let description = self
-            .time_tracker
-            .category_description_by_id(category_id)
-            .map(ToString::to_string)
-            .unwrap_or_default();
+        let description = self.time_tracker.session_description().to_string();
 
         Some(LiveSessionPreview {
             category_id,
@@ -81,6 +198,42 @@ impl App {
 
     pub(super) fn set_report_period(&mut self, period: ReportPeriod) {
         self.report_period = period;
+        self.report_range = None;
+        self.clamp_report_rows();
+    }
+
+    /// Switches the report to custom dates, starting from the period's, or
+    /// back to the period.
+    pub(super) fn toggle_report_range(&mut self) {
+        self.report_range = match self.report_range {
+            Some(_) => None,
+            None => {
+                self.record_usage("tui report range");
+                let (start, end) = period_date_bounds(self.report_period);
+                Some(ReportRange {
+                    start,
+                    end,
+                    editing_end: false,
+                })
+            }
+        };
+        self.clamp_report_rows();
+    }
+
+    pub(super) fn shift_report_range(&mut self, days: i64) {
+        if let Some(range) = self.report_range.as_mut() {
+            range.shift(days, operational_day_key_now());
+            self.clamp_report_rows();
+        }
+    }
+
+    pub(super) fn toggle_report_range_side(&mut self) {
+        if let Some(range) = self.report_range.as_mut() {
+            range.editing_end = !range.editing_end;
+        }
+    }
+
+    fn clamp_report_rows(&mut self) {
         if self.report_logs_category_id.is_some() {
             let row_count = self.report_current_logs().len();
             self.clamp_report_log_selection(row_count);
@@ -90,6 +243,224 @@ impl App {
         }
     }
 
+    pub(super) fn report_footer_visible(&self) -> bool {
+        self.report_show_help || self.report_overlap.is_some() || self.report_bulk.is_some()
+    }
+
+    pub(super) fn clear_report_marks(&mut self) {
+        self.report_marked.clear();
+        self.report_mark_anchor = None;
+        self.report_bulk = None;
+    }
+
+    pub(super) fn toggle_log_mark(&mut self) {
+        let logs = self.report_current_logs();
+        let Some(session_id) = logs
+            .get(self.report_log_selected_index)
+            .and_then(|row| row.session_id)
+        else {
+            return;
+        };
+        if !self.report_marked.remove(&session_id) {
+            self.report_marked.insert(session_id);
+        }
+        self.report_mark_anchor = Some(self.report_log_selected_index);
+    }
+
+    pub(super) fn mark_log_range(&mut self) {
+        let logs = self.report_current_logs();
+        let anchor = self
+            .report_mark_anchor
+            .unwrap_or(self.report_log_selected_index);
+        let (from, to) = if anchor <= self.report_log_selected_index {
+            (anchor, self.report_log_selected_index)
+        } else {
+            (self.report_log_selected_index, anchor)
+        };
+        self.report_marked.extend(
+            logs.iter()
+                .skip(from)
+                .take(to + 1 - from)
+                .filter_map(|row| row.session_id),
+        );
+        self.report_mark_anchor = Some(self.report_log_selected_index);
+    }
+
+    /// Marked sessions, or the selected row when nothing is marked.
+    pub(super) fn bulk_targets(&self) -> HashSet<usize> {
+        if !self.report_marked.is_empty() {
+            return self.report_marked.clone();
+        }
+        self.report_current_logs()
+            .get(self.report_log_selected_index)
+            .and_then(|row| row.session_id)
+            .into_iter()
+            .collect()
+    }
+
+    pub(super) fn start_bulk_move(&mut self) {
+        let current = self.report_logs_category_id;
+        let categories = self.time_tracker.categories_for_storage();
+        let index = categories
+            .iter()
+            .position(|category| Some(category.id) != current)
+            .unwrap_or(0);
+        self.report_bulk = Some(BulkAction::Move(index));
+    }
+
+    pub(super) fn cycle_bulk_move(&mut self, forward: bool) {
+        let Some(BulkAction::Move(index)) = self.report_bulk else {
+            return;
+        };
+        let count = self.time_tracker.category_count();
+        if count == 0 {
+            return;
+        }
+        let next = if forward {
+            ui_helpers::wrap_next_index(index, count)
+        } else {
+            ui_helpers::wrap_prev_index(index, count)
+        };
+        self.report_bulk = Some(BulkAction::Move(next));
+    }
+
+    pub(super) fn bulk_move_target(&self) -> Option<Category> {
+        let Some(BulkAction::Move(index)) = self.report_bulk else {
+            return None;
+        };
+        self.time_tracker
+            .categories_for_storage()
+            .get(index)
+            .cloned()
+    }
+
+    pub(super) fn apply_bulk_action(&mut self) {
+        let Some(action) = self.report_bulk.take() else {
+            return;
+        };
+        let targets = self.bulk_targets();
+        if targets.is_empty() {
+            return;
+        }
+
+        let categories = self.time_tracker.categories_for_storage();
+        self.record_usage(match action {
+            BulkAction::Delete => "tui bulk delete",
+            BulkAction::Move(_) => "tui bulk move",
+        });
+        match action {
+            BulkAction::Delete => {
+                let removed = remove_sessions(self.time_tracker.sessions_mut(), &targets);
+                if removed.is_empty() {
+                    return;
+                }
+                self.trash_sessions(removed);
+            }
+            BulkAction::Move(index) => {
+                let Some(category) = categories.get(index) else {
+                    return;
+                };
+                if recategorize_sessions(self.time_tracker.sessions_mut(), &targets, category.id)
+                    == 0
+                {
+                    return;
+                }
+            }
+        }
+
+        self.persist_sessions();
+        self.clear_report_marks();
+        let row_count = self.report_current_logs().len();
+        self.clamp_report_log_selection(row_count);
+    }
+
+    pub(super) fn selected_log_overlap(&self) -> Option<SessionOverlap> {
+        let logs = self.report_current_logs();
+        let row = logs.get(self.report_log_selected_index)?;
+        if !row.overlaps {
+            return None;
+        }
+        let session_id = row.session_id?;
+
+        unresolved_session_overlaps(&self.time_tracker.sessions, &self.kept_overlaps)
+            .into_iter()
+            .find(|overlap| overlap.first_id == session_id || overlap.second_id == session_id)
+    }
+
+    pub(super) fn apply_overlap_resolution(&mut self, resolution: OverlapResolution) {
+        let Some(overlap) = self.report_overlap.take() else {
+            return;
+        };
+        if resolution == OverlapResolution::KeepBoth {
+            self.kept_overlaps
+                .insert((overlap.first_id, overlap.second_id));
+            self.save(SaveTarget::KeptOverlaps);
+            return;
+        }
+
+        // A trim that leaves nothing of the second session deletes it, so it
+        // goes to the trash like any other deleted row.
+        let removed = (matches!(resolution, OverlapResolution::Trim)
+            && overlap_trim_removes(&self.time_tracker.sessions, &overlap))
+        .then(|| {
+            self.time_tracker
+                .sessions
+                .iter()
+                .find(|session| session.id == overlap.second_id)
+                .cloned()
+        })
+        .flatten();
+
+        if resolve_session_overlap(self.time_tracker.sessions_mut(), &overlap, resolution) {
+            self.trash_sessions(removed.into_iter().collect());
+            self.persist_sessions();
+            let row_count = self.report_current_logs().len();
+            self.clamp_report_log_selection(row_count);
+        }
+    }
+
+    /// Whether `m` is offered: merging sessions of different categories
+    /// would move one side's time to the other's category.
+    pub(super) fn overlap_merge_allowed(&self, overlap: &SessionOverlap) -> bool {
+        overlap_can_merge(&self.time_tracker.sessions, overlap)
+    }
+
+    /// What `t` does to the overlap: trim the later session, or delete it
+    /// when the earlier one covers it entirely.
+    pub(super) fn overlap_trim_label(&self, overlap: &SessionOverlap) -> &'static str {
+        if overlap_trim_removes(&self.time_tracker.sessions, overlap) {
+            "t delete the inner session"
+        } else {
+            "t trim"
+        }
+    }
+
+    pub(super) fn overlap_partner_label(&self, overlap: &SessionOverlap) -> String {
+        let selected_id = self
+            .report_current_logs()
+            .get(self.report_log_selected_index)
+            .and_then(|row| row.session_id);
+        let partner_id = if selected_id == Some(overlap.first_id) {
+            overlap.second_id
+        } else {
+            overlap.first_id
+        };
+
+        self.time_tracker
+            .sessions
+            .iter()
+            .find(|session| session.id == partner_id)
+            .map(|session| {
+                format!(
+                    "{} {}-{}",
+                    self.category_name_for_id(session.category_id),
+                    session.start_time,
+                    session.end_time
+                )
+            })
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
     pub(super) fn clamp_report_selection(&mut self, row_count: usize) {
         if row_count == 0 {
             self.report_selected_index = 0;
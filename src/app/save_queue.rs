@@ -0,0 +1,279 @@
+use std::{
+    collections::BTreeSet,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Clear, Paragraph},
+};
+
+use crate::{constants::SAVE_RETRY, storage};
+
+use super::App;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum SaveTarget {
+    // Trash goes first so removed rows are kept before the log drops them.
+    Trash,
+    Categories,
+    Sessions,
+    ActiveSession,
+    SandState,
+    CategoryTags,
+    CategoryGroups,
+    FocusContract,
+    Plan,
+    KeptOverlaps,
+    Config,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct SaveFailure {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+/// Files with unsaved changes. The data itself stays in memory on the app;
+/// after a failed write the queue holds further writes until the backoff
+/// expires, then retries every pending file.
+#[derive(Debug)]
+pub(super) struct SaveQueue {
+    pending: BTreeSet<SaveTarget>,
+    failure: Option<SaveFailure>,
+    backoff: Duration,
+    retry_at: Option<Instant>,
+}
+
+impl SaveQueue {
+    pub(super) fn new() -> Self {
+        Self {
+            pending: BTreeSet::new(),
+            failure: None,
+            backoff: Duration::from_millis(SAVE_RETRY.initial_ms),
+            retry_at: None,
+        }
+    }
+
+    pub(super) fn mark(&mut self, target: SaveTarget) {
+        self.pending.insert(target);
+    }
+
+    pub(super) fn is_pending(&self, target: SaveTarget) -> bool {
+        self.pending.contains(&target)
+    }
+
+    pub(super) fn due(&self, now: Instant) -> bool {
+        !self.pending.is_empty() && self.retry_at.is_none_or(|retry_at| now >= retry_at)
+    }
+
+    pub(super) fn pending(&self) -> Vec<SaveTarget> {
+        self.pending.iter().copied().collect()
+    }
+
+    pub(super) fn succeeded(&mut self, target: SaveTarget) {
+        self.pending.remove(&target);
+        if self.pending.is_empty() {
+            self.failure = None;
+            self.retry_at = None;
+            self.backoff = Duration::from_millis(SAVE_RETRY.initial_ms);
+        }
+    }
+
+    pub(super) fn failed(&mut self, failure: SaveFailure, now: Instant) {
+        self.failure = Some(failure);
+        self.retry_at = Some(now + self.backoff);
+        self.backoff = (self.backoff * 2).min(Duration::from_millis(SAVE_RETRY.max_ms));
+    }
+
+    pub(super) fn failure(&self) -> Option<&SaveFailure> {
+        self.failure.as_ref()
+    }
+
+    pub(super) fn retry_in(&self, now: Instant) -> Duration {
+        self.retry_at
+            .map(|retry_at| retry_at.saturating_duration_since(now))
+            .unwrap_or_default()
+    }
+}
+
+impl App {
+    pub(super) fn save(&mut self, target: SaveTarget) {
+        self.save_queue.mark(target);
+        self.flush_saves(false);
+    }
+
+    /// Writes pending files when no failure backoff is running, or always
+    /// when `force` is set (on exit).
+    pub(super) fn flush_saves(&mut self, force: bool) {
+        let now = Instant::now();
+        if !force && !self.save_queue.due(now) {
+            return;
+        }
+
+        let had_failure = self.save_queue.failure().is_some();
+        for target in self.save_queue.pending() {
+            if let Err(failure) = self.write_target(target) {
+                self.save_queue.failed(failure, now);
+                self.render_needed = true;
+                return;
+            }
+            self.save_queue.succeeded(target);
+        }
+        if had_failure {
+            self.render_needed = true;
+        }
+    }
+
+    fn write_target(&mut self, target: SaveTarget) -> Result<(), SaveFailure> {
+        let categories = self.time_tracker.categories_for_storage();
+        let (path, result) = match target {
+            SaveTarget::Categories => {
+                let path = storage::get_data_dir().join("categories.csv");
+                let result = storage::save_categories_to_csv(&path, &categories);
+                (path, result)
+            }
+            SaveTarget::Sessions => {
+                let path = storage::get_data_dir().join("time_log.csv");
+                let sessions = &self.time_tracker.sessions;
+                let result = match self.append_sessions_from {
+                    Some(from) => {
+                        let new = &sessions[from.min(sessions.len())..];
+                        storage::append_sessions_locked(&path, new, &categories).inspect(|_| {
+                            self.append_sessions_from = Some(sessions.len());
+                        })
+                    }
+                    None => storage::save_sessions_to_csv(&path, sessions, &categories),
+                };
+                (path, result)
+            }
+            SaveTarget::ActiveSession => {
+                let path = storage::get_active_session_path();
+                let result = match &self.shared_session {
+                    Some(session) => storage::write_json_atomic(&path, session),
+                    None => storage::delete_file_if_exists(&path),
+                };
+                (path, result)
+            }
+            SaveTarget::Trash => {
+                let path = storage::get_trash_path();
+                let result =
+                    storage::append_sessions_to_trash(&path, &self.pending_trash, &categories);
+                if result.is_ok() {
+                    self.pending_trash.clear();
+                }
+                (path, result)
+            }
+            SaveTarget::SandState => {
+                let path = storage::get_sand_state_path();
+                let mut state = self.sand_engine.snapshot_state();
+                state.day = Some(self.sand_day);
+                let result = storage::save_sand_state(&path, &state);
+                (path, result)
+            }
+            SaveTarget::CategoryTags => {
+                let path = storage::get_category_tags_path();
+                let result = storage::save_category_tags(&path, &self.category_tags);
+                (path, result)
+            }
+            SaveTarget::CategoryGroups => {
+                let path = storage::get_category_groups_path();
+                let result = storage::save_category_groups(&path, &self.category_groups);
+                (path, result)
+            }
+            SaveTarget::FocusContract => {
+                let path = storage::get_focus_contract_path();
+                let result = storage::save_focus_contract(&path, &self.focus_contract);
+                (path, result)
+            }
+            SaveTarget::Plan => {
+                let path = storage::get_plan_path();
+                let result = storage::save_plan(&path, &self.plan);
+                (path, result)
+            }
+            SaveTarget::KeptOverlaps => {
+                let path = storage::get_kept_overlaps_path();
+                let result = storage::save_kept_overlaps(&path, &self.kept_overlaps);
+                (path, result)
+            }
+            SaveTarget::Config => {
+                let path = storage::get_config_path();
+                let result = storage::update_config(&path, &self.config_edits);
+                if result.is_ok() {
+                    self.config_edits.clear();
+                }
+                (path, result)
+            }
+        };
+        result.map_err(|error| SaveFailure { path, error })
+    }
+
+    pub(super) fn render_save_warning(&self, f: &mut Frame, area: Rect) {
+        let Some(failure) = self.save_queue.failure() else {
+            return;
+        };
+        if area.width < 3 || area.height < 3 {
+            return;
+        }
+
+        let text = format!(
+            " can't save {}: {} · changes kept in memory, retrying in {}s ",
+            failure.path.display(),
+            failure.error,
+            self.save_queue.retry_in(Instant::now()).as_secs().max(1)
+        );
+        let banner = Rect {
+            x: area.x + 1,
+            y: area.y + 1,
+            width: area.width - 2,
+            height: 1,
+        };
+        f.render_widget(Clear, banner);
+        f.render_widget(
+            Paragraph::new(text).style(Style::default().fg(self.theme().text).bg(Color::Red)),
+            banner,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure() -> SaveFailure {
+        SaveFailure {
+            path: PathBuf::from("/read-only/time_log.csv"),
+            error: "Read-only file system".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_save_queue_backs_off_until_every_target_is_written() {
+        let mut queue = SaveQueue::new();
+        let start = Instant::now();
+        queue.mark(SaveTarget::Sessions);
+        queue.mark(SaveTarget::Categories);
+        assert!(queue.due(start));
+
+        queue.failed(failure(), start);
+        let first_retry = Duration::from_millis(SAVE_RETRY.initial_ms);
+        assert!(!queue.due(start));
+        assert!(queue.due(start + first_retry));
+
+        queue.failed(failure(), start + first_retry);
+        assert!(!queue.due(start + first_retry * 2));
+        assert!(queue.due(start + first_retry * 3));
+
+        queue.succeeded(SaveTarget::Categories);
+        assert!(queue.failure().is_some());
+        queue.succeeded(SaveTarget::Sessions);
+        assert!(queue.failure().is_none());
+        assert!(!queue.due(start));
+
+        queue.mark(SaveTarget::Trash);
+        assert!(queue.due(start));
+    }
+}
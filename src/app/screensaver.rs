@@ -0,0 +1,98 @@
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    event::{self, Event},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use rand::{Rng, seq::SliceRandom};
+use ratatui::{Terminal, backend::CrosstermBackend, style::Color, widgets::Paragraph};
+
+use crate::{
+    constants::{COLORS, TIME_SETTINGS},
+    domain::CategoryId,
+    sand::SandEngine,
+};
+
+/// Made-up categories taking turns at the glass.
+const CATEGORIES: usize = 6;
+
+/// Much faster than the grain a second of real tracking, so the glass fills
+/// and presses into bands while someone is watching.
+const SPAWN_MS: u64 = 40;
+
+/// Seconds one category keeps pouring before another takes over.
+const TURN_SECONDS: std::ops::Range<u64> = 3..12;
+
+/// Runs the sand on its own until any key: random categories, no data
+/// directory, no instance lock and nothing saved.
+pub(super) fn run() -> Result<(), io::Error> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+    terminal.hide_cursor()?;
+
+    let result = animate(&mut terminal);
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result
+}
+
+fn animate(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<(), io::Error> {
+    let mut rng = rand::thread_rng();
+    let mut colors = COLORS.to_vec();
+    colors.shuffle(&mut rng);
+    let palette: Vec<(CategoryId, Color)> = colors
+        .into_iter()
+        .take(CATEGORIES)
+        .enumerate()
+        .map(|(index, color)| (CategoryId::new(index as u64 + 1), color))
+        .collect();
+
+    let size = terminal.size()?;
+    let mut engine = SandEngine::new(size.width, size.height);
+    let mut category = palette[0].0;
+    let mut turn_ends = Instant::now() + Duration::from_secs(rng.gen_range(TURN_SECONDS));
+
+    let spawn_rate = Duration::from_millis(SPAWN_MS);
+    let physics_rate = Duration::from_millis(TIME_SETTINGS.physics_ms);
+    let mut last_spawn = Instant::now();
+    let mut last_physics = Instant::now();
+
+    loop {
+        if Instant::now() >= turn_ends {
+            category = palette[rng.gen_range(0..palette.len())].0;
+            turn_ends = Instant::now() + Duration::from_secs(rng.gen_range(TURN_SECONDS));
+        }
+
+        if last_spawn.elapsed() >= spawn_rate {
+            if engine.spawn(category).is_none() {
+                engine.compact_day(None);
+            }
+            last_spawn = Instant::now();
+        }
+
+        if last_physics.elapsed() >= physics_rate {
+            engine.update();
+            terminal.draw(|f| {
+                let lines = engine.render(palette.iter().copied());
+                f.render_widget(Paragraph::new(lines), f.size());
+            })?;
+            last_physics = Instant::now();
+        }
+
+        if event::poll(Duration::from_millis(1))? {
+            match event::read()? {
+                Event::Key(_) => return Ok(()),
+                Event::Resize(width, height) => engine.resize(width, height),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    prelude::{Line, Span},
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use crate::{duration::format_duration, sand::SpawnStrategy, storage};
+
+use super::{App, UiMode, save_queue::SaveTarget, ui_helpers};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Setting {
+    Theme,
+    SandSpeed,
+    SandSpawn,
+    TimeFormat,
+    Autosave,
+}
+
+const SETTINGS: [Setting; 5] = [
+    Setting::Theme,
+    Setting::SandSpeed,
+    Setting::SandSpawn,
+    Setting::TimeFormat,
+    Setting::Autosave,
+];
+
+const SPAWN_STRATEGIES: [(&str, SpawnStrategy); 3] = [
+    ("scan", SpawnStrategy::Scan),
+    ("least-full", SpawnStrategy::LeastFull),
+    ("center", SpawnStrategy::Center),
+];
+
+const TIME_FORMATS: [&str; 2] = ["hh:mm:ss", "hh:mm"];
+
+const AUTOSAVE_PRESETS: [usize; 4] = [30, 60, 120, 300];
+
+impl Setting {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Theme => "theme",
+            Self::SandSpeed => "sand speed",
+            Self::SandSpawn => "sand spawn",
+            Self::TimeFormat => "time format",
+            Self::Autosave => "autosave every",
+        }
+    }
+
+    /// The `config.json` key the setting is saved under.
+    fn key(self) -> &'static str {
+        match self {
+            Self::Theme => "theme",
+            Self::SandSpeed => "sand_speed",
+            Self::SandSpawn => "sand_spawn",
+            Self::TimeFormat => "time_format",
+            Self::Autosave => "autosave",
+        }
+    }
+}
+
+/// The entry after (or before) `current` in `len` choices; a value that
+/// isn't one of them steps to the first or last.
+fn step(current: Option<usize>, len: usize, forward: bool) -> usize {
+    match (current, forward) {
+        (Some(index), true) => ui_helpers::wrap_next_index(index, len),
+        (Some(index), false) => ui_helpers::wrap_prev_index(index, len),
+        (None, true) => 0,
+        (None, false) => len - 1,
+    }
+}
+
+impl App {
+    pub(super) fn open_settings(&mut self) {
+        self.record_usage("tui settings");
+        self.settings_selected = 0;
+        self.ui_mode = UiMode::SettingsModal;
+        self.render_needed = true;
+    }
+
+    pub(super) fn handle_settings_key(&mut self, key: KeyEvent) {
+        let Some(key) = self.vim_key(key) else {
+            return;
+        };
+        self.render_needed = true;
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('o') => self.ui_mode = UiMode::Main,
+            KeyCode::Up => {
+                self.settings_selected =
+                    ui_helpers::wrap_prev_index(self.settings_selected, SETTINGS.len());
+            }
+            KeyCode::Down => {
+                self.settings_selected =
+                    ui_helpers::wrap_next_index(self.settings_selected, SETTINGS.len());
+            }
+            KeyCode::Left => self.change_setting(SETTINGS[self.settings_selected], false),
+            KeyCode::Right | KeyCode::Enter | KeyCode::Char(' ') => {
+                self.change_setting(SETTINGS[self.settings_selected], true);
+            }
+            _ => {}
+        }
+    }
+
+    fn setting_value(&self, setting: Setting) -> String {
+        match setting {
+            Setting::Theme => self.theme().name.clone(),
+            Setting::SandSpeed => storage::SAND_SPEEDS
+                .iter()
+                .find(|(_, ms)| *ms == self.physics_ms)
+                .map_or_else(
+                    || format!("{}ms", self.physics_ms),
+                    |(name, _)| name.to_string(),
+                ),
+            Setting::SandSpawn => SPAWN_STRATEGIES
+                .iter()
+                .find(|(_, strategy)| *strategy == self.sand_engine.spawn_strategy())
+                .map_or("scan", |(name, _)| name)
+                .to_string(),
+            Setting::TimeFormat => TIME_FORMATS[usize::from(!self.show_seconds)].to_string(),
+            Setting::Autosave => format_duration(self.autosave_seconds),
+        }
+    }
+
+    /// Applies the next or previous value right away and queues it for
+    /// `config.json`.
+    fn change_setting(&mut self, setting: Setting, forward: bool) {
+        match setting {
+            Setting::Theme => {
+                let index = step(Some(self.theme_index), self.themes.len(), forward);
+                self.theme_index = index;
+            }
+            Setting::SandSpeed => {
+                let current = storage::SAND_SPEEDS
+                    .iter()
+                    .position(|(_, ms)| *ms == self.physics_ms);
+                let index = step(current, storage::SAND_SPEEDS.len(), forward);
+                self.physics_ms = storage::SAND_SPEEDS[index].1;
+            }
+            Setting::SandSpawn => {
+                let current = SPAWN_STRATEGIES
+                    .iter()
+                    .position(|(_, strategy)| *strategy == self.sand_engine.spawn_strategy());
+                let index = step(current, SPAWN_STRATEGIES.len(), forward);
+                self.sand_engine
+                    .set_spawn_strategy(SPAWN_STRATEGIES[index].1);
+            }
+            Setting::TimeFormat => self.show_seconds = !self.show_seconds,
+            Setting::Autosave => {
+                let current = AUTOSAVE_PRESETS
+                    .iter()
+                    .position(|seconds| *seconds == self.autosave_seconds);
+                let index = step(current, AUTOSAVE_PRESETS.len(), forward);
+                self.autosave_seconds = AUTOSAVE_PRESETS[index];
+            }
+        }
+        let value = self.setting_value(setting);
+        self.config_edits.insert(setting.key().to_string(), value);
+        self.save(SaveTarget::Config);
+    }
+
+    pub(super) fn render_settings(&self, f: &mut Frame, area: Rect) {
+        let label_width = SETTINGS
+            .iter()
+            .map(|setting| setting.label().len())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = SETTINGS
+            .iter()
+            .enumerate()
+            .map(|(index, setting)| {
+                let value = format!("‹ {} ›", self.setting_value(*setting));
+                let value_style = if index == self.settings_selected {
+                    Style::default().fg(Color::Black).bg(self.theme().text)
+                } else {
+                    Style::default().fg(self.theme().text)
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<width$}  ", setting.label(), width = label_width),
+                        Style::default().fg(self.theme().muted),
+                    ),
+                    Span::styled(value, value_style),
+                ])
+            })
+            .collect();
+
+        let footer = " ←/→ change · saved to config.json · esc close ";
+        let width = lines
+            .iter()
+            .map(Line::width)
+            .chain([footer.chars().count()])
+            .max()
+            .unwrap_or(0) as u16
+            + 4;
+        let width = width.min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let rect = Rect::new(
+            area.x + area.width.saturating_sub(width) / 2,
+            area.y + area.height.saturating_sub(height) / 2,
+            width,
+            height,
+        );
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(Line::from(Span::styled(
+                "settings",
+                Style::default()
+                    .fg(self.theme().text)
+                    .add_modifier(Modifier::BOLD),
+            )))
+            .title_alignment(Alignment::Center)
+            .title_bottom(
+                Line::from(Span::styled(
+                    footer,
+                    Style::default().fg(self.theme().muted),
+                ))
+                .alignment(Alignment::Center),
+            )
+            .border_style(Style::default().fg(self.theme().border));
+
+        f.render_widget(Clear, rect);
+        f.render_widget(Paragraph::new(lines).block(block), rect);
+    }
+}
@@ -0,0 +1,129 @@
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    prelude::{Line, Span},
+    style::{Modifier, Style},
+    widgets::{Block, BorderType, Borders, Paragraph},
+};
+
+use crate::domain::CategoryId;
+
+use super::{App, ui_helpers};
+
+/// Columns the report panel takes beside the sand.
+const PANEL_WIDTH: u16 = 36;
+
+/// Narrowest terminal the split layout is drawn in; below it the sand gets
+/// the full width back until the terminal grows again.
+const MIN_SPLIT_WIDTH: u16 = 90;
+
+impl App {
+    pub(super) fn toggle_split_pane(&mut self) {
+        let narrow = crossterm::terminal::size().is_ok_and(|(width, _)| width < MIN_SPLIT_WIDTH);
+        if !self.split_pane && narrow {
+            self.show_status(format!("split view needs {} columns", MIN_SPLIT_WIDTH));
+            return;
+        }
+        self.split_pane = !self.split_pane;
+        if self.split_pane {
+            self.record_usage("tui split view");
+        }
+        self.resize_to_terminal();
+    }
+
+    /// Columns the report panel takes from a terminal `width` wide.
+    pub(super) fn panel_cols(&self, width: u16) -> u16 {
+        if self.split_pane && !self.zen && width >= MIN_SPLIT_WIDTH {
+            PANEL_WIDTH
+        } else {
+            0
+        }
+    }
+
+    /// The area left for the sand and strips, and the panel's area beside it
+    /// in split layout.
+    pub(super) fn split_areas(&self, area: Rect) -> (Rect, Option<Rect>) {
+        let panel = self.panel_cols(area.width);
+        if panel == 0 {
+            return (area, None);
+        }
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(panel)])
+            .split(area);
+        (chunks[0], Some(chunks[1]))
+    }
+
+    /// The karma report for the report modal's period, kept beside the sand.
+    pub(super) fn render_report_panel(&self, f: &mut Frame, area: Rect) {
+        let summary = self.report_rows();
+        let metric_width = 9;
+        let name_width = (area.width as usize)
+            .saturating_sub(metric_width + 5)
+            .max(4);
+
+        let mut lines: Vec<Line> = summary
+            .entries
+            .iter()
+            .map(|entry| {
+                let color = self.theme().category_color(entry.color);
+                let name = self.truncate_label(&entry.category_name, name_width);
+                let pad = name_width.saturating_sub(name.chars().count()) + 1;
+                let (metric, metric_color) = if entry.category_id == CategoryId::new(0) {
+                    (self.format_time(entry.elapsed_seconds), self.theme().muted)
+                } else {
+                    (
+                        self.format_karma_time(entry.karma_seconds),
+                        self.theme().karma_color(entry.karma_seconds),
+                    )
+                };
+                Line::from(vec![
+                    Span::styled("● ", Style::default().fg(color)),
+                    Span::styled(name, Style::default().fg(self.theme().text)),
+                    Span::raw(" ".repeat(pad)),
+                    Span::styled(metric, Style::default().fg(metric_color)),
+                ])
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "nothing tracked yet",
+                Style::default().fg(self.theme().muted),
+            )));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("tracked {}", self.format_time(summary.total_seconds)),
+            Style::default().fg(self.theme().muted),
+        )));
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(
+                Line::from(Span::styled(
+                    ui_helpers::format_report_interval_label(&summary.date),
+                    Style::default().fg(self.theme().text),
+                ))
+                .alignment(Alignment::Left),
+            )
+            .title(
+                Line::from(Span::styled(
+                    "karma",
+                    Style::default()
+                        .fg(self.theme().text)
+                        .add_modifier(Modifier::BOLD),
+                ))
+                .alignment(Alignment::Center),
+            )
+            .title(
+                Line::from(Span::styled(
+                    self.format_karma_time(summary.total_karma_seconds),
+                    Style::default().fg(self.theme().karma_color(summary.total_karma_seconds)),
+                ))
+                .alignment(Alignment::Right),
+            )
+            .border_style(Style::default().fg(self.theme().border));
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
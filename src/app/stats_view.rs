@@ -0,0 +1,156 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::{Line, Span};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph},
+};
+
+use crate::domain::build_week_stats;
+
+use super::{App, UiMode};
+
+const NAME_WIDTH: usize = 16;
+
+impl App {
+    pub(super) fn open_stats(&mut self) {
+        self.record_usage("tui stats");
+        self.ui_mode = UiMode::StatsView;
+        self.sync_goal_ledger();
+        self.render_needed = true;
+    }
+
+    pub(super) fn handle_stats_key(&mut self, key: KeyEvent) {
+        if matches!(
+            key.code,
+            KeyCode::Esc | KeyCode::Char('s') | KeyCode::Char('S') | KeyCode::Char('q')
+        ) {
+            self.ui_mode = UiMode::Main;
+            self.render_needed = true;
+        }
+    }
+
+    pub(super) fn render_stats(&self, f: &mut Frame, terminal_size: Rect) {
+        let categories = self.time_tracker.categories_for_storage();
+        let stats = build_week_stats(&self.time_tracker.sessions, &categories);
+        let gray = Style::default().fg(self.theme().muted);
+        let white = Style::default().fg(self.theme().text);
+
+        let inner_width = terminal_size.width.saturating_sub(4) as usize;
+        let bar_width = inner_width.saturating_sub(2 + NAME_WIDTH + 2 + 8);
+        let longest = stats
+            .summary
+            .entries
+            .iter()
+            .map(|entry| entry.elapsed_seconds)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let mut lines: Vec<Line> = stats
+            .summary
+            .entries
+            .iter()
+            .map(|entry| {
+                let color = categories
+                    .iter()
+                    .find(|category| category.name == entry.category_name)
+                    .map(|category| self.theme().category_color(category.color))
+                    .unwrap_or(self.theme().text);
+                let filled = (entry.elapsed_seconds * bar_width).div_ceil(longest);
+                Line::from(vec![
+                    Span::styled("● ", Style::default().fg(color)),
+                    Span::styled(
+                        format!(
+                            "{:NAME_WIDTH$}  ",
+                            self.truncate_label(&entry.category_name, NAME_WIDTH)
+                        ),
+                        white,
+                    ),
+                    Span::styled("█".repeat(filled), Style::default().fg(color)),
+                    Span::raw(" ".repeat(bar_width - filled)),
+                    Span::styled(
+                        format!("  {}", self.format_time(entry.elapsed_seconds)),
+                        white,
+                    ),
+                ])
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::from(Span::styled("nothing tracked this week", gray)));
+        }
+
+        let best_day = match stats.best_day {
+            Some((date, seconds)) => {
+                format!(
+                    "{} · {}",
+                    date.format("%a %Y-%m-%d"),
+                    self.format_time(seconds)
+                )
+            }
+            None => "—".to_string(),
+        };
+        let streak = match stats.streak_days {
+            1 => "1 day".to_string(),
+            days => format!("{} days", days),
+        };
+        lines.push(Line::from(""));
+        for (label, value) in [
+            ("week total", self.format_time(stats.summary.total_seconds)),
+            (
+                "daily average",
+                self.format_time(stats.daily_average_seconds),
+            ),
+            ("best day", best_day),
+            ("streak", streak),
+        ] {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:NAME_WIDTH$}  ", label), gray),
+                Span::styled(value, white.add_modifier(Modifier::BOLD)),
+            ]));
+        }
+
+        let goals: Vec<(String, String)> = categories
+            .iter()
+            .filter_map(|category| {
+                let progress = self.week_goal_progress(category.id)?;
+                Some((self.truncate_label(&category.name, NAME_WIDTH), progress))
+            })
+            .collect();
+        if !goals.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("weekly goals", gray)));
+            for (name, progress) in goals {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{:NAME_WIDTH$}  ", name), white),
+                    Span::styled(progress, white),
+                ]));
+            }
+        }
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title(
+                Line::from(Span::styled("stats", white.add_modifier(Modifier::BOLD)))
+                    .alignment(Alignment::Center),
+            )
+            .title(
+                Line::from(Span::styled(format!(" {} ", stats.summary.date), white))
+                    .alignment(Alignment::Left),
+            )
+            .title_bottom(
+                Line::from(Span::styled(
+                    " esc / s close ",
+                    Style::default().fg(Color::DarkGray),
+                ))
+                .alignment(Alignment::Left),
+            )
+            .border_style(Style::default().fg(self.theme().border))
+            .padding(Padding::new(1, 1, 1, 0));
+
+        f.render_widget(Clear, terminal_size);
+        f.render_widget(Paragraph::new(lines).block(block), terminal_size);
+    }
+}
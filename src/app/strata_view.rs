@@ -0,0 +1,73 @@
+use chrono::Utc;
+use ratatui::{
+    prelude::{Line, Span},
+    style::{Color, Style},
+};
+
+use crate::{constants::STRATA_VIEW, domain::CategoryId};
+
+use super::{App, ui_helpers, view_style};
+
+#[derive(Clone, Debug)]
+pub(super) struct StrataLayer {
+    name: String,
+    color: Color,
+    seconds: usize,
+}
+
+impl App {
+    fn strata_layers(&self) -> Vec<StrataLayer> {
+        let minute = Utc::now().timestamp() / STRATA_VIEW.refresh_secs;
+        let key = (self.time_tracker.revision(), minute);
+        self.strata_layers_cache.get_or_compute(key, || {
+            self.time_tracker
+                .categories_ordered()
+                .filter(|category| category.id != CategoryId::new(0))
+                .map(|category| StrataLayer {
+                    name: category.name.clone(),
+                    // Themed when drawn, so switching themes doesn't need a new cache key.
+                    color: category.color,
+                    seconds: self.category_spent_today(category.id),
+                })
+                .filter(|layer| layer.seconds > 0)
+                .collect()
+        })
+    }
+
+    pub(super) fn render_strata_bands(&self, width: u16, height: u16) -> Vec<Line<'static>> {
+        let width = width as usize;
+        let rows = height as usize;
+        let layers = self.strata_layers();
+        let seconds: Vec<usize> = layers.iter().map(|layer| layer.seconds).collect();
+        let heights = ui_helpers::band_heights(&seconds, rows, STRATA_VIEW.full_scale_seconds);
+
+        let filled: usize = heights.iter().sum();
+        let mut lines: Vec<Line<'static>> = (0..rows.saturating_sub(filled))
+            .map(|_| Line::from(""))
+            .collect();
+
+        for (layer, band_height) in layers.iter().zip(&heights).rev() {
+            for row in 0..*band_height {
+                let label = if row == 0 {
+                    format!(" {} {}", layer.name, self.format_time(layer.seconds))
+                } else {
+                    String::new()
+                };
+                let label = self.truncate_label(&label, width);
+                let pad = width.saturating_sub(label.chars().count());
+                let color = self.theme().category_color(layer.color);
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        label,
+                        Style::default()
+                            .fg(view_style::text_color_for_bg(color))
+                            .bg(color),
+                    ),
+                    Span::styled(" ".repeat(pad), Style::default().bg(color)),
+                ]));
+            }
+        }
+
+        lines
+    }
+}
@@ -0,0 +1,178 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    prelude::{Line, Span},
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use crate::domain::CategoryId;
+
+use super::{App, ui_helpers};
+
+/// Tags shown after a category's name in the modal list.
+const ROW_TAGS: usize = 3;
+
+/// Edits the selected category's tags from the category modal. `input`
+/// holds a tag being typed; `selected` is the cursor in the tag list.
+#[derive(Debug)]
+pub(super) struct TagEditor {
+    category_id: CategoryId,
+    input: String,
+    selected: usize,
+}
+
+impl App {
+    pub(super) fn start_tag_edit(&mut self) {
+        if self.is_on_insert_space() {
+            return;
+        }
+        self.tag_editor = self.selected_category_id().map(|category_id| TagEditor {
+            category_id,
+            input: String::new(),
+            selected: 0,
+        });
+    }
+
+    pub(super) fn handle_tag_editor_key(&mut self, key: KeyEvent) {
+        let Some(editor) = self.tag_editor.as_mut() else {
+            return;
+        };
+        let count = self.category_tags.tags(editor.category_id).len();
+
+        match key.code {
+            KeyCode::Esc => self.tag_editor = None,
+            KeyCode::Up => editor.selected = ui_helpers::wrap_prev_index(editor.selected, count),
+            KeyCode::Down => editor.selected = ui_helpers::wrap_next_index(editor.selected, count),
+            KeyCode::Enter if editor.input.trim().is_empty() => self.tag_editor = None,
+            KeyCode::Enter => {
+                let tag = std::mem::take(&mut editor.input);
+                editor.selected = 0;
+                if self.category_tags.add_tag(editor.category_id, &tag) {
+                    self.modal_tag_index = None;
+                    self.persist_category_tags();
+                }
+            }
+            KeyCode::Delete => {
+                let removed = self
+                    .category_tags
+                    .remove_tag(editor.category_id, editor.selected);
+                if removed.is_some() {
+                    editor.selected = editor.selected.min(count.saturating_sub(2));
+                    self.modal_tag_index = None;
+                    self.persist_category_tags();
+                }
+            }
+            KeyCode::Char(c) => editor.input.push(c),
+            KeyCode::Backspace => {
+                editor.input.pop();
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn paste_into_tag_editor(&mut self, text: &str) {
+        if let Some(editor) = self.tag_editor.as_mut() {
+            editor.input.push_str(text);
+        }
+    }
+
+    /// The first few tags as a dim suffix for the category's modal row.
+    pub(super) fn row_tags_span(&self, category_id: CategoryId) -> Span<'static> {
+        let tags = self.category_tags.tags(category_id);
+        if tags.is_empty() {
+            return Span::raw("");
+        }
+        let mut text: String = tags
+            .iter()
+            .take(ROW_TAGS)
+            .map(|tag| format!(" #{}", tag))
+            .collect();
+        if tags.len() > ROW_TAGS {
+            text.push_str(&format!(" +{}", tags.len() - ROW_TAGS));
+        }
+        Span::styled(
+            format!(" {}", text),
+            Style::default().add_modifier(Modifier::DIM),
+        )
+    }
+
+    pub(super) fn render_tag_editor(&self, f: &mut Frame, area: Rect) {
+        let Some(editor) = self.tag_editor.as_ref() else {
+            return;
+        };
+        let color = self
+            .time_tracker
+            .category_color_by_id(editor.category_id)
+            .map_or(self.theme().text, |color| {
+                self.theme().category_color(color)
+            });
+        let tags = self.category_tags.tags(editor.category_id);
+
+        let mut lines = vec![
+            Line::from(Span::styled(
+                format!("tags for {}", self.category_name_for_id(editor.category_id)),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+        ];
+        if tags.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "no tags yet",
+                Style::default().fg(self.theme().muted),
+            )));
+        }
+        for (index, tag) in tags.iter().enumerate() {
+            let style = if index == editor.selected {
+                Style::default().fg(Color::Black).bg(color)
+            } else {
+                Style::default().fg(self.theme().text)
+            };
+            lines.push(Line::from(Span::styled(format!(" {} ", tag), style)));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("+ {}_", editor.input),
+            Style::default()
+                .fg(self.theme().text)
+                .add_modifier(Modifier::UNDERLINED),
+        )));
+
+        let footer = " type + enter add · del remove · esc done ";
+        let width = lines
+            .iter()
+            .map(Line::width)
+            .chain([footer.chars().count()])
+            .max()
+            .unwrap_or(0) as u16
+            + 4;
+        let width = width.min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let rect = Rect::new(
+            area.x + area.width.saturating_sub(width) / 2,
+            area.y + area.height.saturating_sub(height) / 2,
+            width,
+            height,
+        );
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .title_bottom(
+                Line::from(Span::styled(
+                    footer,
+                    Style::default().fg(self.theme().muted),
+                ))
+                .alignment(Alignment::Center),
+            )
+            .border_style(Style::default().fg(color));
+
+        f.render_widget(Clear, rect);
+        f.render_widget(
+            Paragraph::new(lines)
+                .alignment(Alignment::Center)
+                .block(block),
+            rect,
+        );
+    }
+}
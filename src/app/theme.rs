@@ -0,0 +1,155 @@
+use ratatui::style::Color;
+
+use crate::{
+    constants::COLORS,
+    storage::{self, ConfigState, ThemeConfig},
+};
+
+use super::App;
+
+pub(super) const DEFAULT_THEME: &str = "default";
+
+const PASTEL_PALETTE: [Color; 12] = [
+    Color::Rgb(119, 221, 119),
+    Color::Rgb(193, 240, 140),
+    Color::Rgb(253, 253, 150),
+    Color::Rgb(255, 223, 128),
+    Color::Rgb(255, 194, 133),
+    Color::Rgb(255, 154, 138),
+    Color::Rgb(255, 105, 97),
+    Color::Rgb(203, 153, 201),
+    Color::Rgb(177, 156, 217),
+    Color::Rgb(132, 182, 244),
+    Color::Rgb(158, 211, 240),
+    Color::Rgb(175, 238, 238),
+];
+
+/// Colors the TUI draws with. Categories keep their palette slot on disk and
+/// are drawn with the theme's color for that slot.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Theme {
+    pub(super) name: String,
+    palette: [Color; 12],
+    pub(super) border: Color,
+    pub(super) text: Color,
+    pub(super) muted: Color,
+    pub(super) karma_positive: Color,
+    pub(super) karma_negative: Color,
+}
+
+impl Theme {
+    fn base(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            palette: COLORS,
+            border: Color::White,
+            text: Color::White,
+            muted: Color::Gray,
+            karma_positive: Color::Green,
+            karma_negative: Color::Red,
+        }
+    }
+
+    fn builtin() -> Vec<Self> {
+        vec![
+            Self::base(DEFAULT_THEME),
+            Self {
+                palette: PASTEL_PALETTE,
+                border: Color::Rgb(220, 220, 235),
+                text: Color::Rgb(240, 240, 245),
+                muted: Color::Rgb(160, 160, 180),
+                karma_positive: Color::Rgb(119, 221, 119),
+                karma_negative: Color::Rgb(255, 105, 97),
+                ..Self::base("pastel")
+            },
+        ]
+    }
+
+    /// Starts from the built-in theme of the same name, or the default, and
+    /// applies whatever `config` sets; unparseable colors are ignored.
+    fn from_config(name: &str, config: &ThemeConfig, builtin: &[Self]) -> Self {
+        let mut theme = builtin
+            .iter()
+            .find(|theme| theme.name == name)
+            .cloned()
+            .unwrap_or_else(|| Self::base(name));
+        let parse = |value: &str| value.trim().parse::<Color>().ok();
+
+        for (slot, value) in theme.palette.iter_mut().zip(&config.palette) {
+            if let Some(color) = parse(value) {
+                *slot = color;
+            }
+        }
+        for (field, value) in [
+            (&mut theme.border, &config.border),
+            (&mut theme.text, &config.text),
+            (&mut theme.muted, &config.muted),
+            (&mut theme.karma_positive, &config.karma_positive),
+            (&mut theme.karma_negative, &config.karma_negative),
+        ] {
+            if let Some(color) = value.as_deref().and_then(parse) {
+                *field = color;
+            }
+        }
+        theme
+    }
+
+    /// Built-in themes followed by the user's, which replace a built-in of
+    /// the same name.
+    pub(super) fn load_all(config: &ConfigState) -> Vec<Self> {
+        let mut themes = Self::builtin();
+        for (name, theme_config) in storage::load_theme_configs(&storage::get_themes_dir(), config)
+        {
+            let theme = Self::from_config(&name, &theme_config, &themes);
+            match themes.iter_mut().find(|existing| existing.name == name) {
+                Some(existing) => *existing = theme,
+                None => themes.push(theme),
+            }
+        }
+        themes
+    }
+
+    pub(super) fn category_color(&self, color: Color) -> Color {
+        COLORS
+            .iter()
+            .position(|default| *default == color)
+            .map_or(color, |index| self.palette[index])
+    }
+
+    pub(super) fn karma_color(&self, seconds: isize) -> Color {
+        if seconds < 0 {
+            self.karma_negative
+        } else if seconds > 0 {
+            self.karma_positive
+        } else {
+            self.muted
+        }
+    }
+}
+
+impl App {
+    pub(super) fn theme(&self) -> &Theme {
+        &self.themes[self.theme_index]
+    }
+
+    pub(super) fn cycle_theme(&mut self) {
+        self.theme_index = (self.theme_index + 1) % self.themes.len();
+        self.record_usage("tui theme");
+        self.show_status(format!("theme: {}", self.theme().name));
+        self.render_needed = true;
+    }
+}
+
+/// Index of the configured theme, falling back to the default when it is
+/// unset or unknown.
+pub(super) fn configured_theme_index(themes: &[Theme], config: &ConfigState) -> usize {
+    let wanted = config
+        .theme
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or(DEFAULT_THEME);
+    themes
+        .iter()
+        .position(|theme| theme.name.eq_ignore_ascii_case(wanted))
+        .unwrap_or(0)
+}
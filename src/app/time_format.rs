@@ -12,29 +12,39 @@ impl App {
     }
 
     pub(super) fn get_karma_adjusted_time(&self) -> isize {
-        let today = operational_day_key_now().format("%Y-%m-%d").to_string();
-        let mut total: isize = 0;
-        for cat in self.time_tracker.categories_ordered() {
-            if cat.name == "none" {
-                continue;
+        let today = operational_day_key_now();
+        let key = (self.time_tracker.revision(), today, None);
+        self.karma_header_cache.get_or_compute(key, || {
+            let today = today.format("%Y-%m-%d").to_string();
+            let mut total: isize = 0;
+            for cat in self.time_tracker.categories_ordered() {
+                if cat.name == "none" {
+                    continue;
+                }
+                let cat_time: isize = self
+                    .time_tracker
+                    .sessions
+                    .iter()
+                    .filter(|s| s.date == today && s.category_id == cat.id)
+                    .map(|s| s.elapsed_seconds as isize)
+                    .sum();
+                total += cat_time * cat.karma_effect as isize;
             }
-            let cat_time: isize = self
-                .time_tracker
-                .sessions
-                .iter()
-                .filter(|s| s.date == today && s.category_id == cat.id)
-                .map(|s| s.elapsed_seconds as isize)
-                .sum();
-            total += cat_time * cat.karma_effect as isize;
-        }
-        total
+            total
+        })
     }
 
     pub(super) fn get_category_karma_adjusted_time(&self, category_name: &str) -> isize {
-        let today = operational_day_key_now().format("%Y-%m-%d").to_string();
-        let categories = self.time_tracker.categories_ordered();
-        let cat = categories.iter().find(|c| c.name == category_name);
-        if let Some(cat) = cat {
+        let Some(category_id) = self.time_tracker.category_id_by_name(category_name) else {
+            return 0;
+        };
+        let today = operational_day_key_now();
+        let key = (self.time_tracker.revision(), today, Some(category_id));
+        self.karma_header_cache.get_or_compute(key, || {
+            let Some(cat) = self.time_tracker.category_by_id(category_id) else {
+                return 0;
+            };
+            let today = today.format("%Y-%m-%d").to_string();
             let cat_time: isize = self
                 .time_tracker
                 .sessions
@@ -43,9 +53,7 @@ impl App {
                 .map(|s| s.elapsed_seconds as isize)
                 .sum();
             cat_time * cat.karma_effect as isize
-        } else {
-            0
-        }
+        })
     }
 
     pub(super) fn format_signed_time(&self, seconds: isize) -> String {
@@ -73,6 +81,9 @@ impl App {
     }
 
     pub(super) fn format_time(&self, seconds: usize) -> String {
+        if !self.show_seconds {
+            return format!("{:02}:{:02}", seconds / 3600, (seconds % 3600) / 60);
+        }
         format!(
             "{:02}:{:02}:{:02}",
             seconds / 3600,
@@ -0,0 +1,161 @@
+use chrono::Local;
+use ratatui::prelude::{Line, Span};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::Paragraph,
+};
+
+use crate::domain::{
+    CategoryId, TimelineEntry, build_day_timeline, operational_day_key_now, operational_day_start,
+};
+
+use super::{App, totals_strip::TOTALS_ROWS};
+
+pub(super) const TIMELINE_ROWS: u16 = 2;
+const DAY_SECONDS: usize = 24 * 3600;
+const LABEL_STEPS: [usize; 6] = [1, 2, 3, 4, 6, 12];
+
+impl App {
+    pub(super) fn toggle_timeline(&mut self) {
+        self.show_timeline = !self.show_timeline;
+        if self.show_timeline {
+            self.record_usage("tui timeline");
+        }
+        if let Ok((width, height)) = crossterm::terminal::size() {
+            self.queue_resize(width, height);
+        }
+        self.render_needed = true;
+    }
+
+    pub(super) fn timeline_rows(&self) -> u16 {
+        if self.show_timeline { TIMELINE_ROWS } else { 0 }
+    }
+
+    /// Rows under the sand taken by the timeline and totals strips.
+    pub(super) fn strip_rows(&self) -> u16 {
+        if self.zen {
+            return 0;
+        }
+        let totals = if self.show_totals { TOTALS_ROWS } else { 0 };
+        self.timeline_rows() + totals
+    }
+
+    /// Rows inside the sand border, leaving room for the strips when shown.
+    pub(super) fn sand_rows(&self, height: u16) -> u16 {
+        if self.zen {
+            return height;
+        }
+        height.saturating_sub(2 + self.strip_rows())
+    }
+
+    /// Columns inside the sand border, beside the report panel when split;
+    /// all of them in zen mode.
+    pub(super) fn sand_cols(&self, width: u16) -> u16 {
+        if self.zen {
+            width
+        } else {
+            width.saturating_sub(2 + self.panel_cols(width))
+        }
+    }
+
+    /// Today's stored sessions plus the running one, as offsets into the
+    /// operational day.
+    fn today_timeline(&self, now_offset: usize) -> Vec<TimelineEntry> {
+        let day = operational_day_key_now();
+        let mut entries =
+            self.timeline_cache
+                .get_or_compute((self.time_tracker.revision(), day), || {
+                    build_day_timeline(
+                        &self.time_tracker.sessions,
+                        &self.time_tracker.categories_for_storage(),
+                        day,
+                    )
+                });
+
+        let active_id = self.time_tracker.active_category_id();
+        if let Some(elapsed) = self.time_tracker.session_elapsed()
+            && active_id != CategoryId::new(0)
+            && let Some(category) = self.time_tracker.category_by_id(active_id)
+        {
+            entries.push(TimelineEntry {
+                category_name: category.name.clone(),
+                color: category.color,
+                description: self.time_tracker.session_description().to_string(),
+                start_offset_seconds: now_offset.saturating_sub(elapsed.as_secs() as usize),
+                end_offset_seconds: now_offset,
+            });
+        }
+        entries
+    }
+
+    /// Today's sessions on a 24h axis under the sand, with an hour scale and
+    /// a marker for now.
+    pub(super) fn render_timeline_strip(&self, f: &mut Frame, area: Rect) {
+        let width = area.width.saturating_sub(2) as usize;
+        if width == 0 || area.height < TIMELINE_ROWS {
+            return;
+        }
+
+        let day_start = operational_day_start(operational_day_key_now());
+        let now_offset = (Local::now().naive_local() - day_start)
+            .num_seconds()
+            .clamp(0, DAY_SECONDS as i64 - 1) as usize;
+        let entries = self.today_timeline(now_offset);
+
+        let bar: Vec<Span> = (0..width)
+            .map(|column| {
+                let start = column * DAY_SECONDS / width;
+                let end = (column + 1) * DAY_SECONDS / width;
+                let owner = entries
+                    .iter()
+                    .map(|entry| {
+                        let covered = entry
+                            .end_offset_seconds
+                            .min(end)
+                            .saturating_sub(entry.start_offset_seconds.max(start));
+                        (entry, covered)
+                    })
+                    .filter(|(_, covered)| *covered > 0)
+                    .max_by_key(|(_, covered)| *covered);
+                match owner {
+                    Some((entry, _)) => Span::styled(
+                        "━",
+                        Style::default().fg(self.theme().category_color(entry.color)),
+                    ),
+                    None => Span::styled("─", Style::default().fg(Color::DarkGray)),
+                }
+            })
+            .collect();
+
+        let step = LABEL_STEPS
+            .iter()
+            .copied()
+            .find(|hours| hours * 3600 * width / DAY_SECONDS >= 3)
+            .unwrap_or(24);
+        let mut axis = vec![' '; width];
+        for hour in (0..24).step_by(step) {
+            let column = hour * 3600 * width / DAY_SECONDS;
+            let label = (day_start + chrono::Duration::hours(hour as i64))
+                .format("%H")
+                .to_string();
+            for (offset, ch) in label.chars().enumerate() {
+                if let Some(cell) = axis.get_mut(column + offset) {
+                    *cell = ch;
+                }
+            }
+        }
+        let now_column = now_offset * width / DAY_SECONDS;
+        let before: String = axis[..now_column].iter().collect();
+        let after: String = axis[now_column + 1..].iter().collect();
+        let axis = Line::from(vec![
+            Span::styled(before, Style::default().fg(self.theme().muted)),
+            Span::styled("▲", Style::default().fg(self.theme().text)),
+            Span::styled(after, Style::default().fg(self.theme().muted)),
+        ]);
+
+        let strip = Rect::new(area.x + 1, area.y, width as u16, TIMELINE_ROWS);
+        f.render_widget(Paragraph::new(vec![Line::from(bar), axis]), strip);
+    }
+}
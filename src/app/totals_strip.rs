@@ -0,0 +1,63 @@
+use ratatui::prelude::{Line, Span};
+use ratatui::{Frame, layout::Rect, style::Style, widgets::Paragraph};
+
+use crate::domain::CategoryId;
+
+use super::App;
+
+pub(super) const TOTALS_ROWS: u16 = 1;
+
+impl App {
+    pub(super) fn toggle_totals_strip(&mut self) {
+        self.show_totals = !self.show_totals;
+        if self.show_totals {
+            self.record_usage("tui totals strip");
+        }
+        if let Ok((width, height)) = crossterm::terminal::size() {
+            self.queue_resize(width, height);
+        }
+        self.render_needed = true;
+    }
+
+    /// `name hh:mm` chips for every category tracked today, in category
+    /// order; chips that don't fit are left off.
+    pub(super) fn render_totals_strip(&self, f: &mut Frame, area: Rect) {
+        let width = area.width.saturating_sub(2) as usize;
+        if width == 0 || area.height < TOTALS_ROWS {
+            return;
+        }
+
+        let mut spans: Vec<Span> = Vec::new();
+        let mut used = 0;
+        for category in self.time_tracker.categories_ordered() {
+            let seconds = self.category_spent_today(category.id);
+            if seconds < 60 {
+                continue;
+            }
+            let color = if category.id == CategoryId::new(0) {
+                self.theme().muted
+            } else {
+                self.theme().category_color(category.color)
+            };
+            let chip = format!(
+                "● {} {:02}:{:02}",
+                category.name,
+                seconds / 3600,
+                (seconds % 3600) / 60
+            );
+            let gap = if spans.is_empty() { 0 } else { 2 };
+            let chip_width = chip.chars().count();
+            if used + gap + chip_width > width {
+                break;
+            }
+            if gap > 0 {
+                spans.push(Span::raw("  "));
+            }
+            spans.push(Span::styled(chip, Style::default().fg(color)));
+            used += gap + chip_width;
+        }
+
+        let strip = Rect::new(area.x + 1, area.y, width as u16, TOTALS_ROWS);
+        f.render_widget(Paragraph::new(Line::from(spans)), strip);
+    }
+}
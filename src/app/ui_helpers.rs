@@ -1,18 +1,21 @@
 use chrono::{Datelike, NaiveDate};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 use crate::domain::ReportPeriod;
 
 pub fn report_period_prev(period: ReportPeriod) -> ReportPeriod {
     match period {
         ReportPeriod::Today => ReportPeriod::Month,
-        ReportPeriod::Week => ReportPeriod::Today,
+        ReportPeriod::Last24Hours => ReportPeriod::Today,
+        ReportPeriod::Week => ReportPeriod::Last24Hours,
         ReportPeriod::Month => ReportPeriod::Week,
     }
 }
 
 pub fn report_period_next(period: ReportPeriod) -> ReportPeriod {
     match period {
-        ReportPeriod::Today => ReportPeriod::Week,
+        ReportPeriod::Today => ReportPeriod::Last24Hours,
+        ReportPeriod::Last24Hours => ReportPeriod::Week,
         ReportPeriod::Week => ReportPeriod::Month,
         ReportPeriod::Month => ReportPeriod::Today,
     }
@@ -46,6 +49,52 @@ pub fn format_report_interval_label(raw: &str) -> String {
         .unwrap_or_else(|| raw.to_string())
 }
 
+pub fn band_heights(seconds: &[usize], rows: usize, full_scale: usize) -> Vec<usize> {
+    let total: usize = seconds.iter().sum();
+    let scale = total.max(full_scale).max(1);
+    let filled_rows = (total * rows + scale / 2) / scale;
+
+    let mut heights: Vec<usize> = seconds.iter().map(|value| value * rows / scale).collect();
+    let mut remainders: Vec<(usize, usize)> = seconds
+        .iter()
+        .enumerate()
+        .map(|(index, value)| (value * rows % scale, index))
+        .collect();
+    remainders.sort_by(|left, right| right.0.cmp(&left.0).then(left.1.cmp(&right.1)));
+
+    let assigned: usize = heights.iter().sum();
+    for (_, index) in remainders
+        .into_iter()
+        .take(filled_rows.saturating_sub(assigned))
+    {
+        heights[index] += 1;
+    }
+    heights
+}
+
+const BAR_EIGHTHS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Glyph for `row` (0 at the bottom) of a bar stacked from `eighths`, each
+/// segment's height in eighths of a row, and the segment that owns it.
+pub fn stacked_bar_cell(eighths: &[usize], row: usize) -> Option<(char, usize)> {
+    let total: usize = eighths.iter().sum();
+    let floor = row * 8;
+    if total <= floor {
+        return None;
+    }
+    let filled = (total - floor).min(8);
+    let middle = floor + filled / 2;
+    let mut top = 0;
+    let segment = eighths
+        .iter()
+        .position(|height| {
+            top += height;
+            middle < top
+        })
+        .unwrap_or(eighths.len() - 1);
+    Some((BAR_EIGHTHS[filled - 1], segment))
+}
+
 pub fn wrap_prev_index(current: usize, len: usize) -> usize {
     if len == 0 {
         0
@@ -64,9 +113,303 @@ pub fn wrap_next_index(current: usize, len: usize) -> usize {
     }
 }
 
+/// Turns vim motions into the keys the lists already handle: `j/k/h/l` into
+/// arrows, `gg`/`G` into home/end and `dd` into delete. The first `g` or `d`
+/// waits in `pending` and yields nothing; any other key passes through.
+pub fn translate_vim_key(pending: &mut Option<char>, key: KeyEvent) -> Option<KeyEvent> {
+    if key
+        .modifiers
+        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    {
+        *pending = None;
+        return Some(key);
+    }
+    let code = match (pending.take(), key.code) {
+        (Some('g'), KeyCode::Char('g')) => KeyCode::Home,
+        (Some('d'), KeyCode::Char('d')) => KeyCode::Delete,
+        (_, KeyCode::Char(prefix @ ('g' | 'd'))) => {
+            *pending = Some(prefix);
+            return None;
+        }
+        (_, KeyCode::Char('j')) => KeyCode::Down,
+        (_, KeyCode::Char('k')) => KeyCode::Up,
+        (_, KeyCode::Char('h')) => KeyCode::Left,
+        (_, KeyCode::Char('l')) => KeyCode::Right,
+        (_, KeyCode::Char('G')) => KeyCode::End,
+        _ => return Some(key),
+    };
+    Some(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+/// A `width`-cell bar filled to `fraction` in eighths, the rest `·`.
+pub fn progress_gauge(fraction: f64, width: usize) -> String {
+    const PARTIAL: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+    let eighths = (fraction.clamp(0.0, 1.0) * (width * 8) as f64).round() as usize;
+    let full = eighths / 8;
+    let mut gauge = "█".repeat(full);
+    if full < width {
+        match eighths % 8 {
+            0 => gauge.push('·'),
+            part => gauge.push(PARTIAL[part - 1]),
+        }
+        gauge.push_str(&"·".repeat(width - full - 1));
+    }
+    gauge
+}
+
+/// One line of the category modal outside the fuzzy filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModalRow {
+    Category(usize),
+    Group {
+        name: String,
+        collapsed: bool,
+        count: usize,
+    },
+    Insert,
+}
+
+/// Ungrouped categories first, then each group under its header in `order`;
+/// members of collapsed groups are left out. `groups[i]` is category `i`'s group.
+pub fn grouped_modal_rows(
+    groups: &[Option<&str>],
+    order: &[String],
+    is_collapsed: impl Fn(&str) -> bool,
+) -> Vec<ModalRow> {
+    let mut rows: Vec<ModalRow> = (0..groups.len())
+        .filter(|&index| groups[index].is_none())
+        .map(ModalRow::Category)
+        .collect();
+    for name in order {
+        let members: Vec<usize> = (0..groups.len())
+            .filter(|&index| groups[index] == Some(name.as_str()))
+            .collect();
+        let collapsed = is_collapsed(name);
+        rows.push(ModalRow::Group {
+            name: name.clone(),
+            collapsed,
+            count: members.len(),
+        });
+        if !collapsed {
+            rows.extend(members.into_iter().map(ModalRow::Category));
+        }
+    }
+    rows.push(ModalRow::Insert);
+    rows
+}
+
+/// Scores `candidate` against a fuzzy `query` whose characters must all
+/// appear in order. Consecutive runs and word starts score higher, a late
+/// first match lower; `None` means no match.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query.trim().to_lowercase().chars().collect();
+    let chars: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0i64;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query {
+        let position = (next..chars.len()).find(|&index| chars[index] == wanted)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == position) {
+            score += 5;
+        }
+        if position == 0 || !chars[position - 1].is_alphanumeric() {
+            score += 3;
+        }
+        if previous.is_none() {
+            score -= position as i64;
+        }
+        previous = Some(position);
+        next = position + 1;
+    }
+    Some(score)
+}
+
+/// Byte offset of char `cursor` in `text`, clamped to the end.
+fn char_offset(text: &str, cursor: usize) -> usize {
+    text.char_indices()
+        .nth(cursor)
+        .map_or(text.len(), |(offset, _)| offset)
+}
+
+/// Inserts `c` before char `cursor`; returns the cursor after it.
+pub fn insert_char(text: &mut String, cursor: usize, c: char) -> usize {
+    text.insert(char_offset(text, cursor), c);
+    cursor + 1
+}
+
+/// Inserts `s` before char `cursor`; returns the cursor after it.
+pub fn insert_str(text: &mut String, cursor: usize, s: &str) -> usize {
+    text.insert_str(char_offset(text, cursor), s);
+    cursor + s.chars().count()
+}
+
+/// Pasted text as one line: line breaks and other control chars become
+/// spaces, and the ends are trimmed.
+pub fn paste_line(pasted: &str) -> String {
+    pasted
+        .replace("\r\n", "\n")
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Deletes the char before `cursor`; returns the new cursor.
+pub fn delete_char_before(text: &mut String, cursor: usize) -> usize {
+    if cursor == 0 {
+        return 0;
+    }
+    text.remove(char_offset(text, cursor - 1));
+    cursor - 1
+}
+
+/// Deletes back to the start of the word before `cursor`, spaces first,
+/// like Ctrl+W in a shell; returns the new cursor.
+pub fn delete_word_before(text: &mut String, cursor: usize) -> usize {
+    let chars: Vec<char> = text.chars().take(cursor).collect();
+    let mut start = chars.len();
+    while start > 0 && chars[start - 1].is_whitespace() {
+        start -= 1;
+    }
+    while start > 0 && !chars[start - 1].is_whitespace() {
+        start -= 1;
+    }
+    text.replace_range(char_offset(text, start)..char_offset(text, chars.len()), "");
+    start
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{format_report_interval_label, wrap_next_index, wrap_prev_index};
+    use super::{
+        ModalRow, band_heights, delete_char_before, delete_word_before,
+        format_report_interval_label, fuzzy_score, grouped_modal_rows, insert_char, insert_str,
+        paste_line, progress_gauge, stacked_bar_cell, translate_vim_key, wrap_next_index,
+        wrap_prev_index,
+    };
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn test_translate_vim_key_maps_motions_and_waits_for_doubled_prefixes() {
+        let mut pending = None;
+        let mut press = |code, modifiers| {
+            translate_vim_key(&mut pending, KeyEvent::new(code, modifiers)).map(|key| key.code)
+        };
+        let plain = KeyModifiers::NONE;
+
+        assert_eq!(press(KeyCode::Char('j'), plain), Some(KeyCode::Down));
+        assert_eq!(press(KeyCode::Char('l'), plain), Some(KeyCode::Right));
+        assert_eq!(
+            press(KeyCode::Char('G'), KeyModifiers::SHIFT),
+            Some(KeyCode::End)
+        );
+        assert_eq!(press(KeyCode::Char('g'), plain), None);
+        assert_eq!(press(KeyCode::Char('g'), plain), Some(KeyCode::Home));
+        assert_eq!(press(KeyCode::Char('d'), plain), None);
+        assert_eq!(press(KeyCode::Char('d'), plain), Some(KeyCode::Delete));
+        assert_eq!(press(KeyCode::Char('d'), plain), None);
+        assert_eq!(press(KeyCode::Char('k'), plain), Some(KeyCode::Up));
+        assert_eq!(press(KeyCode::Char('d'), plain), None);
+        assert_eq!(press(KeyCode::Char('x'), plain), Some(KeyCode::Char('x')));
+        assert_eq!(
+            press(KeyCode::Char('j'), KeyModifiers::CONTROL),
+            Some(KeyCode::Char('j'))
+        );
+        assert_eq!(press(KeyCode::Down, plain), Some(KeyCode::Down));
+    }
+
+    #[test]
+    fn test_fuzzy_score_requires_ordered_subsequence() {
+        assert!(fuzzy_score("dw", "Deep work").is_some());
+        assert!(fuzzy_score("DW", "deep work").is_some());
+        assert_eq!(fuzzy_score("wd", "Deep work"), None);
+        assert_eq!(fuzzy_score("x", "Deep work"), None);
+        assert_eq!(fuzzy_score("", "Deep work"), Some(0));
+    }
+
+    #[test]
+    fn test_fuzzy_score_prefers_prefixes_runs_and_word_starts() {
+        let score = |query, candidate| fuzzy_score(query, candidate).unwrap();
+        assert!(score("wo", "Work") > score("wo", "Homework"));
+        assert!(score("dw", "Deep work") > score("dw", "Download"));
+        assert!(score("read", "Reading") > score("read", "Rental admin"));
+    }
+
+    #[test]
+    fn test_band_heights_scale_against_full_day_until_exceeded() {
+        assert_eq!(band_heights(&[3600, 3600], 16, 8 * 3600), vec![2, 2]);
+        assert_eq!(band_heights(&[5400, 1800], 8, 3600), vec![6, 2]);
+        assert_eq!(band_heights(&[1000, 1000, 1000], 10, 3000), vec![4, 3, 3]);
+        assert_eq!(band_heights(&[], 10, 3600), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_stacked_bar_cell_colors_by_the_segment_under_the_middle() {
+        assert_eq!(stacked_bar_cell(&[8, 4], 0), Some(('█', 0)));
+        assert_eq!(stacked_bar_cell(&[8, 4], 1), Some(('▄', 1)));
+        assert_eq!(stacked_bar_cell(&[8, 4], 2), None);
+        assert_eq!(stacked_bar_cell(&[3, 9], 0), Some(('█', 1)));
+        assert_eq!(stacked_bar_cell(&[0, 1], 0), Some(('▁', 1)));
+        assert_eq!(stacked_bar_cell(&[], 0), None);
+    }
+
+    #[test]
+    fn test_progress_gauge_fills_in_eighths() {
+        assert_eq!(progress_gauge(0.0, 4), "····");
+        assert_eq!(progress_gauge(0.5, 4), "██··");
+        assert_eq!(progress_gauge(0.3, 4), "█▎··");
+        assert_eq!(progress_gauge(1.7, 4), "████");
+    }
+
+    #[test]
+    fn test_grouped_modal_rows_hide_collapsed_members() {
+        let groups = [None, Some("Work"), Some("Health"), None, Some("Work")];
+        let order = vec!["Work".to_string(), "Health".to_string()];
+        let rows = grouped_modal_rows(&groups, &order, |name| name == "Health");
+
+        assert_eq!(
+            rows,
+            vec![
+                ModalRow::Category(0),
+                ModalRow::Category(3),
+                ModalRow::Group {
+                    name: "Work".to_string(),
+                    collapsed: false,
+                    count: 2,
+                },
+                ModalRow::Category(1),
+                ModalRow::Category(4),
+                ModalRow::Group {
+                    name: "Health".to_string(),
+                    collapsed: true,
+                    count: 1,
+                },
+                ModalRow::Insert,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_edits_work_at_the_cursor() {
+        let mut text = "deep wörk".to_string();
+        assert_eq!(insert_char(&mut text, 5, 'x'), 6);
+        assert_eq!(text, "deep xwörk");
+        assert_eq!(delete_char_before(&mut text, 8), 7);
+        assert_eq!(text, "deep xwrk");
+        assert_eq!(delete_char_before(&mut text, 0), 0);
+        assert_eq!(insert_str(&mut text, 5, &paste_line("new\r\nline\n")), 13);
+        assert_eq!(text, "deep new linexwrk");
+
+        let mut text = "fix the  bug".to_string();
+        assert_eq!(delete_word_before(&mut text, 9), 4);
+        assert_eq!(text, "fix bug");
+        assert_eq!(delete_word_before(&mut text, 7), 4);
+        assert_eq!(text, "fix ");
+        assert_eq!(delete_word_before(&mut text, 4), 0);
+        assert_eq!(text, "");
+    }
 
     #[test]
     fn test_wrap_prev_index_wraps_to_end() {
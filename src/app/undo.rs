@@ -0,0 +1,113 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    constants::UNDO,
+    domain::{Category, CategoryId, Session},
+    sand::SandState,
+};
+
+use super::App;
+
+/// What a destructive TUI action removed, kept so `u` can put it back.
+#[derive(Debug)]
+pub(super) enum UndoAction {
+    DeleteCategory {
+        index: usize,
+        category: Category,
+        tags: Option<Vec<String>>,
+    },
+    ClearSand {
+        sand: SandState,
+    },
+    ResetIdle {
+        sessions: Vec<Session>,
+        sand: SandState,
+        session_start: Option<Instant>,
+    },
+}
+
+impl App {
+    pub(super) fn push_undo(&mut self, action: UndoAction) {
+        if self.undo_stack.len() >= UNDO.max_actions {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(action);
+    }
+
+    pub(super) fn undo_last(&mut self) {
+        let message = match self.undo_stack.pop() {
+            None => "nothing to undo".to_string(),
+            Some(UndoAction::DeleteCategory {
+                index,
+                category,
+                tags,
+            }) => {
+                let name = category.name.clone();
+                if self.time_tracker.restore_category(index, category) {
+                    let id = self.time_tracker.category_by_index(index).map(|c| c.id.0);
+                    if let (Some(id), Some(tags)) = (id, tags) {
+                        self.category_tags.tags_by_category.insert(id, tags);
+                        self.persist_category_tags();
+                    }
+                    self.persist_categories();
+                    format!("undid delete of '{}'", name)
+                } else {
+                    format!("can't restore '{}': the name is taken", name)
+                }
+            }
+            Some(UndoAction::ClearSand { sand }) => {
+                self.restore_sand(&sand);
+                "undid clear sand".to_string()
+            }
+            Some(UndoAction::ResetIdle {
+                sessions,
+                sand,
+                session_start,
+            }) => {
+                let count = sessions.len();
+                self.time_tracker.restore_sessions(sessions);
+                if self.time_tracker.active_category_id() == CategoryId::new(0) {
+                    self.time_tracker.current_session_start = session_start;
+                }
+                self.persist_sessions();
+                self.restore_sand(&sand);
+                format!("undid idle reset ({} session(s) back)", count)
+            }
+        };
+        self.show_status(message);
+    }
+
+    fn restore_sand(&mut self, sand: &SandState) {
+        let valid_category_ids = self
+            .time_tracker
+            .categories_ordered()
+            .map(|category| category.id)
+            .collect::<HashSet<_>>();
+        self.sand_engine.restore_state(sand, &valid_category_ids);
+        self.persist_sand_state();
+    }
+
+    pub(super) fn show_status(&mut self, message: String) {
+        self.status_message = Some((message, Instant::now()));
+        self.render_needed = true;
+    }
+
+    pub(super) fn status_text(&self) -> Option<&str> {
+        self.status_message
+            .as_ref()
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// Drops the status message once it has been shown long enough.
+    pub(super) fn expire_status(&mut self) {
+        if self.status_message.as_ref().is_some_and(|(_, shown_at)| {
+            shown_at.elapsed() >= Duration::from_millis(UNDO.message_ms)
+        }) {
+            self.status_message = None;
+            self.render_needed = true;
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::cell::RefCell;
+
+pub(super) struct ViewCache<K, V> {
+    slot: RefCell<Option<(K, V)>>,
+}
+
+impl<K: PartialEq, V: Clone> ViewCache<K, V> {
+    pub(super) fn new() -> Self {
+        Self {
+            slot: RefCell::new(None),
+        }
+    }
+
+    pub(super) fn get_or_compute(&self, key: K, compute: impl FnOnce() -> V) -> V {
+        if let Some((cached_key, value)) = self.slot.borrow().as_ref()
+            && *cached_key == key
+        {
+            return value.clone();
+        }
+
+        let value = compute();
+        *self.slot.borrow_mut() = Some((key, value.clone()));
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::ViewCache;
+
+    #[test]
+    fn test_view_cache_recomputes_only_on_key_change() {
+        let cache = ViewCache::new();
+        let computed = Cell::new(0);
+        let compute = |value: usize| {
+            computed.set(computed.get() + 1);
+            value
+        };
+
+        assert_eq!(cache.get_or_compute(1, || compute(10)), 10);
+        assert_eq!(cache.get_or_compute(1, || compute(20)), 10);
+        assert_eq!(computed.get(), 1);
+
+        assert_eq!(cache.get_or_compute(2, || compute(30)), 30);
+        assert_eq!(computed.get(), 2);
+    }
+}
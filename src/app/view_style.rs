@@ -16,24 +16,121 @@ pub(super) fn report_period_label_span(label: &str, active: bool) -> Span<'stati
 }
 
 pub(super) fn text_color_for_bg(bg_color: Color) -> Color {
-    if let Color::Rgb(r, g, b) = bg_color {
-        let brightness = (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000;
-        if brightness > 128 {
-            Color::Black
-        } else {
-            Color::White
-        }
+    let Some(bg) = color_to_rgb(bg_color) else {
+        return Color::White;
+    };
+
+    let bg_luminance = relative_luminance(bg);
+    let black_contrast = contrast_ratio(bg_luminance, 0.0);
+    let white_contrast = contrast_ratio(bg_luminance, 1.0);
+    if black_contrast > white_contrast {
+        Color::Black
     } else {
         Color::White
     }
 }
 
-pub(super) fn karma_color(seconds: isize) -> Color {
-    if seconds < 0 {
-        Color::Red
-    } else if seconds > 0 {
-        Color::Green
+fn color_to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    let rgb = match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(index) => return Some(indexed_to_rgb(index)),
+        Color::Black => ANSI_RGB[0],
+        Color::Red => ANSI_RGB[1],
+        Color::Green => ANSI_RGB[2],
+        Color::Yellow => ANSI_RGB[3],
+        Color::Blue => ANSI_RGB[4],
+        Color::Magenta => ANSI_RGB[5],
+        Color::Cyan => ANSI_RGB[6],
+        Color::Gray => ANSI_RGB[7],
+        Color::DarkGray => ANSI_RGB[8],
+        Color::LightRed => ANSI_RGB[9],
+        Color::LightGreen => ANSI_RGB[10],
+        Color::LightYellow => ANSI_RGB[11],
+        Color::LightBlue => ANSI_RGB[12],
+        Color::LightMagenta => ANSI_RGB[13],
+        Color::LightCyan => ANSI_RGB[14],
+        Color::White => ANSI_RGB[15],
+        Color::Reset => return None,
+    };
+    Some(rgb)
+}
+
+const ANSI_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI_RGB[index as usize],
+        16..=231 => {
+            let cube = index - 16;
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            (level(cube / 36), level((cube / 6) % 6), level(cube % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+fn relative_luminance((r, g, b): (u8, u8, u8)) -> f64 {
+    let channel = |value: u8| {
+        let value = value as f64 / 255.0;
+        if value <= 0.03928 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b)
+}
+
+fn contrast_ratio(first: f64, second: f64) -> f64 {
+    let (lighter, darker) = if first > second {
+        (first, second)
     } else {
-        Color::Gray
+        (second, first)
+    };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use super::{indexed_to_rgb, text_color_for_bg};
+
+    #[test]
+    fn test_text_color_for_bg_handles_rgb_and_named_colors() {
+        assert_eq!(text_color_for_bg(Color::Rgb(255, 255, 0)), Color::Black);
+        assert_eq!(text_color_for_bg(Color::Rgb(0, 0, 255)), Color::White);
+        assert_eq!(text_color_for_bg(Color::White), Color::Black);
+        assert_eq!(text_color_for_bg(Color::Blue), Color::White);
+        assert_eq!(text_color_for_bg(Color::Reset), Color::White);
+    }
+
+    #[test]
+    fn test_text_color_for_bg_maps_indexed_colors() {
+        assert_eq!(indexed_to_rgb(196), (255, 0, 0));
+        assert_eq!(indexed_to_rgb(232), (8, 8, 8));
+        assert_eq!(text_color_for_bg(Color::Indexed(226)), Color::Black);
+        assert_eq!(text_color_for_bg(Color::Indexed(17)), Color::White);
+        assert_eq!(text_color_for_bg(Color::Indexed(255)), Color::Black);
     }
 }
@@ -0,0 +1,23 @@
+use super::App;
+
+impl App {
+    /// Hides the border, titles, timers and strips so the sand fills the
+    /// terminal; the next key brings them back.
+    pub(super) fn enter_zen(&mut self) {
+        self.record_usage("tui zen");
+        self.zen = true;
+        self.resize_to_terminal();
+    }
+
+    pub(super) fn exit_zen(&mut self) {
+        self.zen = false;
+        self.resize_to_terminal();
+    }
+
+    pub(super) fn resize_to_terminal(&mut self) {
+        if let Ok((width, height)) = crossterm::terminal::size() {
+            self.queue_resize(width, height);
+        }
+        self.render_needed = true;
+    }
+}
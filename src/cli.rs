@@ -1,20 +1,85 @@
-use std::{io, path::PathBuf};
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+};
 
-use chrono::{DateTime, Duration as ChronoDuration, Local, Utc};
-use clap::{CommandFactory, Parser, ValueEnum};
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Utc,
+};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use crossterm::style::Stylize;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    constants::COLORS,
+    constants::{AUDIT, COLORS},
+    daemon::{self, DaemonRequest, DaemonResponse},
     domain::{
-        CategoryId, ReportPeriod, Session, build_period_report, operational_day_key_for_local,
+        ActivityEvent, Category, CategoryId, Invoice, InvoiceLine, KarmaReportSummary,
+        LiveSessionPreview, MinSessionLength, PeriodLabelStyle, ReportPeriod, ReportSummary,
+        Session, SessionQuery, average_focus_by_category, build_daily_reports, build_day_timeline,
+        build_goal_audit, build_invoice, build_period_karma_report,
+        build_period_karma_report_with_live, build_period_report, color_hex, daily_tracked_seconds,
+        diff_records, drop_micro_sessions, forget_removed_overlaps, format_period_label,
+        goal_ledger_week, latest_clock_time_before, operational_day_key_for_local,
+        operational_day_key_now, operational_day_start, period_date_bounds, period_file_stem,
+        period_sessions, planned_block_start, planned_item_seconds, recategorize_sessions,
+        remove_sessions, rename_project, session_interval, set_period_label_style,
+        set_session_interval, set_week_start, sort_karma_entries_for_display,
+        suggest_activity_sessions, unresolved_session_overlaps, validate_session_end, week_start,
     },
-    storage,
+    duration::{duration_arg, format_duration, parse_duration},
+    hooks::{self, HookEvent, HookSession},
+    notify, sand,
+    storage::{self, CsvHeaderStatus},
 };
 
 #[derive(Parser, Debug)]
 #[command(name = "strata")]
 #[command(about = "Time tracking with falling sand", long_about = None)]
+pub struct CliArgs {
+    #[arg(
+        long,
+        global = true,
+        help = "Emit JSON instead of text (start, stop, status, report, karma, sessions)"
+    )]
+    pub json: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        help = "Use this data directory instead of the default (also STRATA_DATA_DIR)"
+    )]
+    pub data_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        help = "Use a named profile for data, state and config (also STRATA_PROFILE)"
+    )]
+    pub profile: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Panic if the sand engine loses or duplicates grains (always on in debug builds)"
+    )]
+    pub check_invariants: bool,
+
+    #[arg(
+        long,
+        help = "Run the sand animation with random categories, saving nothing; any key quits"
+    )]
+    pub screensaver: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Cli>,
+}
+
+#[derive(Subcommand, Debug)]
 pub enum Cli {
     #[command(about = "Start a new tracking session")]
     Start {
@@ -26,10 +91,73 @@ pub enum Cli {
 
         #[arg(long, short, help = "Category name or ID")]
         category: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "HH:MM",
+            help = "Backdate the start to this clock time"
+        )]
+        at: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "DURATION",
+            conflicts_with = "at",
+            value_parser = duration_arg,
+            help = "Backdate the start by this long, e.g. 20m or 1h15m"
+        )]
+        ago: Option<usize>,
     },
 
     #[command(about = "Stop the current tracking session")]
-    Stop,
+    Stop {
+        #[arg(
+            long,
+            value_name = "HH:MM",
+            help = "End the session at this clock time"
+        )]
+        at: Option<String>,
+
+        #[arg(
+            long,
+            conflicts_with = "at",
+            help = "End the session without recording it"
+        )]
+        discard: bool,
+    },
+
+    #[command(about = "Run a single timer with the sand view")]
+    Stopwatch,
+
+    #[command(about = "Run timed work and break intervals")]
+    Pomodoro {
+        #[arg(
+            long,
+            default_value = "25m",
+            value_name = "DURATION",
+            value_parser = duration_arg,
+            help = "Work interval, e.g. 25m or 1h (bare numbers are minutes)"
+        )]
+        work: usize,
+
+        #[arg(
+            long = "break",
+            default_value = "5m",
+            value_name = "DURATION",
+            value_parser = duration_arg,
+            help = "Break interval, e.g. 5m or 90s (bare numbers are minutes)"
+        )]
+        break_seconds: usize,
+
+        #[arg(long, short, help = "Category name or ID")]
+        category: Option<String>,
+
+        #[arg(long, help = "Stop after this many work intervals")]
+        cycles: Option<usize>,
+
+        #[arg(long, help = "Run in the TUI with the sand as an hourglass")]
+        tui: bool,
+    },
 
     #[command(about = "Show a time report")]
     Report {
@@ -42,7 +170,7 @@ pub enum Cli {
 
         #[arg(
             long,
-            help = "Show last 7 days",
+            help = "Show this week (the last 7 days unless week_start is configured)",
             conflicts_with_all = ["today", "month"]
         )]
         week: bool,
@@ -53,6 +181,51 @@ pub enum Cli {
             conflicts_with_all = ["today", "week"]
         )]
         month: bool,
+
+        #[arg(
+            long,
+            value_enum,
+            help = "Report period",
+            conflicts_with_all = ["today", "week", "month"]
+        )]
+        period: Option<PeriodArg>,
+
+        #[arg(
+            long,
+            value_enum,
+            help = "Print one section per group",
+            conflicts_with = "html"
+        )]
+        group_by: Option<ReportGrouping>,
+
+        #[arg(long, value_name = "PATH", help = "Write a self-contained HTML report")]
+        html: Option<PathBuf>,
+
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = duration_arg,
+            help = "Leave out sessions shorter than this (overrides min_session in the config, 0 keeps all)"
+        )]
+        min_session: Option<usize>,
+    },
+
+    #[command(about = "Show a day's sessions on an hour axis")]
+    Timeline {
+        #[arg(long, help = "Day to show (YYYY-MM-DD, defaults to today)")]
+        date: Option<String>,
+    },
+
+    #[command(about = "Show the karma report")]
+    Karma {
+        #[arg(long, value_enum, default_value_t = PeriodArg::Today, help = "Report period")]
+        period: PeriodArg,
+    },
+
+    #[command(about = "Show a calendar heatmap of tracked time")]
+    Heatmap {
+        #[arg(long, default_value_t = 12, help = "Number of weeks to show")]
+        weeks: usize,
     },
 
     #[command(about = "Export sessions")]
@@ -60,10 +233,181 @@ pub enum Cli {
         #[arg(long, value_enum, help = "Export format")]
         format: ExportFormat,
 
+        #[arg(
+            long,
+            short,
+            help = "Output path or directory (defaults to STRATA_EXPORT_DIR, else stdout)"
+        )]
+        out: Option<PathBuf>,
+
+        #[arg(long, value_enum, help = "Only export sessions from this period")]
+        period: Option<PeriodArg>,
+
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = duration_arg,
+            help = "ICS only: remind this long before planned blocks (default plan_alarm_minutes, 0 for none)"
+        )]
+        alarm_before: Option<usize>,
+
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = duration_arg,
+            help = "Leave out sessions shorter than this (overrides min_session in the config, 0 keeps all)"
+        )]
+        min_session: Option<usize>,
+    },
+
+    #[command(about = "Import sessions from another time tracker")]
+    Import {
+        #[arg(long, value_enum, help = "Import format")]
+        format: ImportFormat,
+
+        #[arg(help = "File to read (defaults to stdin)")]
+        path: Option<PathBuf>,
+
+        #[arg(
+            long,
+            value_name = "HOST:PORT",
+            conflicts_with = "path",
+            help = "Fetch buckets from a running aw-server instead (activitywatch only)"
+        )]
+        server: Option<String>,
+
+        #[arg(long, help = "Show what would be imported without writing anything")]
+        dry_run: bool,
+    },
+
+    #[command(about = "Manage projects")]
+    Project {
+        #[command(subcommand)]
+        command: ProjectCommand,
+    },
+
+    #[command(about = "Manage profiles with separate data, state and config")]
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommand,
+    },
+
+    #[command(about = "Show the active session and today's budgets")]
+    Status,
+
+    #[command(about = "Print a one-line summary for status bars")]
+    Summary {
+        #[arg(
+            long,
+            help = "Template with {icon} {category} {project} {description} {elapsed} {today} {karma}"
+        )]
+        format: Option<String>,
+    },
+
+    #[command(about = "Inspect recorded sessions")]
+    Sessions {
+        #[command(subcommand)]
+        command: SessionsCommand,
+    },
+
+    #[command(about = "Inspect opt-in local usage metrics")]
+    Metrics {
+        #[command(subcommand)]
+        command: MetricsCommand,
+    },
+
+    #[command(about = "Pin planned items for today, shown as a checklist in the TUI")]
+    Plan {
+        #[command(subcommand)]
+        command: PlanCommand,
+    },
+
+    #[command(about = "Manage daily category time budgets")]
+    Budget {
+        #[command(subcommand)]
+        command: BudgetCommand,
+    },
+
+    #[command(about = "Manage hourly billing rates")]
+    Rate {
+        #[command(subcommand)]
+        command: RateCommand,
+    },
+
+    #[command(about = "Produce an invoice from tracked sessions")]
+    Invoice {
+        #[arg(long, help = "First day to bill (YYYY-MM-DD)")]
+        from: String,
+
+        #[arg(long, help = "Last day to bill (YYYY-MM-DD, defaults to today)")]
+        to: Option<String>,
+
+        #[arg(
+            long,
+            default_value = "EUR",
+            help = "Currency code for rates and totals"
+        )]
+        rate_currency: String,
+
+        #[arg(long, default_value_t = 0.0, help = "Tax rate in percent")]
+        tax: f64,
+
+        #[arg(long, value_enum, default_value_t = InvoiceFormat::Markdown, help = "Invoice format")]
+        format: InvoiceFormat,
+
         #[arg(long, short, help = "Output path")]
         out: Option<PathBuf>,
     },
 
+    #[command(about = "Compare daily budgets with the time actually tracked per week")]
+    Audit {
+        #[arg(
+            long,
+            default_value_t = 4,
+            help = "Number of trailing weeks to compare"
+        )]
+        weeks: usize,
+
+        #[arg(long, help = "Print the audit as Markdown")]
+        markdown: bool,
+
+        #[arg(long, short, help = "Write the Markdown audit to this path")]
+        out: Option<PathBuf>,
+    },
+
+    #[command(about = "Check stored data for problems")]
+    Doctor {
+        #[arg(long, help = "Report sessions whose intervals overlap")]
+        overlaps: bool,
+
+        #[arg(long, help = "Restore CSV headers that were renamed or translated")]
+        headers: bool,
+
+        #[arg(long, help = "Flag a high share of very short sessions")]
+        micro_sessions: bool,
+    },
+
+    #[command(about = "Undo the last change to sessions or categories")]
+    Undo,
+
+    #[command(about = "Run in the background and serve start, stop and status over a socket")]
+    Daemon,
+
+    #[command(about = "Serve a local JSON API for start, stop, status, report and sessions")]
+    Serve {
+        #[arg(long, default_value_t = 7878, help = "Port to listen on")]
+        port: u16,
+
+        #[arg(long, default_value = "127.0.0.1", help = "Address to bind")]
+        bind: String,
+
+        #[arg(
+            long,
+            help = "Allow binding a non-loopback address (needs STRATA_SERVE_TOKEN)"
+        )]
+        allow_remote: bool,
+    },
+
     #[command(about = "Generate shell completions")]
     Completions {
         #[arg(help = "Shell type (bash, zsh, fish)")]
@@ -71,10 +415,243 @@ pub enum Cli {
     },
 }
 
-#[derive(Debug, Clone, ValueEnum)]
+#[derive(Subcommand, Debug)]
+pub enum ProjectCommand {
+    #[command(about = "Rename a project across all sessions")]
+    Rename {
+        #[arg(help = "Current project name")]
+        old: String,
+
+        #[arg(help = "New project name")]
+        new: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileCommand {
+    #[command(about = "List profiles, marking the active one")]
+    List,
+
+    #[command(about = "Create a new profile")]
+    Create {
+        #[arg(help = "Profile name")]
+        name: String,
+    },
+
+    #[command(about = "Make a profile the default for future runs")]
+    Switch {
+        #[arg(help = "Profile name")]
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SessionsCommand {
+    #[command(about = "List recorded sessions, newest first")]
+    List {
+        #[arg(
+            long,
+            conflicts_with_all = ["from", "to"],
+            help = "Only sessions from this day (YYYY-MM-DD)"
+        )]
+        date: Option<String>,
+
+        #[arg(long, help = "Only sessions on or after this day (YYYY-MM-DD)")]
+        from: Option<String>,
+
+        #[arg(long, help = "Only sessions on or before this day (YYYY-MM-DD)")]
+        to: Option<String>,
+
+        #[arg(long, short, help = "Only sessions in this category (name or ID)")]
+        category: Option<String>,
+
+        #[arg(long, help = "Only sessions whose description is this tag")]
+        tag: Option<String>,
+
+        #[arg(long, default_value_t = 0, help = "Skip this many matching sessions")]
+        offset: usize,
+
+        #[arg(
+            long,
+            default_value_t = 20,
+            help = "Maximum number of sessions to show"
+        )]
+        limit: usize,
+    },
+
+    #[command(about = "Delete sessions by ID, keeping a copy in trash.csv")]
+    Delete {
+        #[arg(required = true, help = "Session IDs")]
+        ids: Vec<usize>,
+    },
+
+    #[command(about = "Move sessions to another category")]
+    Move {
+        #[arg(long, short, help = "Category name or ID")]
+        category: String,
+
+        #[arg(required = true, help = "Session IDs")]
+        ids: Vec<usize>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MetricsCommand {
+    #[command(about = "Show how often each command and view was used")]
+    Show,
+
+    #[command(about = "Delete the recorded counts")]
+    Reset,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PlanCommand {
+    #[command(about = "Pin an item, typed in or taken from a config template")]
+    Add {
+        #[arg(help = "What to work on")]
+        description: Option<String>,
+
+        #[arg(long, short, help = "Category name or ID")]
+        category: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = duration_arg,
+            help = "Planned time, e.g. 25m or 1h30m"
+        )]
+        planned: Option<usize>,
+
+        #[arg(
+            long,
+            value_name = "HH:MM",
+            value_parser = clock_arg,
+            help = "Schedule the block for this time today"
+        )]
+        at: Option<NaiveTime>,
+
+        #[arg(
+            long,
+            short,
+            value_name = "NAME",
+            help = "Fill in the item from plan_templates in the config"
+        )]
+        template: Option<String>,
+    },
+
+    #[command(about = "List today's pinned items with planned and tracked time")]
+    List,
+
+    #[command(about = "Check off an item, recording the time tracked for it")]
+    Done {
+        #[arg(help = "Item number from plan list")]
+        number: usize,
+    },
+
+    #[command(about = "Unpin an item")]
+    Remove {
+        #[arg(help = "Item number from plan list")]
+        number: usize,
+    },
+
+    #[command(about = "Unpin every item")]
+    Clear,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BudgetCommand {
+    #[command(about = "Set a daily time cap for a category")]
+    Set {
+        #[arg(help = "Category name or ID")]
+        category: String,
+
+        #[arg(value_parser = duration_arg, help = "Daily cap, e.g. 1h, 45m, 1h30m or 0.5h")]
+        limit: usize,
+    },
+
+    #[command(about = "Remove the daily cap for a category")]
+    Clear {
+        #[arg(help = "Category name or ID")]
+        category: String,
+    },
+
+    #[command(about = "List configured daily caps")]
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RateCommand {
+    #[command(about = "Set the hourly rate for a category or project")]
+    Set {
+        #[arg(help = "Category name or ID, or project name with --project")]
+        target: String,
+
+        #[arg(help = "Hourly rate")]
+        rate: f64,
+
+        #[arg(long, help = "Treat the target as a project name")]
+        project: bool,
+    },
+
+    #[command(about = "Remove the hourly rate for a category or project")]
+    Clear {
+        #[arg(help = "Category name or ID, or project name with --project")]
+        target: String,
+
+        #[arg(long, help = "Treat the target as a project name")]
+        project: bool,
+    },
+
+    #[command(about = "List configured hourly rates")]
+    List,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InvoiceFormat {
+    Markdown,
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PeriodArg {
+    Today,
+    #[value(name = "24h")]
+    Last24h,
+    Week,
+    Month,
+}
+
+impl From<PeriodArg> for ReportPeriod {
+    fn from(value: PeriodArg) -> Self {
+        match value {
+            PeriodArg::Today => ReportPeriod::Today,
+            PeriodArg::Last24h => ReportPeriod::Last24Hours,
+            PeriodArg::Week => ReportPeriod::Week,
+            PeriodArg::Month => ReportPeriod::Month,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportGrouping {
+    Day,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum ExportFormat {
     Json,
     Ics,
+    Grains,
+    Timewarrior,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    #[value(name = "timew", alias = "timewarrior")]
+    Timewarrior,
+    Clockify,
+    #[value(name = "activitywatch", alias = "aw")]
+    ActivityWatch,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,6 +674,8 @@ pub struct SessionExport {
     pub start_time: String,
     pub end_time: String,
     pub elapsed_seconds: usize,
+    #[serde(default)]
+    pub focus: Option<u8>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -104,7 +683,9 @@ pub struct CategoryExport {
     pub id: u64,
     pub name: String,
     pub description: String,
+    pub default_description: String,
     pub color_index: usize,
+    pub color: String,
     pub karma_effect: i8,
 }
 
@@ -116,76 +697,388 @@ pub struct DataExport {
     pub sessions: Vec<SessionExport>,
 }
 
-pub fn start_session(
+/// One interval in the JSON shape `timew export` prints and `timew import` reads.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimewarriorInterval {
+    pub start: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct StartOutput {
+    category: String,
     project: String,
-    description: Option<String>,
-    category_name: Option<String>,
-) -> Result<(), String> {
-    let data_dir = storage::get_data_dir();
-    let categories_path = data_dir.join("categories.csv");
-    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    description: String,
+    started_at: DateTime<Utc>,
+}
 
-    let cat_name = category_name.unwrap_or_else(|| "none".to_string());
-    let category = categories
-        .iter()
-        .find(|c| c.name == cat_name || c.id.0.to_string() == cat_name)
-        .ok_or_else(|| format!("Category '{}' not found", cat_name))?;
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct StopOutput {
+    category: String,
+    project: String,
+    description: String,
+    elapsed_seconds: usize,
+    discarded: bool,
+}
 
-    let session = ActiveSession {
-        project: project.clone(),
-        description: description.unwrap_or_default(),
-        category_id: category.id.0,
-        category_name: category.name.clone(),
-        start_time: Utc::now(),
-    };
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct StatusOutput {
+    active: Option<ActiveStatusOutput>,
+    budgets: Vec<BudgetStatusOutput>,
+}
 
-    let session_path = storage::get_active_session_path();
-    storage::write_json_atomic(&session_path, &session)?;
+#[derive(Debug, Serialize, Deserialize)]
+struct ActiveStatusOutput {
+    category: String,
+    project: String,
+    description: String,
+    started_at: DateTime<Utc>,
+    elapsed_seconds: usize,
+}
 
-    println!(
-        "Started session for project '{}' in category '{}'",
-        project, category.name
-    );
-    Ok(())
+#[derive(Debug, Serialize)]
+struct PlanItemOutput {
+    number: usize,
+    category: String,
+    description: String,
+    planned_seconds: Option<usize>,
+    tracked_seconds: usize,
+    done: bool,
 }
 
-pub fn stop_session() -> Result<usize, String> {
-    let session_path = storage::get_active_session_path();
-    if !storage::file_exists(&session_path) {
-        return Err("No active session to stop".to_string());
-    }
+#[derive(Debug, Serialize, Deserialize)]
+struct BudgetStatusOutput {
+    category: String,
+    spent_seconds: usize,
+    budget_seconds: usize,
+    over_budget: bool,
+}
 
-    let active_session: ActiveSession = storage::read_json(&session_path)?;
+#[derive(Debug, Serialize)]
+struct SummaryOutput<'a> {
+    active: bool,
+    category: Option<&'a str>,
+    project: Option<&'a str>,
+    elapsed_seconds: usize,
+    today_seconds: usize,
+    karma_seconds: isize,
+}
 
-    let elapsed = (Utc::now() - active_session.start_time).num_seconds() as usize;
+#[derive(Debug, Serialize)]
+pub(crate) struct ReportOutput {
+    period: String,
+    start: NaiveDate,
+    end: NaiveDate,
+    entries: Vec<ReportEntryOutput>,
+    groups: Vec<GroupTotalOutput>,
+    total_seconds: usize,
+}
 
-    let data_dir = storage::get_data_dir();
-    let sessions_path = data_dir.join("time_log.csv");
-    let categories_path = data_dir.join("categories.csv");
+#[derive(Debug, Serialize)]
+struct GroupTotalOutput {
+    group: String,
+    elapsed_seconds: usize,
+}
 
-    let categories = storage::load_categories_from_csv(&categories_path).categories;
-    let mut sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+#[derive(Debug, Serialize)]
+struct DailyReportOutput {
+    period: String,
+    start: NaiveDate,
+    end: NaiveDate,
+    days: Vec<DayReportOutput>,
+    total_seconds: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct DayReportOutput {
+    date: NaiveDate,
+    entries: Vec<ReportEntryOutput>,
+    total_seconds: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct AuditOutput {
+    start: NaiveDate,
+    end: NaiveDate,
+    weeks: Vec<NaiveDate>,
+    categories: Vec<AuditRowOutput>,
+}
+
+#[derive(Debug, Serialize)]
+struct AuditRowOutput {
+    category: String,
+    weekly_goal_seconds: usize,
+    weekly_actual_seconds: Vec<usize>,
+    average_seconds: usize,
+    delta_percent: f64,
+    suggested_daily_seconds: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportEntryOutput {
+    category: String,
+    elapsed_seconds: usize,
+    over_budget: bool,
+    average_focus: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct KarmaOutput {
+    period: String,
+    start: NaiveDate,
+    end: NaiveDate,
+    entries: Vec<KarmaEntryOutput>,
+    total_seconds: usize,
+    total_karma_seconds: isize,
+}
+
+#[derive(Debug, Serialize)]
+struct KarmaEntryOutput {
+    category: String,
+    elapsed_seconds: usize,
+    karma_effect: i8,
+    karma_seconds: isize,
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{}", json);
+    Ok(())
+}
+
+pub(crate) fn run_session_hook(
+    event: HookEvent,
+    session: &ActiveSession,
+    elapsed_seconds: Option<usize>,
+) {
+    let config = storage::load_config(&storage::get_config_path());
+    hooks::run_hook(
+        &config.hooks,
+        event,
+        &HookSession {
+            category_id: session.category_id,
+            category: &session.category_name,
+            project: &session.project,
+            description: &session.description,
+            started_at: session.start_time.with_timezone(&Local),
+            elapsed_seconds,
+        },
+    );
+}
 
+pub(crate) fn resolve_clock_time(at: &str) -> Result<DateTime<Utc>, String> {
+    let time = latest_clock_time_before(Local::now().naive_local(), at)
+        .ok_or_else(|| format!("Invalid time '{}', expected HH:MM", at))?;
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| format!("Time '{}' does not exist in the local timezone", at))
+}
+
+fn resolve_start_time(at: Option<&str>, ago: Option<usize>) -> Result<DateTime<Utc>, String> {
     let now = Local::now();
-    let today = operational_day_key_for_local(&now)
-        .format("%Y-%m-%d")
-        .to_string();
-    let start_time = now - ChronoDuration::seconds(elapsed as i64);
+    if let Some(at) = at {
+        return resolve_clock_time(at);
+    }
+    if let Some(seconds) = ago {
+        return Ok((now - ChronoDuration::seconds(seconds as i64)).with_timezone(&Utc));
+    }
+    Ok(now.with_timezone(&Utc))
+}
 
-    let new_id = sessions.iter().map(|s| s.id).max().unwrap_or(0) + 1;
-    sessions.push(Session {
-        id: new_id,
-        date: today,
-        category_id: CategoryId::new(active_session.category_id),
-        description: active_session.description.clone(),
-        start_time: start_time.format("%H:%M:%S").to_string(),
-        end_time: now.format("%H:%M:%S").to_string(),
-        elapsed_seconds: elapsed,
-    });
+pub(crate) fn new_active_session(
+    project: String,
+    description: Option<String>,
+    category_name: Option<&str>,
+    start_time: DateTime<Utc>,
+) -> Result<ActiveSession, String> {
+    let data_dir = storage::get_data_dir();
+    let categories_path = data_dir.join("categories.csv");
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
 
-    storage::save_sessions_to_csv(&sessions_path, &sessions, &categories)?;
+    let category = resolve_category(&categories, category_name)?;
+
+    Ok(ActiveSession {
+        project,
+        description: description.unwrap_or_else(|| category.default_description.clone()),
+        category_id: category.id.0,
+        category_name: category.name.clone(),
+        start_time,
+    })
+}
+
+pub(crate) fn start_output(session: &ActiveSession) -> StartOutput {
+    StartOutput {
+        category: session.category_name.clone(),
+        project: session.project.clone(),
+        description: session.description.clone(),
+        started_at: session.start_time,
+    }
+}
+
+pub(crate) fn begin_session(
+    project: String,
+    description: Option<String>,
+    category_name: Option<&str>,
+    start_time: DateTime<Utc>,
+) -> Result<StartOutput, String> {
+    if let Some(response) = daemon::send(&DaemonRequest::Start {
+        project: project.clone(),
+        description: description.clone(),
+        category: category_name.map(str::to_string),
+        start_time,
+    }) {
+        return match response? {
+            DaemonResponse::Started(output) => Ok(output),
+            _ => Err(DaemonResponse::unexpected()),
+        };
+    }
+
+    let session = new_active_session(project, description, category_name, start_time)?;
+
+    let session_path = storage::get_active_session_path();
+    storage::write_json_atomic(&session_path, &session)?;
+    run_session_hook(HookEvent::Start, &session, None);
+
+    Ok(start_output(&session))
+}
+
+pub fn start_session(
+    project: String,
+    description: Option<String>,
+    category_name: Option<String>,
+    start_time: DateTime<Utc>,
+    json: bool,
+) -> Result<(), String> {
+    let started = begin_session(project, description, category_name.as_deref(), start_time)?;
+
+    if json {
+        return print_json(&started);
+    }
+
+    if Utc::now() - started.started_at >= ChronoDuration::minutes(1) {
+        println!(
+            "Started session for project '{}' in category '{}' at {}",
+            started.project,
+            started.category,
+            started.started_at.with_timezone(&Local).format("%H:%M")
+        );
+    } else {
+        println!(
+            "Started session for project '{}' in category '{}'",
+            started.project, started.category
+        );
+    }
+    Ok(())
+}
+
+pub(crate) fn end_active_session(
+    end_time: Option<DateTime<Utc>>,
+    discard: bool,
+) -> Result<StopOutput, String> {
+    if let Some(response) = daemon::send(&DaemonRequest::Stop { end_time, discard }) {
+        return match response? {
+            DaemonResponse::Stopped(output) => Ok(output),
+            _ => Err(DaemonResponse::unexpected()),
+        };
+    }
+
+    let session_path = storage::get_active_session_path();
+    if !storage::file_exists(&session_path) {
+        return Err("No active session to stop".to_string());
+    }
 
+    let active_session: ActiveSession = storage::read_json(&session_path)?;
+    let stopped = if discard {
+        discard_session(active_session)
+    } else {
+        finish_session(active_session, end_time)?
+    };
     storage::delete_file_if_exists(&session_path)?;
+    Ok(stopped)
+}
+
+pub(crate) fn finish_session(
+    active_session: ActiveSession,
+    end_time: Option<DateTime<Utc>>,
+) -> Result<StopOutput, String> {
+    let (end, elapsed) = match end_time {
+        Some(end_time) => {
+            validate_session_end(active_session.start_time, end_time)?;
+            let elapsed = (end_time - active_session.start_time).num_seconds() as usize;
+            (end_time.with_timezone(&Local), elapsed)
+        }
+        None => {
+            let elapsed = (Utc::now() - active_session.start_time).num_seconds() as usize;
+            (Local::now(), elapsed)
+        }
+    };
+
+    append_session(
+        CategoryId::new(active_session.category_id),
+        &active_session.description,
+        &active_session.project,
+        end,
+        elapsed,
+    )?;
+
+    run_session_hook(HookEvent::Stop, &active_session, Some(elapsed));
+
+    Ok(StopOutput {
+        category: active_session.category_name,
+        project: active_session.project,
+        description: active_session.description,
+        elapsed_seconds: elapsed,
+        discarded: false,
+    })
+}
+
+pub(crate) fn discard_session(active_session: ActiveSession) -> StopOutput {
+    run_session_hook(HookEvent::Stop, &active_session, None);
+
+    StopOutput {
+        category: active_session.category_name,
+        project: active_session.project,
+        description: active_session.description,
+        elapsed_seconds: 0,
+        discarded: true,
+    }
+}
+
+/// What `strata stop` reports for a session the TUI recorded.
+pub(crate) fn recorded_stop_output(session: ActiveSession, elapsed_seconds: usize) -> StopOutput {
+    StopOutput {
+        category: session.category_name,
+        project: session.project,
+        description: session.description,
+        elapsed_seconds,
+        discarded: false,
+    }
+}
+
+pub fn stop_session(at: Option<&str>, discard: bool, json: bool) -> Result<usize, String> {
+    let end_time = at.map(resolve_clock_time).transpose()?;
+    let stopped = end_active_session(end_time, discard)?;
+    let elapsed = stopped.elapsed_seconds;
+
+    if json {
+        print_json(&stopped)?;
+        return Ok(elapsed);
+    }
+
+    if stopped.discarded {
+        println!(
+            "Discarded session for project '{}' in category '{}'",
+            stopped.project, stopped.category
+        );
+        return Ok(elapsed);
+    }
 
     println!(
         "Stopped session. Elapsed time: {:02}:{:02}:{:02}",
@@ -196,71 +1089,951 @@ pub fn stop_session() -> Result<usize, String> {
     Ok(elapsed)
 }
 
-pub fn report(period: ReportPeriod) -> Result<(), String> {
+fn resolve_category<'a>(
+    categories: &'a [Category],
+    category_name: Option<&str>,
+) -> Result<&'a Category, String> {
+    let cat_name = category_name.unwrap_or("none");
+    categories
+        .iter()
+        .find(|c| c.name == cat_name || c.id.0.to_string() == cat_name)
+        .ok_or_else(|| format!("Category '{}' not found", cat_name))
+}
+
+fn append_session(
+    category_id: CategoryId,
+    description: &str,
+    project: &str,
+    end: DateTime<Local>,
+    elapsed: usize,
+) -> Result<(), String> {
     let data_dir = storage::get_data_dir();
     let sessions_path = data_dir.join("time_log.csv");
     let categories_path = data_dir.join("categories.csv");
 
     let categories = storage::load_categories_from_csv(&categories_path).categories;
-    let sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+    let mut sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+
+    let day = operational_day_key_for_local(&end)
+        .format("%Y-%m-%d")
+        .to_string();
+    let start_time = end - ChronoDuration::seconds(elapsed as i64);
+
+    let new_id = sessions.iter().map(|s| s.id).max().unwrap_or(0) + 1;
+    sessions.push(Session {
+        id: new_id,
+        date: day,
+        category_id,
+        description: description.to_string(),
+        project: project.to_string(),
+        start_time: start_time.format("%H:%M:%S").to_string(),
+        end_time: end.format("%H:%M:%S").to_string(),
+        elapsed_seconds: elapsed,
+        focus: None,
+    });
+
+    storage::save_sessions_to_csv(&sessions_path, &sessions, &categories)
+}
+
+pub fn pomodoro(
+    work_seconds: usize,
+    break_seconds: usize,
+    category_name: Option<String>,
+    cycles: Option<usize>,
+) -> Result<(), String> {
+    if work_seconds == 0 {
+        return Err("--work must be longer than zero".to_string());
+    }
+
+    let data_dir = storage::get_data_dir();
+    let categories_path = data_dir.join("categories.csv");
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let category = resolve_category(&categories, category_name.as_deref())?.clone();
+    let desktop = storage::load_config(&storage::get_config_path())
+        .notifications
+        .pomodoro;
+    let notify = |message: &str| {
+        println!("\x07{}", message);
+        if desktop {
+            notify::desktop_notification("strata pomodoro", message);
+        }
+    };
+
+    run_pomodoro(
+        work_seconds,
+        break_seconds,
+        cycles,
+        &category.name,
+        notify,
+        |label, seconds| {
+            countdown(label, seconds)?;
+            Ok(Local::now())
+        },
+        |end| {
+            with_instance_lock(|| {
+                append_session(
+                    category.id,
+                    &category.default_description,
+                    "",
+                    end,
+                    work_seconds,
+                )
+            })
+        },
+    )
+}
+
+/// The pomodoro cycle: `wait` sits out a `work` or `break` interval and
+/// returns when it ended, and `record` writes each finished work interval.
+/// Breaks are never recorded.
+fn run_pomodoro(
+    work_seconds: usize,
+    break_seconds: usize,
+    cycles: Option<usize>,
+    category_name: &str,
+    notify: impl Fn(&str),
+    mut wait: impl FnMut(&str, u64) -> Result<DateTime<Local>, String>,
+    mut record: impl FnMut(DateTime<Local>) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut cycle = 1usize;
+    loop {
+        notify(&format!(
+            "Pomodoro {}: work on '{}' for {}",
+            cycle,
+            category_name,
+            format_duration(work_seconds)
+        ));
+        let end = wait("work", work_seconds as u64)?;
+        record(end)?;
+
+        if cycles.is_some_and(|limit| cycle >= limit) {
+            notify(&format!("Pomodoro done after {} interval(s)", cycle));
+            return Ok(());
+        }
+
+        if break_seconds > 0 {
+            notify(&format!(
+                "Work interval recorded. Break for {}",
+                format_duration(break_seconds)
+            ));
+            wait("break", break_seconds as u64)?;
+        }
+        cycle += 1;
+    }
+}
+
+/// Half-second tries at the instance lock before a pomodoro interval gives up.
+const LOCK_RETRIES: usize = 10;
+
+/// Runs `write` under the instance lock, waiting a few seconds for a short
+/// writer such as `strata stop` to finish first.
+fn with_instance_lock(write: impl FnOnce() -> Result<(), String>) -> Result<(), String> {
+    let path = storage::get_instance_lock_path();
+    let mut attempts = 0;
+    let _lock = loop {
+        match storage::acquire_instance_lock(&path) {
+            Ok(lock) => break lock,
+            Err(_) if attempts < LOCK_RETRIES => {
+                attempts += 1;
+                std::thread::sleep(std::time::Duration::from_millis(500));
+            }
+            Err(e) => return Err(e),
+        }
+    };
+    write()
+}
+
+fn pomodoro_tui(
+    work_seconds: usize,
+    break_seconds: usize,
+    category_name: Option<String>,
+    cycles: Option<usize>,
+) -> Result<(), String> {
+    if work_seconds == 0 {
+        return Err("--work must be longer than zero".to_string());
+    }
+
+    let categories_path = storage::get_data_dir().join("categories.csv");
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let category = resolve_category(&categories, category_name.as_deref())?;
+
+    crate::app::run_pomodoro_ui(crate::app::PomodoroSettings {
+        work_seconds,
+        break_seconds,
+        category_id: category.id,
+        cycles,
+    })
+    .map_err(|e| e.to_string())
+}
+
+fn countdown(label: &str, total_seconds: u64) -> Result<(), String> {
+    let started = std::time::Instant::now();
+    loop {
+        let elapsed = started.elapsed().as_secs();
+        if elapsed >= total_seconds {
+            break;
+        }
+        let remaining = total_seconds - elapsed;
+        print!(
+            "\r{} {:02}:{:02} remaining ",
+            label,
+            remaining / 60,
+            remaining % 60
+        );
+        io::Write::flush(&mut io::stdout()).map_err(|e| e.to_string())?;
+        std::thread::sleep(std::time::Duration::from_millis(250));
+    }
+    print!("\r{}\r", " ".repeat(label.len() + 20));
+    Ok(())
+}
+
+fn period_label(period: ReportPeriod, range_label: &str) -> String {
+    match storage::period_label_style() {
+        PeriodLabelStyle::Range => range_label.to_string(),
+        style => {
+            let (start, end) = period_date_bounds(period);
+            format_period_label(period, start, end, style)
+        }
+    }
+}
+
+pub(crate) fn report_output(period: ReportPeriod, min_session: Option<usize>) -> ReportOutput {
+    let data_dir = storage::get_data_dir();
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let sessions = load_report_sessions(&categories, min_session);
+    let sessions = period_sessions(&sessions, period);
 
     let summary = build_period_report(&sessions, &categories, period);
+    let (start, end) = period_date_bounds(period);
+
+    ReportOutput {
+        period: period_label(period, &summary.date),
+        start,
+        end,
+        entries: report_entries(&summary, &sessions, &categories, start, end),
+        groups: group_totals(&summary, &categories),
+        total_seconds: summary.total_seconds,
+    }
+}
+
+/// Subtotals for the category groups set in the TUI, in category order.
+fn group_totals(summary: &ReportSummary, categories: &[Category]) -> Vec<GroupTotalOutput> {
+    let groups = storage::load_category_groups(&storage::get_category_groups_path());
+    groups
+        .ordered_groups(categories)
+        .into_iter()
+        .map(|group| {
+            let elapsed_seconds = summary
+                .entries
+                .iter()
+                .filter(|entry| {
+                    categories.iter().any(|category| {
+                        category.name == entry.category_name
+                            && groups.group_of(category.id) == Some(group.as_str())
+                    })
+                })
+                .map(|entry| entry.elapsed_seconds)
+                .sum();
+            GroupTotalOutput {
+                group,
+                elapsed_seconds,
+            }
+        })
+        .collect()
+}
+
+fn daily_report_output(period: ReportPeriod, min_session: Option<usize>) -> DailyReportOutput {
+    let data_dir = storage::get_data_dir();
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let sessions = load_report_sessions(&categories, min_session);
+    let sessions = period_sessions(&sessions, period);
+
+    let summary = build_period_report(&sessions, &categories, period);
+    let (start, end) = period_date_bounds(period);
+    let days: Vec<DayReportOutput> = build_daily_reports(&sessions, &categories, start, end)
+        .into_iter()
+        .zip(start.iter_days())
+        .map(|(day, date)| DayReportOutput {
+            date,
+            entries: report_entries(&day, &sessions, &categories, date, date),
+            total_seconds: day.total_seconds,
+        })
+        .collect();
+
+    DailyReportOutput {
+        period: period_label(period, &summary.date),
+        start,
+        end,
+        total_seconds: days.iter().map(|day| day.total_seconds).sum(),
+        days,
+    }
+}
+
+/// Minimum session lengths from the config, keyed there by category name;
+/// `--min-session` replaces all of them.
+fn min_session_length(categories: &[Category], min_session: Option<usize>) -> MinSessionLength {
+    if let Some(seconds) = min_session {
+        return MinSessionLength {
+            default_seconds: seconds,
+            by_category: HashMap::new(),
+        };
+    }
+
+    let config = storage::load_config(&storage::get_config_path());
+    let parse = |value: &str| {
+        let seconds = parse_duration(value);
+        if seconds.is_none() {
+            eprintln!("Warning: Ignoring invalid min_session '{}'", value);
+        }
+        seconds
+    };
+    MinSessionLength {
+        default_seconds: config.min_session.as_deref().and_then(parse).unwrap_or(0),
+        by_category: config
+            .min_session_by_category
+            .iter()
+            .filter_map(|(name, value)| {
+                let Some(category) = categories
+                    .iter()
+                    .find(|category| category.name.eq_ignore_ascii_case(name))
+                else {
+                    eprintln!(
+                        "Warning: min_session_by_category: unknown category '{}'",
+                        name
+                    );
+                    return None;
+                };
+                Some((category.id, parse(value)?))
+            })
+            .collect(),
+    }
+}
+
+/// Sessions as reports and exports count them: micro-sessions are left out
+/// here while time_log.csv keeps every row.
+fn load_report_sessions(categories: &[Category], min_session: Option<usize>) -> Vec<Session> {
+    let sessions_path = storage::get_data_dir().join("time_log.csv");
+    let sessions = storage::load_sessions_from_csv(&sessions_path, categories).sessions;
+    drop_micro_sessions(&sessions, &min_session_length(categories, min_session))
+}
+
+fn report_entries(
+    summary: &ReportSummary,
+    sessions: &[Session],
+    categories: &[Category],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Vec<ReportEntryOutput> {
+    let budgets = storage::load_category_budgets(&storage::get_category_budgets_path());
+    let period_days = ((end - start).num_days() + 1) as usize;
+    let over_budget = |category_name: &str, elapsed_seconds: usize| {
+        categories
+            .iter()
+            .find(|category| category.name == category_name)
+            .and_then(|category| budgets.daily_seconds_by_category.get(&category.id.0))
+            .is_some_and(|budget| elapsed_seconds > budget * period_days)
+    };
+    let focus_by_category = average_focus_by_category(sessions, start, end);
+    let average_focus = |category_name: &str| {
+        categories
+            .iter()
+            .find(|category| category.name == category_name)
+            .and_then(|category| focus_by_category.get(&category.id).copied())
+    };
+
+    summary
+        .entries
+        .iter()
+        .map(|entry| ReportEntryOutput {
+            category: entry.category_name.clone(),
+            elapsed_seconds: entry.elapsed_seconds,
+            over_budget: over_budget(&entry.category_name, entry.elapsed_seconds),
+            average_focus: average_focus(&entry.category_name),
+        })
+        .collect()
+}
+
+pub fn report(period: ReportPeriod, json: bool, min_session: Option<usize>) -> Result<(), String> {
+    let output = report_output(period, min_session);
+    if json {
+        return print_json(&output);
+    }
 
     let title = match period {
         ReportPeriod::Today => "Today's Report",
+        ReportPeriod::Last24Hours => "Last 24 Hours Report",
         ReportPeriod::Week => "Weekly Report",
         ReportPeriod::Month => "Monthly Report",
     };
 
-    println!("{} ({})", title, summary.date);
+    println!("{} ({})", title, output.period);
     println!("{}", "-".repeat(40));
-    for entry in &summary.entries {
-        println!(
+    for entry in &output.entries {
+        let mut line = format!(
             "{:20} {:02}:{:02}:{:02}",
-            entry.category_name,
+            entry.category,
             entry.elapsed_seconds / 3600,
             (entry.elapsed_seconds % 3600) / 60,
             entry.elapsed_seconds % 60
         );
+        if let Some(focus) = entry.average_focus {
+            line.push_str(&format!("  focus {:.1}", focus));
+        }
+        if entry.over_budget {
+            println!("{}", format!("{} over budget", line).red());
+        } else {
+            println!("{}", line);
+        }
+    }
+    if !output.groups.is_empty() {
+        println!("{}", "-".repeat(40));
+        for group in &output.groups {
+            println!(
+                "{:20} {}",
+                format!("[{}]", group.group),
+                format_hms(group.elapsed_seconds)
+            );
+        }
     }
     println!("{}", "-".repeat(40));
     println!(
         "{:20} {:02}:{:02}:{:02}",
         "TOTAL",
-        summary.total_seconds / 3600,
-        (summary.total_seconds % 3600) / 60,
-        summary.total_seconds % 60
+        output.total_seconds / 3600,
+        (output.total_seconds % 3600) / 60,
+        output.total_seconds % 60
     );
 
     Ok(())
 }
 
-pub fn export_data(format: ExportFormat, out_path: Option<PathBuf>) -> Result<(), String> {
+pub fn daily_report(
+    period: ReportPeriod,
+    json: bool,
+    min_session: Option<usize>,
+) -> Result<(), String> {
+    let output = daily_report_output(period, min_session);
+    if json {
+        return print_json(&output);
+    }
+
+    println!("Daily Report ({})", output.period);
+    for day in &output.days {
+        println!();
+        println!("{}", day.date.format("%a %Y-%m-%d"));
+        println!("{}", "-".repeat(40));
+        for entry in &day.entries {
+            let line = format!(
+                "{:20} {}",
+                entry.category,
+                format_hms(entry.elapsed_seconds)
+            );
+            if entry.over_budget {
+                println!("{}", format!("{} over budget", line).red());
+            } else {
+                println!("{}", line);
+            }
+        }
+        println!("{:20} {}", "DAY", format_hms(day.total_seconds));
+    }
+    println!();
+    println!("{}", "=".repeat(40));
+    println!("{:20} {}", "TOTAL", format_hms(output.total_seconds));
+
+    Ok(())
+}
+
+pub fn html_report(
+    period: ReportPeriod,
+    out_path: &Path,
+    min_session: Option<usize>,
+) -> Result<(), String> {
     let data_dir = storage::get_data_dir();
-    let sessions_path = data_dir.join("time_log.csv");
     let categories_path = data_dir.join("categories.csv");
 
     let categories = storage::load_categories_from_csv(&categories_path).categories;
-    let sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+    let sessions = load_report_sessions(&categories, min_session);
+    let html = render_html_report(period, &sessions, &categories, Local::now());
 
-    let export = DataExport {
-        schema_version: 1,
-        exported_at: Utc::now(),
-        categories: categories
-            .iter()
-            .skip(1)
-            .map(|c| {
-                let color_pos = COLORS.iter().position(|&col| col == c.color).unwrap_or(0);
-                CategoryExport {
-                    id: c.id.0,
-                    name: c.name.clone(),
-                    description: c.description.clone(),
-                    color_index: color_pos,
-                    karma_effect: c.karma_effect,
-                }
-            })
-            .collect(),
-        sessions: sessions
+    storage::write_text_file(out_path, &html)?;
+    println!("Report written to {}", out_path.display());
+    Ok(())
+}
+
+/// The self-contained page `strata report --html` writes: styles inline and
+/// no external resources, so it opens anywhere.
+fn render_html_report(
+    period: ReportPeriod,
+    sessions: &[Session],
+    categories: &[Category],
+    generated_at: DateTime<Local>,
+) -> String {
+    let sessions = period_sessions(sessions, period);
+
+    let mut summary = build_period_karma_report(&sessions, categories, period);
+    summary.entries.retain(|entry| entry.elapsed_seconds > 0);
+    let (start, end) = period_date_bounds(period);
+    let label = period_label(period, &summary.date);
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!(
+        "<title>strata report {}</title>\n",
+        escape_html(&label)
+    ));
+    html.push_str(HTML_REPORT_STYLE);
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!(
+        "<h1>strata report <small>{}</small></h1>\n",
+        escape_html(&label)
+    ));
+
+    html.push_str("<h2>Categories</h2>\n<table class=\"bars\">\n");
+    let max_seconds = summary
+        .entries
+        .iter()
+        .map(|entry| entry.elapsed_seconds)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    for entry in &summary.entries {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td class=\"bar\"><div style=\"width:{:.1}%;background:{}\"></div></td><td class=\"num\">{}</td></tr>\n",
+            escape_html(&entry.category_name),
+            entry.elapsed_seconds as f64 * 100.0 / max_seconds as f64,
+            css_color(entry.color),
+            format_hms(entry.elapsed_seconds)
+        ));
+    }
+    html.push_str(&format!(
+        "<tr class=\"total\"><td>Total</td><td></td><td class=\"num\">{}</td></tr>\n</table>\n",
+        format_hms(summary.total_seconds)
+    ));
+
+    html.push_str("<h2>Daily timeline</h2>\n<table class=\"timeline\">\n");
+    let mut day = start;
+    while day <= end {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td class=\"track\">",
+            day.format("%a %Y-%m-%d")
+        ));
+        for entry in build_day_timeline(&sessions, categories, day) {
+            let left = entry.start_offset_seconds as f64 * 100.0 / 86_400.0;
+            let width =
+                (entry.end_offset_seconds - entry.start_offset_seconds) as f64 * 100.0 / 86_400.0;
+            let title = if entry.description.is_empty() {
+                entry.category_name.clone()
+            } else {
+                format!("{}: {}", entry.category_name, entry.description)
+            };
+            html.push_str(&format!(
+                "<div title=\"{}\" style=\"left:{:.3}%;width:{:.3}%;background:{}\"></div>",
+                escape_html(&title),
+                left,
+                width,
+                css_color(entry.color)
+            ));
+        }
+        html.push_str("</td></tr>\n");
+        day += ChronoDuration::days(1);
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Karma</h2>\n<table class=\"karma\">\n");
+    for entry in summary
+        .entries
+        .iter()
+        .filter(|entry| entry.karma_effect != 0)
+    {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td class=\"num {}\">{}</td></tr>\n",
+            escape_html(&entry.category_name),
+            if entry.karma_seconds < 0 {
+                "neg"
+            } else {
+                "pos"
+            },
+            format_signed_hms(entry.karma_seconds)
+        ));
+    }
+    html.push_str(&format!(
+        "<tr class=\"total\"><td>Total</td><td class=\"num {}\">{}</td></tr>\n</table>\n",
+        if summary.total_karma_seconds < 0 {
+            "neg"
+        } else {
+            "pos"
+        },
+        format_signed_hms(summary.total_karma_seconds)
+    ));
+
+    html.push_str(&format!(
+        "<footer>Generated {}</footer>\n</body>\n</html>\n",
+        generated_at.format("%Y-%m-%d %H:%M")
+    ));
+    html
+}
+
+const HTML_REPORT_STYLE: &str = "<style>
+body { font-family: system-ui, sans-serif; margin: 2rem auto; max-width: 60rem; color: #222; }
+h1 small { color: #777; font-weight: normal; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 1.5rem; }
+td { padding: 0.2rem 0.5rem; white-space: nowrap; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+td.bar { width: 70%; }
+td.bar div { height: 1rem; border-radius: 2px; }
+td.track { position: relative; width: 80%; height: 1.2rem; background: #f0f0f0; }
+td.track div { position: absolute; top: 0; bottom: 0; }
+tr.total td { border-top: 1px solid #ccc; font-weight: bold; }
+.pos { color: #1a7f37; }
+.neg { color: #cf222e; }
+footer { color: #999; font-size: 0.8rem; }
+</style>
+";
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn css_color(color: ratatui::style::Color) -> String {
+    match color {
+        ratatui::style::Color::Rgb(r, g, b) => format!("rgb({},{},{})", r, g, b),
+        _ => "#888".to_string(),
+    }
+}
+
+fn format_signed_hms(seconds: isize) -> String {
+    format!(
+        "{}{}",
+        if seconds < 0 { "-" } else { "+" },
+        format_hms(seconds.unsigned_abs())
+    )
+}
+
+pub fn karma(period: ReportPeriod, json: bool) -> Result<(), String> {
+    let data_dir = storage::get_data_dir();
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let sessions = load_report_sessions(&categories, None);
+    let sessions = period_sessions(&sessions, period);
+
+    let mut summary = build_period_karma_report(&sessions, &categories, period);
+    sort_karma_entries_for_display(&mut summary.entries);
+
+    if json {
+        let (start, end) = period_date_bounds(period);
+        return print_json(&KarmaOutput {
+            period: period_label(period, &summary.date),
+            start,
+            end,
+            entries: summary
+                .entries
+                .iter()
+                .map(|entry| KarmaEntryOutput {
+                    category: entry.category_name.clone(),
+                    elapsed_seconds: entry.elapsed_seconds,
+                    karma_effect: entry.karma_effect,
+                    karma_seconds: entry.karma_seconds,
+                })
+                .collect(),
+            total_seconds: summary.total_seconds,
+            total_karma_seconds: summary.total_karma_seconds,
+        });
+    }
+
+    print!("{}", format_karma_report(period, &summary));
+    Ok(())
+}
+
+/// The text `strata karma` prints: one line per category, then the net.
+fn format_karma_report(period: ReportPeriod, summary: &KarmaReportSummary) -> String {
+    let title = match period {
+        ReportPeriod::Today => "Today's Karma",
+        ReportPeriod::Last24Hours => "Last 24 Hours Karma",
+        ReportPeriod::Week => "Weekly Karma",
+        ReportPeriod::Month => "Monthly Karma",
+    };
+
+    let mut out = format!("{} ({})\n", title, period_label(period, &summary.date));
+    out.push_str(&format!("{}\n", "-".repeat(40)));
+    for entry in &summary.entries {
+        let marker = if entry.karma_effect < 0 {
+            "◯"
+        } else if entry.karma_effect == 0 {
+            "·"
+        } else {
+            "●"
+        };
+        let karma_value = if entry.karma_effect == 0 {
+            String::new()
+        } else {
+            format_signed_hms(entry.karma_seconds)
+        };
+        let line = format!(
+            "{} {:18} {} {}",
+            marker,
+            entry.category_name,
+            format_hms(entry.elapsed_seconds),
+            karma_value
+        );
+        out.push_str(&format!("{}\n", line.trim_end()));
+    }
+    out.push_str(&format!("{}\n", "-".repeat(40)));
+    out.push_str(&format!(
+        "{:20} {} {}\n",
+        "NET KARMA",
+        format_hms(summary.total_seconds),
+        format_signed_hms(summary.total_karma_seconds)
+    ));
+    out
+}
+
+pub fn timeline(date: Option<String>) -> Result<(), String> {
+    use crossterm::style::{Color as TermColor, Stylize};
+
+    const CELLS: usize = 48;
+    const CELL_SECONDS: usize = 24 * 3600 / CELLS;
+    const LABEL_WIDTH: usize = 14;
+
+    let day = match date {
+        Some(raw) => parse_day(&raw)?,
+        None => operational_day_key_now(),
+    };
+
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+
+    let entries = build_day_timeline(&sessions, &categories, day);
+
+    println!("Timeline ({})", day.format("%Y-%m-%d"));
+
+    let day_start = operational_day_start(day);
+    let mut axis = " ".repeat(LABEL_WIDTH);
+    for cell in (0..CELLS).step_by(6) {
+        let hour = (day_start + ChronoDuration::seconds((cell * CELL_SECONDS) as i64)).format("%H");
+        axis.push_str(&format!("{:<6}", hour));
+    }
+    println!("{}", axis.trim_end());
+
+    if entries.is_empty() {
+        println!("No tracked sessions for this day.");
+        return Ok(());
+    }
+
+    for entry in &entries {
+        let color = match entry.color {
+            ratatui::style::Color::Rgb(r, g, b) => TermColor::Rgb { r, g, b },
+            _ => TermColor::White,
+        };
+
+        let mut bar = String::new();
+        for cell in 0..CELLS {
+            let cell_start = cell * CELL_SECONDS;
+            let cell_end = cell_start + CELL_SECONDS;
+            let covered = entry
+                .end_offset_seconds
+                .min(cell_end)
+                .saturating_sub(entry.start_offset_seconds.max(cell_start));
+
+            let block = if covered * 2 >= CELL_SECONDS {
+                "█".with(color).to_string()
+            } else if covered > 0 {
+                "▌".with(color).to_string()
+            } else {
+                "·".with(TermColor::DarkGrey).to_string()
+            };
+            bar.push_str(&block);
+        }
+
+        let label: String = entry.category_name.chars().take(LABEL_WIDTH - 1).collect();
+        let elapsed = entry.end_offset_seconds - entry.start_offset_seconds;
+        let line = format!(
+            "{:<width$}{} {:02}:{:02}:{:02} {}",
+            label,
+            bar,
+            elapsed / 3600,
+            (elapsed % 3600) / 60,
+            elapsed % 60,
+            entry.description,
+            width = LABEL_WIDTH
+        );
+        println!("{}", line.trim_end());
+    }
+
+    Ok(())
+}
+
+pub fn heatmap(weeks: usize) -> Result<(), String> {
+    const SHADES: [char; 5] = ['·', '░', '▒', '▓', '█'];
+
+    if weeks == 0 {
+        return Err("--weeks must be at least 1".to_string());
+    }
+
+    let today = operational_day_key_now();
+    let current_week_start =
+        today - ChronoDuration::days(today.weekday().num_days_from_monday() as i64);
+    let start = current_week_start - ChronoDuration::weeks(weeks as i64 - 1);
+
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+
+    let totals = daily_tracked_seconds(&sessions, start, today);
+    let max_seconds = totals.values().copied().max().unwrap_or(0);
+    let total_seconds: usize = totals.values().sum();
+
+    println!(
+        "Heatmap ({}..{})",
+        start.format("%Y-%m-%d"),
+        today.format("%Y-%m-%d")
+    );
+
+    let mut month_axis = "    ".to_string();
+    let mut last_month = None;
+    for week in 0..weeks {
+        let week_start = start + ChronoDuration::weeks(week as i64);
+        if last_month != Some(week_start.month()) {
+            month_axis.push_str(&week_start.format("%b").to_string());
+            last_month = Some(week_start.month());
+        }
+        let target_len = 4 + (week + 1) * 2;
+        while month_axis.chars().count() < target_len {
+            month_axis.push(' ');
+        }
+    }
+    println!("{}", month_axis.trim_end());
+
+    for (weekday, label) in ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+        .iter()
+        .enumerate()
+    {
+        let mut row = format!("{} ", label);
+        for week in 0..weeks {
+            let day = start + ChronoDuration::days((week * 7 + weekday) as i64);
+            if day > today {
+                row.push_str("  ");
+                continue;
+            }
+
+            let seconds = totals.get(&day).copied().unwrap_or(0);
+            let level = if seconds == 0 || max_seconds == 0 {
+                0
+            } else {
+                (seconds * 4).div_ceil(max_seconds).clamp(1, 4)
+            };
+            row.push(SHADES[level]);
+            row.push(' ');
+        }
+        println!("{}", row.trim_end());
+    }
+
+    println!(
+        "less {} more   max {:.1}h/day   total {:.1}h",
+        SHADES.iter().collect::<String>(),
+        max_seconds as f64 / 3600.0,
+        total_seconds as f64 / 3600.0
+    );
+
+    Ok(())
+}
+
+fn export_file_path(
+    out_path: Option<PathBuf>,
+    format: ExportFormat,
+    period: Option<ReportPeriod>,
+) -> Option<PathBuf> {
+    let dir = match out_path {
+        Some(path) if !path.is_dir() => return Some(path),
+        Some(path) => path,
+        None => storage::export_dir()?,
+    };
+
+    let period_stem = match period {
+        Some(period) => {
+            let (start, end) = period_date_bounds(period);
+            period_file_stem(period, start, end, storage::period_label_style())
+        }
+        None => "all".to_string(),
+    };
+    let date = operational_day_key_now().format("%Y-%m-%d").to_string();
+    let extension = match format {
+        ExportFormat::Json => "json",
+        ExportFormat::Ics => "ics",
+        ExportFormat::Grains => "csv",
+        ExportFormat::Timewarrior => "json",
+    };
+    let file_name = storage::render_export_filename(
+        &storage::export_filename_template(),
+        &period_stem,
+        &date,
+        extension,
+    );
+    Some(dir.join(file_name))
+}
+
+pub fn export_data(
+    format: ExportFormat,
+    out_path: Option<PathBuf>,
+    period: Option<ReportPeriod>,
+    alarm_before: Option<usize>,
+    min_session: Option<usize>,
+) -> Result<(), String> {
+    let data_dir = storage::get_data_dir();
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let out_path = export_file_path(out_path, format, period);
+
+    if matches!(format, ExportFormat::Grains) {
+        return export_grains(&categories, out_path);
+    }
+
+    let mut sessions = load_report_sessions(&categories, min_session);
+    if let Some(period) = period {
+        sessions = period_sessions(&sessions, period).into_owned();
+        let (start, end) = period_date_bounds(period);
+        sessions.retain(|session| {
+            NaiveDate::parse_from_str(&session.date, "%Y-%m-%d")
+                .is_ok_and(|date| date >= start && date <= end)
+        });
+    }
+
+    let export = DataExport {
+        schema_version: 1,
+        exported_at: Utc::now(),
+        categories: categories
+            .iter()
+            .skip(1)
+            .map(|c| {
+                let color_pos = COLORS.iter().position(|&col| col == c.color).unwrap_or(0);
+                CategoryExport {
+                    id: c.id.0,
+                    name: c.name.clone(),
+                    description: c.description.clone(),
+                    default_description: c.default_description.clone(),
+                    color_index: color_pos,
+                    color: color_hex(c.color),
+                    karma_effect: c.karma_effect,
+                }
+            })
+            .collect(),
+        sessions: sessions
             .iter()
             .map(|s| {
                 let cat_name = categories
@@ -274,17 +2047,19 @@ pub fn export_data(format: ExportFormat, out_path: Option<PathBuf>) -> Result<()
                     date: s.date.clone(),
                     category_id: s.category_id.0,
                     category_name: cat_name,
-                    project: None,
+                    project: (!s.project.is_empty()).then(|| s.project.clone()),
                     description: s.description.clone(),
                     start_time: s.start_time.clone(),
                     end_time: s.end_time.clone(),
                     elapsed_seconds: s.elapsed_seconds,
+                    focus: s.focus,
                 }
             })
             .collect(),
     };
 
     match format {
+        ExportFormat::Grains => {}
         ExportFormat::Json => {
             let json = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
             if let Some(path) = out_path {
@@ -300,12 +2075,15 @@ pub fn export_data(format: ExportFormat, out_path: Option<PathBuf>) -> Result<()
             ics.push_str("VERSION:2.0\r\n");
             ics.push_str("PRODID:-//strata//time tracking//EN\r\n");
 
-            for session in &export.sessions {
+            for (session, source) in export.sessions.iter().zip(&sessions) {
                 if session.category_name == "none" || session.elapsed_seconds == 0 {
                     continue;
                 }
-                let dt_start = format_ics_datetime(&session.date, &session.start_time);
-                let dt_end = format_ics_datetime(&session.date, &session.end_time);
+                let Some((start, end)) = session_interval(source) else {
+                    continue;
+                };
+                let dt_start = format_ics_datetime(start);
+                let dt_end = format_ics_datetime(end);
                 let uid = format!("strata-session-{}", session.id);
 
                 ics.push_str("BEGIN:VEVENT\r\n");
@@ -325,6 +2103,25 @@ pub fn export_data(format: ExportFormat, out_path: Option<PathBuf>) -> Result<()
                 ics.push_str("END:VEVENT\r\n");
             }
 
+            let alarm_seconds = alarm_before.unwrap_or_else(|| {
+                storage::load_config(&storage::get_config_path()).plan_alarm_minutes as usize * 60
+            });
+            let today = operational_day_key_now();
+            let plan = storage::load_plan(&storage::get_plan_path(), today);
+            for (index, item) in plan.items.iter().enumerate() {
+                let Some(starts_at) = item.starts_at.filter(|_| !item.is_done()) else {
+                    continue;
+                };
+                ics.push_str(&plan_ics_event(
+                    today,
+                    index,
+                    starts_at,
+                    item,
+                    &categories,
+                    alarm_seconds,
+                ));
+            }
+
             ics.push_str("END:VCALENDAR\r\n");
 
             if let Some(path) = out_path {
@@ -334,13 +2131,2109 @@ pub fn export_data(format: ExportFormat, out_path: Option<PathBuf>) -> Result<()
                 println!("{}", ics);
             }
         }
+        ExportFormat::Timewarrior => {
+            let session_path = storage::get_active_session_path();
+            let active_session = if storage::file_exists(&session_path) {
+                Some(storage::read_json::<ActiveSession>(&session_path)?)
+            } else {
+                None
+            };
+            let intervals = timew_intervals(&export.sessions, &sessions, active_session.as_ref());
+            let json = serde_json::to_string_pretty(&intervals).map_err(|e| e.to_string())?;
+            if let Some(path) = out_path {
+                storage::write_text_file(&path, &json)?;
+                println!("Exported to {}", path.display());
+            } else {
+                println!("{}", json);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Recorded sessions as `timew import` intervals, tagged with their category
+/// and project, plus the running session as an open interval.
+fn timew_intervals(
+    rows: &[SessionExport],
+    sessions: &[Session],
+    active_session: Option<&ActiveSession>,
+) -> Vec<TimewarriorInterval> {
+    let tags = |category: &String, project: Option<&String>| -> Vec<String> {
+        [Some(category), project]
+            .into_iter()
+            .flatten()
+            .filter(|tag| *tag != "none")
+            .cloned()
+            .collect()
+    };
+    let annotation = |description: &str| (!description.is_empty()).then(|| description.to_string());
+
+    let mut intervals: Vec<TimewarriorInterval> = rows
+        .iter()
+        .zip(sessions)
+        .filter(|(session, _)| session.elapsed_seconds > 0)
+        .filter_map(|(session, source)| {
+            let (start, end) = session_interval(source)?;
+            Some(TimewarriorInterval {
+                start: format_ics_datetime(start),
+                end: Some(format_ics_datetime(end)),
+                tags: tags(&session.category_name, session.project.as_ref()),
+                annotation: annotation(&session.description),
+            })
+        })
+        .collect();
+    if let Some(active) = active_session {
+        let project = (!active.project.is_empty()).then_some(&active.project);
+        intervals.push(TimewarriorInterval {
+            start: format_ics_timestamp(active.start_time),
+            end: None,
+            tags: tags(&active.category_name, project),
+            annotation: annotation(&active.description),
+        });
+    }
+    intervals
+}
+
+/// An interval read from another tracker, before it is mapped onto categories.
+#[derive(Debug)]
+struct ImportedInterval {
+    start: DateTime<Local>,
+    end: Option<DateTime<Local>>,
+    category_candidates: Vec<String>,
+    project: Option<String>,
+    description: String,
+}
+
+fn parse_timew_timestamp(raw: &str) -> Option<DateTime<Local>> {
+    NaiveDateTime::parse_from_str(raw, "%Y%m%dT%H%M%SZ")
+        .ok()
+        .map(|naive| Utc.from_utc_datetime(&naive).with_timezone(&Local))
+}
+
+fn read_timew_intervals(raw: &str) -> Result<Vec<ImportedInterval>, String> {
+    let intervals: Vec<TimewarriorInterval> =
+        serde_json::from_str(raw).map_err(|e| format!("Invalid timew export: {}", e))?;
+    intervals
+        .into_iter()
+        .map(|interval| {
+            let parse = |raw: &str| {
+                parse_timew_timestamp(raw)
+                    .ok_or_else(|| format!("Invalid timew timestamp '{}'", raw))
+            };
+            Ok(ImportedInterval {
+                start: parse(&interval.start)?,
+                end: interval.end.as_deref().map(parse).transpose()?,
+                category_candidates: interval.tags,
+                project: None,
+                description: interval.annotation.unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+fn parse_clockify_datetime(date: &str, time: &str) -> Option<DateTime<Local>> {
+    let date = ["%m/%d/%Y", "%Y-%m-%d", "%d.%m.%Y", "%d/%m/%Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(date.trim(), format).ok())?;
+    let time = ["%I:%M:%S %p", "%H:%M:%S", "%I:%M %p", "%H:%M"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(time.trim(), format).ok())?;
+    Local.from_local_datetime(&date.and_time(time)).earliest()
+}
+
+fn read_clockify_intervals(raw: &str) -> Result<Vec<ImportedInterval>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(raw.trim_start_matches('\u{feff}').as_bytes());
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.trim().eq_ignore_ascii_case(name))
+    };
+    let required = |name: &str| {
+        column(name).ok_or_else(|| format!("Clockify report is missing the '{}' column", name))
+    };
+    let (start_date, start_time) = (required("Start Date")?, required("Start Time")?);
+    let (end_date, end_time) = (required("End Date")?, required("End Time")?);
+    let (project, task, description) = (column("Project"), column("Task"), column("Description"));
+
+    let mut intervals = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| e.to_string())?;
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| record.get(index))
+                .map(str::trim)
+                .unwrap_or_default()
+                .to_string()
+        };
+        let datetime = |date: usize, time: usize| {
+            let (date, time) = (field(Some(date)), field(Some(time)));
+            parse_clockify_datetime(&date, &time)
+                .ok_or_else(|| format!("Invalid Clockify time '{} {}'", date, time))
+        };
+
+        let project = field(project);
+        let description = [field(task), field(description)]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(": ");
+        intervals.push(ImportedInterval {
+            start: datetime(start_date, start_time)?,
+            end: Some(datetime(end_date, end_time)?),
+            category_candidates: vec![project.clone()],
+            project: Some(project),
+            description,
+        });
+    }
+    Ok(intervals)
+}
+
+fn parse_aw_timestamp(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value.as_str()?)
+        .ok()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+fn read_activitywatch_intervals(raw: &str) -> Result<Vec<ImportedInterval>, String> {
+    let config = storage::load_config(&storage::get_config_path()).activitywatch;
+    if config.rules.is_empty() {
+        return Err(
+            "No ActivityWatch rules configured; add activitywatch.rules to config.json".to_string(),
+        );
+    }
+
+    let export: serde_json::Value =
+        serde_json::from_str(raw).map_err(|e| format!("Invalid ActivityWatch export: {}", e))?;
+    let buckets = export.get("buckets").unwrap_or(&export);
+    let buckets: Vec<&serde_json::Value> = if buckets.get("events").is_some() {
+        vec![buckets]
+    } else {
+        buckets
+            .as_object()
+            .map(|buckets| buckets.values().collect())
+            .unwrap_or_default()
+    };
+
+    let mut windows = Vec::new();
+    let mut afk = Vec::new();
+    for bucket in buckets {
+        let kind = bucket.get("type").and_then(|kind| kind.as_str());
+        let events = bucket
+            .get("events")
+            .and_then(|events| events.as_array())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for event in events {
+            let Some(start) = event.get("timestamp").and_then(parse_aw_timestamp) else {
+                continue;
+            };
+            let duration = event
+                .get("duration")
+                .and_then(|duration| duration.as_f64())
+                .unwrap_or(0.0);
+            let end = start + ChronoDuration::milliseconds((duration * 1000.0) as i64);
+            let data = |key: &str| {
+                event
+                    .get("data")
+                    .and_then(|data| data.get(key))
+                    .and_then(|value| value.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            match kind {
+                Some("currentwindow") => windows.push(ActivityEvent {
+                    start,
+                    end,
+                    app: data("app"),
+                    title: data("title"),
+                }),
+                Some("afkstatus") if data("status") == "afk" => afk.push((start, end)),
+                _ => {}
+            }
+        }
+    }
+
+    let suggestions = suggest_activity_sessions(
+        &windows,
+        &afk,
+        |event| {
+            config
+                .rules
+                .iter()
+                .find(|rule| rule.matches(&event.app, &event.title))
+                .map(|rule| rule.category.clone())
+        },
+        config.merge_gap_secs,
+        config.min_session_secs,
+    );
+    Ok(suggestions
+        .into_iter()
+        .map(|suggestion| ImportedInterval {
+            start: suggestion.start.with_timezone(&Local),
+            end: Some(suggestion.end.with_timezone(&Local)),
+            category_candidates: vec![suggestion.category],
+            project: Some(String::new()),
+            description: suggestion.app,
+        })
+        .collect())
+}
+
+fn fetch_aw_export(server: &str) -> Result<String, String> {
+    use std::io::{Read, Write};
+
+    let mut stream = std::net::TcpStream::connect(server)
+        .map_err(|e| format!("Failed to reach aw-server at {}: {}", server, e))?;
+    write!(
+        stream,
+        "GET /api/0/export HTTP/1.0\r\nHost: {}\r\nAccept: application/json\r\n\r\n",
+        server
+    )
+    .map_err(|e| e.to_string())?;
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .map_err(|e| e.to_string())?;
+
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| "Malformed response from aw-server".to_string())?;
+    let status = head.lines().next().unwrap_or_default();
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(format!("aw-server replied '{}'", status));
+    }
+    Ok(body.to_string())
+}
+
+pub fn import_data(
+    format: ImportFormat,
+    path: Option<PathBuf>,
+    server: Option<String>,
+    dry_run: bool,
+) -> Result<(), String> {
+    let raw = match (&path, server) {
+        (_, Some(server)) if format == ImportFormat::ActivityWatch => fetch_aw_export(&server)?,
+        (_, Some(_)) => return Err("--server only applies to --format activitywatch".to_string()),
+        (Some(path), None) => std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?,
+        (None, None) => io::read_to_string(io::stdin()).map_err(|e| e.to_string())?,
+    };
+
+    let intervals = match format {
+        ImportFormat::Timewarrior => read_timew_intervals(&raw)?,
+        ImportFormat::Clockify => read_clockify_intervals(&raw)?,
+        ImportFormat::ActivityWatch => read_activitywatch_intervals(&raw)?,
+    };
+
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let mut sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+    let first_new = sessions.len();
+    let summary = merge_imported_intervals(intervals, &mut sessions, &categories);
+
+    if dry_run {
+        for session in &sessions[first_new..] {
+            println!("{}", describe_session(session, &categories));
+        }
+    } else if summary.imported > 0 {
+        storage::save_sessions_to_csv(&sessions_path, &sessions, &categories)?;
     }
 
+    println!(
+        "{} {} session(s); skipped {} duplicate(s), {} open interval(s) and {} with no length",
+        if dry_run { "Would import" } else { "Imported" },
+        summary.imported,
+        summary.duplicates,
+        summary.open,
+        summary.empty
+    );
+    if summary.uncategorized > 0 {
+        println!(
+            "{} session(s) matched no category and were filed under 'none'",
+            summary.uncategorized
+        );
+    }
     Ok(())
 }
 
-fn format_ics_datetime(date: &str, time: &str) -> String {
-    format!("{}T{}00", date.replace('-', ""), time.replace(':', ""))
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ImportSummary {
+    imported: usize,
+    duplicates: usize,
+    open: usize,
+    /// Intervals ending at or before their start.
+    empty: usize,
+    uncategorized: usize,
+}
+
+/// Appends the closed intervals not already in `sessions` (same start),
+/// filing each under the first candidate naming a category.
+fn merge_imported_intervals(
+    intervals: Vec<ImportedInterval>,
+    sessions: &mut Vec<Session>,
+    categories: &[Category],
+) -> ImportSummary {
+    let mut known_starts: std::collections::HashSet<NaiveDateTime> = sessions
+        .iter()
+        .filter_map(session_interval)
+        .map(|(start, _)| start)
+        .collect();
+    let mut next_id = sessions.iter().map(|s| s.id).max().unwrap_or(0) + 1;
+    let mut summary = ImportSummary::default();
+
+    for interval in intervals {
+        let start = interval.start;
+        let Some(end) = interval.end else {
+            summary.open += 1;
+            continue;
+        };
+        if end <= start {
+            summary.empty += 1;
+            continue;
+        }
+        if !known_starts.insert(start.naive_local()) {
+            summary.duplicates += 1;
+            continue;
+        }
+
+        let category = interval.category_candidates.iter().find_map(|candidate| {
+            categories
+                .iter()
+                .skip(1)
+                .find(|category| category.name.eq_ignore_ascii_case(candidate))
+        });
+        if category.is_none() {
+            summary.uncategorized += 1;
+        }
+        let project = interval.project.unwrap_or_else(|| {
+            interval
+                .category_candidates
+                .iter()
+                .find(|tag| {
+                    category.is_none_or(|category| !category.name.eq_ignore_ascii_case(tag))
+                })
+                .cloned()
+                .unwrap_or_default()
+        });
+
+        let mut session = Session {
+            id: next_id,
+            date: String::new(),
+            category_id: category.map_or(CategoryId::new(0), |category| category.id),
+            description: interval.description,
+            project,
+            start_time: String::new(),
+            end_time: String::new(),
+            elapsed_seconds: 0,
+            focus: None,
+        };
+        set_session_interval(&mut session, start.naive_local(), end.naive_local());
+        sessions.push(session);
+        next_id += 1;
+        summary.imported += 1;
+    }
+    summary
+}
+
+pub fn rename_project_everywhere(old_name: &str, new_name: &str) -> Result<(), String> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        return Err("New project name cannot be empty".to_string());
+    }
+    if old_name == new_name {
+        return Err("Old and new project names are the same".to_string());
+    }
+
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let mut sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+
+    let session_path = storage::get_active_session_path();
+    let mut active_session = if storage::file_exists(&session_path) {
+        Some(storage::read_json::<ActiveSession>(&session_path)?)
+    } else {
+        None
+    };
+    let active_matches = active_session
+        .as_ref()
+        .is_some_and(|session| session.project == old_name);
+
+    let renamed = rename_project(&mut sessions, old_name, new_name);
+    if renamed == 0 && !active_matches {
+        return Err(format!("No sessions found for project '{}'", old_name));
+    }
+
+    // The active session goes first and is put back if the log can't be
+    // saved, so both keep the same name either way.
+    let previous_active = active_session.clone();
+    if active_matches && let Some(session) = active_session.as_mut() {
+        session.project = new_name.to_string();
+        storage::write_json_atomic(&session_path, session)?;
+    }
+    if renamed > 0
+        && let Err(e) = storage::save_sessions_to_csv(&sessions_path, &sessions, &categories)
+    {
+        if active_matches && let Some(previous) = previous_active.as_ref() {
+            storage::write_json_atomic(&session_path, previous).map_err(|restore| {
+                format!(
+                    "{}; the running session was renamed and could not be restored: {}",
+                    e, restore
+                )
+            })?;
+        }
+        return Err(e);
+    }
+
+    println!(
+        "Renamed project '{}' to '{}' in {} session(s){}",
+        old_name,
+        new_name,
+        renamed,
+        if active_matches {
+            " and the active session"
+        } else {
+            ""
+        }
+    );
+    Ok(())
+}
+
+fn parse_day(raw: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", raw))
+}
+
+pub fn profile(command: ProfileCommand) -> Result<(), String> {
+    match command {
+        ProfileCommand::List => {
+            let active = storage::active_profile();
+            for name in storage::list_profiles() {
+                let marker = if name == active { "*" } else { " " };
+                println!("{} {}", marker, name);
+            }
+            Ok(())
+        }
+        ProfileCommand::Create { name } => {
+            storage::create_profile(&name)?;
+            println!("Created profile '{}'", name);
+            Ok(())
+        }
+        ProfileCommand::Switch { name } => {
+            storage::switch_profile(&name)?;
+            println!("Switched to profile '{}'", name);
+            Ok(())
+        }
+    }
+}
+
+pub fn metrics(command: MetricsCommand, json: bool) -> Result<(), String> {
+    let path = storage::get_usage_metrics_path();
+    match command {
+        MetricsCommand::Show => {
+            let metrics = storage::load_usage_metrics(&path);
+            if json {
+                return print_json(&metrics);
+            }
+            if !storage::load_config(&storage::get_config_path()).metrics {
+                println!(
+                    "Usage metrics are off; set \"metrics\": true in config.json to record them"
+                );
+            }
+            if metrics.counts.is_empty() {
+                println!("No usage recorded");
+                return Ok(());
+            }
+            if let Some(since) = metrics.since {
+                println!(
+                    "Usage since {} ({})",
+                    since.with_timezone(&Local).format("%Y-%m-%d"),
+                    path.display()
+                );
+            }
+            let mut counts: Vec<_> = metrics.counts.iter().collect();
+            counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            for (event, count) in counts {
+                println!("{:>6}  {}", count, event);
+            }
+            Ok(())
+        }
+        MetricsCommand::Reset => {
+            storage::delete_file_if_exists(&path)?;
+            println!("Usage metrics cleared");
+            Ok(())
+        }
+    }
+}
+
+/// The subcommand path, e.g. `sessions delete`, without any argument values.
+/// The subcommand path to count, e.g. `sessions delete`; reading or
+/// resetting the metrics themselves isn't counted.
+fn usage_event(matches: &clap::ArgMatches) -> Option<String> {
+    if matches.subcommand_name() == Some("metrics") {
+        return None;
+    }
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    (!names.is_empty()).then(|| names.join(" "))
+}
+
+pub fn budget(command: BudgetCommand) -> Result<(), String> {
+    let data_dir = storage::get_data_dir();
+    let categories_path = data_dir.join("categories.csv");
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+
+    let budgets_path = storage::get_category_budgets_path();
+    let mut budgets = storage::load_category_budgets(&budgets_path);
+
+    match command {
+        BudgetCommand::Set {
+            category,
+            limit: seconds,
+        } => {
+            let category = resolve_category(&categories, Some(&category))?;
+            if category.id == CategoryId::new(0) {
+                return Err("Cannot set a budget for the 'none' category".to_string());
+            }
+            if seconds == 0 {
+                return Err("A daily budget must be longer than zero".to_string());
+            }
+            budgets
+                .daily_seconds_by_category
+                .insert(category.id.0, seconds);
+            println!(
+                "Set daily budget for '{}' to {}",
+                category.name,
+                format_hms(seconds)
+            );
+            storage::save_category_budgets(&budgets_path, &budgets)
+        }
+        BudgetCommand::Clear { category } => {
+            let category = resolve_category(&categories, Some(&category))?;
+            if budgets
+                .daily_seconds_by_category
+                .remove(&category.id.0)
+                .is_none()
+            {
+                return Err(format!("No budget set for '{}'", category.name));
+            }
+            println!("Cleared daily budget for '{}'", category.name);
+            storage::save_category_budgets(&budgets_path, &budgets)
+        }
+        BudgetCommand::List => {
+            if budgets.daily_seconds_by_category.is_empty() {
+                println!("No budgets configured");
+                return Ok(());
+            }
+            let weeks = week_goal_progress(&categories, &budgets)?;
+            for category in &categories {
+                if let Some(seconds) = budgets.daily_seconds_by_category.get(&category.id.0) {
+                    let week = weeks
+                        .get(&category.id)
+                        .map(|progress| format!("  {}", progress))
+                        .unwrap_or_default();
+                    println!("{:20} {}/day{}", category.name, format_hms(*seconds), week);
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// This week's tracked time against each weekly goal including carried debt
+/// or credit, when goal carry-over is on.
+fn week_goal_progress(
+    categories: &[Category],
+    budgets: &storage::CategoryBudgetsState,
+) -> Result<HashMap<CategoryId, String>, String> {
+    let config = storage::load_config(&storage::get_config_path());
+    let Some(rules) = storage::carry_over_rules(&config) else {
+        return Ok(HashMap::new());
+    };
+    let sessions_path = storage::get_data_dir().join("time_log.csv");
+    let sessions = storage::load_sessions_from_csv(&sessions_path, categories).sessions;
+    let today = operational_day_key_now();
+    let current_week = goal_ledger_week(today, week_start());
+    let goals = budgets.weekly_goals();
+    let ledger = storage::sync_goal_ledger(
+        &storage::get_goal_ledger_path(),
+        &sessions,
+        &goals,
+        &rules,
+        current_week,
+    )?;
+
+    Ok(goals
+        .iter()
+        .map(|(category_id, goal_seconds)| {
+            let carried = rules.carried_into(&ledger, *category_id, current_week);
+            let tracked: usize = SessionQuery::new()
+                .between(current_week, today)
+                .category(*category_id)
+                .run(&sessions)
+                .sessions
+                .iter()
+                .map(|session| session.elapsed_seconds)
+                .sum();
+            let target = (*goal_seconds as i64 + carried).max(0) as usize;
+            let balance = match carried {
+                0 => String::new(),
+                debt if debt > 0 => format!(" ({} debt)", format_hm(debt as usize)),
+                credit => format!(" ({} credit)", format_hm(credit.unsigned_abs() as usize)),
+            };
+            (
+                *category_id,
+                format!(
+                    "week {} of {}{}",
+                    format_hm(tracked),
+                    format_hm(target),
+                    balance
+                ),
+            )
+        })
+        .collect())
+}
+
+pub fn plan(command: PlanCommand, json: bool) -> Result<(), String> {
+    let data_dir = storage::get_data_dir();
+    let categories = storage::load_categories_from_csv(&data_dir.join("categories.csv")).categories;
+    let today = operational_day_key_now();
+    let plan_path = storage::get_plan_path();
+    let mut plan = storage::load_plan(&plan_path, today);
+
+    let item_index = |plan: &storage::PlanState, number: usize| {
+        number
+            .checked_sub(1)
+            .filter(|index| *index < plan.items.len())
+            .ok_or_else(|| format!("No plan item {}; see strata plan list", number))
+    };
+
+    match command {
+        PlanCommand::Add {
+            description,
+            category,
+            planned,
+            at,
+            template,
+        } => {
+            let template = match template {
+                Some(name) => Some(
+                    storage::load_config(&storage::get_config_path())
+                        .plan_templates
+                        .into_iter()
+                        .find(|template| template.name.eq_ignore_ascii_case(&name))
+                        .ok_or_else(|| format!("No plan template named '{}'", name))?,
+                ),
+                None => None,
+            };
+            let category_name = category
+                .or_else(|| template.as_ref().map(|template| template.category.clone()))
+                .ok_or("Pass --category or --template")?;
+            let category = resolve_category(&categories, Some(&category_name))?;
+            if category.id == CategoryId::new(0) {
+                return Err("Cannot plan time for the 'none' category".to_string());
+            }
+            let planned_seconds = match planned {
+                Some(seconds) => Some(seconds),
+                None => template
+                    .as_ref()
+                    .and_then(|template| template.planned.as_deref())
+                    .map(duration_arg)
+                    .transpose()?,
+            };
+            let starts_at = match at {
+                Some(time) => Some(time),
+                None => template
+                    .as_ref()
+                    .and_then(|template| template.at.as_deref())
+                    .map(clock_arg)
+                    .transpose()?,
+            };
+            let description = description
+                .or_else(|| template.map(|template| template.description))
+                .unwrap_or_default();
+
+            plan.items.push(storage::PlanItem {
+                category_id: category.id.0,
+                description: description.trim().to_string(),
+                planned_seconds: planned_seconds.filter(|seconds| *seconds > 0),
+                starts_at,
+                actual_seconds: None,
+            });
+            storage::save_plan(&plan_path, &plan)?;
+            println!(
+                "Pinned {}. {}",
+                plan.items.len(),
+                plan_item_label(&plan.items[plan.items.len() - 1], &categories)
+            );
+            Ok(())
+        }
+        PlanCommand::List => {
+            let sessions =
+                storage::load_sessions_from_csv(&data_dir.join("time_log.csv"), &categories)
+                    .sessions;
+            let live = status_output()?.active;
+            let rows: Vec<PlanItemOutput> = plan
+                .items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| PlanItemOutput {
+                    number: index + 1,
+                    category: category_name(&categories, CategoryId::new(item.category_id)),
+                    description: item.description.clone(),
+                    planned_seconds: item.planned_seconds,
+                    tracked_seconds: plan_item_tracked(item, &sessions, &categories, live.as_ref()),
+                    done: item.is_done(),
+                })
+                .collect();
+            if json {
+                return print_json(&rows);
+            }
+            if rows.is_empty() {
+                println!("Nothing pinned for today; add items with strata plan add");
+                return Ok(());
+            }
+            for (row, item) in rows.iter().zip(&plan.items) {
+                let planned = row
+                    .planned_seconds
+                    .map(|seconds| format!(" / {}", format_duration(seconds)))
+                    .unwrap_or_default();
+                println!(
+                    "{:>2}. [{}] {:40} {}{}",
+                    row.number,
+                    if row.done { "x" } else { " " },
+                    plan_item_label(item, &categories),
+                    format_duration(row.tracked_seconds),
+                    planned
+                );
+            }
+            Ok(())
+        }
+        PlanCommand::Done { number } => {
+            let index = item_index(&plan, number)?;
+            if plan.items[index].is_done() {
+                return Err(format!("Plan item {} is already done", number));
+            }
+            let sessions =
+                storage::load_sessions_from_csv(&data_dir.join("time_log.csv"), &categories)
+                    .sessions;
+            let live = status_output()?.active;
+            let item = &mut plan.items[index];
+            let tracked = plan_item_tracked(item, &sessions, &categories, live.as_ref());
+            item.actual_seconds = Some(tracked);
+            let summary = match item.planned_seconds {
+                Some(planned) => format!(
+                    "{} tracked of {} planned",
+                    format_duration(tracked),
+                    format_duration(planned)
+                ),
+                None => format!("{} tracked", format_duration(tracked)),
+            };
+            println!("Done: {} ({})", plan_item_label(item, &categories), summary);
+            storage::save_plan(&plan_path, &plan)
+        }
+        PlanCommand::Remove { number } => {
+            let index = item_index(&plan, number)?;
+            let item = plan.items.remove(index);
+            storage::save_plan(&plan_path, &plan)?;
+            println!("Unpinned {}", plan_item_label(&item, &categories));
+            Ok(())
+        }
+        PlanCommand::Clear => {
+            plan.items.clear();
+            storage::save_plan(&plan_path, &plan)?;
+            println!("Cleared today's plan");
+            Ok(())
+        }
+    }
+}
+
+fn category_name(categories: &[Category], category_id: CategoryId) -> String {
+    categories
+        .iter()
+        .find(|category| category.id == category_id)
+        .map(|category| category.name.clone())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn plan_item_name(item: &storage::PlanItem, categories: &[Category]) -> String {
+    let category = category_name(categories, CategoryId::new(item.category_id));
+    if item.description.is_empty() {
+        category
+    } else {
+        format!("{} · {}", category, item.description)
+    }
+}
+
+fn plan_item_label(item: &storage::PlanItem, categories: &[Category]) -> String {
+    let label = plan_item_name(item, categories);
+    match item.starts_at {
+        Some(time) => format!("{} {}", time.format("%H:%M"), label),
+        None => label,
+    }
+}
+
+fn clock_arg(raw: &str) -> Result<NaiveTime, String> {
+    let raw = raw.trim();
+    NaiveTime::parse_from_str(raw, "%H:%M")
+        .map_err(|_| format!("invalid time '{}', expected HH:MM", raw))
+}
+
+/// Tracked time for a plan item: the frozen value once done, otherwise
+/// today's matching sessions plus a matching running session.
+fn plan_item_tracked(
+    item: &storage::PlanItem,
+    sessions: &[Session],
+    categories: &[Category],
+    live: Option<&ActiveStatusOutput>,
+) -> usize {
+    if let Some(actual) = item.actual_seconds {
+        return actual;
+    }
+    let category_id = CategoryId::new(item.category_id);
+    let logged = planned_item_seconds(
+        sessions,
+        operational_day_key_now(),
+        category_id,
+        &item.description,
+    );
+    let running = live
+        .filter(|active| active.category == category_name(categories, category_id))
+        .filter(|active| {
+            item.description.is_empty()
+                || active
+                    .description
+                    .trim()
+                    .eq_ignore_ascii_case(&item.description)
+        })
+        .map_or(0, |active| active.elapsed_seconds);
+    logged + running
+}
+
+const DEFAULT_SUMMARY_FORMAT: &str = "{icon} {category} {elapsed} | today {today} | karma {karma}";
+
+fn format_hm(seconds: usize) -> String {
+    format!("{:02}:{:02}", seconds / 3600, (seconds % 3600) / 60)
+}
+
+pub fn summary(format: Option<String>, json: bool) -> Result<(), String> {
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+
+    let session_path = storage::get_active_session_path();
+    let active_session = if storage::file_exists(&session_path) {
+        Some(storage::read_json::<ActiveSession>(&session_path)?)
+    } else {
+        None
+    };
+    let elapsed = active_session.as_ref().map_or(0, |session| {
+        (Utc::now() - session.start_time).num_seconds().max(0) as usize
+    });
+    let live = active_session.as_ref().map(|session| LiveSessionPreview {
+        category_id: CategoryId::new(session.category_id),
+        description: session.description.clone(),
+        elapsed_seconds: elapsed,
+        now_local: Local::now(),
+    });
+
+    let karma = build_period_karma_report_with_live(
+        &sessions,
+        &categories,
+        ReportPeriod::Today,
+        live.as_ref(),
+    );
+    let today: usize = karma
+        .entries
+        .iter()
+        .filter(|entry| entry.category_id != CategoryId::new(0))
+        .map(|entry| entry.elapsed_seconds)
+        .sum();
+
+    if json {
+        return print_json(&SummaryOutput {
+            active: active_session.is_some(),
+            category: active_session
+                .as_ref()
+                .map(|session| session.category_name.as_str()),
+            project: active_session
+                .as_ref()
+                .map(|session| session.project.as_str())
+                .filter(|project| !project.is_empty()),
+            elapsed_seconds: elapsed,
+            today_seconds: today,
+            karma_seconds: karma.total_karma_seconds,
+        });
+    }
+
+    let line = summary_line(
+        format.as_deref().unwrap_or(DEFAULT_SUMMARY_FORMAT),
+        active_session.as_ref(),
+        elapsed,
+        today,
+        karma.total_karma_seconds,
+    );
+    println!("{}", line);
+    Ok(())
+}
+
+fn summary_line(
+    format: &str,
+    active_session: Option<&ActiveSession>,
+    elapsed: usize,
+    today: usize,
+    karma_seconds: isize,
+) -> String {
+    let (icon, category, project, description) = match active_session {
+        Some(session) => (
+            "▶",
+            session.category_name.as_str(),
+            session.project.as_str(),
+            session.description.as_str(),
+        ),
+        None => ("■", "idle", "", ""),
+    };
+    let karma_sign = if karma_seconds < 0 { "-" } else { "+" };
+    let line = fill_template(format, |name| match name {
+        "icon" => Some(icon.to_string()),
+        "category" => Some(category.to_string()),
+        "project" => Some(project.to_string()),
+        "description" => Some(description.to_string()),
+        "elapsed" => Some(format_hm(elapsed)),
+        "today" => Some(format_hm(today)),
+        "karma" => Some(format!(
+            "{}{}",
+            karma_sign,
+            format_hm(karma_seconds.unsigned_abs())
+        )),
+        _ => None,
+    });
+    line.trim_end().to_string()
+}
+
+/// Replaces each `{name}` in `template` that `value` knows, in one pass, so
+/// braces inside the substituted text are never expanded. Unknown names and
+/// unmatched braces are left as written.
+fn fill_template(template: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        filled.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let token = after
+            .find('}')
+            .map(|close| (close, &after[..close]))
+            .filter(|(_, name)| !name.contains('{'))
+            .and_then(|(close, name)| value(name).map(|text| (close, text)));
+        match token {
+            Some((close, text)) => {
+                filled.push_str(&text);
+                rest = &after[close + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = after;
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+pub(crate) fn status_output() -> Result<StatusOutput, String> {
+    if let Some(response) = daemon::send(&DaemonRequest::Status) {
+        return match response? {
+            DaemonResponse::Status(output) => Ok(output),
+            _ => Err(DaemonResponse::unexpected()),
+        };
+    }
+
+    let session_path = storage::get_active_session_path();
+    let active_session = if storage::file_exists(&session_path) {
+        Some(storage::read_json::<ActiveSession>(&session_path)?)
+    } else {
+        None
+    };
+    Ok(status_output_for(active_session.as_ref()))
+}
+
+pub(crate) fn status_output_for(active_session: Option<&ActiveSession>) -> StatusOutput {
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+    let budgets = storage::load_category_budgets(&storage::get_category_budgets_path());
+
+    let active = active_session.map(|session| ActiveStatusOutput {
+        category: session.category_name.clone(),
+        project: session.project.clone(),
+        description: session.description.clone(),
+        started_at: session.start_time,
+        elapsed_seconds: (Utc::now() - session.start_time).num_seconds().max(0) as usize,
+    });
+
+    let today = operational_day_key_now().format("%Y-%m-%d").to_string();
+    let mut budget_rows = Vec::new();
+    for category in &categories {
+        let Some(budget) = budgets.daily_seconds_by_category.get(&category.id.0) else {
+            continue;
+        };
+        let mut spent: usize = sessions
+            .iter()
+            .filter(|session| session.date == today && session.category_id == category.id)
+            .map(|session| session.elapsed_seconds)
+            .sum();
+        if let (Some(session), Some(active)) = (active_session, &active)
+            && session.category_id == category.id.0
+        {
+            spent += active.elapsed_seconds;
+        }
+        budget_rows.push(BudgetStatusOutput {
+            category: category.name.clone(),
+            spent_seconds: spent,
+            budget_seconds: *budget,
+            over_budget: spent > *budget,
+        });
+    }
+
+    StatusOutput {
+        active,
+        budgets: budget_rows,
+    }
+}
+
+pub fn status(json: bool) -> Result<(), String> {
+    let output = status_output()?;
+    if json {
+        return print_json(&output);
+    }
+
+    match &output.active {
+        Some(active) => println!(
+            "Tracking '{}' for {}{}",
+            active.category,
+            format_hms(active.elapsed_seconds),
+            if active.project.is_empty() {
+                String::new()
+            } else {
+                format!(" on {}", active.project)
+            }
+        ),
+        None => println!("No active session"),
+    }
+
+    for row in &output.budgets {
+        let line = format!(
+            "{:20} {} / {}",
+            row.category,
+            format_hms(row.spent_seconds),
+            format_hms(row.budget_seconds)
+        );
+        if row.over_budget {
+            println!("{}", format!("{} over budget", line).red());
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    Ok(())
+}
+
+/// Filters for listing sessions, as given on the command line or in a
+/// `/sessions` query string.
+#[derive(Debug, Default)]
+pub(crate) struct SessionFilter<'a> {
+    pub(crate) date: Option<&'a str>,
+    pub(crate) from: Option<&'a str>,
+    pub(crate) to: Option<&'a str>,
+    pub(crate) category: Option<&'a str>,
+    pub(crate) tag: Option<&'a str>,
+    pub(crate) offset: usize,
+    pub(crate) limit: usize,
+}
+
+pub(crate) fn session_rows(filter: &SessionFilter) -> Result<Vec<SessionExport>, String> {
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+
+    let mut query = SessionQuery::new()
+        .offset(filter.offset)
+        .limit(filter.limit);
+    if let Some(day) = filter.date.map(parse_day).transpose()? {
+        query = query.between(day, day);
+    }
+    if let Some(from) = filter.from.map(parse_day).transpose()? {
+        query = query.since(from);
+    }
+    if let Some(to) = filter.to.map(parse_day).transpose()? {
+        query = query.until(to);
+    }
+    if filter.category.is_some() {
+        query = query.category(resolve_category(&categories, filter.category)?.id);
+    }
+    if let Some(tag) = filter.tag {
+        query = query.tag(tag);
+    }
+
+    Ok(query
+        .run(&sessions)
+        .sessions
+        .into_iter()
+        .map(|session| SessionExport {
+            id: session.id,
+            date: session.date.clone(),
+            category_id: session.category_id.0,
+            category_name: categories
+                .iter()
+                .find(|category| category.id == session.category_id)
+                .map(|category| category.name.clone())
+                .unwrap_or_else(|| "none".to_string()),
+            project: (!session.project.is_empty()).then(|| session.project.clone()),
+            description: session.description.clone(),
+            start_time: session.start_time.clone(),
+            end_time: session.end_time.clone(),
+            elapsed_seconds: session.elapsed_seconds,
+            focus: session.focus,
+        })
+        .collect())
+}
+
+pub fn sessions(command: SessionsCommand, json: bool) -> Result<(), String> {
+    match command {
+        SessionsCommand::List {
+            date,
+            from,
+            to,
+            category,
+            tag,
+            offset,
+            limit,
+        } => list_sessions(
+            &SessionFilter {
+                date: date.as_deref(),
+                from: from.as_deref(),
+                to: to.as_deref(),
+                category: category.as_deref(),
+                tag: tag.as_deref(),
+                offset,
+                limit,
+            },
+            json,
+        ),
+        SessionsCommand::Delete { ids } => delete_sessions(&ids),
+        SessionsCommand::Move { category, ids } => move_sessions(&category, &ids),
+    }
+}
+
+fn delete_sessions(ids: &[usize]) -> Result<(), String> {
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let mut sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+
+    let removed = remove_sessions(&mut sessions, &ids.iter().copied().collect());
+    if removed.is_empty() {
+        return Err("No matching sessions".to_string());
+    }
+    storage::append_sessions_to_trash(&storage::get_trash_path(), &removed, &categories)?;
+    storage::save_sessions_to_csv(&sessions_path, &sessions, &categories)?;
+    let kept_path = storage::get_kept_overlaps_path();
+    let mut kept = storage::load_kept_overlaps(&kept_path);
+    if forget_removed_overlaps(&mut kept, &sessions) {
+        storage::save_kept_overlaps(&kept_path, &kept)?;
+    }
+    println!("Deleted {} session(s)", removed.len());
+    Ok(())
+}
+
+fn move_sessions(category_name: &str, ids: &[usize]) -> Result<(), String> {
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let mut sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+    let category = resolve_category(&categories, Some(category_name))?;
+
+    let moved = recategorize_sessions(&mut sessions, &ids.iter().copied().collect(), category.id);
+    if moved > 0 {
+        storage::save_sessions_to_csv(&sessions_path, &sessions, &categories)?;
+    }
+    println!("Moved {} session(s) to '{}'", moved, category.name);
+    Ok(())
+}
+
+fn list_sessions(filter: &SessionFilter, json: bool) -> Result<(), String> {
+    let rows = session_rows(filter)?;
+
+    if json {
+        return print_json(&rows);
+    }
+
+    if rows.is_empty() {
+        println!("No sessions found");
+        return Ok(());
+    }
+    for row in &rows {
+        let line = format!(
+            "{:>5} {} {}-{} {:16} {} {}",
+            row.id,
+            row.date,
+            row.start_time,
+            row.end_time,
+            row.category_name,
+            format_hms(row.elapsed_seconds),
+            row.description
+        );
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+fn format_hms(seconds: usize) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds % 3600) / 60,
+        seconds % 60
+    )
+}
+
+pub fn rate(command: RateCommand) -> Result<(), String> {
+    let data_dir = storage::get_data_dir();
+    let categories_path = data_dir.join("categories.csv");
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+
+    let rates_path = storage::get_billing_rates_path();
+    let mut rates = storage::load_billing_rates(&rates_path)?;
+
+    match command {
+        RateCommand::Set {
+            target,
+            rate,
+            project,
+        } => {
+            if !rate.is_finite() || rate < 0.0 {
+                return Err("Rate must be a non-negative number".to_string());
+            }
+            if project {
+                rates.project_rates.insert(target.clone(), rate);
+                println!("Set rate for project '{}' to {:.2}/h", target, rate);
+            } else {
+                let category = resolve_category(&categories, Some(&target))?;
+                if category.id == CategoryId::new(0) {
+                    return Err("Cannot set a rate for the 'none' category".to_string());
+                }
+                rates.category_rates.insert(category.id.0, rate);
+                println!("Set rate for category '{}' to {:.2}/h", category.name, rate);
+            }
+            storage::save_billing_rates(&rates_path, &rates)
+        }
+        RateCommand::Clear { target, project } => {
+            let removed = if project {
+                rates.project_rates.remove(&target).is_some()
+            } else {
+                let category = resolve_category(&categories, Some(&target))?;
+                rates.category_rates.remove(&category.id.0).is_some()
+            };
+            if !removed {
+                return Err(format!("No rate set for '{}'", target));
+            }
+            println!("Cleared rate for '{}'", target);
+            storage::save_billing_rates(&rates_path, &rates)
+        }
+        RateCommand::List => {
+            if rates.category_rates.is_empty() && rates.project_rates.is_empty() {
+                println!("No rates configured");
+                return Ok(());
+            }
+
+            let mut category_rates: Vec<(&str, f64)> = rates
+                .category_rates
+                .iter()
+                .map(|(id, rate)| {
+                    let name = categories
+                        .iter()
+                        .find(|category| category.id.0 == *id)
+                        .map(|category| category.name.as_str())
+                        .unwrap_or("(deleted category)");
+                    (name, *rate)
+                })
+                .collect();
+            category_rates.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, rate) in category_rates {
+                println!("category {:20} {:>10.2}/h", name, rate);
+            }
+
+            let mut project_rates: Vec<(&String, &f64)> = rates.project_rates.iter().collect();
+            project_rates.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, rate) in project_rates {
+                println!("project  {:20} {:>10.2}/h", name, rate);
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn invoice(
+    from: &str,
+    to: Option<&str>,
+    currency: &str,
+    tax_percent: f64,
+    format: InvoiceFormat,
+    out_path: Option<PathBuf>,
+) -> Result<(), String> {
+    let start = parse_day(from)?;
+    let end = match to {
+        Some(raw) => parse_day(raw)?,
+        None => operational_day_key_now(),
+    };
+    if end < start {
+        return Err("--to must not be before --from".to_string());
+    }
+    if !tax_percent.is_finite() || tax_percent < 0.0 {
+        return Err("--tax must be a non-negative percentage".to_string());
+    }
+
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+    let rates = storage::load_billing_rates(&storage::get_billing_rates_path())?;
+
+    let category_rates: HashMap<CategoryId, f64> = rates
+        .category_rates
+        .iter()
+        .map(|(id, rate)| (CategoryId::new(*id), *rate))
+        .collect();
+    let invoice = build_invoice(
+        &sessions,
+        &categories,
+        start,
+        end,
+        &category_rates,
+        &rates.project_rates,
+        tax_percent,
+    );
+
+    if invoice.lines.is_empty() {
+        return Err(format!(
+            "No billable sessions between {} and {}. Set rates with `strata rate set`.",
+            start, end
+        ));
+    }
+
+    let content = match format {
+        InvoiceFormat::Markdown => {
+            format_invoice_markdown(&invoice, start, end, currency, tax_percent)
+        }
+        InvoiceFormat::Csv => format_invoice_csv(&invoice, currency, tax_percent)?,
+    };
+
+    if invoice.unbilled_seconds > 0 {
+        eprintln!(
+            "Note: {:.2}h in this range had no rate and was left off the invoice",
+            invoice.unbilled_seconds as f64 / 3600.0
+        );
+    }
+
+    if let Some(path) = out_path {
+        storage::write_text_file(&path, &content)?;
+        println!("Invoice written to {}", path.display());
+    } else {
+        print!("{}", content);
+    }
+    Ok(())
+}
+
+fn audit_output(weeks: usize) -> AuditOutput {
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+    let budgets = storage::load_category_budgets(&storage::get_category_budgets_path());
+    let goals: HashMap<CategoryId, usize> = budgets
+        .daily_seconds_by_category
+        .iter()
+        .map(|(id, seconds)| (CategoryId::new(*id), *seconds))
+        .collect();
+
+    let weeks = weeks.max(1);
+    let end = operational_day_key_now();
+    let rows = build_goal_audit(
+        &sessions,
+        &categories,
+        &goals,
+        end,
+        weeks,
+        AUDIT.tolerance_percent,
+        AUDIT.round_seconds,
+    );
+    let week_starts: Vec<NaiveDate> = (0..weeks)
+        .rev()
+        .map(|back| end - ChronoDuration::days(7 * back as i64 + 6))
+        .collect();
+
+    AuditOutput {
+        start: week_starts[0],
+        end,
+        weeks: week_starts,
+        categories: rows
+            .into_iter()
+            .map(|row| AuditRowOutput {
+                category: row.category_name,
+                weekly_goal_seconds: row.weekly_goal_seconds,
+                weekly_actual_seconds: row.weekly_actual_seconds,
+                average_seconds: row.average_seconds,
+                delta_percent: row.delta_percent,
+                suggested_daily_seconds: row.suggested_daily_seconds,
+            })
+            .collect(),
+    }
+}
+
+pub fn audit(
+    weeks: usize,
+    markdown: bool,
+    out_path: Option<PathBuf>,
+    json: bool,
+) -> Result<(), String> {
+    let output = audit_output(weeks);
+    if json {
+        return print_json(&output);
+    }
+    if output.categories.is_empty() {
+        return Err("No daily budgets to audit. Set one with `strata budget set`.".to_string());
+    }
+
+    if let Some(path) = out_path {
+        storage::write_text_file(&path, &format_audit_markdown(&output))?;
+        println!("Audit written to {}", path.display());
+        return Ok(());
+    }
+    if markdown {
+        print!("{}", format_audit_markdown(&output));
+        return Ok(());
+    }
+
+    println!("Goal Audit ({}..{})", output.start, output.end);
+    println!("{}", "-".repeat(60));
+    println!(
+        "{:20} {:>9} {:>9} {:>8}  suggestion",
+        "category", "goal/wk", "avg/wk", "delta"
+    );
+    for row in &output.categories {
+        let line = format!(
+            "{:20} {:>9} {:>9} {:>+7.0}%  {}",
+            row.category,
+            format_hm(row.weekly_goal_seconds),
+            format_hm(row.average_seconds),
+            row.delta_percent,
+            audit_suggestion(row)
+        );
+        if row.delta_percent > AUDIT.tolerance_percent {
+            println!("{}", line.red());
+        } else {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
+
+fn audit_suggestion(row: &AuditRowOutput) -> String {
+    match row.suggested_daily_seconds {
+        Some(seconds) if row.delta_percent > 0.0 => {
+            format!("raise daily goal to {}", format_hm(seconds))
+        }
+        Some(seconds) => format!("lower daily goal to {}", format_hm(seconds)),
+        None => "keep".to_string(),
+    }
+}
+
+fn format_audit_markdown(output: &AuditOutput) -> String {
+    let mut out = String::new();
+    out.push_str(
+        "# Goal Audit
+
+",
+    );
+    out.push_str(&format!(
+        "Weeks: {} to {}
+
+",
+        output.start, output.end
+    ));
+    out.push_str("| Category | Goal/week |");
+    for week in &output.weeks {
+        out.push_str(&format!(" {} |", week.format("%m-%d")));
+    }
+    out.push_str(
+        " Average | Delta | Suggestion |
+",
+    );
+    out.push_str("|---|---:|");
+    out.push_str(&"---:|".repeat(output.weeks.len()));
+    out.push_str(
+        "---:|---:|---|
+",
+    );
+    for row in &output.categories {
+        out.push_str(&format!(
+            "| {} | {} |",
+            row.category,
+            format_hm(row.weekly_goal_seconds)
+        ));
+        for seconds in &row.weekly_actual_seconds {
+            out.push_str(&format!(" {} |", format_hm(*seconds)));
+        }
+        out.push_str(&format!(
+            " {} | {:+.0}% | {} |
+",
+            format_hm(row.average_seconds),
+            row.delta_percent,
+            audit_suggestion(row)
+        ));
+    }
+    out
+}
+
+fn invoice_line_label(line: &InvoiceLine) -> String {
+    if line.project.is_empty() {
+        line.category_name.clone()
+    } else {
+        format!("{} / {}", line.project, line.category_name)
+    }
+}
+
+fn format_invoice_markdown(
+    invoice: &Invoice,
+    start: NaiveDate,
+    end: NaiveDate,
+    currency: &str,
+    tax_percent: f64,
+) -> String {
+    let mut out = String::new();
+    out.push_str("# Invoice\n\n");
+    out.push_str(&format!("Period: {} to {}\n\n", start, end));
+    out.push_str(&format!(
+        "| Item | Hours | Rate ({0}) | Amount ({0}) |\n",
+        currency
+    ));
+    out.push_str("|---|---:|---:|---:|\n");
+    for line in &invoice.lines {
+        out.push_str(&format!(
+            "| {} | {:.2} | {:.2} | {:.2} |\n",
+            invoice_line_label(line),
+            line.elapsed_seconds as f64 / 3600.0,
+            line.hourly_rate,
+            line.amount
+        ));
+    }
+    out.push('\n');
+    out.push_str(&format!("Subtotal: {:.2} {}\n", invoice.subtotal, currency));
+    out.push_str(&format!(
+        "Tax ({}%): {:.2} {}\n",
+        tax_percent, invoice.tax, currency
+    ));
+    out.push_str(&format!("**Total: {:.2} {}**\n", invoice.total, currency));
+    out
+}
+
+fn format_invoice_csv(
+    invoice: &Invoice,
+    currency: &str,
+    tax_percent: f64,
+) -> Result<String, String> {
+    let mut writer = csv::WriterBuilder::new().from_writer(vec![]);
+    writer
+        .write_record(["item", "hours", "rate", "amount", "currency"])
+        .map_err(|e| e.to_string())?;
+    for line in &invoice.lines {
+        writer
+            .write_record([
+                invoice_line_label(line),
+                format!("{:.2}", line.elapsed_seconds as f64 / 3600.0),
+                format!("{:.2}", line.hourly_rate),
+                format!("{:.2}", line.amount),
+                currency.to_string(),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    let summary_rows = [
+        ("subtotal".to_string(), invoice.subtotal),
+        (format!("tax {}%", tax_percent), invoice.tax),
+        ("total".to_string(), invoice.total),
+    ];
+    for (label, amount) in summary_rows {
+        writer
+            .write_record([
+                label,
+                String::new(),
+                String::new(),
+                format!("{:.2}", amount),
+                currency.to_string(),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+pub fn doctor(
+    check_overlaps: bool,
+    repair_headers: bool,
+    check_micro_sessions: bool,
+) -> Result<(), String> {
+    if !check_overlaps && !repair_headers && !check_micro_sessions {
+        println!("No checks selected. Use --overlaps, --headers or --micro-sessions.");
+        return Ok(());
+    }
+    if repair_headers {
+        doctor_headers()?;
+    }
+    if check_overlaps {
+        doctor_overlaps()?;
+    }
+    if check_micro_sessions {
+        doctor_micro_sessions()?;
+    }
+    Ok(())
+}
+
+fn doctor_headers() -> Result<(), String> {
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories_status =
+        storage::categories_header_status(&categories_path).map_err(|e| e.to_string())?;
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    report_header_repair("categories.csv", categories_status, || {
+        storage::save_categories_to_csv(&categories_path, &categories)
+    })?;
+
+    if categories_status == CsvHeaderStatus::Invalid {
+        println!("time_log.csv: skipped until categories.csv loads");
+        return Ok(());
+    }
+    let sessions_status =
+        storage::sessions_header_status(&sessions_path).map_err(|e| e.to_string())?;
+    let sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+    report_header_repair("time_log.csv", sessions_status, || {
+        storage::save_sessions_to_csv(&sessions_path, &sessions, &categories)
+    })
+}
+
+fn report_header_repair(
+    file: &str,
+    status: CsvHeaderStatus,
+    save: impl FnOnce() -> Result<(), String>,
+) -> Result<(), String> {
+    match status {
+        CsvHeaderStatus::Missing => println!("{}: not found", file),
+        CsvHeaderStatus::Recognized => println!("{}: headers OK", file),
+        CsvHeaderStatus::Legacy => {
+            save()?;
+            println!("{}: upgraded to the current columns", file);
+        }
+        CsvHeaderStatus::Positional => {
+            save()?;
+            println!("{}: restored headers", file);
+        }
+        CsvHeaderStatus::Invalid => println!(
+            "{}: columns match no known schema; restore a copy from the backups directory",
+            file
+        ),
+    }
+    Ok(())
+}
+
+fn doctor_overlaps() -> Result<(), String> {
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+
+    let kept = storage::load_kept_overlaps(&storage::get_kept_overlaps_path());
+    let overlaps = unresolved_session_overlaps(&sessions, &kept);
+    if overlaps.is_empty() {
+        println!("No overlapping sessions found.");
+        return Ok(());
+    }
+
+    let describe = |id: usize| -> String {
+        sessions
+            .iter()
+            .find(|session| session.id == id)
+            .map(|session| {
+                let name = categories
+                    .iter()
+                    .find(|category| category.id == session.category_id)
+                    .map(|category| category.name.as_str())
+                    .unwrap_or("none");
+                format!(
+                    "#{} {} {} {}-{}",
+                    session.id, session.date, name, session.start_time, session.end_time
+                )
+            })
+            .unwrap_or_else(|| format!("#{}", id))
+    };
+
+    let total_overlap: usize = overlaps.iter().map(|overlap| overlap.overlap_seconds).sum();
+    println!("Overlapping sessions ({})", overlaps.len());
+    println!("{}", "-".repeat(40));
+    for overlap in &overlaps {
+        println!(
+            "{} <> {} ({:02}:{:02}:{:02})",
+            describe(overlap.first_id),
+            describe(overlap.second_id),
+            overlap.overlap_seconds / 3600,
+            (overlap.overlap_seconds % 3600) / 60,
+            overlap.overlap_seconds % 60
+        );
+    }
+    println!("{}", "-".repeat(40));
+    println!(
+        "{:20} {:02}:{:02}:{:02}",
+        "DOUBLE-COUNTED",
+        total_overlap / 3600,
+        (total_overlap % 3600) / 60,
+        total_overlap % 60
+    );
+    println!("Resolve overlaps from the category logs in the TUI (o).");
+
+    Ok(())
+}
+
+const MICRO_SESSION_SECS: usize = 60;
+const MICRO_SESSION_DAYS: i64 = 30;
+const MICRO_SESSION_WARN_PERCENT: usize = 20;
+
+/// Counts sessions under the configured minimum (a minute when none is set)
+/// over the last 30 days; a high share usually means accidental switching.
+fn doctor_micro_sessions() -> Result<(), String> {
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let categories = storage::load_categories_from_csv(&categories_path).categories;
+    let sessions = storage::load_sessions_from_csv(&sessions_path, &categories).sessions;
+
+    let mut min_length = min_session_length(&categories, None);
+    let configured = min_length != MinSessionLength::default();
+    if !configured {
+        min_length.default_seconds = MICRO_SESSION_SECS;
+    }
+    let since = operational_day_key_now() - ChronoDuration::days(MICRO_SESSION_DAYS - 1);
+    let recent: Vec<&Session> = sessions
+        .iter()
+        .filter(|session| session.category_id != CategoryId::new(0))
+        .filter(|session| {
+            NaiveDate::parse_from_str(&session.date, "%Y-%m-%d").is_ok_and(|date| date >= since)
+        })
+        .collect();
+
+    let mut by_category: Vec<(&Category, usize)> = categories
+        .iter()
+        .map(|category| {
+            let count = recent
+                .iter()
+                .filter(|session| session.category_id == category.id)
+                .filter(|session| min_length.is_micro(session))
+                .count();
+            (category, count)
+        })
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    by_category.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    let micro: usize = by_category.iter().map(|(_, count)| count).sum();
+    if micro == 0 {
+        println!("No micro-sessions in the last {} days.", MICRO_SESSION_DAYS);
+        return Ok(());
+    }
+
+    let percent = micro * 100 / recent.len().max(1);
+    println!(
+        "Micro-sessions in the last {} days: {} of {} ({}%)",
+        MICRO_SESSION_DAYS,
+        micro,
+        recent.len(),
+        percent
+    );
+    println!("{}", "-".repeat(40));
+    for (category, count) in &by_category {
+        println!(
+            "{:20} {:>4}  under {}",
+            category.name,
+            count,
+            format_duration(min_length.threshold(category.id))
+        );
+    }
+    println!("{}", "-".repeat(40));
+    if percent >= MICRO_SESSION_WARN_PERCENT {
+        println!("That many accidental starts usually means switching categories by mistake.");
+        if !configured {
+            println!("Set min_session in the config to leave them out of reports.");
+        }
+    } else {
+        println!("Within the usual range.");
+    }
+
+    Ok(())
+}
+
+pub fn undo() -> Result<(), String> {
+    let data_dir = storage::get_data_dir();
+    let sessions_path = data_dir.join("time_log.csv");
+    let categories_path = data_dir.join("categories.csv");
+
+    let sessions_candidate = storage::find_undo_candidate(&sessions_path);
+    let categories_candidate = storage::find_undo_candidate(&categories_path);
+
+    let undo_categories = match (&sessions_candidate, &categories_candidate) {
+        (Some(sessions), Some(categories)) => categories.timestamp > sessions.timestamp,
+        (None, Some(_)) => true,
+        (Some(_), None) => false,
+        (None, None) => return Err("Nothing to undo".to_string()),
+    };
+
+    let categories_before = storage::load_categories_from_csv(&categories_path).categories;
+
+    if undo_categories {
+        let Some(candidate) = categories_candidate else {
+            return Err("Nothing to undo".to_string());
+        };
+        storage::apply_undo(&categories_path, &candidate)?;
+
+        let categories_after = storage::load_categories_from_csv(&categories_path).categories;
+        let changes = diff_records(&categories_before, &categories_after, |category| {
+            category.id
+        });
+
+        println!(
+            "Restored categories.csv from backup {}",
+            candidate.timestamp
+        );
+
+        if changes.is_empty() {
+            println!("  (no row changes)");
+        }
+        for category in &changes.added {
+            println!("  + {}", describe_category(category));
+        }
+        for category in &changes.removed {
+            println!("  - {}", describe_category(category));
+        }
+        for (before, after) in &changes.modified {
+            println!(
+                "  ~ {} -> {}",
+                describe_category(before),
+                describe_category(after)
+            );
+        }
+    } else {
+        let Some(candidate) = sessions_candidate else {
+            return Err("Nothing to undo".to_string());
+        };
+        let sessions_before =
+            storage::load_sessions_from_csv(&sessions_path, &categories_before).sessions;
+        storage::apply_undo(&sessions_path, &candidate)?;
+
+        let sessions_after =
+            storage::load_sessions_from_csv(&sessions_path, &categories_before).sessions;
+        let changes = diff_records(&sessions_before, &sessions_after, |session| session.id);
+
+        println!("Restored time_log.csv from backup {}", candidate.timestamp);
+
+        if changes.is_empty() {
+            println!("  (no row changes)");
+        }
+        for session in &changes.added {
+            println!("  + {}", describe_session(session, &categories_before));
+        }
+        for session in &changes.removed {
+            println!("  - {}", describe_session(session, &categories_before));
+        }
+        for (before, after) in &changes.modified {
+            println!(
+                "  ~ {} -> {}",
+                describe_session(before, &categories_before),
+                describe_session(after, &categories_before)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn describe_category(category: &Category) -> String {
+    format!(
+        "#{} {} (color {}, karma {:+})",
+        category.id.0,
+        category.name,
+        color_hex(category.color),
+        category.karma_effect
+    )
+}
+
+fn describe_session(session: &Session, categories: &[Category]) -> String {
+    let category_name = categories
+        .iter()
+        .find(|category| category.id == session.category_id)
+        .map(|category| category.name.as_str())
+        .unwrap_or("none");
+    format!(
+        "#{} {} {} {}-{} ({:02}:{:02}:{:02})",
+        session.id,
+        session.date,
+        category_name,
+        session.start_time,
+        session.end_time,
+        session.elapsed_seconds / 3600,
+        (session.elapsed_seconds % 3600) / 60,
+        session.elapsed_seconds % 60
+    )
+}
+
+fn export_grains(categories: &[Category], out_path: Option<PathBuf>) -> Result<(), String> {
+    let events = storage::load_grain_events(&storage::get_grain_log_path());
+    if events.is_empty() && !storage::grain_log_enabled() {
+        return Err("No grain history recorded. Set STRATA_GRAIN_LOG=1 to record it.".to_string());
+    }
+
+    let mut writer = csv::WriterBuilder::new().from_writer(vec![]);
+    writer
+        .write_record([
+            "timestamp",
+            "category_id",
+            "category_name",
+            "x",
+            "grid_width",
+        ])
+        .map_err(|e| e.to_string())?;
+    for event in &events {
+        let category_name = categories
+            .iter()
+            .find(|category| category.id.0 == event.category_id)
+            .map(|category| category.name.as_str())
+            .unwrap_or("none");
+        writer
+            .write_record([
+                event.timestamp.to_rfc3339(),
+                event.category_id.to_string(),
+                category_name.to_string(),
+                event.x.to_string(),
+                event.grid_width.to_string(),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.error().to_string())?;
+    let content = String::from_utf8_lossy(&bytes).to_string();
+
+    if let Some(path) = out_path {
+        storage::write_text_file(&path, &content)?;
+        println!("Exported {} grains to {}", events.len(), path.display());
+    } else {
+        print!("{}", content);
+    }
+    Ok(())
+}
+
+fn format_ics_datetime(local: NaiveDateTime) -> String {
+    format_ics_timestamp(local_to_utc(local, &Local))
+}
+
+/// `local` read off the wall clock of `tz`. A time the clocks pass twice
+/// takes its first occurrence; one a spring-forward gap skips is read as
+/// the hour after it, as the clock showed it.
+fn local_to_utc<Tz: TimeZone>(local: NaiveDateTime, tz: &Tz) -> DateTime<Utc> {
+    if let Some(dt) = tz.from_local_datetime(&local).earliest() {
+        return dt.with_timezone(&Utc);
+    }
+    let hour = ChronoDuration::hours(1);
+    tz.from_local_datetime(&(local - hour))
+        .earliest()
+        .map(|before| before.with_timezone(&Utc) + hour)
+        .unwrap_or_else(|| Utc.from_utc_datetime(&local))
+}
+
+/// A planned block as a VEVENT, with a VALARM `alarm_seconds` before it.
+fn plan_ics_event(
+    day: NaiveDate,
+    index: usize,
+    starts_at: NaiveTime,
+    item: &storage::PlanItem,
+    categories: &[Category],
+    alarm_seconds: usize,
+) -> String {
+    let start = planned_block_start(day, starts_at);
+    let end = start + ChronoDuration::seconds(item.planned_seconds.unwrap_or(0) as i64);
+    let label = plan_item_name(item, categories);
+
+    let mut event = String::new();
+    event.push_str("BEGIN:VEVENT\r\n");
+    event.push_str(&format!(
+        "UID:strata-plan-{}-{}\r\n",
+        day.format("%Y%m%d"),
+        index + 1
+    ));
+    event.push_str(&format!("DTSTAMP:{}\r\n", format_ics_timestamp(Utc::now())));
+    event.push_str(&format!("DTSTART:{}\r\n", format_ics_datetime(start)));
+    event.push_str(&format!("DTEND:{}\r\n", format_ics_datetime(end)));
+    event.push_str(&format!("SUMMARY:Planned: {}\r\n", label));
+    event.push_str(&format!(
+        "CATEGORIES:{}\r\n",
+        category_name(categories, CategoryId::new(item.category_id))
+    ));
+    if alarm_seconds > 0 {
+        event.push_str("BEGIN:VALARM\r\n");
+        event.push_str("ACTION:DISPLAY\r\n");
+        event.push_str(&format!("DESCRIPTION:Start tracking {}\r\n", label));
+        event.push_str(&format!("TRIGGER:{}\r\n", ics_offset_before(alarm_seconds)));
+        event.push_str("END:VALARM\r\n");
+    }
+    event.push_str("END:VEVENT\r\n");
+    event
+}
+
+/// An RFC 5545 duration before the event, in minutes when it is whole ones.
+fn ics_offset_before(seconds: usize) -> String {
+    if seconds.is_multiple_of(60) {
+        format!("-PT{}M", seconds / 60)
+    } else {
+        format!("-PT{}S", seconds)
+    }
 }
 
 fn format_ics_timestamp(dt: DateTime<Utc>) -> String {
@@ -353,18 +4246,23 @@ pub fn print_completions(shell: &str) -> Result<(), String> {
         "bash" => {
             clap_complete::generate(
                 Shell::Bash,
-                &mut Cli::command(),
+                &mut CliArgs::command(),
                 "strata",
                 &mut io::stdout(),
             );
         }
         "zsh" => {
-            clap_complete::generate(Shell::Zsh, &mut Cli::command(), "strata", &mut io::stdout());
+            clap_complete::generate(
+                Shell::Zsh,
+                &mut CliArgs::command(),
+                "strata",
+                &mut io::stdout(),
+            );
         }
         "fish" => {
             clap_complete::generate(
                 Shell::Fish,
-                &mut Cli::command(),
+                &mut CliArgs::command(),
                 "strata",
                 &mut io::stdout(),
             );
@@ -379,27 +4277,156 @@ pub fn print_completions(shell: &str) -> Result<(), String> {
     Ok(())
 }
 
+impl Cli {
+    fn rewrites_sessions(&self) -> bool {
+        matches!(
+            self,
+            Cli::Stop { .. }
+                | Cli::Project { .. }
+                | Cli::Import { .. }
+                | Cli::Sessions {
+                    command: SessionsCommand::Delete { .. } | SessionsCommand::Move { .. }
+                }
+                | Cli::Undo
+                | Cli::Doctor { headers: true, .. }
+        )
+    }
+
+    fn proxies_to_daemon(&self) -> bool {
+        matches!(self, Cli::Start { .. } | Cli::Stop { .. } | Cli::Status)
+    }
+}
+
 pub fn run_cli() {
-    let cli = Cli::parse();
-    match cli {
+    let matches = CliArgs::command().get_matches();
+    let args = CliArgs::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let json = args.json;
+    let data_dir = args.data_dir.or_else(storage::data_dir_from_env);
+    let requested_profile = args.profile.clone().or_else(storage::profile_from_env);
+    if let Err(e) =
+        storage::check_profile_with_data_dir(data_dir.as_deref(), requested_profile.as_deref())
+    {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+    if let Some(data_dir) = data_dir {
+        storage::set_data_dir_override(data_dir);
+    }
+    if let Some(profile) = args.profile {
+        if let Err(e) = storage::validate_profile_name(&profile) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        storage::set_profile_override(profile);
+    }
+    sand::set_check_invariants(args.check_invariants);
+    let config = storage::load_config(&storage::get_config_path());
+    set_week_start(storage::week_start_setting(&config));
+    set_period_label_style(storage::period_label_style());
+    if config.metrics
+        && let Some(event) = usage_event(&matches)
+    {
+        let _ = storage::record_usage([event.as_str()]);
+    }
+    if args.screensaver {
+        if let Err(e) = crate::app::run_screensaver_ui() {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let Some(command) = args.command else {
+        if let Err(e) = crate::app::run_ui() {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    };
+    let _lock =
+        if command.rewrites_sessions() && !(command.proxies_to_daemon() && daemon::is_running()) {
+            match storage::acquire_instance_lock(&storage::get_instance_lock_path()) {
+                Ok(lock) => Some(lock),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            None
+        };
+    match command {
         Cli::Start {
             project,
             desc,
             category,
+            at,
+            ago,
+        } => {
+            let result = resolve_start_time(at.as_deref(), ago)
+                .and_then(|start| start_session(project, desc, category, start, json));
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Stop { at, discard } => {
+            if let Err(e) = stop_session(at.as_deref(), discard, json) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Daemon => {
+            if let Err(e) = daemon::run() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Serve {
+            port,
+            bind,
+            allow_remote,
         } => {
-            if let Err(e) = start_session(project, desc, category) {
+            let token = std::env::var("STRATA_SERVE_TOKEN").ok();
+            if let Err(e) = crate::serve::serve(&bind, port, allow_remote, token) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Stopwatch => {
+            if let Err(e) = crate::app::run_stopwatch_ui() {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
         }
-        Cli::Stop => {
-            if let Err(e) = stop_session() {
+        Cli::Pomodoro {
+            work,
+            break_seconds,
+            category,
+            cycles,
+            tui,
+        } => {
+            let result = if tui {
+                pomodoro_tui(work, break_seconds, category, cycles)
+            } else {
+                pomodoro(work, break_seconds, category, cycles)
+            };
+            if let Err(e) = result {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
         }
-        Cli::Report { week, month, .. } => {
-            let period = if month {
+        Cli::Report {
+            week,
+            month,
+            period,
+            group_by,
+            html,
+            min_session,
+            ..
+        } => {
+            let period = if let Some(period) = period {
+                period.into()
+            } else if month {
                 ReportPeriod::Month
             } else if week {
                 ReportPeriod::Week
@@ -407,13 +4434,154 @@ pub fn run_cli() {
                 ReportPeriod::Today
             };
 
-            if let Err(e) = report(period) {
+            let result = match (html, group_by) {
+                (Some(path), _) => html_report(period, &path, min_session),
+                (None, Some(ReportGrouping::Day)) => daily_report(period, json, min_session),
+                (None, None) => report(period, json, min_session),
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Timeline { date } => {
+            if let Err(e) = timeline(date) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Karma { period } => {
+            if let Err(e) = karma(period.into(), json) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Heatmap { weeks } => {
+            if let Err(e) = heatmap(weeks) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Export {
+            format,
+            out,
+            period,
+            alarm_before,
+            min_session,
+        } => {
+            if let Err(e) = export_data(
+                format,
+                out,
+                period.map(ReportPeriod::from),
+                alarm_before,
+                min_session,
+            ) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Import {
+            format,
+            path,
+            server,
+            dry_run,
+        } => {
+            if let Err(e) = import_data(format, path, server, dry_run) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Project {
+            command: ProjectCommand::Rename { old, new },
+        } => {
+            if let Err(e) = rename_project_everywhere(&old, &new) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Profile { command } => {
+            if let Err(e) = profile(command) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Status => {
+            if let Err(e) = status(json) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Summary { format } => {
+            if let Err(e) = summary(format, json) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Sessions { command } => {
+            if let Err(e) = sessions(command, json) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Metrics { command } => {
+            if let Err(e) = metrics(command, json) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Plan { command } => {
+            if let Err(e) = plan(command, json) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
         }
-        Cli::Export { format, out } => {
-            if let Err(e) = export_data(format, out) {
+        Cli::Budget { command } => {
+            if let Err(e) = budget(command) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Rate { command } => {
+            if let Err(e) = rate(command) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Audit {
+            weeks,
+            markdown,
+            out,
+        } => {
+            if let Err(e) = audit(weeks, markdown, out, json) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Invoice {
+            from,
+            to,
+            rate_currency,
+            tax,
+            format,
+            out,
+        } => {
+            if let Err(e) = invoice(&from, to.as_deref(), &rate_currency, tax, format, out) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Doctor {
+            overlaps,
+            headers,
+            micro_sessions,
+        } => {
+            if let Err(e) = doctor(overlaps, headers, micro_sessions) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Undo => {
+            if let Err(e) = undo() {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
@@ -426,3 +4594,789 @@ pub fn run_cli() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, LocalResult};
+    use ratatui::style::Color;
+
+    use super::*;
+
+    fn category(id: u64, name: &str) -> Category {
+        Category {
+            id: CategoryId::new(id),
+            name: name.to_string(),
+            color: Color::Reset,
+            description: String::new(),
+            default_description: String::new(),
+            karma_effect: 1,
+        }
+    }
+
+    fn categories() -> Vec<Category> {
+        vec![category(0, "none"), category(1, "Work")]
+    }
+
+    fn active_session(description: &str) -> ActiveSession {
+        ActiveSession {
+            project: "strata".to_string(),
+            description: description.to_string(),
+            category_id: 1,
+            category_name: "Work".to_string(),
+            start_time: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_summary_line_fills_the_default_and_custom_templates() {
+        let session = active_session("spec");
+
+        assert_eq!(
+            summary_line(DEFAULT_SUMMARY_FORMAT, Some(&session), 1500, 7200, -900),
+            "▶ Work 00:25 | today 02:00 | karma -00:15"
+        );
+        assert_eq!(
+            summary_line(DEFAULT_SUMMARY_FORMAT, None, 0, 3600, 60),
+            "■ idle 00:00 | today 01:00 | karma +00:01"
+        );
+        assert_eq!(
+            summary_line(
+                "{project}/{description} {elapsed} {unknown} {",
+                Some(&session),
+                60,
+                0,
+                0
+            ),
+            "strata/spec 00:01 {unknown} {"
+        );
+    }
+
+    #[test]
+    fn test_summary_line_leaves_braces_in_descriptions_alone() {
+        let session = active_session("fix {today} in {elapsed}");
+
+        assert_eq!(
+            summary_line("{description} ({today})", Some(&session), 60, 3600, 0),
+            "fix {today} in {elapsed} (01:00)"
+        );
+        assert_eq!(
+            fill_template("{{icon}} {a{b}", |name| (name == "icon")
+                .then(|| "▶".to_string())),
+            "{▶} {a{b}"
+        );
+    }
+
+    #[test]
+    fn test_html_report_is_self_contained_and_escaped() {
+        let mut categories = categories();
+        categories[1].name = "R&D <core>".to_string();
+        categories[1].color = Color::Rgb(10, 20, 30);
+        categories.push(Category {
+            karma_effect: -1,
+            color: Color::Rgb(200, 0, 0),
+            ..category(2, "Doomscroll")
+        });
+        let (day, _) = period_date_bounds(ReportPeriod::Week);
+        let day = day.format("%Y-%m-%d").to_string();
+        let session =
+            |id, category_id, description: &str, start: &str, end: &str, seconds| Session {
+                id,
+                date: day.clone(),
+                category_id: CategoryId::new(category_id),
+                description: description.to_string(),
+                project: String::new(),
+                start_time: start.to_string(),
+                end_time: end.to_string(),
+                elapsed_seconds: seconds,
+                focus: None,
+            };
+        let sessions = vec![
+            session(
+                1,
+                1,
+                "fix \"quoted\" <b>bug</b>",
+                "00:00:00",
+                "00:01:00",
+                60,
+            ),
+            session(2, 2, "", "12:01:00", "12:01:30", 30),
+        ];
+
+        let html = render_html_report(ReportPeriod::Week, &sessions, &categories, Local::now());
+
+        assert!(!html.contains("http://") && !html.contains("https://"));
+        assert!(!html.contains("<script") && !html.contains("<link"));
+        assert!(html.contains("<style>"));
+
+        assert!(html.contains("R&amp;D &lt;core&gt;"));
+        assert!(html.contains("fix &quot;quoted&quot; &lt;b&gt;bug&lt;/b&gt;"));
+        assert!(!html.contains("<core>") && !html.contains("<b>bug"));
+
+        assert!(html.contains("<table class=\"bars\">"));
+        assert!(html.contains("width:100.0%;background:rgb(10,20,30)"));
+        assert!(html.contains("width:50.0%;background:rgb(200,0,0)"));
+        assert!(html.contains("<table class=\"timeline\">"));
+        assert!(html.contains("<div title=\"R&amp;D &lt;core&gt;: fix"));
+        assert!(html.contains("<table class=\"karma\">"));
+        assert!(html.contains(&format!(
+            "<tr class=\"total\"><td>Total</td><td class=\"num pos\">{}</td></tr>",
+            format_signed_hms(30)
+        )));
+    }
+
+    /// Field names of a `--json` object, which scripts depend on.
+    fn json_keys(value: &serde_json::Value) -> Vec<&str> {
+        let mut keys: Vec<&str> = value
+            .as_object()
+            .expect("a JSON object")
+            .keys()
+            .map(String::as_str)
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    #[test]
+    fn test_status_json_field_names() {
+        let output = StatusOutput {
+            active: Some(ActiveStatusOutput {
+                category: "Work".to_string(),
+                project: "strata".to_string(),
+                description: "spec".to_string(),
+                started_at: Utc::now(),
+                elapsed_seconds: 60,
+            }),
+            budgets: vec![BudgetStatusOutput {
+                category: "Work".to_string(),
+                spent_seconds: 60,
+                budget_seconds: 3600,
+                over_budget: false,
+            }],
+        };
+        let value = serde_json::to_value(&output).unwrap();
+
+        assert_eq!(json_keys(&value), ["active", "budgets"]);
+        assert_eq!(
+            json_keys(&value["active"]),
+            [
+                "category",
+                "description",
+                "elapsed_seconds",
+                "project",
+                "started_at"
+            ]
+        );
+        assert_eq!(
+            json_keys(&value["budgets"][0]),
+            ["budget_seconds", "category", "over_budget", "spent_seconds"]
+        );
+    }
+
+    #[test]
+    fn test_report_json_field_names() {
+        let day = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let output = ReportOutput {
+            period: "Week".to_string(),
+            start: day,
+            end: day,
+            entries: vec![ReportEntryOutput {
+                category: "Work".to_string(),
+                elapsed_seconds: 60,
+                over_budget: false,
+                average_focus: None,
+            }],
+            groups: vec![GroupTotalOutput {
+                group: "deep".to_string(),
+                elapsed_seconds: 60,
+            }],
+            total_seconds: 60,
+        };
+        let value = serde_json::to_value(&output).unwrap();
+
+        assert_eq!(
+            json_keys(&value),
+            [
+                "end",
+                "entries",
+                "groups",
+                "period",
+                "start",
+                "total_seconds"
+            ]
+        );
+        assert_eq!(value["start"], "2026-03-02");
+        assert_eq!(
+            json_keys(&value["entries"][0]),
+            [
+                "average_focus",
+                "category",
+                "elapsed_seconds",
+                "over_budget"
+            ]
+        );
+        assert_eq!(json_keys(&value["groups"][0]), ["elapsed_seconds", "group"]);
+    }
+
+    #[test]
+    fn test_stop_json_field_names() {
+        let value = serde_json::to_value(recorded_stop_output(active_session("spec"), 90)).unwrap();
+
+        assert_eq!(
+            json_keys(&value),
+            [
+                "category",
+                "description",
+                "discarded",
+                "elapsed_seconds",
+                "project"
+            ]
+        );
+        assert_eq!(value["category"], "Work");
+        assert_eq!(value["elapsed_seconds"], 90);
+        assert_eq!(value["discarded"], false);
+    }
+
+    #[test]
+    fn test_sessions_list_json_field_names() {
+        let row = SessionExport {
+            id: 7,
+            date: "2026-03-02".to_string(),
+            category_id: 1,
+            category_name: "Work".to_string(),
+            project: None,
+            description: "spec".to_string(),
+            start_time: "09:00:00".to_string(),
+            end_time: "10:00:00".to_string(),
+            elapsed_seconds: 3600,
+            focus: Some(4),
+        };
+        let value = serde_json::to_value(vec![row]).unwrap();
+
+        assert_eq!(
+            json_keys(&value[0]),
+            [
+                "category_id",
+                "category_name",
+                "date",
+                "description",
+                "elapsed_seconds",
+                "end_time",
+                "focus",
+                "id",
+                "project",
+                "start_time"
+            ]
+        );
+        assert!(value[0]["project"].is_null());
+    }
+
+    #[test]
+    fn test_timew_export_round_trips_through_the_importer() {
+        let session = Session {
+            id: 1,
+            date: "2026-03-02".to_string(),
+            category_id: CategoryId::new(1),
+            description: "spec".to_string(),
+            project: "strata".to_string(),
+            start_time: "09:00:00".to_string(),
+            end_time: "10:30:00".to_string(),
+            elapsed_seconds: 5400,
+            focus: None,
+        };
+        let idle = Session {
+            id: 2,
+            category_id: CategoryId::new(0),
+            description: String::new(),
+            project: String::new(),
+            start_time: "10:30:00".to_string(),
+            end_time: "10:45:00".to_string(),
+            elapsed_seconds: 900,
+            ..session.clone()
+        };
+        let row = |session: &Session, category_name: &str| SessionExport {
+            id: session.id,
+            date: session.date.clone(),
+            category_id: session.category_id.0,
+            category_name: category_name.to_string(),
+            project: (!session.project.is_empty()).then(|| session.project.clone()),
+            description: session.description.clone(),
+            start_time: session.start_time.clone(),
+            end_time: session.end_time.clone(),
+            elapsed_seconds: session.elapsed_seconds,
+            focus: None,
+        };
+        let mut running = active_session("review");
+        running.start_time = Utc.with_ymd_and_hms(2026, 3, 2, 14, 0, 0).unwrap();
+
+        let intervals = timew_intervals(
+            &[row(&session, "Work"), row(&idle, "none")],
+            &[session, idle],
+            Some(&running),
+        );
+        let json = serde_json::to_string(&intervals).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let utc_stamp = |raw: &serde_json::Value| {
+            NaiveDateTime::parse_from_str(raw.as_str().unwrap(), "%Y%m%dT%H%M%SZ").is_ok()
+        };
+        assert!(utc_stamp(&value[0]["start"]) && utc_stamp(&value[0]["end"]));
+        assert_eq!(value[0]["tags"], serde_json::json!(["Work", "strata"]));
+        assert!(value[1].get("tags").is_none());
+        assert_eq!(value[2]["start"], "20260302T140000Z");
+        assert!(value[2].get("end").is_none());
+
+        let imported = read_timew_intervals(&json).unwrap();
+        assert_eq!(imported.len(), 3);
+        let local = |h, m| {
+            Local
+                .from_local_datetime(
+                    &NaiveDate::from_ymd_opt(2026, 3, 2)
+                        .unwrap()
+                        .and_hms_opt(h, m, 0)
+                        .unwrap(),
+                )
+                .earliest()
+                .unwrap()
+        };
+        assert_eq!(imported[0].start, local(9, 0));
+        assert_eq!(imported[0].end, Some(local(10, 30)));
+        assert_eq!(imported[0].category_candidates, ["Work", "strata"]);
+        assert_eq!(imported[0].description, "spec");
+        assert_eq!(imported[1].end, Some(local(10, 45)));
+        assert!(imported[1].category_candidates.is_empty());
+        assert_eq!(imported[2].start, running.start_time.with_timezone(&Local));
+        assert!(imported[2].end.is_none());
+        assert_eq!(imported[2].category_candidates, ["Work", "strata"]);
+        assert_eq!(imported[2].description, "review");
+
+        // Imported back, the closed intervals are the same sessions.
+        let mut sessions = Vec::new();
+        let summary = merge_imported_intervals(imported, &mut sessions, &categories());
+        assert_eq!((summary.imported, summary.open), (2, 1));
+        assert_eq!(sessions[0].elapsed_seconds, 5400);
+        assert_eq!(sessions[0].project, "strata");
+        assert_eq!(sessions[1].elapsed_seconds, 900);
+    }
+
+    #[test]
+    fn test_karma_report_nets_a_negative_category() {
+        let mut categories = categories();
+        categories.push(Category {
+            karma_effect: -1,
+            ..category(2, "Doomscroll")
+        });
+        categories.push(Category {
+            karma_effect: 0,
+            ..category(3, "Chores")
+        });
+        let (day, _) = period_date_bounds(ReportPeriod::Week);
+        let session = |id, category_id, start: &str, end: &str, seconds| Session {
+            id,
+            date: day.format("%Y-%m-%d").to_string(),
+            category_id: CategoryId::new(category_id),
+            description: String::new(),
+            project: String::new(),
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            elapsed_seconds: seconds,
+            focus: None,
+        };
+        let sessions = vec![
+            session(1, 1, "09:00:00", "10:00:00", 3600),
+            session(2, 2, "10:00:00", "11:30:00", 5400),
+            session(3, 3, "11:30:00", "11:45:00", 900),
+        ];
+
+        let mut summary = build_period_karma_report(&sessions, &categories, ReportPeriod::Week);
+        sort_karma_entries_for_display(&mut summary.entries);
+        assert_eq!(summary.total_karma_seconds, -1800);
+
+        let report = format_karma_report(ReportPeriod::Week, &summary);
+        let lines: Vec<&str> = report.lines().collect();
+        assert!(lines[0].starts_with("Weekly Karma ("));
+        assert!(lines.contains(&"● Work               01:00:00 +01:00:00"));
+        assert!(lines.contains(&"◯ Doomscroll         01:30:00 -01:30:00"));
+        assert!(lines.contains(&"· Chores             00:15:00"));
+        assert_eq!(
+            lines.last(),
+            Some(&"NET KARMA            02:45:00 -00:30:00")
+        );
+    }
+
+    #[test]
+    fn test_pomodoro_records_each_work_interval_and_no_breaks() {
+        let start = Local.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let now = std::cell::Cell::new(start);
+        let mut waits = Vec::new();
+        let mut recorded = Vec::new();
+
+        run_pomodoro(
+            25 * 60,
+            5 * 60,
+            Some(3),
+            "Work",
+            |_| {},
+            |label, seconds| {
+                waits.push((label.to_string(), seconds));
+                now.set(now.get() + ChronoDuration::seconds(seconds as i64));
+                Ok(now.get())
+            },
+            |end| {
+                recorded.push(end);
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            waits,
+            [
+                ("work".to_string(), 1500),
+                ("break".to_string(), 300),
+                ("work".to_string(), 1500),
+                ("break".to_string(), 300),
+                ("work".to_string(), 1500),
+            ]
+        );
+        let minutes = |m| start + ChronoDuration::minutes(m);
+        assert_eq!(recorded, [minutes(25), minutes(55), minutes(85)]);
+    }
+
+    #[test]
+    fn test_pomodoro_interrupted_mid_interval_records_nothing_more() {
+        let start = Local.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let mut elapsed = 0;
+        let mut recorded = Vec::new();
+
+        let result = run_pomodoro(
+            25 * 60,
+            0,
+            None,
+            "Work",
+            |_| {},
+            |label, seconds| {
+                assert_eq!(label, "work", "no break is taken without break time");
+                if elapsed >= 50 * 60 {
+                    return Err("interrupted".to_string());
+                }
+                elapsed += seconds as i64;
+                Ok(start + ChronoDuration::seconds(elapsed))
+            },
+            |end| {
+                recorded.push(end);
+                Ok(())
+            },
+        );
+
+        assert_eq!(result, Err("interrupted".to_string()));
+        assert_eq!(
+            recorded,
+            [
+                start + ChronoDuration::minutes(25),
+                start + ChronoDuration::minutes(50)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_usage_metrics_count_a_start_and_stop() {
+        let path = std::env::temp_dir().join(format!(
+            "strata_metrics_{}.json",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let run = |args: &[&str]| {
+            let matches = CliArgs::command()
+                .try_get_matches_from(std::iter::once("strata").chain(args.iter().copied()))
+                .unwrap();
+            if let Some(event) = usage_event(&matches) {
+                storage::record_usage_at(&path, [event.as_str()]).unwrap();
+            }
+        };
+
+        run(&["start", "strata", "-c", "Work"]);
+        let since = storage::load_usage_metrics(&path).since;
+        assert!(since.is_some());
+        run(&["stop"]);
+        run(&["start", "strata", "--desc", "secret plans"]);
+        run(&["sessions", "delete", "3"]);
+        run(&["metrics", "show"]);
+
+        let metrics = storage::load_usage_metrics(&path);
+        assert_eq!(metrics.since, since);
+        assert_eq!(
+            metrics.counts,
+            std::collections::BTreeMap::from([
+                ("sessions delete".to_string(), 1),
+                ("start".to_string(), 2),
+                ("stop".to_string(), 1),
+            ])
+        );
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_read_timew_intervals_keeps_open_and_tagged_intervals() {
+        let raw = r#"[
+            {"start": "20260301T090000Z", "end": "20260301T100000Z", "tags": ["work", "strata"], "annotation": "spec"},
+            {"start": "20260301T110000Z"}
+        ]"#;
+        let intervals = read_timew_intervals(raw).unwrap();
+
+        assert_eq!(intervals.len(), 2);
+        assert_eq!(
+            intervals[0].start.with_timezone(&Utc),
+            Utc.with_ymd_and_hms(2026, 3, 1, 9, 0, 0).unwrap()
+        );
+        assert_eq!(intervals[0].category_candidates, ["work", "strata"]);
+        assert_eq!(intervals[0].description, "spec");
+        assert!(intervals[1].end.is_none());
+        assert!(intervals[1].category_candidates.is_empty());
+
+        assert!(read_timew_intervals(r#"[{"start": "yesterday"}]"#).is_err());
+    }
+
+    #[test]
+    fn test_merge_imported_intervals_files_tags_and_skips_open_ones() {
+        let raw = r#"[
+            {"start": "20260301T090000Z", "end": "20260301T100000Z", "tags": ["work", "strata"]},
+            {"start": "20260301T110000Z", "end": "20260301T113000Z", "tags": ["errands"]},
+            {"start": "20260301T120000Z"}
+        ]"#;
+        let mut sessions = Vec::new();
+        let summary = merge_imported_intervals(
+            read_timew_intervals(raw).unwrap(),
+            &mut sessions,
+            &categories(),
+        );
+
+        assert_eq!(
+            summary,
+            ImportSummary {
+                imported: 2,
+                open: 1,
+                uncategorized: 1,
+                ..ImportSummary::default()
+            }
+        );
+        assert_eq!(sessions[0].category_id, CategoryId::new(1));
+        assert_eq!(sessions[0].project, "strata");
+        assert_eq!(sessions[0].elapsed_seconds, 3600);
+        assert_eq!(sessions[1].category_id, CategoryId::new(0));
+        assert_eq!(sessions[1].project, "errands");
+    }
+
+    #[test]
+    fn test_read_clockify_intervals_maps_a_detailed_report_row() {
+        // Clockify's detailed report, columns in its own order with a BOM.
+        let raw = "\u{feff}Project,Client,Description,Task,User,Start Date,Start Time,End Date,End Time,Duration (h)\n\
+                   Work,Acme,Write spec,Planning,Ana,03/01/2026,09:15:00 AM,03/01/2026,10:45:30 AM,01:30:30\n";
+        let intervals = read_clockify_intervals(raw).unwrap();
+
+        assert_eq!(intervals.len(), 1);
+        let interval = &intervals[0];
+        let local = |h, m, s| {
+            Local
+                .from_local_datetime(
+                    &NaiveDate::from_ymd_opt(2026, 3, 1)
+                        .unwrap()
+                        .and_hms_opt(h, m, s)
+                        .unwrap(),
+                )
+                .earliest()
+                .unwrap()
+        };
+        assert_eq!(interval.start, local(9, 15, 0));
+        assert_eq!(interval.end, Some(local(10, 45, 30)));
+        assert_eq!(interval.category_candidates, ["Work"]);
+        assert_eq!(interval.project.as_deref(), Some("Work"));
+        assert_eq!(interval.description, "Planning: Write spec");
+
+        let mut sessions = Vec::new();
+        merge_imported_intervals(intervals, &mut sessions, &categories());
+        assert_eq!(sessions[0].category_id, CategoryId::new(1));
+        assert_eq!(sessions[0].start_time, "09:15:00");
+        assert_eq!(sessions[0].end_time, "10:45:30");
+        assert_eq!(sessions[0].elapsed_seconds, 90 * 60 + 30);
+
+        let missing = "Project,Start Date,Start Time\nWork,2026-03-01,09:00\n";
+        assert!(
+            read_clockify_intervals(missing)
+                .unwrap_err()
+                .contains("End Date")
+        );
+    }
+
+    /// Central European time in 2026: +01:00, and +02:00 from 29 March to
+    /// 25 October, switching at 01:00 UTC.
+    #[derive(Clone, Copy, Debug)]
+    struct Cet2026;
+
+    impl Cet2026 {
+        fn winter() -> FixedOffset {
+            FixedOffset::east_opt(3600).unwrap()
+        }
+
+        fn summer() -> FixedOffset {
+            FixedOffset::east_opt(7200).unwrap()
+        }
+    }
+
+    impl TimeZone for Cet2026 {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Cet2026
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(12, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let fits: Vec<FixedOffset> = [Self::summer(), Self::winter()]
+                .into_iter()
+                .filter(|offset| {
+                    let utc = *local - ChronoDuration::seconds(offset.local_minus_utc() as i64);
+                    self.offset_from_utc_datetime(&utc) == *offset
+                })
+                .collect();
+            match fits.as_slice() {
+                [] => LocalResult::None,
+                [offset] => LocalResult::Single(*offset),
+                [first, second, ..] => LocalResult::Ambiguous(*first, *second),
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(12, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let at = |month, day| {
+                NaiveDate::from_ymd_opt(2026, month, day)
+                    .unwrap()
+                    .and_hms_opt(1, 0, 0)
+                    .unwrap()
+            };
+            if (at(3, 29)..at(10, 25)).contains(utc) {
+                Self::summer()
+            } else {
+                Self::winter()
+            }
+        }
+    }
+
+    #[test]
+    fn test_local_to_utc_handles_dst_edges() {
+        let local = |month, day, h, m| {
+            NaiveDate::from_ymd_opt(2026, month, day)
+                .unwrap()
+                .and_hms_opt(h, m, 0)
+                .unwrap()
+        };
+        let ics = |month, day, h, m| {
+            format_ics_timestamp(local_to_utc(local(month, day, h, m), &Cet2026))
+        };
+
+        assert_eq!(ics(1, 15, 9, 0), "20260115T080000Z");
+        assert_eq!(ics(7, 15, 9, 0), "20260715T070000Z");
+        // 02:30 doesn't exist on 29 March; the clock read 03:30 then.
+        assert_eq!(ics(3, 29, 2, 30), "20260329T013000Z");
+        assert_eq!(ics(3, 29, 3, 30), "20260329T013000Z");
+        // 02:30 happens twice on 25 October; the first is still summer time.
+        assert_eq!(ics(10, 25, 2, 30), "20261025T003000Z");
+        assert_eq!(ics(10, 25, 3, 30), "20261025T023000Z");
+    }
+
+    #[test]
+    fn test_plan_ics_event_adds_an_alarm_before_the_block() {
+        let day = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let starts_at = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        let item = storage::PlanItem {
+            category_id: 1,
+            description: "spec".to_string(),
+            planned_seconds: Some(25 * 60),
+            starts_at: Some(starts_at),
+            actual_seconds: None,
+        };
+
+        let event = plan_ics_event(day, 0, starts_at, &item, &categories(), 600);
+        let start = format_ics_datetime(day.and_time(starts_at));
+        let end = format_ics_datetime(day.and_hms_opt(9, 25, 0).unwrap());
+        assert!(event.starts_with("BEGIN:VEVENT\r\nUID:strata-plan-20260302-1\r\n"));
+        assert!(event.contains(&format!("DTSTART:{}\r\nDTEND:{}\r\n", start, end)));
+        assert!(event.contains("SUMMARY:Planned: Work · spec\r\n"));
+        assert!(event.ends_with(
+            "BEGIN:VALARM\r\nACTION:DISPLAY\r\nDESCRIPTION:Start tracking Work · spec\r\nTRIGGER:-PT10M\r\nEND:VALARM\r\nEND:VEVENT\r\n"
+        ));
+
+        let silent = plan_ics_event(day, 0, starts_at, &item, &categories(), 0);
+        assert!(!silent.contains("VALARM"));
+    }
+
+    #[test]
+    fn test_plan_ics_alarm_follows_the_configured_minutes() {
+        let day = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let starts_at = NaiveTime::from_hms_opt(14, 30, 0).unwrap();
+        let item = storage::PlanItem {
+            category_id: 1,
+            description: String::new(),
+            planned_seconds: Some(50 * 60),
+            starts_at: Some(starts_at),
+            actual_seconds: None,
+        };
+        let alarm = |seconds| {
+            let event = plan_ics_event(day, 2, starts_at, &item, &categories(), seconds);
+            event
+                .find("BEGIN:VALARM\r\n")
+                .map(|at| event[at..].to_string())
+        };
+
+        let minutes = storage::ConfigState::default().plan_alarm_minutes as usize;
+        let block = alarm(minutes * 60).expect("an alarm by default");
+        assert_eq!(
+            block,
+            format!(
+                "BEGIN:VALARM\r\nACTION:DISPLAY\r\nDESCRIPTION:Start tracking Work\r\nTRIGGER:-PT{}M\r\nEND:VALARM\r\nEND:VEVENT\r\n",
+                minutes
+            )
+        );
+        assert!(alarm(5 * 60).unwrap().contains("TRIGGER:-PT5M\r\n"));
+        assert!(alarm(90).unwrap().contains("TRIGGER:-PT90S\r\n"));
+        assert_eq!(alarm(0), None);
+    }
+
+    #[test]
+    fn test_merge_imported_intervals_skips_duplicates_and_empty_intervals() {
+        let raw = r#"[
+            {"start": "20260301T090000Z", "end": "20260301T100000Z", "tags": ["work"]},
+            {"start": "20260301T110000Z", "end": "20260301T110000Z"},
+            {"start": "20260301T130000Z", "end": "20260301T120000Z"}
+        ]"#;
+        let mut sessions = Vec::new();
+        merge_imported_intervals(
+            read_timew_intervals(raw).unwrap(),
+            &mut sessions,
+            &categories(),
+        );
+        assert_eq!(sessions.len(), 1);
+
+        // Importing the same export again adds nothing.
+        let summary = merge_imported_intervals(
+            read_timew_intervals(raw).unwrap(),
+            &mut sessions,
+            &categories(),
+        );
+        assert_eq!(
+            summary,
+            ImportSummary {
+                duplicates: 1,
+                empty: 2,
+                ..ImportSummary::default()
+            }
+        );
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, 1);
+    }
+}
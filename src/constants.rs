@@ -19,6 +19,7 @@ pub const TIME_SETTINGS: TimeSettings = TimeSettings {
     tick_ms: 1000,
     physics_ms: 32,
     target_fps: 24,
+    resize_debounce_ms: 120,
 };
 
 pub const SAND_ENGINE: SandEngineSettings = SandEngineSettings {
@@ -27,6 +28,42 @@ pub const SAND_ENGINE: SandEngineSettings = SandEngineSettings {
     dot_width: 2,
 };
 
+pub const STRATA_VIEW: StrataViewSettings = StrataViewSettings {
+    full_scale_seconds: 8 * 3600,
+    refresh_secs: 60,
+};
+
+pub const GOAL_LINE: GoalLineSettings = GoalLineSettings {
+    dash: '┄',
+    sparkles: &['✦', '✧', '·', '*'],
+    sparkle_frames: 48,
+};
+
+pub const DAEMON: DaemonSettings = DaemonSettings {
+    persist_secs: 5,
+    poll_ms: 200,
+};
+
+pub const AUDIT: AuditSettings = AuditSettings {
+    tolerance_percent: 20.0,
+    round_seconds: 5 * 60,
+};
+
+pub const FOCUS_CONTRACT: FocusContractSettings = FocusContractSettings {
+    default_length: "50m",
+    break_phrase: "break contract",
+};
+
+pub const SAVE_RETRY: SaveRetrySettings = SaveRetrySettings {
+    initial_ms: 1000,
+    max_ms: 60_000,
+};
+
+pub const UNDO: UndoSettings = UndoSettings {
+    max_actions: 20,
+    message_ms: 3000,
+};
+
 pub const BLINK_SETTINGS: BlinkSettings = BlinkSettings {
     interval_min_frames: 150,
     interval_max_frames: 300,
@@ -52,6 +89,7 @@ pub struct TimeSettings {
     pub tick_ms: u64,
     pub physics_ms: u64,
     pub target_fps: u64,
+    pub resize_debounce_ms: u64,
 }
 
 pub struct SandEngineSettings {
@@ -60,6 +98,42 @@ pub struct SandEngineSettings {
     pub dot_width: usize,
 }
 
+pub struct StrataViewSettings {
+    pub full_scale_seconds: usize,
+    pub refresh_secs: i64,
+}
+
+pub struct GoalLineSettings {
+    pub dash: char,
+    pub sparkles: &'static [char],
+    pub sparkle_frames: u16,
+}
+
+pub struct DaemonSettings {
+    pub persist_secs: u64,
+    pub poll_ms: u64,
+}
+
+pub struct AuditSettings {
+    pub tolerance_percent: f64,
+    pub round_seconds: usize,
+}
+
+pub struct FocusContractSettings {
+    pub default_length: &'static str,
+    pub break_phrase: &'static str,
+}
+
+pub struct SaveRetrySettings {
+    pub initial_ms: u64,
+    pub max_ms: u64,
+}
+
+pub struct UndoSettings {
+    pub max_actions: usize,
+    pub message_ms: u64,
+}
+
 pub struct BlinkSettings {
     pub interval_min_frames: i32,
     pub interval_max_frames: i32,
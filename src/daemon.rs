@@ -0,0 +1,436 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{StartOutput, StatusOutput, StopOutput};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum DaemonRequest {
+    Start {
+        project: String,
+        description: Option<String>,
+        category: Option<String>,
+        start_time: DateTime<Utc>,
+    },
+    Stop {
+        end_time: Option<DateTime<Utc>>,
+        discard: bool,
+    },
+    Status,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "response", content = "data", rename_all = "snake_case")]
+pub enum DaemonResponse {
+    Started(StartOutput),
+    Stopped(StopOutput),
+    Status(StatusOutput),
+    Error(String),
+}
+
+impl DaemonResponse {
+    pub fn unexpected() -> String {
+        "Unexpected response from strata daemon".to_string()
+    }
+}
+
+pub fn is_running() -> bool {
+    imp::connect().is_some()
+}
+
+/// Forwards a request to the running daemon, or returns `None` when there is none.
+pub fn send(request: &DaemonRequest) -> Option<Result<DaemonResponse, String>> {
+    let stream = imp::connect()?;
+    Some(
+        imp::exchange(stream, request).and_then(|response| match response {
+            DaemonResponse::Error(message) => Err(message),
+            response => Ok(response),
+        }),
+    )
+}
+
+pub fn run() -> Result<(), String> {
+    imp::run()
+}
+
+/// The daemon socket served from another program's loop, so `strata start`,
+/// `stop` and `status` reach the TUI while it holds the instance lock.
+pub struct Listener(imp::Listener);
+
+/// Binds the daemon socket; only call it while holding the instance lock.
+pub fn listen() -> Result<Listener, String> {
+    imp::listen(&crate::storage::get_daemon_socket_path()).map(Listener)
+}
+
+impl Listener {
+    /// Answers every request already waiting, without blocking.
+    pub fn serve_pending(&self, mut handle: impl FnMut(DaemonRequest) -> DaemonResponse) {
+        imp::serve_pending(&self.0, &mut handle);
+    }
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::{
+        io::{BufRead, BufReader, ErrorKind, Write},
+        os::unix::net::{UnixListener, UnixStream},
+        path::{Path, PathBuf},
+        time::{Duration, Instant},
+    };
+
+    use super::{DaemonRequest, DaemonResponse};
+    use crate::{
+        cli::{self, ActiveSession},
+        constants::DAEMON,
+        hooks::HookEvent,
+        storage,
+    };
+
+    const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+    struct SocketFile(PathBuf);
+
+    impl Drop for SocketFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    struct DaemonState {
+        active: Option<ActiveSession>,
+        session_path: PathBuf,
+        dirty: bool,
+        last_persist: Instant,
+    }
+
+    pub(super) struct Listener {
+        listener: UnixListener,
+        _socket: SocketFile,
+    }
+
+    pub(super) fn connect() -> Option<UnixStream> {
+        UnixStream::connect(storage::get_daemon_socket_path()).ok()
+    }
+
+    pub(super) fn listen(socket_path: &Path) -> Result<Listener, String> {
+        storage::delete_file_if_exists(socket_path)?;
+        let listener = UnixListener::bind(socket_path)
+            .map_err(|e| format!("Failed to bind {}: {}", socket_path.display(), e))?;
+        let socket = SocketFile(socket_path.to_path_buf());
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        Ok(Listener {
+            listener,
+            _socket: socket,
+        })
+    }
+
+    pub(super) fn serve_pending(
+        listener: &Listener,
+        handle: &mut dyn FnMut(DaemonRequest) -> DaemonResponse,
+    ) {
+        // A client that hangs up early only fails its own request.
+        while let Ok((stream, _)) = listener.listener.accept() {
+            let _ = serve_client(stream, handle);
+        }
+    }
+
+    pub(super) fn exchange(
+        mut stream: UnixStream,
+        request: &DaemonRequest,
+    ) -> Result<DaemonResponse, String> {
+        stream
+            .set_read_timeout(Some(IO_TIMEOUT))
+            .map_err(|e| e.to_string())?;
+        let mut line = serde_json::to_string(request).map_err(|e| e.to_string())?;
+        line.push('\n');
+        stream
+            .write_all(line.as_bytes())
+            .map_err(|e| format!("Failed to reach strata daemon: {}", e))?;
+
+        let mut reply = String::new();
+        BufReader::new(&stream)
+            .read_line(&mut reply)
+            .map_err(|e| format!("Failed to read from strata daemon: {}", e))?;
+        serde_json::from_str(&reply).map_err(|e| format!("Invalid daemon response: {}", e))
+    }
+
+    pub(super) fn run() -> Result<(), String> {
+        let _lock = storage::acquire_instance_lock(&storage::get_instance_lock_path())?;
+
+        let socket_path = storage::get_daemon_socket_path();
+        let listener = listen(&socket_path)?;
+
+        let session_path = storage::get_active_session_path();
+        let mut state = DaemonState {
+            active: if storage::file_exists(&session_path) {
+                Some(storage::read_json(&session_path)?)
+            } else {
+                None
+            },
+            session_path,
+            dirty: false,
+            last_persist: Instant::now(),
+        };
+        println!("strata daemon listening on {}", socket_path.display());
+
+        loop {
+            match listener.listener.accept() {
+                Ok((stream, _)) => {
+                    if let Err(e) = serve_client(stream, &mut |request| handle(request, &mut state))
+                    {
+                        eprintln!("Daemon request failed: {}", e);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(DAEMON.poll_ms));
+                }
+                Err(e) => eprintln!("Daemon connection failed: {}", e),
+            }
+
+            persist_if_due(&mut state);
+        }
+    }
+
+    /// Saves unsaved changes once `persist_secs` have passed. A failed save
+    /// is logged and retried on the next round instead of ending the daemon,
+    /// which would lose the session it holds.
+    fn persist_if_due(state: &mut DaemonState) {
+        if !state.dirty || state.last_persist.elapsed().as_secs() < DAEMON.persist_secs {
+            return;
+        }
+        if let Err(e) = persist(state) {
+            eprintln!("Failed to persist daemon state, retrying: {}", e);
+            state.last_persist = Instant::now();
+        }
+    }
+
+    fn serve_client(
+        stream: UnixStream,
+        handle: &mut dyn FnMut(DaemonRequest) -> DaemonResponse,
+    ) -> Result<(), String> {
+        stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+        stream
+            .set_read_timeout(Some(IO_TIMEOUT))
+            .map_err(|e| e.to_string())?;
+
+        let mut line = String::new();
+        BufReader::new(&stream)
+            .read_line(&mut line)
+            .map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        let response = match serde_json::from_str::<DaemonRequest>(&line) {
+            Ok(request) => handle(request),
+            Err(e) => DaemonResponse::Error(format!("Invalid daemon request: {}", e)),
+        };
+
+        let mut reply = serde_json::to_string(&response).map_err(|e| e.to_string())?;
+        reply.push('\n');
+        (&stream)
+            .write_all(reply.as_bytes())
+            .map_err(|e| e.to_string())
+    }
+
+    fn handle(request: DaemonRequest, state: &mut DaemonState) -> DaemonResponse {
+        match request {
+            DaemonRequest::Start {
+                project,
+                description,
+                category,
+                start_time,
+            } => {
+                match cli::new_active_session(project, description, category.as_deref(), start_time)
+                {
+                    Ok(session) => {
+                        cli::run_session_hook(HookEvent::Start, &session, None);
+                        let output = cli::start_output(&session);
+                        state.active = Some(session);
+                        state.dirty = true;
+                        DaemonResponse::Started(output)
+                    }
+                    Err(e) => DaemonResponse::Error(e),
+                }
+            }
+            DaemonRequest::Stop { end_time, discard } => {
+                let Some(session) = state.active.take() else {
+                    return DaemonResponse::Error("No active session to stop".to_string());
+                };
+                let finished = if discard {
+                    Ok(cli::discard_session(session.clone()))
+                } else {
+                    cli::finish_session(session.clone(), end_time)
+                };
+                match finished {
+                    Ok(output) => {
+                        // The session is already logged or discarded, so drop the
+                        // persisted copy now rather than on the next tick.
+                        state.dirty = true;
+                        if let Err(e) = persist(state) {
+                            eprintln!("Failed to persist daemon state: {}", e);
+                        }
+                        DaemonResponse::Stopped(output)
+                    }
+                    Err(e) => {
+                        state.active = Some(session);
+                        DaemonResponse::Error(e)
+                    }
+                }
+            }
+            DaemonRequest::Status => {
+                DaemonResponse::Status(cli::status_output_for(state.active.as_ref()))
+            }
+        }
+    }
+
+    fn persist(state: &mut DaemonState) -> Result<(), String> {
+        match &state.active {
+            Some(session) => storage::write_json_atomic(&state.session_path, session)?,
+            None => storage::delete_file_if_exists(&state.session_path)?,
+        }
+        state.dirty = false;
+        state.last_persist = Instant::now();
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use chrono::Utc;
+
+        use super::*;
+
+        #[test]
+        fn test_a_failed_persist_keeps_the_session_and_retries() {
+            let dir =
+                std::env::temp_dir().join(format!("strata_daemon_persist_{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            // A file where the state directory should be makes every write fail.
+            let blocker = dir.join("blocker");
+            std::fs::write(&blocker, "").unwrap();
+            let overdue = Instant::now()
+                .checked_sub(Duration::from_secs(DAEMON.persist_secs))
+                .unwrap();
+            let mut state = DaemonState {
+                active: Some(ActiveSession {
+                    project: "strata".to_string(),
+                    description: String::new(),
+                    category_id: 1,
+                    category_name: "Work".to_string(),
+                    start_time: Utc::now(),
+                }),
+                session_path: blocker.join("active_session.json"),
+                dirty: true,
+                last_persist: overdue,
+            };
+
+            persist_if_due(&mut state);
+            assert!(state.dirty);
+            assert!(state.active.is_some());
+
+            state.session_path = dir.join("active_session.json");
+            persist_if_due(&mut state);
+            assert!(state.dirty, "the retry waits for the next round");
+
+            state.last_persist = overdue;
+            persist_if_due(&mut state);
+            assert!(!state.dirty);
+            let saved: ActiveSession = storage::read_json(&state.session_path).unwrap();
+            assert_eq!(saved.project, "strata");
+
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use super::{DaemonRequest, DaemonResponse};
+
+    pub(super) enum Never {}
+
+    pub(super) type Listener = Never;
+
+    pub(super) fn connect() -> Option<Never> {
+        None
+    }
+
+    pub(super) fn exchange(stream: Never, _: &DaemonRequest) -> Result<DaemonResponse, String> {
+        match stream {}
+    }
+
+    pub(super) fn listen(_: &std::path::Path) -> Result<Never, String> {
+        Err("Daemon mode needs Unix domain sockets, which this platform lacks".to_string())
+    }
+
+    pub(super) fn serve_pending(
+        listener: &Never,
+        _: &mut dyn FnMut(DaemonRequest) -> DaemonResponse,
+    ) {
+        match *listener {}
+    }
+
+    pub(super) fn run() -> Result<(), String> {
+        Err("Daemon mode needs Unix domain sockets, which this platform lacks".to_string())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{os::unix::net::UnixStream, path::PathBuf, thread, time::Duration};
+
+    use super::*;
+    use crate::storage;
+
+    fn unique_path(prefix: &str, extension: &str) -> PathBuf {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        PathBuf::from(format!("/tmp/{}_{}.{}", prefix, now, extension))
+    }
+
+    #[test]
+    fn test_stop_reaches_the_lock_holder_over_the_socket() {
+        let lock_path = unique_path("strata_daemon_lock", "lock");
+        let socket_path = unique_path("strata_daemon", "sock");
+        let _lock = storage::acquire_instance_lock(&lock_path).unwrap();
+        let listener = Listener(imp::listen(&socket_path).unwrap());
+
+        // The lock is taken, so a stop has to go through the holder.
+        assert!(storage::acquire_instance_lock(&lock_path).is_err());
+        let client_path = socket_path.clone();
+        let client = thread::spawn(move || {
+            let stream = UnixStream::connect(&client_path).unwrap();
+            imp::exchange(
+                stream,
+                &DaemonRequest::Stop {
+                    end_time: None,
+                    discard: true,
+                },
+            )
+        });
+
+        let mut received = Vec::new();
+        while !client.is_finished() {
+            listener.serve_pending(|request| {
+                received.push(request);
+                DaemonResponse::Error("No active session to stop".to_string())
+            });
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        let response = client.join().unwrap().unwrap();
+        assert!(matches!(
+            received.as_slice(),
+            [DaemonRequest::Stop {
+                end_time: None,
+                discard: true
+            }]
+        ));
+        assert!(
+            matches!(response, DaemonResponse::Error(message) if message.contains("No active"))
+        );
+        drop(listener);
+        assert!(!socket_path.exists());
+    }
+}
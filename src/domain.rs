@@ -1,9 +1,15 @@
 use std::{
-    collections::{HashMap, HashSet},
-    time::Instant,
+    borrow::Cow,
+    collections::{BTreeSet, HashMap, HashSet},
+    hash::Hash,
+    sync::OnceLock,
+    time::{Duration, Instant},
 };
 
-use chrono::{DateTime, Duration as ChronoDuration, FixedOffset, Local, NaiveDate, NaiveTime, Utc};
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, FixedOffset, Local, NaiveDate, NaiveDateTime,
+    NaiveTime, Utc,
+};
 use ratatui::style::Color;
 
 use crate::constants::COLORS;
@@ -17,24 +23,45 @@ impl CategoryId {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Category {
     pub id: CategoryId,
     pub name: String,
     pub color: Color,
     pub description: String,
+    pub default_description: String,
     pub karma_effect: i8,
 }
 
-#[derive(Clone, Debug)]
+/// `#rrggbb` for RGB colors; other colors keep their name, which
+/// `parse_color` reads back.
+pub fn color_hex(color: Color) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        other => other.to_string(),
+    }
+}
+
+/// A palette index from older files, or anything `color_hex` wrote.
+pub fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim();
+    match value.parse::<usize>() {
+        Ok(index) => Some(COLORS[index % COLORS.len()]),
+        Err(_) => value.parse::<Color>().ok(),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Session {
     pub id: usize,
     pub date: String,
     pub category_id: CategoryId,
     pub description: String,
+    pub project: String,
     pub start_time: String,
     pub end_time: String,
     pub elapsed_seconds: usize,
+    pub focus: Option<u8>,
 }
 
 #[derive(Debug, Clone)]
@@ -50,6 +77,145 @@ pub struct ReportSummary {
     pub total_seconds: usize,
 }
 
+/// Shortest session reports count, per category with a fallback; shorter
+/// ones are treated as accidental starts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MinSessionLength {
+    pub default_seconds: usize,
+    pub by_category: HashMap<CategoryId, usize>,
+}
+
+impl MinSessionLength {
+    pub fn threshold(&self, category_id: CategoryId) -> usize {
+        self.by_category
+            .get(&category_id)
+            .copied()
+            .unwrap_or(self.default_seconds)
+    }
+
+    pub fn is_micro(&self, session: &Session) -> bool {
+        session.elapsed_seconds < self.threshold(session.category_id)
+    }
+}
+
+pub fn drop_micro_sessions(sessions: &[Session], min_length: &MinSessionLength) -> Vec<Session> {
+    sessions
+        .iter()
+        .filter(|session| !min_length.is_micro(session))
+        .cloned()
+        .collect()
+}
+
+/// Filters stored sessions and pages through them newest first, so every
+/// view that lists sessions agrees on what matches and in which order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    category: Option<CategoryId>,
+    tag: Option<String>,
+    offset: usize,
+    limit: Option<usize>,
+}
+
+/// One page of a `SessionQuery`, with how many sessions matched in total.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionPage<'a> {
+    pub sessions: Vec<&'a Session>,
+    pub total: usize,
+}
+
+impl SessionQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Operational days from `from` through `to`, both included.
+    pub fn between(mut self, from: NaiveDate, to: NaiveDate) -> Self {
+        self.from = Some(from);
+        self.to = Some(to);
+        self
+    }
+
+    pub fn since(mut self, from: NaiveDate) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    pub fn until(mut self, to: NaiveDate) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    pub fn category(mut self, category_id: CategoryId) -> Self {
+        self.category = Some(category_id);
+        self
+    }
+
+    /// Category tags fill in the description, so a tag matches sessions
+    /// whose description is that tag, ignoring case.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn matches(&self, session: &Session) -> bool {
+        if self.from.is_some() || self.to.is_some() {
+            let Ok(date) = NaiveDate::parse_from_str(&session.date, "%Y-%m-%d") else {
+                return false;
+            };
+            if self.from.is_some_and(|from| date < from) || self.to.is_some_and(|to| date > to) {
+                return false;
+            }
+        }
+        self.category
+            .is_none_or(|category_id| session.category_id == category_id)
+            && self
+                .tag
+                .as_deref()
+                .is_none_or(|tag| session.description.trim().eq_ignore_ascii_case(tag.trim()))
+    }
+
+    pub fn run<'a>(&self, sessions: &'a [Session]) -> SessionPage<'a> {
+        let mut matching: Vec<&Session> = sessions
+            .iter()
+            .filter(|session| self.matches(session))
+            .collect();
+        matching.sort_by(|a, b| {
+            (b.date.as_str(), b.start_time.as_str(), b.id).cmp(&(
+                a.date.as_str(),
+                a.start_time.as_str(),
+                a.id,
+            ))
+        });
+        let total = matching.len();
+        let sessions = matching
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect();
+        SessionPage { sessions, total }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WeekStats {
+    pub summary: ReportSummary,
+    pub daily_average_seconds: usize,
+    pub best_day: Option<(NaiveDate, usize)>,
+    pub streak_days: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct KarmaReportEntry {
     pub category_id: CategoryId,
@@ -70,6 +236,8 @@ pub struct KarmaReportSummary {
 
 #[derive(Debug, Clone)]
 pub struct CategoryLogEntry {
+    pub session_id: Option<usize>,
+    pub overlaps: bool,
     pub date: String,
     pub start_time: String,
     pub end_time: String,
@@ -87,13 +255,108 @@ pub struct LiveSessionPreview {
     pub now_local: DateTime<Local>,
 }
 
+#[derive(Debug, Clone)]
+pub struct TimelineEntry {
+    pub category_name: String,
+    pub color: Color,
+    pub description: String,
+    pub start_offset_seconds: usize,
+    pub end_offset_seconds: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionOverlap {
+    pub first_id: usize,
+    pub second_id: usize,
+    pub overlap_seconds: usize,
+}
+
+/// Overlapping session pairs the user chose to keep both of, as
+/// `(first_id, second_id)`.
+pub type KeptOverlaps = BTreeSet<(usize, usize)>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlapResolution {
+    Trim,
+    Merge,
+    KeepBoth,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordChanges<T> {
+    pub added: Vec<T>,
+    pub removed: Vec<T>,
+    pub modified: Vec<(T, T)>,
+}
+
+impl<T> RecordChanges<T> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvoiceLine {
+    pub category_name: String,
+    pub project: String,
+    pub elapsed_seconds: usize,
+    pub hourly_rate: f64,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invoice {
+    pub lines: Vec<InvoiceLine>,
+    pub unbilled_seconds: usize,
+    pub subtotal: f64,
+    pub tax: f64,
+    pub total: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityEvent {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub app: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivitySuggestion {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub category: String,
+    pub app: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoalAuditRow {
+    pub category_id: CategoryId,
+    pub category_name: String,
+    pub weekly_goal_seconds: usize,
+    /// Tracked seconds per trailing 7-day window, oldest first.
+    pub weekly_actual_seconds: Vec<usize>,
+    pub average_seconds: usize,
+    pub delta_percent: f64,
+    pub suggested_daily_seconds: Option<usize>,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ReportPeriod {
     Today,
+    /// The 24 hours up to now, regardless of day boundaries.
+    Last24Hours,
     Week,
     Month,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PeriodLabelStyle {
+    #[default]
+    Range,
+    Iso,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[allow(dead_code)]
 pub enum DayBoundaryMode {
@@ -124,6 +387,45 @@ pub fn day_boundary_config() -> DayBoundaryConfig {
     DayBoundaryConfig::default()
 }
 
+/// How `ReportPeriod::Week` is bounded: the last seven days, or the calendar
+/// week so far starting on the given weekday.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WeekStart {
+    #[default]
+    Rolling,
+    Monday,
+    Sunday,
+}
+
+static WEEK_START: OnceLock<WeekStart> = OnceLock::new();
+
+pub fn set_week_start(week_start: WeekStart) {
+    let _ = WEEK_START.set(week_start);
+}
+
+pub fn week_start() -> WeekStart {
+    WEEK_START.get().copied().unwrap_or_default()
+}
+
+static PERIOD_LABEL_STYLE: OnceLock<PeriodLabelStyle> = OnceLock::new();
+
+pub fn set_period_label_style(style: PeriodLabelStyle) {
+    let _ = PERIOD_LABEL_STYLE.set(style);
+}
+
+pub fn period_label_style() -> PeriodLabelStyle {
+    PERIOD_LABEL_STYLE.get().copied().unwrap_or_default()
+}
+
+pub fn week_bounds(today: NaiveDate, week_start: WeekStart) -> (NaiveDate, NaiveDate) {
+    let days_back = match week_start {
+        WeekStart::Rolling => 6,
+        WeekStart::Monday => today.weekday().num_days_from_monday(),
+        WeekStart::Sunday => today.weekday().num_days_from_sunday(),
+    };
+    (today - ChronoDuration::days(days_back as i64), today)
+}
+
 pub fn operational_day_key_now() -> NaiveDate {
     operational_day_key_from_utc(Utc::now(), &day_boundary_config())
 }
@@ -149,21 +451,214 @@ fn operational_day_key_from_utc(now_utc: DateTime<Utc>, config: &DayBoundaryConf
     };
     let local = now_utc.with_timezone(&offset);
 
-    let cutoff = match config.mode {
+    operational_day_key_for_naive(local.naive_local(), config)
+}
+
+fn day_boundary_cutoff(config: &DayBoundaryConfig) -> NaiveTime {
+    match config.mode {
         DayBoundaryMode::FixedHour | DayBoundaryMode::Sunrise => {
             NaiveTime::from_hms_opt(config.fixed_hour, config.fixed_minute, 0)
                 .or_else(|| NaiveTime::from_hms_opt(6, 0, 0))
                 .unwrap_or(NaiveTime::MIN)
         }
-    };
+    }
+}
 
-    let mut day = local.date_naive();
-    if local.time() < cutoff {
+fn operational_day_key_for_naive(local: NaiveDateTime, config: &DayBoundaryConfig) -> NaiveDate {
+    let mut day = local.date();
+    if local.time() < day_boundary_cutoff(config) {
         day -= ChronoDuration::days(1);
     }
     day
 }
 
+pub fn session_interval(session: &Session) -> Option<(NaiveDateTime, NaiveDateTime)> {
+    let day = NaiveDate::parse_from_str(&session.date, "%Y-%m-%d").ok()?;
+    let end_time = NaiveTime::parse_from_str(&session.end_time, "%H:%M:%S").ok()?;
+
+    let end_date = if end_time < day_boundary_cutoff(&day_boundary_config()) {
+        day + ChronoDuration::days(1)
+    } else {
+        day
+    };
+    let end = end_date.and_time(end_time);
+    let start = end - ChronoDuration::seconds(session.elapsed_seconds as i64);
+    Some((start, end))
+}
+
+/// Operational days touched by the 24 hours ending at `now`.
+pub fn rolling_day_bounds(now: NaiveDateTime) -> (NaiveDate, NaiveDate) {
+    let config = day_boundary_config();
+    (
+        operational_day_key_for_naive(now - ChronoDuration::hours(24), &config),
+        operational_day_key_for_naive(now, &config),
+    )
+}
+
+/// Sessions overlapping the 24 hours ending at `now`, trimmed to that
+/// window so a session crossing its start only counts the part inside.
+pub fn rolling_day_sessions(sessions: &[Session], now: NaiveDateTime) -> Vec<Session> {
+    let window_start = now - ChronoDuration::hours(24);
+    sessions
+        .iter()
+        .filter_map(|session| {
+            let (start, end) = session_interval(session)?;
+            let (start, end) = (start.max(window_start), end.min(now));
+            if end <= start {
+                return None;
+            }
+            let mut clipped = session.clone();
+            set_session_interval(&mut clipped, start, end);
+            Some(clipped)
+        })
+        .collect()
+}
+
+/// Sessions counted by `period`; only the rolling window needs clipping.
+pub fn period_sessions(sessions: &[Session], period: ReportPeriod) -> Cow<'_, [Session]> {
+    match period {
+        ReportPeriod::Last24Hours => {
+            Cow::Owned(rolling_day_sessions(sessions, Local::now().naive_local()))
+        }
+        _ => Cow::Borrowed(sessions),
+    }
+}
+
+pub fn set_session_interval(session: &mut Session, start: NaiveDateTime, end: NaiveDateTime) {
+    session.date = operational_day_key_for_naive(end, &day_boundary_config())
+        .format("%Y-%m-%d")
+        .to_string();
+    session.start_time = start.format("%H:%M:%S").to_string();
+    session.end_time = end.format("%H:%M:%S").to_string();
+    session.elapsed_seconds = (end - start).num_seconds().max(0) as usize;
+}
+
+pub fn find_session_overlaps(sessions: &[Session]) -> Vec<SessionOverlap> {
+    let mut intervals: Vec<(usize, NaiveDateTime, NaiveDateTime)> = sessions
+        .iter()
+        .filter(|session| session.category_id != CategoryId::new(0))
+        .filter(|session| session.elapsed_seconds > 0)
+        .filter_map(|session| {
+            session_interval(session).map(|(start, end)| (session.id, start, end))
+        })
+        .collect();
+    intervals.sort_by_key(|(id, start, _)| (*start, *id));
+
+    let mut overlaps = Vec::new();
+    for (idx, (first_id, first_start, first_end)) in intervals.iter().enumerate() {
+        for (second_id, second_start, second_end) in &intervals[idx + 1..] {
+            if second_start >= first_end {
+                break;
+            }
+
+            let overlap_end = (*first_end).min(*second_end);
+            let overlap_start = (*first_start).max(*second_start);
+            overlaps.push(SessionOverlap {
+                first_id: *first_id,
+                second_id: *second_id,
+                overlap_seconds: (overlap_end - overlap_start).num_seconds().max(0) as usize,
+            });
+        }
+    }
+
+    overlaps
+}
+
+/// Overlaps that haven't been kept as they are.
+pub fn unresolved_session_overlaps(
+    sessions: &[Session],
+    kept: &KeptOverlaps,
+) -> Vec<SessionOverlap> {
+    find_session_overlaps(sessions)
+        .into_iter()
+        .filter(|overlap| !kept.contains(&(overlap.first_id, overlap.second_id)))
+        .collect()
+}
+
+/// Only sessions of the same category merge; otherwise one side's time
+/// would move to the other category.
+pub fn overlap_can_merge(sessions: &[Session], overlap: &SessionOverlap) -> bool {
+    let category = |id| {
+        sessions
+            .iter()
+            .find(|s| s.id == id)
+            .map(|session| session.category_id)
+    };
+    matches!(
+        (category(overlap.first_id), category(overlap.second_id)),
+        (Some(first), Some(second)) if first == second
+    )
+}
+
+/// Drops kept pairs naming a session that no longer exists. Ids are reused
+/// after the newest session is deleted, so a stale pair would otherwise
+/// hide a new overlap. Returns whether any pair was dropped.
+pub fn forget_removed_overlaps(kept: &mut KeptOverlaps, sessions: &[Session]) -> bool {
+    let ids: HashSet<usize> = sessions.iter().map(|session| session.id).collect();
+    let before = kept.len();
+    kept.retain(|(first, second)| ids.contains(first) && ids.contains(second));
+    kept.len() != before
+}
+
+/// Whether trimming `overlap` deletes its second session outright, because
+/// the first one covers all of it.
+pub fn overlap_trim_removes(sessions: &[Session], overlap: &SessionOverlap) -> bool {
+    let interval = |id| {
+        sessions
+            .iter()
+            .find(|s| s.id == id)
+            .and_then(session_interval)
+    };
+    match (interval(overlap.first_id), interval(overlap.second_id)) {
+        (Some((_, first_end)), Some((_, second_end))) => second_end <= first_end,
+        _ => false,
+    }
+}
+
+pub fn resolve_session_overlap(
+    sessions: &mut Vec<Session>,
+    overlap: &SessionOverlap,
+    resolution: OverlapResolution,
+) -> bool {
+    let Some(first_pos) = sessions.iter().position(|s| s.id == overlap.first_id) else {
+        return false;
+    };
+    let Some(second_pos) = sessions.iter().position(|s| s.id == overlap.second_id) else {
+        return false;
+    };
+    let (Some((first_start, first_end)), Some((second_start, second_end))) = (
+        session_interval(&sessions[first_pos]),
+        session_interval(&sessions[second_pos]),
+    ) else {
+        return false;
+    };
+
+    match resolution {
+        OverlapResolution::KeepBoth => false,
+        OverlapResolution::Trim => {
+            if second_end <= first_end {
+                sessions.remove(second_pos);
+            } else {
+                set_session_interval(&mut sessions[second_pos], first_end, second_end);
+            }
+            true
+        }
+        OverlapResolution::Merge => {
+            if sessions[first_pos].category_id != sessions[second_pos].category_id {
+                return false;
+            }
+            let start = first_start.min(second_start);
+            let end = first_end.max(second_end);
+            if sessions[first_pos].description.trim().is_empty() {
+                sessions[first_pos].description = sessions[second_pos].description.clone();
+            }
+            set_session_interval(&mut sessions[first_pos], start, end);
+            sessions.remove(second_pos);
+            true
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct CategoryStore {
     by_id: HashMap<CategoryId, Category>,
@@ -179,6 +674,7 @@ impl CategoryStore {
             name: "none".to_string(),
             color: Color::White,
             description: String::new(),
+            default_description: String::new(),
             karma_effect: 0,
         };
         by_id.insert(none.id, none);
@@ -238,10 +734,6 @@ impl CategoryStore {
         self.by_id.get(&id)
     }
 
-    pub fn get_mut_by_id(&mut self, id: CategoryId) -> Option<&mut Category> {
-        self.by_id.get_mut(&id)
-    }
-
     pub fn get_by_index(&self, index: usize) -> Option<&Category> {
         let id = self.id_at_index(index)?;
         self.by_id.get(&id)
@@ -255,10 +747,11 @@ impl CategoryStore {
     }
 
     pub fn ordered_categories(&self) -> Vec<Category> {
-        self.order
-            .iter()
-            .filter_map(|id| self.by_id.get(id).cloned())
-            .collect()
+        self.iter_ordered().cloned().collect()
+    }
+
+    pub fn iter_ordered(&self) -> impl Iterator<Item = &Category> + '_ {
+        self.order.iter().filter_map(|id| self.by_id.get(id))
     }
 
     pub fn add_category(
@@ -292,6 +785,7 @@ impl CategoryStore {
                 name: trimmed.to_string(),
                 color: COLORS[color_idx % COLORS.len()],
                 description,
+                default_description: String::new(),
                 karma_effect: 1,
             },
         );
@@ -310,6 +804,24 @@ impl CategoryStore {
         Some(removed_id)
     }
 
+    /// Puts a deleted category back at `index`, keeping its id.
+    pub fn restore(&mut self, index: usize, category: Category) -> bool {
+        if category.id.0 == 0
+            || self.by_id.contains_key(&category.id)
+            || self
+                .iter_ordered()
+                .any(|existing| existing.name.eq_ignore_ascii_case(&category.name))
+        {
+            return false;
+        }
+
+        let index = index.clamp(1, self.order.len());
+        self.next_id = self.next_id.max(category.id.0 + 1);
+        self.order.insert(index, category.id);
+        self.by_id.insert(category.id, category);
+        true
+    }
+
     pub fn move_up(&mut self, index: usize) -> bool {
         if index <= 1 || index >= self.order.len() {
             return false;
@@ -343,16 +855,53 @@ impl CategoryStore {
         true
     }
 
-    pub fn set_description_by_index(&mut self, index: usize, description: String) -> bool {
+    pub fn set_default_description_by_index(
+        &mut self,
+        index: usize,
+        default_description: String,
+    ) -> bool {
+        if index == 0 {
+            return false;
+        }
+
+        let Some(id) = self.id_at_index(index) else {
+            return false;
+        };
+
+        let Some(category) = self.by_id.get_mut(&id) else {
+            return false;
+        };
+
+        category.default_description = default_description;
+        true
+    }
+
+    /// Renames in place; the id, and so every session, stays attached.
+    pub fn rename_by_index(&mut self, index: usize, name: &str) -> bool {
+        let trimmed = name.trim();
+        if index == 0 || trimmed.is_empty() || trimmed.eq_ignore_ascii_case("none") {
+            return false;
+        }
+
         let Some(id) = self.id_at_index(index) else {
             return false;
         };
 
+        if self
+            .iter_ordered()
+            .any(|cat| cat.id != id && cat.name.eq_ignore_ascii_case(trimmed))
+        {
+            return false;
+        }
+
         let Some(category) = self.by_id.get_mut(&id) else {
             return false;
         };
 
-        category.description = description;
+        if category.name == trimmed {
+            return false;
+        }
+        category.name = trimmed.to_string();
         true
     }
 
@@ -378,8 +927,27 @@ pub struct TimeTracker {
     pub sessions: Vec<Session>,
     category_store: CategoryStore,
     pub current_session_start: Option<Instant>,
+    paused_at: Option<Instant>,
     pub session_id_counter: usize,
     active_category_id: CategoryId,
+    session_description: String,
+    session_project: String,
+    revision: u64,
+    switch_grace: Duration,
+    recent_ends: [Option<EndedSession>; 2],
+}
+
+/// Longest pause between ending one session and starting the next that
+/// still counts as a direct switch.
+const SWITCH_GAP: Duration = Duration::from_secs(2);
+
+/// When a session recorded by `end_session` ran, kept for the last two so a
+/// quick switch away and back can be folded into the earlier one.
+#[derive(Debug, Clone, Copy)]
+struct EndedSession {
+    id: usize,
+    started: Instant,
+    ended: Instant,
 }
 
 impl TimeTracker {
@@ -388,9 +956,41 @@ impl TimeTracker {
             sessions: Vec::new(),
             category_store: CategoryStore::new(),
             current_session_start: None,
+            paused_at: None,
             session_id_counter: 1,
             active_category_id: CategoryId::new(0),
+            session_description: String::new(),
+            session_project: String::new(),
+            revision: 0,
+            switch_grace: Duration::ZERO,
+            recent_ends: [None, None],
+        }
+    }
+
+    /// Switching away and back within `grace` merges the fragments into the
+    /// original session; zero turns this off.
+    pub fn set_switch_grace(&mut self, grace: Duration) {
+        self.switch_grace = grace;
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn touch(&mut self) {
+        self.revision = self.revision.wrapping_add(1);
+    }
+
+    fn touch_if(&mut self, changed: bool) -> bool {
+        if changed {
+            self.touch();
         }
+        changed
+    }
+
+    pub fn sessions_mut(&mut self) -> &mut Vec<Session> {
+        self.touch();
+        &mut self.sessions
     }
 
     pub fn apply_loaded_state(
@@ -403,6 +1003,8 @@ impl TimeTracker {
         self.category_store = CategoryStore::from_loaded(categories, next_category_id);
         self.sessions = sessions;
         self.session_id_counter = next_session_id;
+        self.recent_ends = [None, None];
+        self.touch();
 
         if self
             .category_store
@@ -421,8 +1023,8 @@ impl TimeTracker {
         self.category_store.ordered_categories()
     }
 
-    pub fn categories_ordered(&self) -> Vec<Category> {
-        self.category_store.ordered_categories()
+    pub fn categories_ordered(&self) -> impl Iterator<Item = &Category> + '_ {
+        self.category_store.iter_ordered()
     }
 
     pub fn category_by_index(&self, index: usize) -> Option<&Category> {
@@ -433,9 +1035,9 @@ impl TimeTracker {
         self.category_store.get_by_id(id)
     }
 
-    pub fn category_description_by_index(&self, index: usize) -> Option<String> {
+    pub fn category_default_description_by_index(&self, index: usize) -> Option<String> {
         self.category_by_index(index)
-            .map(|category| category.description.clone())
+            .map(|category| category.default_description.clone())
     }
 
     pub fn category_id_by_name(&self, name: &str) -> Option<CategoryId> {
@@ -451,11 +1053,6 @@ impl TimeTracker {
         self.category_by_id(id).map(|category| category.color)
     }
 
-    pub fn category_description_by_id(&self, id: CategoryId) -> Option<&str> {
-        self.category_by_id(id)
-            .map(|category| category.description.as_str())
-    }
-
     pub fn active_category_id(&self) -> CategoryId {
         self.active_category_id
     }
@@ -469,28 +1066,64 @@ impl TimeTracker {
             return false;
         };
         self.active_category_id = id;
+        self.touch();
         true
     }
 
-    pub fn set_category_description_by_index(&mut self, index: usize, description: String) -> bool {
-        self.category_store
-            .set_description_by_index(index, description)
+    pub fn set_category_default_description_by_index(
+        &mut self,
+        index: usize,
+        default_description: String,
+    ) -> bool {
+        let changed = self
+            .category_store
+            .set_default_description_by_index(index, default_description);
+        self.touch_if(changed)
+    }
+
+    pub fn session_description(&self) -> &str {
+        &self.session_description
+    }
+
+    pub fn set_session_description(&mut self, description: String) {
+        self.session_description = description;
+        self.touch();
+    }
+
+    /// The project the running session is recorded under; sessions started
+    /// in the TUI have none, but one picked up from `strata start` keeps it.
+    pub fn session_project(&self) -> &str {
+        &self.session_project
+    }
+
+    pub fn set_session_project(&mut self, project: String) {
+        self.session_project = project;
+        self.touch();
     }
 
     pub fn set_category_color_by_index(&mut self, index: usize, color: Color) -> bool {
-        self.category_store.set_color_by_index(index, color)
+        let changed = self.category_store.set_color_by_index(index, color);
+        self.touch_if(changed)
     }
 
     pub fn set_category_karma_by_index(&mut self, index: usize, karma_effect: i8) -> bool {
-        self.category_store.set_karma_by_index(index, karma_effect)
+        let changed = self.category_store.set_karma_by_index(index, karma_effect);
+        self.touch_if(changed)
+    }
+
+    pub fn rename_category_by_index(&mut self, index: usize, name: &str) -> bool {
+        let changed = self.category_store.rename_by_index(index, name);
+        self.touch_if(changed)
     }
 
     pub fn move_category_up(&mut self, index: usize) -> bool {
-        self.category_store.move_up(index)
+        let changed = self.category_store.move_up(index);
+        self.touch_if(changed)
     }
 
     pub fn move_category_down(&mut self, index: usize) -> bool {
-        self.category_store.move_down(index)
+        let changed = self.category_store.move_down(index);
+        self.touch_if(changed)
     }
 
     pub fn add_category(
@@ -499,13 +1132,35 @@ impl TimeTracker {
         description: String,
         color_index: Option<usize>,
     ) -> Option<CategoryId> {
-        self.category_store
-            .add_category(name, description, color_index)
+        let added = self
+            .category_store
+            .add_category(name, description, color_index);
+        self.touch_if(added.is_some());
+        added
+    }
+
+    pub fn restore_category(&mut self, index: usize, category: Category) -> bool {
+        let restored = self.category_store.restore(index, category);
+        if restored {
+            self.touch();
+        }
+        restored
+    }
+
+    /// Adds back sessions removed earlier, keeping the log ordered by id.
+    pub fn restore_sessions(&mut self, sessions: Vec<Session>) {
+        if sessions.is_empty() {
+            return;
+        }
+        self.sessions.extend(sessions);
+        self.sessions.sort_by_key(|session| session.id);
+        self.touch();
     }
 
     pub fn delete_category(&mut self, index: usize) -> bool {
         let removed = self.category_store.delete_by_index(index);
         if let Some(removed_id) = removed {
+            self.touch();
             if self.active_category_id == removed_id {
                 self.active_category_id = CategoryId::new(0);
             }
@@ -514,46 +1169,217 @@ impl TimeTracker {
         false
     }
 
-    pub fn start_session(&mut self) {
-        self.current_session_start = Some(Instant::now());
+    /// Starts tracking the active category; returns whether this resumed the
+    /// session left moments ago instead (see `set_switch_grace`).
+    pub fn start_session(&mut self) -> bool {
+        self.start_session_at(Instant::now())
     }
 
-    pub fn end_session(&mut self) -> Option<usize> {
-        let start_instant = self.current_session_start?;
-
-        let elapsed = start_instant.elapsed().as_secs() as usize;
-        let cat_id = self.active_category_id;
-        let cat_description = self
-            .category_store
-            .get_by_id(cat_id)
-            .map(|category| category.description.clone())
-            .unwrap_or_default();
-
-        self.record_session(cat_id, &cat_description, elapsed);
-
-        if let Some(category) = self.category_store.get_mut_by_id(cat_id) {
-            category.description.clear();
+    pub fn start_session_at(&mut self, now: Instant) -> bool {
+        self.current_session_start = Some(now);
+        self.paused_at = None;
+        self.touch();
+
+        if self.session_description.trim().is_empty() {
+            self.session_description = self
+                .category_store
+                .get_by_id(self.active_category_id)
+                .map(|category| category.default_description.clone())
+                .unwrap_or_default();
         }
-
-        self.current_session_start = None;
-        Some(elapsed)
+        self.resume_after_micro_switch(now)
     }
 
-    pub fn record_session(&mut self, cat_id: CategoryId, cat_description: &str, elapsed: usize) {
+    /// Drops the last two recorded sessions when the newer one is a fragment
+    /// shorter than the grace period and the session starting at `now`
+    /// continues the older one, then backdates the start to cover both.
+    fn resume_after_micro_switch(&mut self, now: Instant) -> bool {
+        let [Some(earlier), Some(fragment)] = self.recent_ends else {
+            return false;
+        };
+        let [.., resumed, last] = self.sessions.as_slice() else {
+            return false;
+        };
+        let continues = !self.switch_grace.is_zero()
+            && fragment.ended.saturating_duration_since(fragment.started) < self.switch_grace
+            && fragment.started.saturating_duration_since(earlier.ended) <= SWITCH_GAP
+            && now.saturating_duration_since(fragment.ended) <= SWITCH_GAP
+            && resumed.id == earlier.id
+            && last.id == fragment.id
+            && resumed.date == last.date
+            && resumed.category_id == self.active_category_id
+            && resumed.description == self.session_description;
+        if !continues {
+            return false;
+        }
+
+        let elapsed = resumed.elapsed_seconds + last.elapsed_seconds;
+        self.sessions.truncate(self.sessions.len() - 2);
+        self.recent_ends = [None, None];
+        self.current_session_start = Some(
+            now.checked_sub(Duration::from_secs(elapsed as u64))
+                .unwrap_or(now),
+        );
+        true
+    }
+
+    /// Time the running session has been tracking, excluding any pause.
+    pub fn session_elapsed(&self) -> Option<Duration> {
+        self.session_elapsed_at(Instant::now())
+    }
+
+    fn session_elapsed_at(&self, now: Instant) -> Option<Duration> {
+        let start = self.current_session_start?;
+        let until = self.paused_at.unwrap_or(now);
+        Some(until.saturating_duration_since(start))
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// How long the running session has been paused for, if it is.
+    pub fn paused_for(&self) -> Option<Duration> {
+        self.paused_at.map(|paused_at| paused_at.elapsed())
+    }
+
+    /// Pauses or resumes the running session; returns whether it is now paused.
+    pub fn toggle_pause(&mut self) -> bool {
+        let Some(start) = self.current_session_start else {
+            return false;
+        };
+        match self.paused_at.take() {
+            Some(paused_at) => {
+                // Shift the start forward so the pause never counts as elapsed.
+                self.current_session_start = Some(start + paused_at.elapsed());
+            }
+            None => self.paused_at = Some(Instant::now()),
+        }
+        self.touch();
+        self.paused_at.is_some()
+    }
+
+    pub fn end_session(&mut self) -> Option<usize> {
+        self.end_session_at(Instant::now(), Local::now())
+    }
+
+    pub fn end_session_at(&mut self, now: Instant, wall_now: DateTime<Local>) -> Option<usize> {
+        let started = self.current_session_start?;
+        let elapsed = self.session_elapsed_at(now)?.as_secs() as usize;
+        let cat_id = self.active_category_id;
+        let description = std::mem::take(&mut self.session_description);
+        let project = std::mem::take(&mut self.session_project);
+
+        let id = self.session_id_counter;
+        self.record_session_ending_at(cat_id, &description, &project, elapsed, wall_now);
+        self.recent_ends = [
+            self.recent_ends[1],
+            Some(EndedSession {
+                id,
+                started,
+                ended: now,
+            }),
+        ];
+
+        self.current_session_start = None;
+        self.paused_at = None;
+        Some(elapsed)
+    }
+
+    /// Stops the running session without recording it, for one that was
+    /// recorded somewhere else.
+    pub fn discard_session(&mut self) {
+        self.current_session_start = None;
+        self.paused_at = None;
+        self.session_description.clear();
+        self.session_project.clear();
+        self.recent_ends = [None, None];
+        self.touch();
+    }
+
+    pub fn end_session_idle(&mut self, idle_seconds: usize) -> Option<usize> {
+        let elapsed = (self.session_elapsed()?.as_secs() as usize).saturating_sub(idle_seconds);
+        let end = Local::now() - ChronoDuration::seconds(idle_seconds as i64);
+        let cat_id = self.active_category_id;
+        let description = self.session_description.clone();
+        let project = self.session_project.clone();
+
+        if elapsed > 0 {
+            self.record_session_ending_at(cat_id, &description, &project, elapsed, end);
+        }
+        self.recent_ends = [None, None];
+
+        self.current_session_start = None;
+        self.paused_at = None;
+        Some(elapsed)
+    }
+
+    /// Records the running session up to `idle_seconds` ago and keeps it
+    /// running from now. The idle stretch is recorded for `reassign_to`, or
+    /// dropped when that is `None`. Returns the seconds kept.
+    pub fn split_idle_tail(
+        &mut self,
+        idle_seconds: usize,
+        reassign_to: Option<CategoryId>,
+    ) -> Option<usize> {
         let now = Local::now();
+        let elapsed = self.session_elapsed()?.as_secs() as usize;
+        let idle = idle_seconds.min(elapsed);
+        let cat_id = self.active_category_id;
+        let description = self.session_description.clone();
+        let project = self.session_project.clone();
+
+        if elapsed > idle {
+            let end = now - ChronoDuration::seconds(idle as i64);
+            self.record_session_ending_at(cat_id, &description, &project, elapsed - idle, end);
+        }
+        if let Some(other) = reassign_to
+            && idle > 0
+        {
+            let other_description = self
+                .category_store
+                .get_by_id(other)
+                .map(|category| category.default_description.clone())
+                .unwrap_or_default();
+            self.record_session_ending_at(other, &other_description, "", idle, now);
+        }
+        self.recent_ends = [None, None];
+
+        self.current_session_start = Some(Instant::now());
+        self.paused_at = None;
+        self.touch();
+        Some(elapsed - idle)
+    }
+
+    #[cfg(test)]
+    fn record_session(&mut self, cat_id: CategoryId, cat_description: &str, elapsed: usize) {
+        self.record_session_ending_at(cat_id, cat_description, "", elapsed, Local::now());
+    }
+
+    fn record_session_ending_at(
+        &mut self,
+        cat_id: CategoryId,
+        cat_description: &str,
+        project: &str,
+        elapsed: usize,
+        now: DateTime<Local>,
+    ) {
         let start_time = now - ChronoDuration::seconds(elapsed as i64);
         let today = operational_day_key_for_local(&now)
             .format("%Y-%m-%d")
             .to_string();
 
+        self.touch();
         self.sessions.push(Session {
             id: self.session_id_counter,
             date: today,
             category_id: cat_id,
             description: cat_description.to_string(),
+            project: project.to_string(),
             start_time: start_time.format("%H:%M:%S").to_string(),
             end_time: now.format("%H:%M:%S").to_string(),
             elapsed_seconds: elapsed,
+            focus: None,
         });
         self.session_id_counter += 1;
     }
@@ -571,6 +1397,10 @@ impl TimeTracker {
         let cat_id = self
             .category_id_by_name(category_name)
             .unwrap_or(CategoryId::new(0));
+        self.category_time_today(cat_id)
+    }
+
+    pub fn category_time_today(&self, cat_id: CategoryId) -> usize {
         let today = operational_day_key_now().format("%Y-%m-%d").to_string();
         self.sessions
             .iter()
@@ -579,16 +1409,490 @@ impl TimeTracker {
             .sum()
     }
 
-    pub fn reset_none_counter_today(&mut self) {
+    pub fn set_session_focus(&mut self, session_id: usize, rating: u8) -> bool {
+        if !(1..=5).contains(&rating) {
+            return false;
+        }
+        let Some(session) = self
+            .sessions
+            .iter_mut()
+            .find(|session| session.id == session_id)
+        else {
+            return false;
+        };
+        session.focus = Some(rating);
+        self.touch();
+        true
+    }
+
+    /// Rewrites a stored session, keeping its start and moving the end to
+    /// match the new elapsed time.
+    pub fn edit_session(
+        &mut self,
+        session_id: usize,
+        category_id: CategoryId,
+        description: &str,
+        elapsed_seconds: usize,
+    ) -> bool {
+        if self.category_store.get_by_id(category_id).is_none() {
+            return false;
+        }
+        let Some(session) = self
+            .sessions
+            .iter_mut()
+            .find(|session| session.id == session_id)
+        else {
+            return false;
+        };
+        let Some((start, _)) = session_interval(session) else {
+            return false;
+        };
+        set_session_interval(
+            session,
+            start,
+            start + ChronoDuration::seconds(elapsed_seconds as i64),
+        );
+        session.category_id = category_id;
+        session.description = description.trim().to_string();
+        self.touch();
+        true
+    }
+
+    pub fn reset_none_counter_today(&mut self) -> Vec<Session> {
         let today = operational_day_key_now().format("%Y-%m-%d").to_string();
-        self.sessions.retain(|session| {
-            !(session.category_id == CategoryId::new(0) && session.date == today)
-        });
+        self.touch();
+        let (removed, kept) = std::mem::take(&mut self.sessions)
+            .into_iter()
+            .partition(|session| {
+                session.category_id == CategoryId::new(0) && session.date == today
+            });
+        self.sessions = kept;
 
         if self.active_category_id == CategoryId::new(0) {
             self.current_session_start = Some(Instant::now());
         }
+        removed
+    }
+}
+
+pub fn daily_tracked_seconds(
+    sessions: &[Session],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> HashMap<NaiveDate, usize> {
+    let mut totals: HashMap<NaiveDate, usize> = HashMap::new();
+    for session in sessions {
+        if session.category_id == CategoryId::new(0) {
+            continue;
+        }
+        let Some(session_date) = NaiveDate::parse_from_str(&session.date, "%Y-%m-%d").ok() else {
+            continue;
+        };
+        if session_date < start || session_date > end {
+            continue;
+        }
+        *totals.entry(session_date).or_insert(0) += session.elapsed_seconds;
     }
+    totals
+}
+
+/// Seconds tracked on `day` in `category_id` whose description matches a
+/// planned item; an empty description matches every session in the category.
+pub fn planned_item_seconds(
+    sessions: &[Session],
+    day: NaiveDate,
+    category_id: CategoryId,
+    description: &str,
+) -> usize {
+    let day = day.format("%Y-%m-%d").to_string();
+    let description = description.trim();
+    sessions
+        .iter()
+        .filter(|session| session.date == day && session.category_id == category_id)
+        .filter(|session| {
+            description.is_empty() || session.description.trim().eq_ignore_ascii_case(description)
+        })
+        .map(|session| session.elapsed_seconds)
+        .sum()
+}
+
+pub fn build_invoice(
+    sessions: &[Session],
+    categories: &[Category],
+    start: NaiveDate,
+    end: NaiveDate,
+    category_rates: &HashMap<CategoryId, f64>,
+    project_rates: &HashMap<String, f64>,
+    tax_percent: f64,
+) -> Invoice {
+    let mut totals: HashMap<(String, CategoryId), usize> = HashMap::new();
+    let mut unbilled_seconds = 0;
+
+    for session in sessions {
+        if session.category_id == CategoryId::new(0) {
+            continue;
+        }
+        let Some(session_date) = NaiveDate::parse_from_str(&session.date, "%Y-%m-%d").ok() else {
+            continue;
+        };
+        if session_date < start || session_date > end {
+            continue;
+        }
+
+        let has_rate = project_rates.contains_key(&session.project)
+            || category_rates.contains_key(&session.category_id);
+        if !has_rate {
+            unbilled_seconds += session.elapsed_seconds;
+            continue;
+        }
+        *totals
+            .entry((session.project.clone(), session.category_id))
+            .or_insert(0) += session.elapsed_seconds;
+    }
+
+    let mut lines: Vec<InvoiceLine> = totals
+        .into_iter()
+        .map(|((project, category_id), elapsed_seconds)| {
+            let hourly_rate = project_rates
+                .get(&project)
+                .or_else(|| category_rates.get(&category_id))
+                .copied()
+                .unwrap_or(0.0);
+            let category_name = categories
+                .iter()
+                .find(|category| category.id == category_id)
+                .map(|category| category.name.clone())
+                .unwrap_or_else(|| "none".to_string());
+            InvoiceLine {
+                category_name,
+                project,
+                elapsed_seconds,
+                hourly_rate,
+                amount: round_cents(elapsed_seconds as f64 / 3600.0 * hourly_rate),
+            }
+        })
+        .collect();
+    lines.sort_by(|a, b| {
+        a.project
+            .cmp(&b.project)
+            .then_with(|| a.category_name.cmp(&b.category_name))
+    });
+
+    let subtotal = round_cents(lines.iter().map(|line| line.amount).sum());
+    let tax = round_cents(subtotal * tax_percent / 100.0);
+
+    Invoice {
+        lines,
+        unbilled_seconds,
+        subtotal,
+        tax,
+        total: round_cents(subtotal + tax),
+    }
+}
+
+fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+fn subtract_intervals(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    holes: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut pieces = vec![(start, end)];
+    for &(hole_start, hole_end) in holes {
+        pieces = pieces
+            .into_iter()
+            .flat_map(|(start, end)| {
+                if hole_end <= start || hole_start >= end {
+                    return vec![(start, end)];
+                }
+                [(start, hole_start.min(end)), (hole_end.max(start), end)]
+                    .into_iter()
+                    .filter(|(start, end)| start < end)
+                    .collect()
+            })
+            .collect();
+    }
+    pieces
+}
+
+/// Turns window events into category sessions: AFK spans are cut out, events
+/// with no category are dropped, and runs of one category separated by less
+/// than `merge_gap_secs` are joined.
+pub fn suggest_activity_sessions(
+    events: &[ActivityEvent],
+    afk: &[(DateTime<Utc>, DateTime<Utc>)],
+    categorize: impl Fn(&ActivityEvent) -> Option<String>,
+    merge_gap_secs: i64,
+    min_session_secs: i64,
+) -> Vec<ActivitySuggestion> {
+    let mut pieces: Vec<(DateTime<Utc>, DateTime<Utc>, String, &str)> = events
+        .iter()
+        .filter_map(|event| Some((event, categorize(event)?)))
+        .flat_map(|(event, category)| {
+            subtract_intervals(event.start, event.end, afk)
+                .into_iter()
+                .map(move |(start, end)| (start, end, category.clone(), event.app.as_str()))
+        })
+        .collect();
+    pieces.sort_by_key(|piece| piece.0);
+
+    let mut suggestions: Vec<(ActivitySuggestion, HashMap<&str, i64>)> = Vec::new();
+    for (start, end, category, app) in pieces {
+        let seconds = (end - start).num_seconds();
+        if let Some((last, apps)) = suggestions.last_mut()
+            && last.category == category
+            && (start - last.end).num_seconds() <= merge_gap_secs
+        {
+            last.end = last.end.max(end);
+            *apps.entry(app).or_insert(0) += seconds;
+            continue;
+        }
+        suggestions.push((
+            ActivitySuggestion {
+                start,
+                end,
+                category,
+                app: String::new(),
+            },
+            HashMap::from([(app, seconds)]),
+        ));
+    }
+
+    suggestions
+        .into_iter()
+        .filter(|(suggestion, _)| {
+            (suggestion.end - suggestion.start).num_seconds() >= min_session_secs
+        })
+        .map(|(mut suggestion, apps)| {
+            if let Some((app, _)) = apps
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            {
+                suggestion.app = app.to_string();
+            }
+            suggestion
+        })
+        .collect()
+}
+
+pub fn latest_clock_time_before(now: NaiveDateTime, raw: &str) -> Option<NaiveDateTime> {
+    let raw = raw.trim();
+    let time = NaiveTime::parse_from_str(raw, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(raw, "%H:%M:%S"))
+        .ok()?;
+    let candidate = now.date().and_time(time);
+    if candidate > now {
+        Some(candidate - ChronoDuration::days(1))
+    } else {
+        Some(candidate)
+    }
+}
+
+/// Sessions are stored whole on the day they end, so one may reach back at most
+/// this many day boundaries.
+pub const MAX_SESSION_DAY_SPAN: i64 = 1;
+
+pub fn validate_session_end(start: DateTime<Utc>, end: DateTime<Utc>) -> Result<(), String> {
+    validate_session_end_with_config(start, end, &day_boundary_config())
+}
+
+fn validate_session_end_with_config(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    config: &DayBoundaryConfig,
+) -> Result<(), String> {
+    if end <= start {
+        return Err("End time must be after the session start".to_string());
+    }
+    let span = (operational_day_key_from_utc(end, config)
+        - operational_day_key_from_utc(start, config))
+    .num_days();
+    if span > MAX_SESSION_DAY_SPAN {
+        return Err(format!(
+            "Session would span {} days; sessions may cross at most {} day boundary",
+            span + 1,
+            MAX_SESSION_DAY_SPAN
+        ));
+    }
+    Ok(())
+}
+
+pub fn operational_day_start(day: NaiveDate) -> NaiveDateTime {
+    day.and_time(day_boundary_cutoff(&day_boundary_config()))
+}
+
+/// When a block planned for `time` on operational `day` starts; times before
+/// the day boundary fall on the following calendar date.
+pub fn planned_block_start(day: NaiveDate, time: NaiveTime) -> NaiveDateTime {
+    let start = day.and_time(time);
+    if start < operational_day_start(day) {
+        start + ChronoDuration::days(1)
+    } else {
+        start
+    }
+}
+
+pub fn build_day_timeline(
+    sessions: &[Session],
+    categories: &[Category],
+    day: NaiveDate,
+) -> Vec<TimelineEntry> {
+    let day_start = operational_day_start(day);
+    let day_end = day_start + ChronoDuration::days(1);
+
+    let mut entries: Vec<TimelineEntry> = sessions
+        .iter()
+        .filter(|session| session.category_id != CategoryId::new(0))
+        .filter_map(|session| {
+            let (start, end) = session_interval(session)?;
+            if end <= day_start || start >= day_end || end <= start {
+                return None;
+            }
+            let category = categories
+                .iter()
+                .find(|category| category.id == session.category_id)?;
+
+            Some(TimelineEntry {
+                category_name: category.name.clone(),
+                color: category.color,
+                description: session.description.clone(),
+                start_offset_seconds: (start.max(day_start) - day_start).num_seconds() as usize,
+                end_offset_seconds: (end.min(day_end) - day_start).num_seconds() as usize,
+            })
+        })
+        .collect();
+
+    entries.sort_by_key(|entry| entry.start_offset_seconds);
+    entries
+}
+
+pub fn rename_project(sessions: &mut [Session], old_name: &str, new_name: &str) -> usize {
+    let mut renamed = 0;
+    for session in sessions
+        .iter_mut()
+        .filter(|session| session.project == old_name)
+    {
+        session.project = new_name.to_string();
+        renamed += 1;
+    }
+    renamed
+}
+
+pub fn remove_sessions(sessions: &mut Vec<Session>, ids: &HashSet<usize>) -> Vec<Session> {
+    let (removed, kept) = std::mem::take(sessions)
+        .into_iter()
+        .partition(|session| ids.contains(&session.id));
+    *sessions = kept;
+    removed
+}
+
+pub fn recategorize_sessions(
+    sessions: &mut [Session],
+    ids: &HashSet<usize>,
+    category_id: CategoryId,
+) -> usize {
+    let mut moved = 0;
+    for session in sessions
+        .iter_mut()
+        .filter(|session| ids.contains(&session.id) && session.category_id != category_id)
+    {
+        session.category_id = category_id;
+        moved += 1;
+    }
+    moved
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CategorySuggestionIndex {
+    counts_by_token: HashMap<String, HashMap<CategoryId, usize>>,
+}
+
+impl CategorySuggestionIndex {
+    pub fn from_sessions(sessions: &[Session]) -> Self {
+        let mut index = Self::default();
+        for session in sessions {
+            index.add_session(session);
+        }
+        index
+    }
+
+    pub fn add_session(&mut self, session: &Session) {
+        if session.category_id == CategoryId::new(0) {
+            return;
+        }
+        for token in description_tokens(&session.description) {
+            *self
+                .counts_by_token
+                .entry(token)
+                .or_default()
+                .entry(session.category_id)
+                .or_insert(0) += 1;
+        }
+    }
+
+    pub fn suggest(&self, description: &str) -> Option<CategoryId> {
+        let mut scores: HashMap<CategoryId, f64> = HashMap::new();
+        for token in description_tokens(description) {
+            let Some(counts) = self.counts_by_token.get(&token) else {
+                continue;
+            };
+            let total: usize = counts.values().sum();
+            for (category_id, count) in counts {
+                *scores.entry(*category_id).or_insert(0.0) += *count as f64 / total as f64;
+            }
+        }
+
+        scores
+            .into_iter()
+            .max_by(|(left_id, left), (right_id, right)| {
+                left.total_cmp(right).then(right_id.0.cmp(&left_id.0))
+            })
+            .map(|(category_id, _)| category_id)
+    }
+}
+
+fn description_tokens(description: &str) -> HashSet<String> {
+    description
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| token.chars().count() >= 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+pub fn diff_records<T, K, F>(before: &[T], after: &[T], key: F) -> RecordChanges<T>
+where
+    T: Clone + PartialEq,
+    K: Eq + Hash,
+    F: Fn(&T) -> K,
+{
+    let before_by_key: HashMap<K, &T> = before.iter().map(|record| (key(record), record)).collect();
+    let after_keys: HashSet<K> = after.iter().map(&key).collect();
+
+    let mut changes = RecordChanges {
+        added: Vec::new(),
+        removed: Vec::new(),
+        modified: Vec::new(),
+    };
+
+    for record in after {
+        match before_by_key.get(&key(record)) {
+            None => changes.added.push(record.clone()),
+            Some(previous) if *previous != record => {
+                changes.modified.push(((*previous).clone(), record.clone()));
+            }
+            Some(_) => {}
+        }
+    }
+
+    changes.removed = before
+        .iter()
+        .filter(|record| !after_keys.contains(&key(record)))
+        .cloned()
+        .collect();
+
+    changes
 }
 
 pub fn build_today_report(sessions: &[Session], categories: &[Category]) -> ReportSummary {
@@ -606,8 +1910,264 @@ pub fn build_period_report(
     }
 
     let (start, end, label) = period_bounds(period);
+    let sessions = period_sessions(sessions, period);
+
+    build_report_for_date_range(&sessions, categories, start, end, label)
+}
+
+/// One report per day from `start` to `end`, including days with nothing tracked.
+pub fn build_daily_reports(
+    sessions: &[Session],
+    categories: &[Category],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Vec<ReportSummary> {
+    start
+        .iter_days()
+        .take_while(|day| *day <= end)
+        .map(|day| {
+            let label = day.format("%Y-%m-%d").to_string();
+            build_report_for_date_range(sessions, categories, day, day, label)
+        })
+        .collect()
+}
+
+/// This week's per-category totals with the daily average over the days so
+/// far, the best day, and the tracking streak ending today.
+pub fn build_week_stats(sessions: &[Session], categories: &[Category]) -> WeekStats {
+    let summary = build_period_report(sessions, categories, ReportPeriod::Week);
+    let (start, end) = period_date_bounds(ReportPeriod::Week);
+    let days = build_daily_reports(sessions, categories, start, end);
+    let best_day = start
+        .iter_days()
+        .zip(&days)
+        .filter(|(_, day)| day.total_seconds > 0)
+        .max_by_key(|(date, day)| (day.total_seconds, std::cmp::Reverse(*date)))
+        .map(|(date, day)| (date, day.total_seconds));
+
+    WeekStats {
+        daily_average_seconds: summary.total_seconds / days.len().max(1),
+        best_day,
+        streak_days: tracking_streak(sessions, end),
+        summary,
+    }
+}
+
+/// Consecutive days with tracked time up to `today`. Today not being tracked
+/// yet keeps the streak that ran through yesterday.
+pub fn tracking_streak(sessions: &[Session], today: NaiveDate) -> usize {
+    let tracked: HashSet<NaiveDate> = sessions
+        .iter()
+        .filter(|session| session.category_id != CategoryId::new(0) && session.elapsed_seconds > 0)
+        .filter_map(|session| NaiveDate::parse_from_str(&session.date, "%Y-%m-%d").ok())
+        .collect();
+
+    let mut day = today;
+    if !tracked.contains(&day) {
+        day -= ChronoDuration::days(1);
+    }
+    let mut streak = 0;
+    while tracked.contains(&day) {
+        streak += 1;
+        day -= ChronoDuration::days(1);
+    }
+    streak
+}
+
+/// Compares daily goals (scaled to a week) with the last `weeks` trailing
+/// 7-day windows ending on `end`. When the average misses the goal by more
+/// than `tolerance_percent`, the average is suggested as the new daily goal.
+pub fn build_goal_audit(
+    sessions: &[Session],
+    categories: &[Category],
+    daily_goals: &HashMap<CategoryId, usize>,
+    end: NaiveDate,
+    weeks: usize,
+    tolerance_percent: f64,
+    round_seconds: usize,
+) -> Vec<GoalAuditRow> {
+    let weeks = weeks.max(1);
+    let windows: Vec<(NaiveDate, NaiveDate)> = (0..weeks)
+        .rev()
+        .map(|back| {
+            let window_end = end - ChronoDuration::days(7 * back as i64);
+            (window_end - ChronoDuration::days(6), window_end)
+        })
+        .collect();
 
-    build_report_for_date_range(sessions, categories, start, end, label)
+    let mut rows: Vec<GoalAuditRow> = categories
+        .iter()
+        .filter_map(|category| {
+            let daily_goal = *daily_goals.get(&category.id)?;
+            let weekly_goal_seconds = daily_goal * 7;
+            let weekly_actual_seconds: Vec<usize> = windows
+                .iter()
+                .map(|(start, end)| {
+                    sessions
+                        .iter()
+                        .filter(|session| session.category_id == category.id)
+                        .filter(|session| {
+                            NaiveDate::parse_from_str(&session.date, "%Y-%m-%d")
+                                .is_ok_and(|date| date >= *start && date <= *end)
+                        })
+                        .map(|session| session.elapsed_seconds)
+                        .sum()
+                })
+                .collect();
+            let average_seconds = weekly_actual_seconds.iter().sum::<usize>() / weeks;
+            let delta_percent = if weekly_goal_seconds == 0 {
+                0.0
+            } else {
+                (average_seconds as f64 - weekly_goal_seconds as f64) * 100.0
+                    / weekly_goal_seconds as f64
+            };
+            let suggested_daily_seconds = (delta_percent.abs() > tolerance_percent).then(|| {
+                let step = round_seconds.max(1);
+                let daily = (average_seconds / 7 + step / 2) / step * step;
+                daily.max(step)
+            });
+
+            Some(GoalAuditRow {
+                category_id: category.id,
+                category_name: category.name.clone(),
+                weekly_goal_seconds,
+                weekly_actual_seconds,
+                average_seconds,
+                delta_percent,
+                suggested_daily_seconds,
+            })
+        })
+        .collect();
+    rows.sort_by(|a, b| a.category_name.cmp(&b.category_name));
+    rows
+}
+
+/// When a carried goal balance is dropped instead of rolling into the next
+/// week.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CarryReset {
+    #[default]
+    Never,
+    /// At the first week starting in a new month.
+    Month,
+    /// At the first week starting in a new year.
+    Year,
+}
+
+/// How unmet weekly goal time rolls into the next week as debt, and surplus
+/// as credit when `credit` is set. `cap_seconds` bounds both.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CarryOverRules {
+    pub credit: bool,
+    pub cap_seconds: Option<usize>,
+    pub reset: CarryReset,
+}
+
+/// One closed week of one category's goal. Balances are positive for debt
+/// and negative for credit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GoalLedgerEntry {
+    pub week_start: NaiveDate,
+    pub category_id: CategoryId,
+    pub goal_seconds: usize,
+    pub actual_seconds: usize,
+    pub carried_in: i64,
+    pub carried_out: i64,
+}
+
+impl CarryOverRules {
+    pub fn carry(&self, carried_in: i64, goal_seconds: usize, actual_seconds: usize) -> i64 {
+        let mut balance = carried_in + goal_seconds as i64 - actual_seconds as i64;
+        if !self.credit {
+            balance = balance.max(0);
+        }
+        match self.cap_seconds {
+            Some(cap) => balance.clamp(-(cap as i64), cap as i64),
+            None => balance,
+        }
+    }
+
+    fn resets_at(&self, week_start: NaiveDate) -> bool {
+        let previous = week_start - ChronoDuration::days(7);
+        match self.reset {
+            CarryReset::Never => false,
+            CarryReset::Month => previous.month() != week_start.month(),
+            CarryReset::Year => previous.year() != week_start.year(),
+        }
+    }
+
+    /// Balance the category starts `week_start` with: what the week before
+    /// carried out, unless the reset rule drops it.
+    pub fn carried_into(
+        &self,
+        ledger: &[GoalLedgerEntry],
+        category_id: CategoryId,
+        week_start: NaiveDate,
+    ) -> i64 {
+        if self.resets_at(week_start) {
+            return 0;
+        }
+        let previous = week_start - ChronoDuration::days(7);
+        ledger
+            .iter()
+            .find(|entry| entry.category_id == category_id && entry.week_start == previous)
+            .map_or(0, |entry| entry.carried_out)
+    }
+}
+
+/// Start of the ledger week holding `day`. Ledger weeks are calendar weeks,
+/// starting on Monday unless `week_start` is Sunday.
+pub fn goal_ledger_week(day: NaiveDate, week_start: WeekStart) -> NaiveDate {
+    let week_start = match week_start {
+        WeekStart::Sunday => WeekStart::Sunday,
+        WeekStart::Rolling | WeekStart::Monday => WeekStart::Monday,
+    };
+    week_bounds(day, week_start).0
+}
+
+/// Closes every week from `first_week` up to, not including, `current_week`
+/// for each category with a weekly goal, carrying balances forward.
+pub fn close_goal_weeks(
+    ledger: &[GoalLedgerEntry],
+    sessions: &[Session],
+    weekly_goals: &HashMap<CategoryId, usize>,
+    first_week: NaiveDate,
+    current_week: NaiveDate,
+    rules: &CarryOverRules,
+) -> Vec<GoalLedgerEntry> {
+    let mut goals: Vec<(CategoryId, usize)> = weekly_goals
+        .iter()
+        .map(|(category_id, seconds)| (*category_id, *seconds))
+        .collect();
+    goals.sort_by_key(|(category_id, _)| category_id.0);
+
+    let mut closed: Vec<GoalLedgerEntry> = ledger.to_vec();
+    let mut week = first_week;
+    while week < current_week {
+        let week_end = week + ChronoDuration::days(6);
+        for (category_id, goal_seconds) in &goals {
+            let actual_seconds = sessions
+                .iter()
+                .filter(|session| session.category_id == *category_id)
+                .filter(|session| {
+                    NaiveDate::parse_from_str(&session.date, "%Y-%m-%d")
+                        .is_ok_and(|date| date >= week && date <= week_end)
+                })
+                .map(|session| session.elapsed_seconds)
+                .sum();
+            let carried_in = rules.carried_into(&closed, *category_id, week);
+            closed.push(GoalLedgerEntry {
+                week_start: week,
+                category_id: *category_id,
+                goal_seconds: *goal_seconds,
+                actual_seconds,
+                carried_in,
+                carried_out: rules.carry(carried_in, *goal_seconds, actual_seconds),
+            });
+        }
+        week += ChronoDuration::days(7);
+    }
+    closed.split_off(ledger.len())
 }
 
 pub fn build_period_karma_report(
@@ -620,8 +2180,37 @@ pub fn build_period_karma_report(
     }
 
     let (start, end, label) = period_bounds(period);
+    let sessions = period_sessions(sessions, period);
+
+    build_karma_report_for_date_range(&sessions, categories, start, end, label)
+}
+
+pub fn average_focus_by_category(
+    sessions: &[Session],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> HashMap<CategoryId, f64> {
+    let mut ratings: HashMap<CategoryId, (usize, usize)> = HashMap::new();
+    for session in sessions {
+        let Some(focus) = session.focus else {
+            continue;
+        };
+        let Some(session_date) = NaiveDate::parse_from_str(&session.date, "%Y-%m-%d").ok() else {
+            continue;
+        };
+        if session_date < start || session_date > end {
+            continue;
+        }
 
-    build_karma_report_for_date_range(sessions, categories, start, end, label)
+        let (sum, count) = ratings.entry(session.category_id).or_insert((0, 0));
+        *sum += focus as usize;
+        *count += 1;
+    }
+
+    ratings
+        .into_iter()
+        .map(|(category_id, (sum, count))| (category_id, sum as f64 / count as f64))
+        .collect()
 }
 
 fn period_bounds(period: ReportPeriod) -> (NaiveDate, NaiveDate, String) {
@@ -632,19 +2221,80 @@ fn period_bounds(period: ReportPeriod) -> (NaiveDate, NaiveDate, String) {
             let label = today.format("%Y-%m-%d").to_string();
             (today, today, label)
         }
-        ReportPeriod::Week => {
-            let start = today - ChronoDuration::days(6);
-            let label = format!("{}..{}", start.format("%Y-%m-%d"), today.format("%Y-%m-%d"));
-            (start, today, label)
+        ReportPeriod::Last24Hours => {
+            let (start, end) = rolling_day_bounds(Local::now().naive_local());
+            (start, end, "last 24h".to_string())
         }
-        ReportPeriod::Month => {
-            let start = today - ChronoDuration::days(29);
+        ReportPeriod::Week | ReportPeriod::Month => {
+            let (start, today) = period_range(period, today, week_start(), period_label_style());
             let label = format!("{}..{}", start.format("%Y-%m-%d"), today.format("%Y-%m-%d"));
             (start, today, label)
         }
     }
 }
 
+/// The days a week or month report covers up to `today`. ISO labels name a
+/// calendar week or month, so with them the week starts on Monday and the
+/// month on its first day; otherwise the month is the last 30 days.
+pub fn period_range(
+    period: ReportPeriod,
+    today: NaiveDate,
+    week_start: WeekStart,
+    style: PeriodLabelStyle,
+) -> (NaiveDate, NaiveDate) {
+    match (style, period) {
+        (PeriodLabelStyle::Iso, ReportPeriod::Week) => week_bounds(today, WeekStart::Monday),
+        (PeriodLabelStyle::Iso, ReportPeriod::Month) => (today.with_day(1).unwrap_or(today), today),
+        (_, ReportPeriod::Week) => week_bounds(today, week_start),
+        (_, ReportPeriod::Month) => (today - ChronoDuration::days(29), today),
+        (_, ReportPeriod::Today | ReportPeriod::Last24Hours) => (today, today),
+    }
+}
+
+pub fn period_date_bounds(period: ReportPeriod) -> (NaiveDate, NaiveDate) {
+    let (start, end, _) = period_bounds(period);
+    (start, end)
+}
+
+pub fn format_period_label(
+    period: ReportPeriod,
+    start: NaiveDate,
+    end: NaiveDate,
+    style: PeriodLabelStyle,
+) -> String {
+    match (style, period) {
+        (_, ReportPeriod::Today) => end.format("%Y-%m-%d").to_string(),
+        (_, ReportPeriod::Last24Hours) => "last 24h".to_string(),
+        (PeriodLabelStyle::Range, _) => {
+            format!("{}..{}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"))
+        }
+        (PeriodLabelStyle::Iso, ReportPeriod::Week) => iso_week_label(end),
+        (PeriodLabelStyle::Iso, ReportPeriod::Month) => end.format("%B %Y").to_string(),
+    }
+}
+
+pub fn period_file_stem(
+    period: ReportPeriod,
+    start: NaiveDate,
+    end: NaiveDate,
+    style: PeriodLabelStyle,
+) -> String {
+    match (style, period) {
+        (_, ReportPeriod::Today) => end.format("%Y-%m-%d").to_string(),
+        (_, ReportPeriod::Last24Hours) => format!("{}_last24h", end.format("%Y-%m-%d")),
+        (PeriodLabelStyle::Range, _) => {
+            format!("{}_{}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"))
+        }
+        (PeriodLabelStyle::Iso, ReportPeriod::Week) => iso_week_label(end),
+        (PeriodLabelStyle::Iso, ReportPeriod::Month) => end.format("%Y-%m").to_string(),
+    }
+}
+
+fn iso_week_label(date: NaiveDate) -> String {
+    let week = date.iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
 pub fn build_today_karma_report(
     sessions: &[Session],
     categories: &[Category],
@@ -676,7 +2326,7 @@ pub fn build_karma_report_for_date(
     )
 }
 
-fn build_karma_report_for_date_range(
+pub fn build_karma_report_for_date_range(
     sessions: &[Session],
     categories: &[Category],
     start: NaiveDate,
@@ -792,7 +2442,7 @@ fn build_report_for_date_range(
             })
         })
         .collect();
-    entries.sort_by(|a, b| b.elapsed_seconds.cmp(&a.elapsed_seconds));
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.elapsed_seconds));
 
     let total_seconds = entries.iter().map(|entry| entry.elapsed_seconds).sum();
 
@@ -870,9 +2520,34 @@ pub fn build_period_karma_report_with_live(
     live_session: Option<&LiveSessionPreview>,
 ) -> KarmaReportSummary {
     let mut summary = build_period_karma_report(sessions, categories, period);
+    if report_period_contains_today(period) {
+        add_live_session(&mut summary, live_session);
+    }
+    sort_karma_entries_for_display(&mut summary.entries);
+    summary
+}
 
-    if report_period_contains_today(period)
-        && let Some(live) = live_session
+/// The karma report for any `start..=end` span of operational days, with the
+/// running session counted when the span includes today.
+pub fn build_range_karma_report_with_live(
+    sessions: &[Session],
+    categories: &[Category],
+    start: NaiveDate,
+    end: NaiveDate,
+    live_session: Option<&LiveSessionPreview>,
+) -> KarmaReportSummary {
+    let label = format!("{}..{}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"));
+    let mut summary = build_karma_report_for_date_range(sessions, categories, start, end, label);
+    let today = operational_day_key_now();
+    if start <= today && today <= end {
+        add_live_session(&mut summary, live_session);
+    }
+    sort_karma_entries_for_display(&mut summary.entries);
+    summary
+}
+
+fn add_live_session(summary: &mut KarmaReportSummary, live_session: Option<&LiveSessionPreview>) {
+    if let Some(live) = live_session
         && let Some(entry) = summary
             .entries
             .iter_mut()
@@ -883,22 +2558,72 @@ pub fn build_period_karma_report_with_live(
         summary.total_seconds += live.elapsed_seconds;
         summary.total_karma_seconds += live.elapsed_seconds as isize * entry.karma_effect as isize;
     }
+}
 
-    sort_karma_entries_for_display(&mut summary.entries);
-    summary
+pub fn build_category_logs_for_period(
+    sessions: &[Session],
+    kept_overlaps: &KeptOverlaps,
+    categories: &[Category],
+    category_id: CategoryId,
+    period: ReportPeriod,
+    live_session: Option<&LiveSessionPreview>,
+) -> Vec<CategoryLogEntry> {
+    let (start, end) = report_period_date_bounds(period);
+    let in_period = period_sessions(sessions, period);
+    category_logs(
+        &overlapping_session_ids(sessions, kept_overlaps),
+        &in_period,
+        categories,
+        category_id,
+        start,
+        end,
+        live_session,
+    )
 }
 
-pub fn build_category_logs_for_period(
+pub fn build_category_logs_for_date_range(
     sessions: &[Session],
+    kept_overlaps: &KeptOverlaps,
     categories: &[Category],
     category_id: CategoryId,
-    period: ReportPeriod,
+    start: NaiveDate,
+    end: NaiveDate,
+    live_session: Option<&LiveSessionPreview>,
+) -> Vec<CategoryLogEntry> {
+    category_logs(
+        &overlapping_session_ids(sessions, kept_overlaps),
+        sessions,
+        categories,
+        category_id,
+        start,
+        end,
+        live_session,
+    )
+}
+
+/// Sessions in an overlap that hasn't been kept.
+fn overlapping_session_ids(sessions: &[Session], kept: &KeptOverlaps) -> HashSet<usize> {
+    unresolved_session_overlaps(sessions, kept)
+        .into_iter()
+        .flat_map(|overlap| [overlap.first_id, overlap.second_id])
+        .collect()
+}
+
+/// Logs of `category_id` among `in_period` between `start` and `end`, with
+/// the sessions in `overlapping_ids` flagged.
+fn category_logs(
+    overlapping_ids: &HashSet<usize>,
+    in_period: &[Session],
+    categories: &[Category],
+    category_id: CategoryId,
+    start: NaiveDate,
+    end: NaiveDate,
     live_session: Option<&LiveSessionPreview>,
 ) -> Vec<CategoryLogEntry> {
-    let (start, end) = report_period_date_bounds(period);
     let karma_effect = category_karma_effect(categories, category_id);
+    let today = operational_day_key_now();
 
-    let mut logs: Vec<CategoryLogEntry> = sessions
+    let mut logs: Vec<CategoryLogEntry> = in_period
         .iter()
         .filter_map(|session| {
             if session.category_id != category_id {
@@ -912,6 +2637,8 @@ pub fn build_category_logs_for_period(
             }
 
             Some(CategoryLogEntry {
+                session_id: Some(session.id),
+                overlaps: overlapping_ids.contains(&session.id),
                 date: session.date.clone(),
                 start_time: session.start_time.clone(),
                 end_time: session.end_time.clone(),
@@ -923,7 +2650,8 @@ pub fn build_category_logs_for_period(
         })
         .collect();
 
-    if report_period_contains_today(period)
+    if start <= today
+        && today <= end
         && let Some(live) = live_session
         && live.category_id == category_id
         && live.elapsed_seconds > 0
@@ -937,6 +2665,8 @@ pub fn build_category_logs_for_period(
             .to_string();
 
         logs.push(CategoryLogEntry {
+            session_id: None,
+            overlaps: false,
             date: day,
             start_time,
             end_time,
@@ -973,6 +2703,7 @@ mod tests {
                 name: "none".to_string(),
                 color: Color::White,
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
             Category {
@@ -980,6 +2711,7 @@ mod tests {
                 name: "Work".to_string(),
                 color: COLORS[0],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
             Category {
@@ -987,6 +2719,7 @@ mod tests {
                 name: "Work Duplicate Id".to_string(),
                 color: COLORS[1],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
             Category {
@@ -994,6 +2727,7 @@ mod tests {
                 name: "work".to_string(),
                 color: COLORS[2],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
         ];
@@ -1002,52 +2736,699 @@ mod tests {
         let ordered = store.ordered_categories();
 
         assert_eq!(
-            ordered.first().map(|category| category.id),
-            Some(CategoryId::new(0))
+            ordered.first().map(|category| category.id),
+            Some(CategoryId::new(0))
+        );
+        assert_eq!(ordered.len(), 2, "none + one deduped category");
+    }
+
+    #[test]
+    fn test_restore_category_after_delete_keeps_id_and_position() {
+        let mut tracker = TimeTracker::new();
+        let _ = tracker.add_category("Work".to_string(), String::new(), Some(0));
+        let _ = tracker.add_category("Read".to_string(), String::new(), Some(1));
+        let work = tracker.category_by_index(1).cloned().unwrap();
+
+        assert!(tracker.delete_category(1));
+        assert!(!tracker.restore_category(0, tracker.category_by_index(1).cloned().unwrap()));
+        assert!(tracker.restore_category(1, work.clone()));
+        assert_eq!(tracker.category_by_index(1), Some(&work));
+        assert!(!tracker.restore_category(1, work));
+
+        let added = tracker.add_category("Write".to_string(), String::new(), None);
+        assert_eq!(added, Some(CategoryId::new(3)));
+    }
+
+    #[test]
+    fn test_min_session_length_prefers_the_category_threshold() {
+        let session = |id, category_id, elapsed_seconds| Session {
+            id,
+            date: "2026-03-02".to_string(),
+            category_id: CategoryId::new(category_id),
+            description: String::new(),
+            project: String::new(),
+            start_time: "09:00:00".to_string(),
+            end_time: "09:01:00".to_string(),
+            elapsed_seconds,
+            focus: None,
+        };
+        let sessions = vec![
+            session(1, 1, 20),
+            session(2, 1, 45),
+            session(3, 2, 45),
+            session(4, 2, 300),
+        ];
+        let min_length = MinSessionLength {
+            default_seconds: 30,
+            by_category: HashMap::from([(CategoryId::new(2), 120)]),
+        };
+
+        let kept: Vec<usize> = drop_micro_sessions(&sessions, &min_length)
+            .iter()
+            .map(|session| session.id)
+            .collect();
+        assert_eq!(kept, vec![2, 4]);
+        assert_eq!(
+            drop_micro_sessions(&sessions, &MinSessionLength::default()).len(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_carry_over_rules_apply_credit_cap_and_reset() {
+        let hours = |h: i64| h * 3600;
+        let debt_only = CarryOverRules::default();
+        assert_eq!(debt_only.carry(0, 10 * 3600, 7 * 3600), hours(3));
+        assert_eq!(debt_only.carry(hours(3), 10 * 3600, 14 * 3600), 0);
+
+        let capped = CarryOverRules {
+            credit: true,
+            cap_seconds: Some(4 * 3600),
+            reset: CarryReset::Month,
+        };
+        assert_eq!(capped.carry(hours(3), 10 * 3600, 14 * 3600), hours(-1));
+        assert_eq!(capped.carry(0, 10 * 3600, 0), hours(4));
+        assert_eq!(capped.carry(0, 0, 10 * 3600), hours(-4));
+
+        let day = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+        let session = |id, date: NaiveDate, elapsed_seconds| Session {
+            id,
+            date: date.format("%Y-%m-%d").to_string(),
+            category_id: CategoryId::new(1),
+            description: String::new(),
+            project: String::new(),
+            start_time: "09:00:00".to_string(),
+            end_time: "10:00:00".to_string(),
+            elapsed_seconds,
+            focus: None,
+        };
+        let sessions = vec![session(1, day(3, 17), 3600), session(2, day(3, 25), 7200)];
+        let goals = HashMap::from([(CategoryId::new(1), 3 * 3600)]);
+        let current_week = goal_ledger_week(day(4, 8), WeekStart::Rolling);
+        assert_eq!(current_week, day(4, 6));
+
+        let ledger = close_goal_weeks(&[], &sessions, &goals, day(3, 16), current_week, &capped);
+        let carried: Vec<(NaiveDate, i64, i64)> = ledger
+            .iter()
+            .map(|entry| (entry.week_start, entry.carried_in, entry.carried_out))
+            .collect();
+        assert_eq!(
+            carried,
+            vec![
+                (day(3, 16), 0, hours(2)),
+                (day(3, 23), hours(2), hours(3)),
+                (day(3, 30), hours(3), hours(4)),
+            ]
+        );
+        assert_eq!(
+            capped.carried_into(&ledger, CategoryId::new(1), current_week),
+            0
+        );
+    }
+
+    #[test]
+    fn test_session_query_filters_and_pages_newest_first() {
+        let session = |id, date: &str, start: &str, category_id, description: &str| Session {
+            id,
+            date: date.to_string(),
+            category_id: CategoryId::new(category_id),
+            description: description.to_string(),
+            project: String::new(),
+            start_time: start.to_string(),
+            end_time: "23:00:00".to_string(),
+            elapsed_seconds: 600,
+            focus: None,
+        };
+        let sessions = vec![
+            session(1, "2026-03-01", "09:00:00", 1, "review"),
+            session(2, "2026-03-02", "14:00:00", 1, "Review "),
+            session(3, "2026-03-02", "09:00:00", 2, "review"),
+            session(4, "2026-03-03", "09:00:00", 1, "standup"),
+            session(5, "2026-03-02", "11:00:00", 1, ""),
+        ];
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let ids = |page: SessionPage| -> Vec<usize> {
+            page.sessions.iter().map(|session| session.id).collect()
+        };
+
+        assert_eq!(ids(SessionQuery::new().run(&sessions)), vec![4, 2, 5, 3, 1]);
+        assert_eq!(
+            ids(SessionQuery::new().between(day(2), day(2)).run(&sessions)),
+            vec![2, 5, 3]
+        );
+        assert_eq!(
+            ids(SessionQuery::new()
+                .category(CategoryId::new(1))
+                .tag("review")
+                .run(&sessions)),
+            vec![2, 1]
+        );
+        assert_eq!(
+            ids(SessionQuery::new()
+                .since(day(2))
+                .until(day(3))
+                .run(&sessions)),
+            vec![4, 2, 5, 3]
+        );
+
+        let page = SessionQuery::new().offset(1).limit(2).run(&sessions);
+        assert_eq!(page.total, 5);
+        assert_eq!(ids(page), vec![2, 5]);
+        assert_eq!(
+            SessionQuery::new().offset(9).run(&sessions).sessions.len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_tracking_streak_counts_back_from_today_or_yesterday() {
+        let session = |id, date: &str, category_id| Session {
+            id,
+            date: date.to_string(),
+            category_id: CategoryId::new(category_id),
+            description: String::new(),
+            project: String::new(),
+            start_time: "09:00:00".to_string(),
+            end_time: "10:00:00".to_string(),
+            elapsed_seconds: 3600,
+            focus: None,
+        };
+        let sessions = vec![
+            session(1, "2026-03-02", 1),
+            session(2, "2026-03-03", 1),
+            session(3, "2026-03-04", 1),
+            session(4, "2026-03-05", 0),
+            session(5, "2026-02-27", 1),
+        ];
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+
+        assert_eq!(tracking_streak(&sessions, day(4)), 3);
+        assert_eq!(tracking_streak(&sessions, day(5)), 3);
+        assert_eq!(tracking_streak(&sessions, day(6)), 0);
+        assert_eq!(tracking_streak(&[], day(4)), 0);
+    }
+
+    #[test]
+    fn test_rolling_day_clips_sessions_to_the_last_24_hours() {
+        let session = |id, date: &str, start: &str, end: &str, elapsed| Session {
+            id,
+            date: date.to_string(),
+            category_id: CategoryId::new(1),
+            description: String::new(),
+            project: String::new(),
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            elapsed_seconds: elapsed,
+            focus: None,
+        };
+        let sessions = vec![
+            session(1, "2026-02-23", "01:00:00", "04:00:00", 3 * 3600),
+            session(2, "2026-02-24", "23:00:00", "01:00:00", 2 * 3600),
+            session(3, "2026-02-23", "10:00:00", "12:00:00", 2 * 3600),
+            session(4, "2026-02-23", "02:00:00", "03:00:00", 3600),
+        ];
+        let now = NaiveDate::from_ymd_opt(2026, 2, 25)
+            .unwrap()
+            .and_hms_opt(3, 0, 0)
+            .unwrap();
+
+        let clipped = rolling_day_sessions(&sessions, now);
+        let ids: Vec<usize> = clipped.iter().map(|session| session.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(clipped[0].start_time, "03:00:00");
+        assert_eq!(clipped[0].elapsed_seconds, 3600);
+        assert_eq!(clipped[1].date, "2026-02-24");
+        assert_eq!(clipped[1].elapsed_seconds, 2 * 3600);
+
+        assert_eq!(
+            rolling_day_bounds(now),
+            (
+                NaiveDate::from_ymd_opt(2026, 2, 23).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 2, 24).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_rename_category_keeps_id_and_sessions() {
+        let mut tracker = TimeTracker::new();
+        let _ = tracker.add_category("Work".to_string(), String::new(), Some(0));
+        let _ = tracker.add_category("Read".to_string(), String::new(), Some(1));
+        tracker.record_session(CategoryId::new(1), "work session", 100);
+
+        assert!(tracker.rename_category_by_index(1, " Deep work "));
+        let renamed = tracker.category_by_index(1).unwrap();
+        assert_eq!(renamed.id, CategoryId::new(1));
+        assert_eq!(renamed.name, "Deep work");
+        assert_eq!(tracker.sessions[0].category_id, CategoryId::new(1));
+
+        assert!(!tracker.rename_category_by_index(1, "read"));
+        assert!(!tracker.rename_category_by_index(1, "  "));
+        assert!(!tracker.rename_category_by_index(1, "None"));
+        assert!(!tracker.rename_category_by_index(0, "Idle"));
+        assert!(tracker.rename_category_by_index(1, "deep work"));
+    }
+
+    #[test]
+    fn test_edit_session_keeps_start_and_moves_end() {
+        let mut tracker = TimeTracker::new();
+        let _ = tracker.add_category("Work".to_string(), String::new(), Some(0));
+        tracker.sessions.push(Session {
+            id: 7,
+            date: "2026-02-25".to_string(),
+            category_id: CategoryId::new(0),
+            description: String::new(),
+            project: String::new(),
+            start_time: "09:00:00".to_string(),
+            end_time: "10:00:00".to_string(),
+            elapsed_seconds: 3600,
+            focus: None,
+        });
+
+        assert!(tracker.edit_session(7, CategoryId::new(1), " review ", 5400));
+        let session = &tracker.sessions[0];
+        assert_eq!(session.category_id, CategoryId::new(1));
+        assert_eq!(session.description, "review");
+        assert_eq!(session.start_time, "09:00:00");
+        assert_eq!(session.end_time, "10:30:00");
+        assert_eq!(session.elapsed_seconds, 5400);
+
+        assert!(!tracker.edit_session(7, CategoryId::new(9), "", 60));
+        assert!(!tracker.edit_session(8, CategoryId::new(1), "", 60));
+    }
+
+    #[test]
+    fn test_category_id_stability_on_reorder() {
+        let mut tracker = TimeTracker::new();
+        let _ = tracker.add_category("Work".to_string(), "Work category".to_string(), Some(0));
+        let _ = tracker.add_category(
+            "Personal".to_string(),
+            "Personal category".to_string(),
+            Some(1),
+        );
+
+        tracker.record_session(CategoryId::new(1), "work session", 100);
+        tracker.record_session(CategoryId::new(2), "personal session", 200);
+
+        let work_count_before = tracker
+            .sessions
+            .iter()
+            .filter(|session| session.category_id == CategoryId::new(1))
+            .count();
+        let personal_count_before = tracker
+            .sessions
+            .iter()
+            .filter(|session| session.category_id == CategoryId::new(2))
+            .count();
+
+        let moved_down = tracker.move_category_down(1);
+        assert!(moved_down);
+
+        let work_count_after = tracker
+            .sessions
+            .iter()
+            .filter(|session| session.category_id == CategoryId::new(1))
+            .count();
+        let personal_count_after = tracker
+            .sessions
+            .iter()
+            .filter(|session| session.category_id == CategoryId::new(2))
+            .count();
+
+        assert_eq!(work_count_before, work_count_after);
+        assert_eq!(personal_count_before, personal_count_after);
+    }
+
+    #[test]
+    fn test_default_description_applies_per_session_without_mutating_category() {
+        let mut tracker = TimeTracker::new();
+        let _ = tracker.add_category("Work".to_string(), String::new(), Some(0));
+        assert!(tracker.set_category_default_description_by_index(1, "deep work".to_string()));
+        assert!(tracker.set_active_category_by_index(1));
+
+        tracker.start_session();
+        assert_eq!(tracker.session_description(), "deep work");
+        tracker.set_session_description("review".to_string());
+        tracker.end_session();
+
+        tracker.start_session();
+        assert_eq!(tracker.session_description(), "deep work");
+        tracker.end_session();
+
+        assert_eq!(tracker.sessions[0].description, "review");
+        assert_eq!(tracker.sessions[1].description, "deep work");
+        assert_eq!(
+            tracker
+                .category_by_index(1)
+                .map(|category| category.default_description.as_str()),
+            Some("deep work")
+        );
+        assert_eq!(tracker.session_description(), "");
+    }
+
+    #[test]
+    fn test_iso_period_labels_use_week_numbers_and_month_names() {
+        let start = NaiveDate::from_ymd_opt(2026, 2, 19).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 2, 25).unwrap();
+
+        assert_eq!(
+            format_period_label(ReportPeriod::Week, start, end, PeriodLabelStyle::Iso),
+            "2026-W09"
+        );
+        assert_eq!(
+            format_period_label(ReportPeriod::Month, start, end, PeriodLabelStyle::Iso),
+            "February 2026"
+        );
+        assert_eq!(
+            period_file_stem(ReportPeriod::Month, start, end, PeriodLabelStyle::Iso),
+            "2026-02"
+        );
+        assert_eq!(
+            format_period_label(ReportPeriod::Week, start, end, PeriodLabelStyle::Range),
+            "2026-02-19..2026-02-25"
+        );
+
+        let new_year = NaiveDate::from_ymd_opt(2027, 1, 1).unwrap();
+        assert_eq!(
+            period_file_stem(
+                ReportPeriod::Week,
+                new_year,
+                new_year,
+                PeriodLabelStyle::Iso
+            ),
+            "2026-W53"
+        );
+    }
+
+    #[test]
+    fn test_iso_period_labels_cover_their_calendar_period() {
+        // 2026-02-25 is the Wednesday of 2026-W09.
+        let today = NaiveDate::from_ymd_opt(2026, 2, 25).unwrap();
+        let day = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+        let labelled = |period, week_start| {
+            let (start, end) = period_range(period, today, week_start, PeriodLabelStyle::Iso);
+            let label = format_period_label(period, start, end, PeriodLabelStyle::Iso);
+            (label, start, end)
+        };
+
+        for week_start in [WeekStart::Rolling, WeekStart::Sunday, WeekStart::Monday] {
+            let (label, start, end) = labelled(ReportPeriod::Week, week_start);
+            assert_eq!(
+                (label.as_str(), start, end),
+                ("2026-W09", day(2, 23), today)
+            );
+            assert_eq!(start.iso_week(), end.iso_week());
+        }
+
+        let (label, start, end) = labelled(ReportPeriod::Month, WeekStart::Rolling);
+        assert_eq!(
+            (label.as_str(), start, end),
+            ("February 2026", day(2, 1), today)
+        );
+
+        assert_eq!(
+            period_range(
+                ReportPeriod::Month,
+                today,
+                WeekStart::Rolling,
+                PeriodLabelStyle::Range
+            ),
+            (day(1, 27), today)
+        );
+        assert_eq!(
+            period_range(
+                ReportPeriod::Week,
+                today,
+                WeekStart::Rolling,
+                PeriodLabelStyle::Range
+            ),
+            (day(2, 19), today)
+        );
+    }
+
+    #[test]
+    fn test_build_invoice_prefers_project_rate_and_applies_tax() {
+        let mut sessions = overlap_test_sessions();
+        sessions[0].project = "acme".to_string();
+        sessions[1].elapsed_seconds = 1800;
+        let categories = vec![
+            Category {
+                id: CategoryId::new(1),
+                name: "work".to_string(),
+                color: COLORS[0],
+                description: String::new(),
+                default_description: String::new(),
+                karma_effect: 1,
+            },
+            Category {
+                id: CategoryId::new(2),
+                name: "admin".to_string(),
+                color: COLORS[1],
+                description: String::new(),
+                default_description: String::new(),
+                karma_effect: 1,
+            },
+        ];
+        let category_rates = HashMap::from([(CategoryId::new(1), 50.0)]);
+        let project_rates = HashMap::from([("acme".to_string(), 80.0)]);
+        let day = NaiveDate::from_ymd_opt(2026, 2, 25).unwrap();
+
+        let invoice = build_invoice(
+            &sessions,
+            &categories,
+            day,
+            day,
+            &category_rates,
+            &project_rates,
+            20.0,
+        );
+
+        assert_eq!(invoice.lines.len(), 1);
+        assert_eq!(invoice.lines[0].project, "acme");
+        assert_eq!(invoice.lines[0].hourly_rate, 80.0);
+        assert_eq!(invoice.lines[0].amount, 80.0);
+        assert_eq!(invoice.unbilled_seconds, 1800);
+        assert_eq!(invoice.subtotal, 80.0);
+        assert_eq!(invoice.tax, 16.0);
+        assert_eq!(invoice.total, 96.0);
+    }
+
+    #[test]
+    fn test_latest_clock_time_before_rolls_back_past_midnight() {
+        let now = NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(0, 10, 0)
+            .unwrap();
+        assert_eq!(
+            latest_clock_time_before(now, "00:05"),
+            NaiveDate::from_ymd_opt(2026, 3, 2)
+                .unwrap()
+                .and_hms_opt(0, 5, 0)
+        );
+        assert_eq!(
+            latest_clock_time_before(now, "23:50"),
+            NaiveDate::from_ymd_opt(2026, 3, 1)
+                .unwrap()
+                .and_hms_opt(23, 50, 0)
+        );
+        assert_eq!(latest_clock_time_before(now, "25:00"), None);
+    }
+
+    #[test]
+    fn test_revision_bumps_on_mutation_only() {
+        let mut tracker = TimeTracker::new();
+        let initial = tracker.revision();
+
+        let _ = tracker.add_category("Work".to_string(), String::new(), Some(0));
+        let after_add = tracker.revision();
+        assert_ne!(after_add, initial);
+
+        assert!(!tracker.move_category_up(0));
+        assert_eq!(tracker.categories_ordered().count(), 2);
+        assert_eq!(tracker.revision(), after_add);
+
+        tracker.record_session(CategoryId::new(1), "focus", 60);
+        assert_ne!(tracker.revision(), after_add);
+    }
+
+    #[test]
+    fn test_paused_time_is_not_counted() {
+        let mut tracker = TimeTracker::new();
+        let _ = tracker.add_category("Work".to_string(), String::new(), Some(0));
+        assert!(tracker.set_active_category_by_index(1));
+        tracker.current_session_start =
+            Instant::now().checked_sub(std::time::Duration::from_secs(100));
+
+        assert!(tracker.toggle_pause());
+        // Simulate a 50s pause.
+        tracker.paused_at = tracker
+            .paused_at
+            .and_then(|paused| paused.checked_sub(std::time::Duration::from_secs(50)));
+        assert_eq!(tracker.session_elapsed().map(|d| d.as_secs()), Some(50));
+
+        assert!(!tracker.toggle_pause());
+        assert!(!tracker.is_paused());
+        assert_eq!(tracker.end_session(), Some(50));
+        assert!(!tracker.toggle_pause());
+    }
+
+    #[test]
+    fn test_session_project_is_recorded_and_cleared() {
+        let mut tracker = TimeTracker::new();
+        let _ = tracker.add_category("Work".to_string(), String::new(), Some(0));
+        assert!(tracker.set_active_category_by_index(1));
+        tracker.set_session_project("strata".to_string());
+        tracker.current_session_start =
+            Instant::now().checked_sub(std::time::Duration::from_secs(100));
+
+        assert_eq!(tracker.end_session(), Some(100));
+        assert_eq!(tracker.sessions.last().unwrap().project, "strata");
+        assert_eq!(tracker.session_project(), "");
+
+        tracker.set_session_project("strata".to_string());
+        let _ = tracker.start_session();
+        tracker.discard_session();
+        assert!(tracker.current_session_start.is_none());
+        assert_eq!(tracker.session_project(), "");
+        assert_eq!(tracker.sessions.len(), 1);
+    }
+
+    #[test]
+    fn test_end_session_idle_trims_idle_tail() {
+        let mut tracker = TimeTracker::new();
+        let _ = tracker.add_category("Work".to_string(), String::new(), Some(0));
+        assert!(tracker.set_active_category_by_index(1));
+        tracker.set_session_description("deep work".to_string());
+        tracker.current_session_start =
+            Instant::now().checked_sub(std::time::Duration::from_secs(100));
+
+        let elapsed = tracker.end_session_idle(40);
+
+        assert_eq!(elapsed, Some(60));
+        assert!(tracker.current_session_start.is_none());
+        assert_eq!(tracker.session_description(), "deep work");
+        let session = tracker.sessions.last().unwrap();
+        assert_eq!(session.elapsed_seconds, 60);
+        assert_eq!(session.description, "deep work");
+    }
+
+    #[test]
+    fn test_split_idle_tail_keeps_the_session_running() {
+        let mut tracker = TimeTracker::new();
+        let _ = tracker.add_category("Work".to_string(), String::new(), Some(0));
+        let _ = tracker.add_category("Break".to_string(), String::new(), Some(1));
+        assert!(tracker.set_active_category_by_index(1));
+        tracker.set_session_description("deep work".to_string());
+        let started_100s_ago = || Instant::now().checked_sub(std::time::Duration::from_secs(100));
+        tracker.current_session_start = started_100s_ago();
+
+        assert_eq!(
+            tracker.split_idle_tail(40, Some(CategoryId::new(2))),
+            Some(60)
+        );
+        let [kept, idle] = tracker.sessions.as_slice() else {
+            panic!("expected two sessions");
+        };
+        assert_eq!(
+            (kept.category_id, kept.elapsed_seconds),
+            (CategoryId::new(1), 60)
+        );
+        assert_eq!(kept.description, "deep work");
+        assert_eq!(
+            (idle.category_id, idle.elapsed_seconds),
+            (CategoryId::new(2), 40)
         );
-        assert_eq!(ordered.len(), 2, "none + one deduped category");
+        assert_eq!(tracker.active_category_id(), CategoryId::new(1));
+        assert!(tracker.session_elapsed().unwrap().as_secs() < 2);
+
+        tracker.current_session_start = started_100s_ago();
+        assert_eq!(tracker.split_idle_tail(30, None), Some(70));
+        assert_eq!(tracker.sessions.len(), 3);
+        assert_eq!(tracker.sessions[2].elapsed_seconds, 70);
+    }
+
+    fn switch_at(
+        tracker: &mut TimeTracker,
+        index: usize,
+        at: Instant,
+        wall: DateTime<Local>,
+    ) -> bool {
+        tracker.end_session_at(at, wall);
+        assert!(tracker.set_active_category_by_index(index));
+        tracker.start_session_at(at)
     }
 
     #[test]
-    fn test_category_id_stability_on_reorder() {
+    fn test_switching_back_within_grace_resumes_the_session() {
         let mut tracker = TimeTracker::new();
-        let _ = tracker.add_category("Work".to_string(), "Work category".to_string(), Some(0));
-        let _ = tracker.add_category(
-            "Personal".to_string(),
-            "Personal category".to_string(),
-            Some(1),
+        let _ = tracker.add_category("Work".to_string(), String::new(), Some(0));
+        let _ = tracker.add_category("Chat".to_string(), String::new(), Some(1));
+        tracker.set_switch_grace(Duration::from_secs(60));
+        let start = Instant::now();
+        let wall = Local.with_ymd_and_hms(2026, 3, 2, 10, 0, 0).unwrap();
+        let at = |seconds: u64| start + Duration::from_secs(seconds);
+        let wall_at = |seconds: i64| wall + ChronoDuration::seconds(seconds);
+
+        assert!(tracker.set_active_category_by_index(1));
+        assert!(!tracker.start_session_at(start));
+        assert!(!switch_at(&mut tracker, 2, at(600), wall_at(600)));
+        assert_eq!(tracker.sessions.len(), 1);
+        assert!(switch_at(&mut tracker, 1, at(630), wall_at(630)));
+
+        assert!(tracker.sessions.is_empty());
+        assert_eq!(
+            tracker.session_elapsed_at(at(630)).map(|d| d.as_secs()),
+            Some(630)
         );
+        assert_eq!(tracker.end_session_at(at(900), wall_at(900)), Some(900));
+        let session = tracker.sessions.last().unwrap();
+        assert_eq!(session.category_id, CategoryId::new(1));
+        assert_eq!(session.start_time, "10:00:00");
+        assert_eq!(session.end_time, "10:15:00");
+    }
 
-        tracker.record_session(CategoryId::new(1), "work session", 100);
-        tracker.record_session(CategoryId::new(2), "personal session", 200);
-
-        let work_count_before = tracker
-            .sessions
-            .iter()
-            .filter(|session| session.category_id == CategoryId::new(1))
-            .count();
-        let personal_count_before = tracker
-            .sessions
-            .iter()
-            .filter(|session| session.category_id == CategoryId::new(2))
-            .count();
+    #[test]
+    fn test_switches_outside_grace_keep_their_rows() {
+        let start = Instant::now();
+        let wall = Local.with_ymd_and_hms(2026, 3, 2, 10, 0, 0).unwrap();
+        let at = |seconds: u64| start + Duration::from_secs(seconds);
+        let wall_at = |seconds: i64| wall + ChronoDuration::seconds(seconds);
+        let tracker_with_grace = |grace| {
+            let mut tracker = TimeTracker::new();
+            let _ = tracker.add_category("Work".to_string(), String::new(), Some(0));
+            let _ = tracker.add_category("Chat".to_string(), String::new(), Some(1));
+            let _ = tracker.add_category("Read".to_string(), String::new(), Some(2));
+            tracker.set_switch_grace(Duration::from_secs(grace));
+            assert!(tracker.set_active_category_by_index(1));
+            tracker.start_session_at(start);
+            tracker
+        };
 
-        let moved_down = tracker.move_category_down(1);
-        assert!(moved_down);
+        // The fragment ran longer than the grace period.
+        let mut tracker = tracker_with_grace(60);
+        switch_at(&mut tracker, 2, at(600), wall_at(600));
+        assert!(!switch_at(&mut tracker, 1, at(690), wall_at(690)));
+        assert_eq!(tracker.sessions.len(), 2);
 
-        let work_count_after = tracker
-            .sessions
-            .iter()
-            .filter(|session| session.category_id == CategoryId::new(1))
-            .count();
-        let personal_count_after = tracker
-            .sessions
-            .iter()
-            .filter(|session| session.category_id == CategoryId::new(2))
-            .count();
+        // Moving on to a third category is not a switch back.
+        let mut tracker = tracker_with_grace(60);
+        switch_at(&mut tracker, 2, at(600), wall_at(600));
+        assert!(!switch_at(&mut tracker, 3, at(630), wall_at(630)));
+        assert_eq!(tracker.sessions.len(), 2);
 
-        assert_eq!(work_count_before, work_count_after);
-        assert_eq!(personal_count_before, personal_count_after);
+        // A new description means a new task.
+        let mut tracker = tracker_with_grace(60);
+        switch_at(&mut tracker, 2, at(600), wall_at(600));
+        tracker.end_session_at(at(630), wall_at(630));
+        assert!(tracker.set_active_category_by_index(1));
+        tracker.set_session_description("review".to_string());
+        assert!(!tracker.start_session_at(at(630)));
+
+        // Grace zero turns coalescing off.
+        let mut tracker = tracker_with_grace(0);
+        switch_at(&mut tracker, 2, at(600), wall_at(600));
+        assert!(!switch_at(&mut tracker, 1, at(610), wall_at(610)));
+        assert_eq!(tracker.sessions.len(), 2);
     }
 
     #[test]
@@ -1085,6 +3466,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_suggest_activity_sessions_merges_and_cuts_afk() {
+        let at = |minute: u32| {
+            Utc.with_ymd_and_hms(2026, 2, 10, 9, minute, 0)
+                .single()
+                .expect("valid datetime")
+        };
+        let event = |start, end, app: &str| ActivityEvent {
+            start: at(start),
+            end: at(end),
+            app: app.to_string(),
+            title: String::new(),
+        };
+        let events = vec![
+            event(0, 10, "code"),
+            event(11, 30, "terminal"),
+            event(30, 32, "slack"),
+            event(32, 40, "browser"),
+            event(41, 50, "code"),
+        ];
+        let afk = vec![(at(20), at(25))];
+        let categorize = |event: &ActivityEvent| match event.app.as_str() {
+            "code" | "terminal" => Some("Work".to_string()),
+            "browser" => Some("Reading".to_string()),
+            _ => None,
+        };
+
+        let suggestions = suggest_activity_sessions(&events, &afk, categorize, 120, 300);
+
+        let summary: Vec<_> = suggestions
+            .iter()
+            .map(|s| (s.start, s.end, s.category.as_str(), s.app.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (at(0), at(20), "Work", "code"),
+                (at(25), at(30), "Work", "terminal"),
+                (at(32), at(40), "Reading", "browser"),
+                (at(41), at(50), "Work", "code"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_session_end_checks_order_and_day_span() {
+        let config = DayBoundaryConfig {
+            utc_offset_seconds: 0,
+            ..DayBoundaryConfig::default()
+        };
+        let at = |day, hour| {
+            Utc.with_ymd_and_hms(2026, 2, day, hour, 0, 0)
+                .single()
+                .expect("valid datetime")
+        };
+
+        assert!(validate_session_end_with_config(at(10, 9), at(10, 17), &config).is_ok());
+        assert!(validate_session_end_with_config(at(10, 22), at(11, 7), &config).is_ok());
+        assert!(validate_session_end_with_config(at(10, 17), at(10, 9), &config).is_err());
+        assert!(validate_session_end_with_config(at(10, 9), at(10, 9), &config).is_err());
+        assert!(validate_session_end_with_config(at(10, 9), at(12, 9), &config).is_err());
+    }
+
     #[test]
     fn test_build_report_for_date_excludes_none_and_sorts() {
         let categories = vec![
@@ -1093,6 +3537,7 @@ mod tests {
                 name: "none".to_string(),
                 color: Color::White,
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
             Category {
@@ -1100,6 +3545,7 @@ mod tests {
                 name: "Work".to_string(),
                 color: COLORS[0],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
             Category {
@@ -1107,6 +3553,7 @@ mod tests {
                 name: "Personal".to_string(),
                 color: COLORS[1],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
         ];
@@ -1117,36 +3564,44 @@ mod tests {
                 date: "2026-02-25".to_string(),
                 category_id: CategoryId::new(1),
                 description: String::new(),
+                project: String::new(),
                 start_time: "09:00:00".to_string(),
                 end_time: "10:00:00".to_string(),
                 elapsed_seconds: 3600,
+                focus: None,
             },
             Session {
                 id: 2,
                 date: "2026-02-25".to_string(),
                 category_id: CategoryId::new(2),
                 description: String::new(),
+                project: String::new(),
                 start_time: "10:00:00".to_string(),
                 end_time: "10:30:00".to_string(),
                 elapsed_seconds: 1800,
+                focus: None,
             },
             Session {
                 id: 3,
                 date: "2026-02-25".to_string(),
                 category_id: CategoryId::new(0),
                 description: String::new(),
+                project: String::new(),
                 start_time: "11:00:00".to_string(),
                 end_time: "12:00:00".to_string(),
                 elapsed_seconds: 3600,
+                focus: None,
             },
             Session {
                 id: 4,
                 date: "2026-02-24".to_string(),
                 category_id: CategoryId::new(1),
                 description: String::new(),
+                project: String::new(),
                 start_time: "09:00:00".to_string(),
                 end_time: "10:00:00".to_string(),
                 elapsed_seconds: 3600,
+                focus: None,
             },
         ];
 
@@ -1167,6 +3622,7 @@ mod tests {
                 name: "none".to_string(),
                 color: Color::White,
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
             Category {
@@ -1174,6 +3630,7 @@ mod tests {
                 name: "Work".to_string(),
                 color: COLORS[0],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
             Category {
@@ -1181,6 +3638,7 @@ mod tests {
                 name: "Gaming".to_string(),
                 color: COLORS[5],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: -1,
             },
             Category {
@@ -1188,6 +3646,7 @@ mod tests {
                 name: "Reading".to_string(),
                 color: COLORS[2],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
         ];
@@ -1198,18 +3657,22 @@ mod tests {
                 date: "2026-02-25".to_string(),
                 category_id: CategoryId::new(1),
                 description: String::new(),
+                project: String::new(),
                 start_time: "08:00:00".to_string(),
                 end_time: "09:00:00".to_string(),
                 elapsed_seconds: 3600,
+                focus: None,
             },
             Session {
                 id: 2,
                 date: "2026-02-25".to_string(),
                 category_id: CategoryId::new(2),
                 description: String::new(),
+                project: String::new(),
                 start_time: "10:00:00".to_string(),
                 end_time: "10:30:00".to_string(),
                 elapsed_seconds: 1800,
+                focus: None,
             },
         ];
 
@@ -1263,6 +3726,7 @@ mod tests {
                 name: "none".to_string(),
                 color: Color::White,
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
             Category {
@@ -1270,6 +3734,7 @@ mod tests {
                 name: "Work".to_string(),
                 color: COLORS[0],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
         ];
@@ -1280,18 +3745,22 @@ mod tests {
                 date: "2026-02-25".to_string(),
                 category_id: CategoryId::new(0),
                 description: String::new(),
+                project: String::new(),
                 start_time: "08:00:00".to_string(),
                 end_time: "08:20:00".to_string(),
                 elapsed_seconds: 1200,
+                focus: None,
             },
             Session {
                 id: 2,
                 date: "2026-02-25".to_string(),
                 category_id: CategoryId::new(1),
                 description: String::new(),
+                project: String::new(),
                 start_time: "09:00:00".to_string(),
                 end_time: "09:30:00".to_string(),
                 elapsed_seconds: 1800,
+                focus: None,
             },
         ];
 
@@ -1318,6 +3787,7 @@ mod tests {
                 name: "none".to_string(),
                 color: Color::White,
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
             Category {
@@ -1325,6 +3795,7 @@ mod tests {
                 name: "Work".to_string(),
                 color: COLORS[0],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
         ];
@@ -1343,27 +3814,33 @@ mod tests {
                 date: today.format("%Y-%m-%d").to_string(),
                 category_id: CategoryId::new(1),
                 description: String::new(),
+                project: String::new(),
                 start_time: "09:00:00".to_string(),
                 end_time: "10:00:00".to_string(),
                 elapsed_seconds: 3600,
+                focus: None,
             },
             Session {
                 id: 2,
                 date: in_window,
                 category_id: CategoryId::new(1),
                 description: String::new(),
+                project: String::new(),
                 start_time: "09:00:00".to_string(),
                 end_time: "09:30:00".to_string(),
                 elapsed_seconds: 1800,
+                focus: None,
             },
             Session {
                 id: 3,
                 date: out_window,
                 category_id: CategoryId::new(1),
                 description: String::new(),
+                project: String::new(),
                 start_time: "09:00:00".to_string(),
                 end_time: "11:00:00".to_string(),
                 elapsed_seconds: 7200,
+                focus: None,
             },
         ];
 
@@ -1374,6 +3851,67 @@ mod tests {
         assert_eq!(summary.entries[0].elapsed_seconds, 5400);
     }
 
+    #[test]
+    fn test_build_range_karma_report_counts_only_the_range() {
+        let categories = vec![Category {
+            id: CategoryId::new(1),
+            name: "Work".to_string(),
+            color: COLORS[0],
+            description: String::new(),
+            default_description: String::new(),
+            karma_effect: 1,
+        }];
+        let today = operational_day_key_now();
+        let session = |id: usize, days_ago: i64| Session {
+            id,
+            date: (today - ChronoDuration::days(days_ago))
+                .format("%Y-%m-%d")
+                .to_string(),
+            category_id: CategoryId::new(1),
+            description: String::new(),
+            project: String::new(),
+            start_time: "08:00:00".to_string(),
+            end_time: "09:00:00".to_string(),
+            elapsed_seconds: 3600,
+            focus: None,
+        };
+        let sessions = vec![session(1, 40), session(2, 45), session(3, 50)];
+        let live = LiveSessionPreview {
+            category_id: CategoryId::new(1),
+            description: String::new(),
+            elapsed_seconds: 60,
+            now_local: Local::now(),
+        };
+
+        let start = today - ChronoDuration::days(46);
+        let end = today - ChronoDuration::days(40);
+        let summary =
+            build_range_karma_report_with_live(&sessions, &categories, start, end, Some(&live));
+        assert_eq!(summary.total_seconds, 7200);
+        assert_eq!(
+            summary.date,
+            format!("{}..{}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"))
+        );
+
+        let summary =
+            build_range_karma_report_with_live(&sessions, &categories, start, today, Some(&live));
+        assert_eq!(summary.total_seconds, 7260);
+
+        let logs = build_category_logs_for_date_range(
+            &sessions,
+            &KeptOverlaps::new(),
+            &categories,
+            CategoryId::new(1),
+            start,
+            end,
+            Some(&live),
+        );
+        assert_eq!(
+            logs.iter().map(|log| log.session_id).collect::<Vec<_>>(),
+            vec![Some(1), Some(2)]
+        );
+    }
+
     #[test]
     fn test_build_period_karma_report_month_aggregates_range() {
         let categories = vec![
@@ -1382,6 +3920,7 @@ mod tests {
                 name: "none".to_string(),
                 color: Color::White,
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
             Category {
@@ -1389,6 +3928,7 @@ mod tests {
                 name: "Work".to_string(),
                 color: COLORS[0],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
             Category {
@@ -1396,6 +3936,7 @@ mod tests {
                 name: "Gaming".to_string(),
                 color: COLORS[5],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: -1,
             },
         ];
@@ -1414,27 +3955,33 @@ mod tests {
                 date: in_window,
                 category_id: CategoryId::new(1),
                 description: String::new(),
+                project: String::new(),
                 start_time: "08:00:00".to_string(),
                 end_time: "09:00:00".to_string(),
                 elapsed_seconds: 3600,
+                focus: None,
             },
             Session {
                 id: 2,
                 date: today.format("%Y-%m-%d").to_string(),
                 category_id: CategoryId::new(2),
                 description: String::new(),
+                project: String::new(),
                 start_time: "10:00:00".to_string(),
                 end_time: "10:30:00".to_string(),
                 elapsed_seconds: 1800,
+                focus: None,
             },
             Session {
                 id: 3,
                 date: out_window,
                 category_id: CategoryId::new(1),
                 description: String::new(),
+                project: String::new(),
                 start_time: "12:00:00".to_string(),
                 end_time: "13:00:00".to_string(),
                 elapsed_seconds: 3600,
+                focus: None,
             },
         ];
 
@@ -1467,6 +4014,7 @@ mod tests {
                 name: "none".to_string(),
                 color: Color::White,
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
             Category {
@@ -1474,6 +4022,7 @@ mod tests {
                 name: "Work".to_string(),
                 color: COLORS[0],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
         ];
@@ -1484,9 +4033,11 @@ mod tests {
             date: today.clone(),
             category_id: CategoryId::new(1),
             description: String::new(),
+            project: String::new(),
             start_time: "09:00:00".to_string(),
             end_time: "09:10:00".to_string(),
             elapsed_seconds: 600,
+            focus: None,
         }];
 
         let summary = build_period_karma_report(&sessions, &categories, ReportPeriod::Today);
@@ -1509,32 +4060,42 @@ mod tests {
                 date: today.clone(),
                 category_id: CategoryId::new(0),
                 description: String::new(),
+                project: String::new(),
                 start_time: "08:00:00".to_string(),
                 end_time: "08:10:00".to_string(),
                 elapsed_seconds: 600,
+                focus: None,
             },
             Session {
                 id: 2,
                 date: yesterday,
                 category_id: CategoryId::new(0),
                 description: String::new(),
+                project: String::new(),
                 start_time: "08:00:00".to_string(),
                 end_time: "08:10:00".to_string(),
                 elapsed_seconds: 600,
+                focus: None,
             },
             Session {
                 id: 3,
                 date: today,
                 category_id: CategoryId::new(1),
                 description: String::new(),
+                project: String::new(),
                 start_time: "09:00:00".to_string(),
                 end_time: "09:10:00".to_string(),
                 elapsed_seconds: 600,
+                focus: None,
             },
         ];
 
-        tracker.reset_none_counter_today();
+        let removed = tracker.reset_none_counter_today();
 
+        assert_eq!(
+            removed.iter().map(|session| session.id).collect::<Vec<_>>(),
+            vec![1]
+        );
         assert_eq!(
             tracker
                 .sessions
@@ -1555,6 +4116,7 @@ mod tests {
                 name: "none".to_string(),
                 color: Color::White,
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 0,
             },
             Category {
@@ -1562,6 +4124,7 @@ mod tests {
                 name: "Work".to_string(),
                 color: COLORS[0],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
         ];
@@ -1572,23 +4135,28 @@ mod tests {
                 date: today.clone(),
                 category_id: CategoryId::new(1),
                 description: "focus".to_string(),
+                project: String::new(),
                 start_time: "09:00:00".to_string(),
                 end_time: "09:10:00".to_string(),
                 elapsed_seconds: 600,
+                focus: None,
             },
             Session {
                 id: 2,
                 date: today,
                 category_id: CategoryId::new(1),
                 description: "review".to_string(),
+                project: String::new(),
                 start_time: "10:00:00".to_string(),
                 end_time: "10:05:00".to_string(),
                 elapsed_seconds: 300,
+                focus: None,
             },
         ];
 
         let logs = build_category_logs_for_period(
             &sessions,
+            &KeptOverlaps::new(),
             &categories,
             CategoryId::new(1),
             ReportPeriod::Today,
@@ -1599,4 +4167,481 @@ mod tests {
         assert!(logs.iter().any(|row| row.description == "focus"));
         assert!(logs.iter().any(|row| row.description == "review"));
     }
+
+    #[test]
+    fn test_diff_records_reports_added_removed_and_modified() {
+        let mut before = overlap_test_sessions();
+        let mut after = before.clone();
+        after.remove(0);
+        after[0].description = "edited".to_string();
+        let mut added = before[0].clone();
+        added.id = 9;
+        after.push(added);
+        before.truncate(3);
+
+        let changes = diff_records(&before, &after, |session| session.id);
+        assert_eq!(
+            changes.added.iter().map(|s| s.id).collect::<Vec<_>>(),
+            vec![9]
+        );
+        assert_eq!(
+            changes.removed.iter().map(|s| s.id).collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert_eq!(changes.modified.len(), 1);
+        assert_eq!(changes.modified[0].1.description, "edited");
+        assert!(diff_records(&before, &before, |session| session.id).is_empty());
+    }
+
+    #[test]
+    fn test_build_day_timeline_clips_to_operational_day() {
+        let categories = vec![
+            Category {
+                id: CategoryId::new(0),
+                name: "none".to_string(),
+                color: Color::White,
+                description: String::new(),
+                default_description: String::new(),
+                karma_effect: 0,
+            },
+            Category {
+                id: CategoryId::new(1),
+                name: "Work".to_string(),
+                color: COLORS[0],
+                description: String::new(),
+                default_description: String::new(),
+                karma_effect: 1,
+            },
+            Category {
+                id: CategoryId::new(2),
+                name: "Play".to_string(),
+                color: COLORS[1],
+                description: String::new(),
+                default_description: String::new(),
+                karma_effect: 1,
+            },
+        ];
+        let mut sessions = overlap_test_sessions();
+        sessions[0].start_time = "05:00:00".to_string();
+        sessions[0].end_time = "07:00:00".to_string();
+        sessions[0].elapsed_seconds = 7200;
+
+        let day = NaiveDate::from_ymd_opt(2026, 2, 25).unwrap();
+        let timeline = build_day_timeline(&sessions, &categories, day);
+
+        assert_eq!(timeline.len(), 2, "none sessions are skipped");
+        assert_eq!(timeline[0].category_name, "Work");
+        assert_eq!(timeline[0].start_offset_seconds, 0);
+        assert_eq!(timeline[0].end_offset_seconds, 3600);
+        assert_eq!(timeline[1].category_name, "Play");
+        assert_eq!(timeline[1].start_offset_seconds, 3 * 3600 + 1800);
+    }
+
+    #[test]
+    fn test_bulk_session_mutations_touch_only_selected_ids() {
+        let mut sessions = overlap_test_sessions();
+        let ids = HashSet::from([1, 2]);
+
+        assert_eq!(
+            recategorize_sessions(&mut sessions, &ids, CategoryId::new(2)),
+            1
+        );
+        assert_eq!(sessions[0].category_id, CategoryId::new(2));
+        assert_eq!(sessions[2].category_id, CategoryId::new(0));
+
+        let removed = remove_sessions(&mut sessions, &HashSet::from([2, 3]));
+        assert_eq!(
+            removed.iter().map(|session| session.id).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert_eq!(sessions.len(), 1);
+    }
+
+    #[test]
+    fn test_daily_tracked_seconds_groups_by_date_and_skips_none() {
+        let mut sessions = overlap_test_sessions();
+        sessions[1].date = "2026-02-24".to_string();
+
+        let start = NaiveDate::from_ymd_opt(2026, 2, 24).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 2, 25).unwrap();
+        let totals = daily_tracked_seconds(&sessions, start, end);
+
+        assert_eq!(totals.get(&end).copied(), Some(3600));
+        assert_eq!(totals.get(&start).copied(), Some(3600));
+        assert_eq!(
+            daily_tracked_seconds(
+                &sessions,
+                end + ChronoDuration::days(1),
+                end + ChronoDuration::days(2)
+            )
+            .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_planned_item_seconds_matches_category_and_description() {
+        let mut sessions = overlap_test_sessions();
+        sessions.push(Session {
+            id: 4,
+            description: "Focus ".to_string(),
+            elapsed_seconds: 600,
+            ..sessions[0].clone()
+        });
+        sessions.push(Session {
+            id: 5,
+            date: "2026-02-24".to_string(),
+            ..sessions[0].clone()
+        });
+        let day = NaiveDate::from_ymd_opt(2026, 2, 25).unwrap();
+
+        assert_eq!(
+            planned_item_seconds(&sessions, day, CategoryId::new(1), "focus"),
+            4200
+        );
+        assert_eq!(
+            planned_item_seconds(&sessions, day, CategoryId::new(1), "email"),
+            0
+        );
+        assert_eq!(
+            planned_item_seconds(&sessions, day, CategoryId::new(2), ""),
+            3600
+        );
+    }
+
+    #[test]
+    fn test_build_daily_reports_splits_range_by_day() {
+        let mut sessions = overlap_test_sessions();
+        sessions[1].date = "2026-02-23".to_string();
+        let categories: Vec<Category> = ["none", "Work", "Read"]
+            .iter()
+            .enumerate()
+            .map(|(id, name)| Category {
+                id: CategoryId::new(id as u64),
+                name: name.to_string(),
+                color: Color::White,
+                description: String::new(),
+                default_description: String::new(),
+                karma_effect: 1,
+            })
+            .collect();
+
+        let start = NaiveDate::from_ymd_opt(2026, 2, 23).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 2, 25).unwrap();
+        let days = build_daily_reports(&sessions, &categories, start, end);
+
+        assert_eq!(
+            days.iter().map(|day| day.date.as_str()).collect::<Vec<_>>(),
+            vec!["2026-02-23", "2026-02-24", "2026-02-25"]
+        );
+        assert_eq!(days[0].entries[0].category_name, "Read");
+        assert_eq!(days[0].total_seconds, 3600);
+        assert!(days[1].entries.is_empty());
+        assert_eq!(days[2].entries.len(), 1);
+        assert_eq!(days[2].total_seconds, 3600);
+    }
+
+    #[test]
+    fn test_build_goal_audit_flags_goals_off_by_more_than_tolerance() {
+        let mut sessions = overlap_test_sessions();
+        sessions[0].elapsed_seconds = 7 * 3600;
+        sessions[1].elapsed_seconds = 7 * 1800;
+        sessions[1].date = "2026-02-17".to_string();
+        let categories: Vec<Category> = ["none", "Work", "Read"]
+            .iter()
+            .enumerate()
+            .map(|(id, name)| Category {
+                id: CategoryId::new(id as u64),
+                name: name.to_string(),
+                color: Color::White,
+                description: String::new(),
+                default_description: String::new(),
+                karma_effect: 1,
+            })
+            .collect();
+        let goals = HashMap::from([(CategoryId::new(1), 3600), (CategoryId::new(2), 900)]);
+        let end = NaiveDate::from_ymd_opt(2026, 2, 25).unwrap();
+
+        let rows = build_goal_audit(&sessions, &categories, &goals, end, 2, 20.0, 300);
+
+        assert_eq!(rows.len(), 2);
+        let read = &rows[0];
+        assert_eq!(read.category_name, "Read");
+        assert_eq!(read.weekly_actual_seconds, vec![7 * 1800, 0]);
+        assert_eq!(read.suggested_daily_seconds, None);
+        let work = &rows[1];
+        assert_eq!(work.weekly_actual_seconds, vec![0, 7 * 3600]);
+        assert_eq!(work.average_seconds, 7 * 1800);
+        assert!((work.delta_percent + 50.0).abs() < f64::EPSILON);
+        assert_eq!(work.suggested_daily_seconds, Some(1800));
+    }
+
+    #[test]
+    fn test_week_bounds_respects_week_start() {
+        // 2026-02-25 is a Wednesday.
+        let today = NaiveDate::from_ymd_opt(2026, 2, 25).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2026, 2, d).unwrap();
+
+        assert_eq!(week_bounds(today, WeekStart::Rolling), (day(19), today));
+        assert_eq!(week_bounds(today, WeekStart::Monday), (day(23), today));
+        assert_eq!(week_bounds(today, WeekStart::Sunday), (day(22), today));
+        assert_eq!(week_bounds(day(22), WeekStart::Sunday), (day(22), day(22)));
+        assert_eq!(week_bounds(day(22), WeekStart::Monday), (day(16), day(22)));
+    }
+
+    #[test]
+    fn test_rename_project_only_touches_exact_matches() {
+        let mut sessions = overlap_test_sessions();
+        sessions[0].project = "strat".to_string();
+        sessions[1].project = "strat".to_string();
+        sessions[2].project = "strata-web".to_string();
+
+        assert_eq!(rename_project(&mut sessions, "strat", "strata"), 2);
+        assert_eq!(sessions[0].project, "strata");
+        assert_eq!(sessions[1].project, "strata");
+        assert_eq!(sessions[2].project, "strata-web");
+    }
+
+    fn overlap_test_sessions() -> Vec<Session> {
+        vec![
+            Session {
+                id: 1,
+                date: "2026-02-25".to_string(),
+                category_id: CategoryId::new(1),
+                description: "focus".to_string(),
+                project: String::new(),
+                start_time: "09:00:00".to_string(),
+                end_time: "10:00:00".to_string(),
+                elapsed_seconds: 3600,
+                focus: None,
+            },
+            Session {
+                id: 2,
+                date: "2026-02-25".to_string(),
+                category_id: CategoryId::new(2),
+                description: String::new(),
+                project: String::new(),
+                start_time: "09:30:00".to_string(),
+                end_time: "10:30:00".to_string(),
+                elapsed_seconds: 3600,
+                focus: None,
+            },
+            Session {
+                id: 3,
+                date: "2026-02-25".to_string(),
+                category_id: CategoryId::new(0),
+                description: String::new(),
+                project: String::new(),
+                start_time: "09:00:00".to_string(),
+                end_time: "11:00:00".to_string(),
+                elapsed_seconds: 7200,
+                focus: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_find_session_overlaps_ignores_none_sessions() {
+        let sessions = overlap_test_sessions();
+        let overlaps = find_session_overlaps(&sessions);
+
+        assert_eq!(
+            overlaps,
+            vec![SessionOverlap {
+                first_id: 1,
+                second_id: 2,
+                overlap_seconds: 1800,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_session_interval_crosses_midnight() {
+        let session = Session {
+            id: 1,
+            date: "2026-02-19".to_string(),
+            category_id: CategoryId::new(1),
+            description: String::new(),
+            project: String::new(),
+            start_time: "23:39:03".to_string(),
+            end_time: "00:13:32".to_string(),
+            elapsed_seconds: 2069,
+            focus: None,
+        };
+
+        let (start, end) = session_interval(&session).expect("valid interval");
+        assert_eq!(start.date(), NaiveDate::from_ymd_opt(2026, 2, 19).unwrap());
+        assert_eq!(end.date(), NaiveDate::from_ymd_opt(2026, 2, 20).unwrap());
+    }
+
+    #[test]
+    fn test_resolve_session_overlap_trim_and_merge() {
+        let mut trimmed = overlap_test_sessions();
+        let overlap = find_session_overlaps(&trimmed)[0];
+        assert!(resolve_session_overlap(
+            &mut trimmed,
+            &overlap,
+            OverlapResolution::Trim
+        ));
+        assert_eq!(trimmed[1].start_time, "10:00:00");
+        assert_eq!(trimmed[1].elapsed_seconds, 1800);
+        assert!(find_session_overlaps(&trimmed).is_empty());
+
+        let mut merged = overlap_test_sessions();
+        merged[1].category_id = CategoryId::new(1);
+        assert!(resolve_session_overlap(
+            &mut merged,
+            &overlap,
+            OverlapResolution::Merge
+        ));
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].start_time, "09:00:00");
+        assert_eq!(merged[0].end_time, "10:30:00");
+        assert_eq!(merged[0].elapsed_seconds, 5400);
+
+        let mut kept = overlap_test_sessions();
+        assert!(!resolve_session_overlap(
+            &mut kept,
+            &overlap,
+            OverlapResolution::KeepBoth
+        ));
+        assert_eq!(kept.len(), 3);
+    }
+
+    #[test]
+    fn test_resolve_session_overlap_only_merges_within_a_category() {
+        // The fixture's overlapping pair is in categories 1 and 2.
+        let mut sessions = overlap_test_sessions();
+        let overlap = find_session_overlaps(&sessions)[0];
+
+        assert!(!overlap_can_merge(&sessions, &overlap));
+        assert!(!resolve_session_overlap(
+            &mut sessions,
+            &overlap,
+            OverlapResolution::Merge
+        ));
+        assert_eq!(sessions, overlap_test_sessions());
+
+        sessions[1].category_id = CategoryId::new(1);
+        assert!(overlap_can_merge(&sessions, &overlap));
+    }
+
+    #[test]
+    fn test_kept_overlaps_stay_resolved() {
+        let sessions = overlap_test_sessions();
+        let overlap = find_session_overlaps(&sessions)[0];
+        let mut kept = KeptOverlaps::new();
+        assert_eq!(unresolved_session_overlaps(&sessions, &kept), vec![overlap]);
+
+        kept.insert((overlap.first_id, overlap.second_id));
+        assert!(unresolved_session_overlaps(&sessions, &kept).is_empty());
+        let logs = build_category_logs_for_date_range(
+            &sessions,
+            &kept,
+            &[],
+            CategoryId::new(1),
+            NaiveDate::from_ymd_opt(2026, 2, 25).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 2, 25).unwrap(),
+            None,
+        );
+        assert_eq!(logs.len(), 1);
+        assert!(!logs[0].overlaps);
+    }
+
+    #[test]
+    fn test_kept_pairs_of_deleted_sessions_do_not_hide_new_overlaps() {
+        let mut sessions = overlap_test_sessions();
+        let overlap = find_session_overlaps(&sessions)[0];
+        let mut kept = KeptOverlaps::from([(overlap.first_id, overlap.second_id)]);
+
+        assert!(!forget_removed_overlaps(&mut kept, &sessions));
+        assert_eq!(kept.len(), 1);
+
+        // Deleting the newest session frees its id for the next one.
+        let removed = remove_sessions(&mut sessions, &HashSet::from([overlap.second_id]));
+        assert_eq!(removed.len(), 1);
+        assert!(forget_removed_overlaps(&mut kept, &sessions));
+        assert!(kept.is_empty());
+
+        sessions.push(removed[0].clone());
+        assert_eq!(unresolved_session_overlaps(&sessions, &kept), vec![overlap]);
+    }
+
+    #[test]
+    fn test_overlap_trim_removes_a_contained_session() {
+        let mut sessions = overlap_test_sessions();
+        let overlap = find_session_overlaps(&sessions)[0];
+        assert!(!overlap_trim_removes(&sessions, &overlap));
+
+        sessions[1].end_time = "09:45:00".to_string();
+        sessions[1].elapsed_seconds = 900;
+        let overlap = find_session_overlaps(&sessions)[0];
+        assert!(overlap_trim_removes(&sessions, &overlap));
+        assert!(resolve_session_overlap(
+            &mut sessions,
+            &overlap,
+            OverlapResolution::Trim
+        ));
+        assert!(sessions.iter().all(|session| session.id != 2));
+    }
+
+    #[test]
+    fn test_average_focus_by_category_ignores_unrated_and_out_of_range() {
+        let mut sessions = overlap_test_sessions();
+        sessions[0].focus = Some(4);
+        sessions[1].focus = Some(2);
+        sessions.push(Session {
+            id: 4,
+            date: "2026-02-25".to_string(),
+            category_id: CategoryId::new(1),
+            description: String::new(),
+            project: String::new(),
+            start_time: "12:00:00".to_string(),
+            end_time: "13:00:00".to_string(),
+            elapsed_seconds: 3600,
+            focus: Some(5),
+        });
+        sessions.push(Session {
+            id: 5,
+            date: "2026-02-20".to_string(),
+            category_id: CategoryId::new(2),
+            description: String::new(),
+            project: String::new(),
+            start_time: "12:00:00".to_string(),
+            end_time: "13:00:00".to_string(),
+            elapsed_seconds: 3600,
+            focus: Some(5),
+        });
+
+        let day = NaiveDate::from_ymd_opt(2026, 2, 25).unwrap();
+        let averages = average_focus_by_category(&sessions, day, day);
+        assert_eq!(averages.len(), 2);
+        assert_eq!(averages[&CategoryId::new(1)], 4.5);
+        assert_eq!(averages[&CategoryId::new(2)], 2.0);
+    }
+
+    #[test]
+    fn test_category_suggestion_prefers_dominant_category_for_tokens() {
+        let mut sessions = overlap_test_sessions();
+        sessions[0].description = "Review PR for parser".to_string();
+        sessions[1].description = "parser benchmarks".to_string();
+        sessions[2].description = "parser".to_string();
+        sessions.push(Session {
+            id: 4,
+            date: "2026-02-25".to_string(),
+            category_id: CategoryId::new(1),
+            description: "review docs".to_string(),
+            project: String::new(),
+            start_time: "12:00:00".to_string(),
+            end_time: "13:00:00".to_string(),
+            elapsed_seconds: 3600,
+            focus: None,
+        });
+
+        let index = CategorySuggestionIndex::from_sessions(&sessions);
+        assert_eq!(index.suggest("review the parser"), Some(CategoryId::new(1)));
+        assert_eq!(index.suggest("BENCHMARKS"), Some(CategoryId::new(2)));
+        assert_eq!(index.suggest("gardening"), None);
+        assert_eq!(index.suggest(""), None);
+    }
 }
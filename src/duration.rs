@@ -0,0 +1,175 @@
+/// Formats tried in order; the first one that accepts the input wins.
+const PARSERS: &[fn(&str) -> Option<f64>] = &[parse_minutes, parse_clock, parse_units];
+
+/// Parses a human duration such as `1h30m`, `90m`, `0.5h`, `45` (minutes) or
+/// `1:30` into whole seconds.
+pub fn parse_duration(raw: &str) -> Option<usize> {
+    let raw = raw.trim().to_ascii_lowercase();
+    if raw.is_empty() {
+        return None;
+    }
+    PARSERS
+        .iter()
+        .find_map(|parser| parser(&raw))
+        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+        .map(|seconds| seconds.round() as usize)
+}
+
+/// Clap value parser for duration arguments.
+pub fn duration_arg(raw: &str) -> Result<usize, String> {
+    parse_duration(raw).ok_or_else(|| {
+        format!(
+            "invalid duration '{}', expected e.g. 1h30m, 90m, 0.5h or 1:30",
+            raw
+        )
+    })
+}
+
+/// Compact form of a duration, e.g. `1h30m`, `25m` or `45s`.
+pub fn format_duration(seconds: usize) -> String {
+    let (hours, minutes, secs) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    let mut out = String::new();
+    if hours > 0 {
+        out.push_str(&format!("{}h", hours));
+    }
+    if minutes > 0 {
+        out.push_str(&format!("{}m", minutes));
+    }
+    if secs > 0 || out.is_empty() {
+        out.push_str(&format!("{}s", secs));
+    }
+    out
+}
+
+fn parse_number(raw: &str) -> Option<f64> {
+    if raw.is_empty() || !raw.chars().all(|ch| ch.is_ascii_digit() || ch == '.') {
+        return None;
+    }
+    raw.parse::<f64>().ok()
+}
+
+/// A bare number counts as minutes.
+fn parse_minutes(raw: &str) -> Option<f64> {
+    parse_number(raw).map(|minutes| minutes * 60.0)
+}
+
+/// `H:MM` or `H:MM:SS`.
+fn parse_clock(raw: &str) -> Option<f64> {
+    let parts = raw
+        .split(':')
+        .map(|part| part.parse::<usize>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let (hours, minutes, seconds) = match parts[..] {
+        [hours, minutes] if minutes < 60 => (hours, minutes, 0),
+        [hours, minutes, seconds] if minutes < 60 && seconds < 60 => (hours, minutes, seconds),
+        _ => return None,
+    };
+    let total = hours
+        .checked_mul(3600)?
+        .checked_add(minutes * 60 + seconds)?;
+    Some(total as f64)
+}
+
+/// Number and unit pairs such as `1h 30m` or `1.5hrs`. Every number needs a
+/// unit, so `1h30` is rejected rather than guessed.
+fn parse_units(raw: &str) -> Option<f64> {
+    let mut total = 0.0;
+    let mut rest = raw.trim_start();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|ch: char| !ch.is_ascii_digit() && ch != '.')
+            .unwrap_or(rest.len());
+        let value = parse_number(&rest[..number_end])?;
+        rest = rest[number_end..].trim_start();
+
+        let unit_end = rest
+            .find(|ch: char| !ch.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let scale = unit_seconds(&rest[..unit_end])?;
+        total += value * scale;
+        rest = rest[unit_end..].trim_start();
+    }
+    Some(total)
+}
+
+fn unit_seconds(unit: &str) -> Option<f64> {
+    match unit {
+        "h" | "hr" | "hrs" | "hour" | "hours" => Some(3600.0),
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(60.0),
+        "s" | "sec" | "secs" | "second" | "seconds" => Some(1.0),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_accepts_unit_forms() {
+        assert_eq!(parse_duration("1h"), Some(3600));
+        assert_eq!(parse_duration("1h30m"), Some(5400));
+        assert_eq!(parse_duration("90m"), Some(5400));
+        assert_eq!(parse_duration("90s"), Some(90));
+        assert_eq!(parse_duration("1h 30m 15s"), Some(5415));
+        assert_eq!(parse_duration("2 hours 5 mins"), Some(7500));
+        assert_eq!(parse_duration("1H30M"), Some(5400));
+    }
+
+    #[test]
+    fn test_parse_duration_accepts_fractions() {
+        assert_eq!(parse_duration("0.5h"), Some(1800));
+        assert_eq!(parse_duration("1.25h"), Some(4500));
+        assert_eq!(parse_duration("2.5m"), Some(150));
+        assert_eq!(parse_duration(".5h"), Some(1800));
+        assert_eq!(parse_duration("1.5"), Some(90));
+    }
+
+    #[test]
+    fn test_parse_duration_bare_numbers_are_minutes() {
+        assert_eq!(parse_duration("45"), Some(2700));
+        assert_eq!(parse_duration(" 0 "), Some(0));
+    }
+
+    #[test]
+    fn test_parse_duration_accepts_clock_forms() {
+        assert_eq!(parse_duration("2:15"), Some(8100));
+        assert_eq!(parse_duration("0:45"), Some(2700));
+        assert_eq!(parse_duration("1:02:03"), Some(3723));
+        assert_eq!(parse_duration("1:60"), None);
+        assert_eq!(parse_duration("1:30:60"), None);
+        assert_eq!(parse_duration("1:2:3:4"), None);
+        assert_eq!(parse_duration("99999999999999999:00"), None);
+        assert_eq!(parse_duration("99999999999999999:00:00"), None);
+    }
+
+    #[test]
+    fn test_parse_duration_rejects_ambiguous_or_invalid_input() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("soon"), None);
+        assert_eq!(parse_duration("1h30"), None);
+        assert_eq!(parse_duration("h"), None);
+        assert_eq!(parse_duration("1d"), None);
+        assert_eq!(parse_duration("1..5h"), None);
+        assert_eq!(parse_duration("-5m"), None);
+        assert_eq!(parse_duration("1h-30m"), None);
+    }
+
+    #[test]
+    fn test_duration_arg_reports_the_rejected_input() {
+        assert_eq!(duration_arg("25m"), Ok(1500));
+        assert!(duration_arg("later").unwrap_err().contains("'later'"));
+    }
+
+    #[test]
+    fn test_format_duration_is_compact() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(1500), "25m");
+        assert_eq!(format_duration(5400), "1h30m");
+        assert_eq!(format_duration(3605), "1h5s");
+    }
+}
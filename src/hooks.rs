@@ -0,0 +1,141 @@
+use std::process::{Command, Stdio};
+
+use chrono::{DateTime, Local};
+
+use crate::storage::HooksConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    Start,
+    Stop,
+}
+
+impl HookEvent {
+    fn name(self) -> &'static str {
+        match self {
+            HookEvent::Start => "start",
+            HookEvent::Stop => "stop",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HookSession<'a> {
+    pub category_id: u64,
+    pub category: &'a str,
+    pub project: &'a str,
+    pub description: &'a str,
+    pub started_at: DateTime<Local>,
+    pub elapsed_seconds: Option<usize>,
+}
+
+pub fn run_hook(hooks: &HooksConfig, event: HookEvent, session: &HookSession) {
+    let Some(mut command) = hook_command(hooks, event, session) else {
+        return;
+    };
+    if let Ok(mut child) = command.spawn() {
+        std::thread::spawn(move || {
+            let _ = child.wait();
+        });
+    }
+}
+
+/// The configured command for `event` with the session in its environment,
+/// or `None` when no hook is set.
+fn hook_command(hooks: &HooksConfig, event: HookEvent, session: &HookSession) -> Option<Command> {
+    let command_line = match event {
+        HookEvent::Start => hooks.on_start.as_deref(),
+        HookEvent::Stop => hooks.on_stop.as_deref(),
+    };
+    let command_line = command_line.filter(|command| !command.trim().is_empty())?;
+
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(command_line);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(command_line);
+        command
+    };
+
+    command
+        .env("STRATA_EVENT", event.name())
+        .env("STRATA_CATEGORY", session.category)
+        .env("STRATA_CATEGORY_ID", session.category_id.to_string())
+        .env("STRATA_PROJECT", session.project)
+        .env("STRATA_DESCRIPTION", session.description)
+        .env("STRATA_STARTED_AT", session.started_at.to_rfc3339())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(elapsed) = session.elapsed_seconds {
+        command.env("STRATA_ELAPSED_SECONDS", elapsed.to_string());
+    }
+    Some(command)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{collections::HashMap, fs, path::PathBuf, time::SystemTime};
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn unique_path(prefix: &str) -> PathBuf {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        PathBuf::from(format!("/tmp/{}_{}.env", prefix, now))
+    }
+
+    #[test]
+    fn test_hook_sees_the_session_in_its_environment() {
+        let out = unique_path("strata_hook");
+        let hooks = HooksConfig {
+            on_start: None,
+            on_stop: Some(format!("env | grep '^STRATA_' > '{}'", out.display())),
+        };
+        let started_at = Local.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let session = HookSession {
+            category_id: 3,
+            category: "Work",
+            project: "strata",
+            description: "write spec",
+            started_at,
+            elapsed_seconds: Some(1500),
+        };
+
+        assert!(hook_command(&hooks, HookEvent::Start, &session).is_none());
+        let status = hook_command(&hooks, HookEvent::Stop, &session)
+            .expect("stop hook")
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let content = fs::read_to_string(&out).unwrap();
+        let env: HashMap<&str, &str> = content
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            // Anything strata's own environment passed down.
+            .filter(|(name, _)| std::env::var_os(name).is_none())
+            .collect();
+        let started = started_at.to_rfc3339();
+        assert_eq!(
+            env,
+            HashMap::from([
+                ("STRATA_EVENT", "stop"),
+                ("STRATA_CATEGORY", "Work"),
+                ("STRATA_CATEGORY_ID", "3"),
+                ("STRATA_PROJECT", "strata"),
+                ("STRATA_DESCRIPTION", "write spec"),
+                ("STRATA_STARTED_AT", started.as_str()),
+                ("STRATA_ELAPSED_SECONDS", "1500"),
+            ])
+        );
+
+        fs::remove_file(&out).ok();
+    }
+}
@@ -0,0 +1,237 @@
+//! How long the machine has gone without input, for stopping a TUI session
+//! that was left running. Each backend sits behind a feature: `idle-x11`
+//! asks the X server's screensaver extension, `idle-wayland` listens for the
+//! compositor's ext-idle-notify-v1 events, and `idle-macos` reads IOKit's
+//! `HIDIdleTime`.
+
+/// Seconds since the last input, or why no backend could tell.
+pub fn system_idle_seconds() -> Result<u64, String> {
+    let backends: &[fn() -> Result<u64, String>] = &[
+        #[cfg(feature = "idle-x11")]
+        x11::idle_seconds,
+        #[cfg(feature = "idle-wayland")]
+        wayland::idle_seconds,
+        #[cfg(all(feature = "idle-macos", target_os = "macos"))]
+        macos::idle_seconds,
+    ];
+    if backends.is_empty() {
+        return Err("strata was built without an idle feature".to_string());
+    }
+
+    let mut reasons = Vec::new();
+    for backend in backends {
+        match backend() {
+            Ok(seconds) => return Ok(seconds),
+            Err(reason) => reasons.push(reason),
+        }
+    }
+    Err(reasons.join("; "))
+}
+
+/// Idle seconds once they reach `threshold`; `None` while the user is
+/// around, with no threshold, or when idle time can't be read.
+pub fn idle_beyond(threshold: Option<u64>) -> Option<u64> {
+    let threshold = threshold?;
+    beyond_threshold(system_idle_seconds().ok(), threshold)
+}
+
+fn beyond_threshold(idle: Option<u64>, threshold: u64) -> Option<u64> {
+    idle.filter(|idle| threshold > 0 && *idle >= threshold)
+}
+
+#[cfg(feature = "idle-x11")]
+mod x11 {
+    use x11rb::{connection::Connection, protocol::screensaver::ConnectionExt};
+
+    pub(super) fn idle_seconds() -> Result<u64, String> {
+        std::env::var_os("DISPLAY").ok_or("DISPLAY is not set")?;
+        let (connection, screen) = x11rb::connect(None).map_err(|e| format!("X11: {}", e))?;
+        let root = connection
+            .setup()
+            .roots
+            .get(screen)
+            .ok_or("X11: no default screen")?
+            .root;
+        let info = connection
+            .screensaver_query_info(root)
+            .map_err(|e| format!("X11 screensaver extension: {}", e))?
+            .reply()
+            .map_err(|e| format!("X11 screensaver extension: {}", e))?;
+        Ok(info.ms_since_user_input as u64 / 1000)
+    }
+}
+
+#[cfg(feature = "idle-wayland")]
+mod wayland {
+    use std::{
+        sync::{Mutex, OnceLock, PoisonError},
+        time::{Duration, Instant},
+    };
+
+    use wayland_client::{
+        Connection, Dispatch, QueueHandle,
+        globals::{GlobalListContents, registry_queue_init},
+        protocol::{wl_registry::WlRegistry, wl_seat::WlSeat},
+    };
+    use wayland_protocols::ext::idle_notify::v1::client::{
+        ext_idle_notification_v1::{self, ExtIdleNotificationV1},
+        ext_idle_notifier_v1::ExtIdleNotifierV1,
+    };
+
+    /// The notification's timeout; idle time is counted back from when it
+    /// fires, so it only needs to be short.
+    const NOTIFY_AFTER: Duration = Duration::from_secs(1);
+
+    /// When the compositor last said the seat went idle, until it resumes.
+    static IDLE_SINCE: Mutex<Option<Instant>> = Mutex::new(None);
+    static MONITOR: OnceLock<Result<(), String>> = OnceLock::new();
+
+    struct Monitor;
+
+    pub(super) fn idle_seconds() -> Result<u64, String> {
+        MONITOR.get_or_init(start).clone()?;
+        let idle_since = *IDLE_SINCE.lock().unwrap_or_else(PoisonError::into_inner);
+        Ok(idle_since.map_or(0, |since| since.elapsed().as_secs()))
+    }
+
+    /// Subscribes to idle notifications and dispatches them on a thread for
+    /// the rest of the process.
+    fn start() -> Result<(), String> {
+        let connection = Connection::connect_to_env().map_err(|e| format!("Wayland: {}", e))?;
+        let (globals, mut queue) =
+            registry_queue_init::<Monitor>(&connection).map_err(|e| format!("Wayland: {}", e))?;
+        let handle = queue.handle();
+        let seat: WlSeat = globals
+            .bind(&handle, 1..=1, ())
+            .map_err(|_| "the Wayland compositor has no seat".to_string())?;
+        let notifier: ExtIdleNotifierV1 = globals
+            .bind(&handle, 1..=1, ())
+            .map_err(|_| "the Wayland compositor lacks ext-idle-notify-v1".to_string())?;
+        notifier.get_idle_notification(NOTIFY_AFTER.as_millis() as u32, &seat, &handle, ());
+
+        std::thread::spawn(move || {
+            let _connection = connection;
+            let mut monitor = Monitor;
+            while queue.blocking_dispatch(&mut monitor).is_ok() {}
+        });
+        Ok(())
+    }
+
+    impl Dispatch<ExtIdleNotificationV1, ()> for Monitor {
+        fn event(
+            _: &mut Self,
+            _: &ExtIdleNotificationV1,
+            event: ext_idle_notification_v1::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+            let mut idle_since = IDLE_SINCE.lock().unwrap_or_else(PoisonError::into_inner);
+            match event {
+                ext_idle_notification_v1::Event::Idled => {
+                    let now = Instant::now();
+                    *idle_since = Some(now.checked_sub(NOTIFY_AFTER).unwrap_or(now));
+                }
+                ext_idle_notification_v1::Event::Resumed => *idle_since = None,
+                _ => {}
+            }
+        }
+    }
+
+    impl Dispatch<WlRegistry, GlobalListContents> for Monitor {
+        fn event(
+            _: &mut Self,
+            _: &WlRegistry,
+            _: <WlRegistry as wayland_client::Proxy>::Event,
+            _: &GlobalListContents,
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+        }
+    }
+
+    impl Dispatch<WlSeat, ()> for Monitor {
+        fn event(
+            _: &mut Self,
+            _: &WlSeat,
+            _: <WlSeat as wayland_client::Proxy>::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+        }
+    }
+
+    impl Dispatch<ExtIdleNotifierV1, ()> for Monitor {
+        fn event(
+            _: &mut Self,
+            _: &ExtIdleNotifierV1,
+            _: <ExtIdleNotifierV1 as wayland_client::Proxy>::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+        }
+    }
+}
+
+// IOKit has no safe binding; this is the one place the crate allows unsafe.
+#[cfg(all(feature = "idle-macos", target_os = "macos"))]
+#[allow(unsafe_code)]
+mod macos {
+    use core_foundation::{
+        base::{CFType, TCFType, kCFAllocatorDefault},
+        number::CFNumber,
+        string::CFString,
+    };
+    use io_kit_sys::{
+        IOObjectRelease, IORegistryEntryCreateCFProperty, IOServiceGetMatchingService,
+        IOServiceMatching, kIOMasterPortDefault,
+    };
+
+    pub(super) fn idle_seconds() -> Result<u64, String> {
+        let key = CFString::from_static_string("HIDIdleTime");
+        // SAFETY: the matching dictionary is consumed by
+        // IOServiceGetMatchingService, the service is released after the
+        // property read, and the property is owned under the create rule.
+        unsafe {
+            let service = IOServiceGetMatchingService(
+                kIOMasterPortDefault,
+                IOServiceMatching(c"IOHIDSystem".as_ptr()) as _,
+            );
+            if service == 0 {
+                return Err("IOHIDSystem not found".to_string());
+            }
+            let property = IORegistryEntryCreateCFProperty(
+                service,
+                key.as_concrete_TypeRef(),
+                kCFAllocatorDefault,
+                0,
+            );
+            IOObjectRelease(service);
+            if property.is_null() {
+                return Err("IOHIDSystem has no HIDIdleTime".to_string());
+            }
+            let nanos = CFType::wrap_under_create_rule(property)
+                .downcast::<CFNumber>()
+                .and_then(|number| number.to_i64())
+                .ok_or("HIDIdleTime is not a number")?;
+            Ok(nanos.max(0) as u64 / 1_000_000_000)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_beyond_threshold_reports_only_long_idle_stretches() {
+        assert_eq!(beyond_threshold(Some(599), 600), None);
+        assert_eq!(beyond_threshold(Some(600), 600), Some(600));
+        assert_eq!(beyond_threshold(Some(4000), 600), Some(4000));
+        assert_eq!(beyond_threshold(None, 600), None);
+        assert_eq!(beyond_threshold(Some(4000), 0), None);
+        assert_eq!(idle_beyond(None), None);
+    }
+}
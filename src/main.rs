@@ -1,12 +1,22 @@
-#![forbid(unsafe_code)]
+#![cfg_attr(
+    not(all(feature = "idle-macos", target_os = "macos")),
+    forbid(unsafe_code)
+)]
+#![cfg_attr(all(feature = "idle-macos", target_os = "macos"), deny(unsafe_code))]
 
 use std::io;
 
 mod app;
 mod cli;
 mod constants;
+mod daemon;
 mod domain;
+mod duration;
+mod hooks;
+mod idle;
+mod notify;
 mod sand;
+mod serve;
 mod storage;
 
 fn main() -> Result<(), io::Error> {
@@ -16,5 +26,9 @@ fn main() -> Result<(), io::Error> {
         return Ok(());
     }
 
-    app::run_ui()
+    if let Err(e) = app::run_ui() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+    Ok(())
 }
@@ -0,0 +1,35 @@
+#[cfg(not(feature = "notifications"))]
+use std::process::{Command, Stdio};
+
+#[cfg(feature = "notifications")]
+pub fn desktop_notification(summary: &str, body: &str) {
+    let mut notification = notify_rust::Notification::new();
+    notification.appname("strata").summary(summary).body(body);
+    std::thread::spawn(move || {
+        let _ = notification.show();
+    });
+}
+
+/// Without the `notifications` feature, shells out to `notify-send`, or
+/// `osascript` on macOS.
+#[cfg(not(feature = "notifications"))]
+pub fn desktop_notification(summary: &str, body: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!("display notification {:?} with title {:?}", body, summary);
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(script);
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.arg("--app-name=strata").arg(summary).arg(body);
+        command
+    };
+
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    std::thread::spawn(move || {
+        let _ = command.status();
+    });
+}
@@ -1,18 +1,32 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    sync::atomic::{AtomicBool, Ordering},
+};
 
-use rand::Rng;
+use chrono::NaiveDate;
+use rand::{Rng, seq::SliceRandom};
 use ratatui::{
     prelude::{Line, Span},
     style::{Color, Stylize},
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{
-    constants::SAND_ENGINE,
-    domain::{Category, CategoryId},
-};
+use crate::{constants::SAND_ENGINE, domain::CategoryId};
+
+use super::resize::{resize_grid, resize_grid_in_place};
+
+static CHECK_INVARIANTS: AtomicBool = AtomicBool::new(false);
+
+/// Runs the grain conservation checks in release builds too; debug builds
+/// always run them.
+pub fn set_check_invariants(enabled: bool) {
+    CHECK_INVARIANTS.store(enabled, Ordering::Relaxed);
+}
 
-use super::resize::resize_grid;
+fn invariants_enabled() -> bool {
+    cfg!(debug_assertions) || CHECK_INVARIANTS.load(Ordering::Relaxed)
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SandStateGrain {
@@ -27,36 +41,140 @@ pub struct SandState {
     pub grid_width: usize,
     pub grid_height: usize,
     pub grains: Vec<SandStateGrain>,
+    /// Operational day the pile was saved on; older files have none.
+    #[serde(default)]
+    pub day: Option<NaiveDate>,
+    /// Earlier days pressed into bands below the pile, oldest first.
+    #[serde(default)]
+    pub layers: Vec<SandLayer>,
+}
+
+/// One compacted day: its grains per category id, largest share first.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SandLayer {
+    pub day: Option<NaiveDate>,
+    pub counts: Vec<(u64, usize)>,
 }
 
+/// Layers kept in the saved state; only as many as fit are drawn.
+const MAX_LAYERS: usize = 366;
+
 impl SandState {
     pub const VERSION: u8 = 1;
 }
 
+/// How `spawn` picks a column among the free cells of the top row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SpawnStrategy {
+    /// Random start column, then the next free one to the right (wrapping).
+    #[default]
+    Scan,
+    /// Random, weighted by how many empty cells each column has left.
+    LeastFull,
+    /// Random, weighted toward the middle of the glass.
+    Center,
+}
+
+impl SpawnStrategy {
+    fn pick(
+        self,
+        grid: &[Vec<Option<CategoryId>>],
+        row: usize,
+        free: &[usize],
+        rng: &mut impl Rng,
+    ) -> usize {
+        let width = grid[row].len();
+        match self {
+            Self::Scan => {
+                let start = rng.gen_range(0..width);
+                free.iter()
+                    .copied()
+                    .find(|x| *x >= start)
+                    .unwrap_or(free[0])
+            }
+            Self::LeastFull => weighted_pick(free, rng, |x| {
+                grid[row..]
+                    .iter()
+                    .filter(|cells| cells[x].is_none())
+                    .count()
+            }),
+            Self::Center => weighted_pick(free, rng, |x| (x + 1).min(width - x)),
+        }
+    }
+}
+
+fn weighted_pick(free: &[usize], rng: &mut impl Rng, weight: impl Fn(usize) -> usize) -> usize {
+    let weights: Vec<usize> = free.iter().map(|x| weight(*x).max(1)).collect();
+    let mut target = rng.gen_range(0..weights.iter().sum::<usize>());
+    for (x, weight) in free.iter().zip(&weights) {
+        if target < *weight {
+            return *x;
+        }
+        target -= weight;
+    }
+    free[free.len() - 1]
+}
+
 pub struct SandEngine {
     pub(crate) grid: Vec<Vec<Option<CategoryId>>>,
+    back_buffer: Vec<Vec<Option<CategoryId>>>,
     pub width: u16,
     pub height: u16,
     frame_count: usize,
     pub grain_count: usize,
+    spawn_strategy: SpawnStrategy,
+    hourglass: bool,
+    layers: Vec<SandLayer>,
+    /// Bands drawn below `grid`, newest (topmost) first.
+    layer_rows: Vec<Vec<Option<CategoryId>>>,
 }
 
 impl SandEngine {
     pub fn new(width: u16, height: u16) -> Self {
         let mut se = Self {
             grid: vec![],
+            back_buffer: vec![],
             width,
             height,
             frame_count: 0,
             grain_count: 0,
+            spawn_strategy: SpawnStrategy::default(),
+            hourglass: false,
+            layers: vec![],
+            layer_rows: vec![],
         };
         se.resize(width, height);
         se
     }
 
+    pub fn with_spawn_strategy(mut self, spawn_strategy: SpawnStrategy) -> Self {
+        self.spawn_strategy = spawn_strategy;
+        self
+    }
+
+    pub fn spawn_strategy(&self) -> SpawnStrategy {
+        self.spawn_strategy
+    }
+
+    pub fn set_spawn_strategy(&mut self, spawn_strategy: SpawnStrategy) {
+        self.spawn_strategy = spawn_strategy;
+    }
+
     pub fn resize(&mut self, width: u16, height: u16) {
         self.width = width * SAND_ENGINE.dot_width as u16;
         self.height = height * SAND_ENGINE.dot_height as u16;
+        self.fit_grid();
+    }
+
+    /// Lays the bands out below the pile and resizes `grid` to the rows
+    /// left above them.
+    fn fit_grid(&mut self) {
+        let shown = self.layers.len().min(self.height as usize / 4);
+        self.layer_rows = self.layers[self.layers.len() - shown..]
+            .iter()
+            .rev()
+            .map(|layer| layer_band(layer, self.width as usize))
+            .collect();
 
         let old_w = if self.grid.is_empty() {
             0
@@ -64,9 +182,10 @@ impl SandEngine {
             self.grid[0].len()
         };
         let old_h = self.grid.len();
+        let before = self.occupied_cells();
 
         let new_w = self.width as usize;
-        let new_h = self.height as usize;
+        let new_h = self.height as usize - shown;
 
         if old_w == 0 || old_h == 0 {
             self.grid = vec![vec![None; new_w]; new_h];
@@ -78,22 +197,44 @@ impl SandEngine {
             return;
         }
 
-        self.grid = resize_grid(
-            &self.grid,
+        resize_grid_in_place(
+            &mut self.grid,
             new_w,
             new_h,
             SAND_ENGINE.dot_width,
             SAND_ENGINE.dot_height,
         );
+        self.grain_count = self.occupied_cells();
 
         self.apply_gravity();
+        self.assert_grains("resize", Some(before.min(self.capacity())));
+    }
 
-        self.grain_count = self
-            .grid
+    fn occupied_cells(&self) -> usize {
+        self.grid
             .iter()
             .flat_map(|row| row.iter())
             .filter(|c| c.is_some())
-            .count();
+            .count()
+    }
+
+    /// Panics when `grain_count` has drifted from the grid, or when the grid
+    /// no longer holds the `expected` number of grains after `operation`.
+    fn assert_grains(&self, operation: &str, expected: Option<usize>) {
+        if !invariants_enabled() {
+            return;
+        }
+        let occupied = self.occupied_cells();
+        assert_eq!(
+            self.grain_count, occupied,
+            "sand invariant: grain_count disagrees with the grid after {operation}"
+        );
+        if let Some(expected) = expected {
+            assert_eq!(
+                occupied, expected,
+                "sand invariant: {operation} lost or duplicated grains"
+            );
+        }
     }
 
     fn capacity(&self) -> usize {
@@ -104,51 +245,196 @@ impl SandEngine {
         }
     }
 
-    pub fn spawn(&mut self, category_id: CategoryId) {
-        let capacity = self.capacity();
-        if capacity == 0 {
-            return;
+    pub fn cell_row_for_grains(&self, grains: usize) -> Option<usize> {
+        let grid_h = self.grid.len();
+        let grid_w = self.grid.first().map_or(0, |row| row.len());
+        if grid_w == 0 || grains == 0 {
+            return None;
         }
 
-        let mut rng = rand::thread_rng();
-        let w = self.grid[0].len();
+        let dot_rows = grains.div_ceil(grid_w);
+        if dot_rows > grid_h {
+            return None;
+        }
+        Some((grid_h - dot_rows) / SAND_ENGINE.dot_height)
+    }
 
-        let x = rng.gen_range(0..w);
+    pub fn spawn(&mut self, category_id: CategoryId) -> Option<usize> {
+        self.spawn_with(category_id, &mut rand::thread_rng())
+    }
 
-        if self.grid[0][x].is_none() {
-            self.grid[0][x] = Some(category_id);
-            self.grain_count += 1;
-        } else {
-            let fallback_x = rng.gen_range(0..w);
-            if self.grid[0][fallback_x].is_none() {
-                self.grid[0][fallback_x] = Some(category_id);
-                self.grain_count += 1;
-            }
+    /// Drops a grain into the highest row with room, normally the top one, so
+    /// a grain is only lost when the glass is full.
+    fn spawn_with(&mut self, category_id: CategoryId, rng: &mut impl Rng) -> Option<usize> {
+        if self.capacity() == 0 {
+            return None;
         }
+
+        let y = self
+            .grid
+            .iter()
+            .position(|row| row.iter().any(Option::is_none))?;
+        let free: Vec<usize> = (0..self.grid[y].len())
+            .filter(|x| self.grid[y][*x].is_none())
+            .collect();
+        let x = self.spawn_strategy.pick(&self.grid, y, &free, rng);
+
+        self.grid[y][x] = Some(category_id);
+        self.grain_count += 1;
+        self.assert_grains("spawn", None);
+        Some(x)
     }
 
     fn apply_gravity(&mut self) {
+        self.apply_gravity_with(&mut rand::thread_rng());
+    }
+
+    /// One bottom-up pass that reads the current grid and writes into a back
+    /// buffer. A grain only moves into a cell that was empty before the pass
+    /// and hasn't been claimed in it, so nothing falls into a cell another
+    /// grain just left. Columns go in a fresh random order every row to avoid
+    /// a left/right bias.
+    fn apply_gravity_with(&mut self, rng: &mut impl Rng) {
         let h = self.grid.len();
-        let w = self.grid[0].len();
+        let w = self.grid.first().map_or(0, |row| row.len());
+        if h < 2 || w == 0 {
+            return;
+        }
+        let before = self.occupied_cells();
+        let mut written = invariants_enabled().then(|| vec![false; w * h]);
+        let neck = self.hourglass_neck();
+
+        let mut next = std::mem::take(&mut self.back_buffer);
+        next.clone_from(&self.grid);
+        let mut columns: Vec<usize> = (0..w).collect();
 
         for y in (0..h - 1).rev() {
-            for x in 0..w {
-                if let Some(cat) = self.grid[y][x] {
-                    if self.grid[y + 1][x].is_none() {
-                        self.grid[y + 1][x] = Some(cat);
-                        self.grid[y][x] = None;
+            columns.shuffle(rng);
+            for &x in &columns {
+                let Some(cat) = self.grid[y][x] else {
+                    continue;
+                };
+                if neck == Some(y + 1) {
+                    continue;
+                }
+                let free = |tx: usize| self.grid[y + 1][tx].is_none() && next[y + 1][tx].is_none();
+                let target = if free(x) {
+                    Some(x)
+                } else {
+                    let nx = if rng.gen_bool(0.5) {
+                        x.checked_add(1).filter(|nx| *nx < w)
                     } else {
-                        let dir: isize = if rand::random() { 1 } else { -1 };
-                        let nx = (x as isize) + dir;
+                        x.checked_sub(1)
+                    };
+                    nx.filter(|nx| free(*nx))
+                };
 
-                        if nx >= 0 && (nx as usize) < w && self.grid[y + 1][nx as usize].is_none() {
-                            self.grid[y + 1][nx as usize] = Some(cat);
-                            self.grid[y][x] = None;
-                        }
+                if let Some(tx) = target {
+                    if let Some(written) = written.as_mut() {
+                        let cell = &mut written[(y + 1) * w + tx];
+                        assert!(
+                            !*cell,
+                            "sand invariant: cell ({tx}, {}) written twice in one gravity pass",
+                            y + 1
+                        );
+                        *cell = true;
+                    }
+                    next[y + 1][tx] = Some(cat);
+                    next[y][x] = None;
+                }
+            }
+        }
+
+        self.back_buffer = std::mem::replace(&mut self.grid, next);
+        self.assert_grains("gravity", Some(before));
+    }
+
+    /// First row below the upper chamber while the glass is an hourglass.
+    /// Grains above it only pass through `drip`.
+    fn hourglass_neck(&self) -> Option<usize> {
+        self.hourglass.then_some(self.grid.len() / 2)
+    }
+
+    /// Grains that fill three quarters of the upper chamber.
+    pub fn hourglass_grains(&self) -> usize {
+        let w = self.grid.first().map_or(0, |row| row.len());
+        self.grid.len() / 2 * w * 3 / 4
+    }
+
+    /// Empties the glass and turns it into an hourglass holding `total`
+    /// grains of `category_id`, `top` of them still in the upper chamber.
+    pub fn fill_hourglass(&mut self, category_id: CategoryId, total: usize, top: usize) {
+        self.clear();
+        self.hourglass = true;
+        let Some(neck) = self.hourglass_neck() else {
+            return;
+        };
+        let h = self.grid.len();
+        let total = total.min(self.capacity());
+        let top = top.min(total);
+
+        let mut place = |rows: &mut dyn Iterator<Item = usize>, count: usize| {
+            let mut placed = 0;
+            for y in rows {
+                for cell in &mut self.grid[y] {
+                    if placed == count {
+                        return placed;
                     }
+                    *cell = Some(category_id);
+                    placed += 1;
                 }
             }
+            placed
+        };
+        let placed = place(&mut (0..neck).rev(), top) + place(&mut (neck..h).rev(), total - top);
+        self.grain_count = placed;
+        self.assert_grains("hourglass fill", None);
+    }
+
+    /// Grains still in the upper chamber.
+    pub fn top_grains(&self) -> usize {
+        self.hourglass_neck().map_or(0, |neck| {
+            self.grid[..neck]
+                .iter()
+                .flatten()
+                .filter(|cell| cell.is_some())
+                .count()
+        })
+    }
+
+    /// Lets one grain through the neck: the upper chamber's grain nearest the
+    /// middle of its lowest row, so the surface sinks into a funnel. Returns
+    /// false when the upper chamber is empty or the neck is blocked.
+    pub fn drip(&mut self) -> bool {
+        let Some(neck) = self.hourglass_neck() else {
+            return false;
+        };
+        let w = self.grid.first().map_or(0, |row| row.len());
+        if neck == 0 || neck >= self.grid.len() || w == 0 {
+            return false;
         }
+        let center = w / 2;
+        let nearest = |row: &[Option<CategoryId>], occupied: bool| {
+            (0..w)
+                .filter(|x| row[*x].is_some() == occupied)
+                .min_by_key(|x| x.abs_diff(center))
+        };
+        let source = (0..neck)
+            .rev()
+            .find_map(|y| nearest(&self.grid[y], true).map(|x| (x, y)));
+        let (Some((x, y)), Some(tx)) = (source, nearest(&self.grid[neck], false)) else {
+            return false;
+        };
+
+        let before = self.occupied_cells();
+        self.grid[neck][tx] = self.grid[y][x].take();
+        self.assert_grains("drip", Some(before));
+        true
+    }
+
+    /// Turns the hourglass upside down.
+    pub fn flip_hourglass(&mut self) {
+        self.grid.reverse();
     }
 
     pub fn update(&mut self) {
@@ -158,17 +444,17 @@ impl SandEngine {
         }
     }
 
-    pub fn render(&self, categories: &[Category]) -> Vec<Line<'static>> {
+    pub fn render(
+        &self,
+        category_colors: impl IntoIterator<Item = (CategoryId, Color)>,
+    ) -> Vec<Line<'static>> {
         let cell_w = self.width as usize;
         let cell_h = (self.height / SAND_ENGINE.dot_height as u16) as usize;
         let grid_h = self.grid.len();
         let grid_w = self.grid.first().map_or(0, |row| row.len());
         let mut lines: Vec<Line<'static>> = Vec::with_capacity(cell_h);
 
-        let category_colors: HashMap<CategoryId, Color> = categories
-            .iter()
-            .map(|category| (category.id, category.color))
-            .collect();
+        let category_colors: HashMap<CategoryId, Color> = category_colors.into_iter().collect();
         let none_id = CategoryId::new(0);
 
         for cy in 0..cell_h {
@@ -183,9 +469,13 @@ impl SandEngine {
                         let gx = cx * SAND_ENGINE.dot_width + dx;
                         let gy = cy * SAND_ENGINE.dot_height + dy;
 
-                        if gy < grid_h
-                            && gx < grid_w
-                            && let Some(cat_id) = self.grid[gy][gx]
+                        let cell = if gy < grid_h {
+                            self.grid[gy].get(gx)
+                        } else {
+                            self.layer_rows.get(gy - grid_h).and_then(|row| row.get(gx))
+                        };
+                        if gx < grid_w
+                            && let Some(&Some(cat_id)) = cell
                         {
                             let dot_index = match (dx, dy) {
                                 (0, 0) => 0,
@@ -255,6 +545,37 @@ impl SandEngine {
         self.grain_count = 0;
     }
 
+    /// Presses the pile into a band one dot high at the bottom of the glass,
+    /// split by each category's share of it.
+    pub fn compact_day(&mut self, day: Option<NaiveDate>) {
+        let mut counts: Vec<(u64, usize)> = Vec::new();
+        for category_id in self.grid.iter().flatten().flatten() {
+            match counts.iter_mut().find(|(id, _)| *id == category_id.0) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((category_id.0, 1)),
+            }
+        }
+        self.clear();
+        if counts.is_empty() {
+            return;
+        }
+
+        counts.sort_by_key(|(id, count)| (Reverse(*count), *id));
+        self.layers.push(SandLayer { day, counts });
+        if self.layers.len() > MAX_LAYERS {
+            self.layers.remove(0);
+        }
+        self.fit_grid();
+    }
+
+    pub fn category_grains(&self, category_id: CategoryId) -> usize {
+        self.grid
+            .iter()
+            .flat_map(|row| row.iter())
+            .filter(|cell| **cell == Some(category_id))
+            .count()
+    }
+
     pub fn clear_category(&mut self, category_id: CategoryId) {
         let mut removed = 0usize;
 
@@ -292,6 +613,8 @@ impl SandEngine {
             grid_width,
             grid_height,
             grains,
+            day: None,
+            layers: self.layers.clone(),
         }
     }
 
@@ -300,6 +623,9 @@ impl SandEngine {
             return;
         }
 
+        self.layers = state.layers.clone();
+        self.fit_grid();
+
         if state.grid_width == 0 || state.grid_height == 0 {
             self.clear();
             return;
@@ -323,6 +649,11 @@ impl SandEngine {
             restored[grain.y][grain.x] = Some(normalized_id);
         }
 
+        let restored_count = restored
+            .iter()
+            .flat_map(|row| row.iter())
+            .filter(|cell| cell.is_some())
+            .count();
         let target_height = self.grid.len();
         let target_width = self.grid.first().map_or(0, |row| row.len());
 
@@ -341,21 +672,184 @@ impl SandEngine {
             )
         };
 
-        self.grain_count = self
-            .grid
-            .iter()
-            .flat_map(|row| row.iter())
-            .filter(|cell| cell.is_some())
-            .count();
+        self.grain_count = self.occupied_cells();
+        let capacity = if target_width == 0 || target_height == 0 {
+            restored_count
+        } else {
+            self.capacity()
+        };
+        self.assert_grains("restore", Some(restored_count.min(capacity)));
     }
 }
 
+/// Spreads a layer's counts over `width` cells, rounding by largest remainder
+/// so the band is always full.
+fn layer_band(layer: &SandLayer, width: usize) -> Vec<Option<CategoryId>> {
+    let total: usize = layer.counts.iter().map(|(_, count)| count).sum();
+    if total == 0 {
+        return vec![None; width];
+    }
+
+    let mut cells: Vec<usize> = layer
+        .counts
+        .iter()
+        .map(|(_, count)| count * width / total)
+        .collect();
+    let mut order: Vec<usize> = (0..cells.len()).collect();
+    order.sort_by_key(|i| Reverse(layer.counts[*i].1 * width % total));
+    let short = width - cells.iter().sum::<usize>();
+    for i in order.into_iter().take(short) {
+        cells[i] += 1;
+    }
+
+    layer
+        .counts
+        .iter()
+        .zip(cells)
+        .flat_map(|((id, _), n)| std::iter::repeat_n(Some(CategoryId::new(*id)), n))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
 
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::SpawnStrategy;
     use crate::{constants::SAND_ENGINE, domain::CategoryId, sand::SandEngine};
 
+    const STRATEGIES: [SpawnStrategy; 3] = [
+        SpawnStrategy::Scan,
+        SpawnStrategy::LeastFull,
+        SpawnStrategy::Center,
+    ];
+
+    #[test]
+    fn test_spawn_places_a_grain_while_any_cell_is_free() {
+        for strategy in STRATEGIES {
+            let mut se = SandEngine::new(2, 1).with_spawn_strategy(strategy);
+            let mut rng = StdRng::seed_from_u64(7);
+            let capacity = se.grid.len() * se.grid[0].len();
+
+            for _ in 0..capacity {
+                assert!(se.spawn_with(CategoryId::new(1), &mut rng).is_some());
+            }
+            assert_eq!(se.grain_count, capacity);
+            assert!(se.grid.iter().flatten().all(Option::is_some));
+            assert_eq!(se.spawn_with(CategoryId::new(1), &mut rng), None);
+        }
+    }
+
+    #[test]
+    fn test_hourglass_only_lets_grains_through_the_neck() {
+        let mut se = SandEngine::new(4, 2);
+        let total = se.hourglass_grains();
+        se.fill_hourglass(CategoryId::new(3), total, total);
+        assert_eq!(se.grain_count, total);
+        assert_eq!(se.top_grains(), total);
+
+        for _ in 0..20 {
+            se.update();
+        }
+        assert_eq!(se.top_grains(), total);
+
+        let mut dripped = 0;
+        while se.top_grains() > 0 {
+            assert!(se.drip());
+            dripped += 1;
+            for _ in 0..40 {
+                se.update();
+            }
+        }
+        assert_eq!(dripped, total);
+        assert!(!se.drip());
+        assert_eq!(se.grain_count, total);
+
+        se.flip_hourglass();
+        assert_eq!(se.top_grains(), total);
+        assert!(
+            se.grid
+                .iter()
+                .flatten()
+                .flatten()
+                .all(|id| *id == CategoryId::new(3))
+        );
+    }
+
+    #[test]
+    fn test_gravity_never_falls_into_a_cell_vacated_in_the_same_pass() {
+        let mut se = SandEngine::new(1, 1);
+        let (a, b) = (CategoryId::new(1), CategoryId::new(2));
+        se.grid = vec![vec![Some(a)], vec![Some(b)], vec![None], vec![None]];
+        se.grain_count = 2;
+
+        se.apply_gravity_with(&mut StdRng::seed_from_u64(3));
+
+        assert_eq!(se.grid[0][0], Some(a));
+        assert_eq!(se.grid[1][0], None);
+        assert_eq!(se.grid[2][0], Some(b));
+    }
+
+    #[test]
+    fn test_gravity_piles_evenly_on_both_sides() {
+        let (mut left, mut right) = (0usize, 0usize);
+        for seed in 0..3 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut se = SandEngine::new(1, 1);
+            se.grid = vec![vec![None; 21]; 16];
+            se.grain_count = 0;
+            for _ in 0..250 {
+                if se.grid[0][10].is_none() {
+                    se.grid[0][10] = Some(CategoryId::new(1));
+                    se.grain_count += 1;
+                }
+                se.apply_gravity_with(&mut rng);
+            }
+            for row in &se.grid {
+                left += row[..10].iter().flatten().count();
+                right += row[11..].iter().flatten().count();
+            }
+        }
+
+        assert!(left.abs_diff(right) * 10 <= left + right);
+    }
+
+    #[test]
+    fn test_spawn_strategies_weight_columns() {
+        let picks = |strategy, prepare: fn(&mut SandEngine)| {
+            let mut rng = StdRng::seed_from_u64(11);
+            let mut counts = vec![0usize; 8];
+            for _ in 0..2000 {
+                let mut se = SandEngine::new(4, 2).with_spawn_strategy(strategy);
+                prepare(&mut se);
+                counts[se.spawn_with(CategoryId::new(1), &mut rng).unwrap()] += 1;
+            }
+            counts
+        };
+
+        let center = picks(SpawnStrategy::Center, |_| {});
+        assert!(center[3] + center[4] > 3 * (center[0] + center[7]));
+
+        let least_full = picks(SpawnStrategy::LeastFull, |se| {
+            for row in se.grid.iter_mut().skip(1) {
+                row[0] = Some(CategoryId::new(1));
+            }
+            se.grain_count = se.occupied_cells();
+        });
+        assert!(least_full[1] > 4 * least_full[0]);
+    }
+
+    #[test]
+    fn test_cell_row_for_grains_maps_pile_height_to_rows() {
+        let se = SandEngine::new(10, 5);
+        assert_eq!(se.cell_row_for_grains(0), None);
+        assert_eq!(se.cell_row_for_grains(20), Some(4));
+        assert_eq!(se.cell_row_for_grains(100), Some(3));
+        assert_eq!(se.cell_row_for_grains(400), Some(0));
+        assert_eq!(se.cell_row_for_grains(401), None);
+    }
+
     #[test]
     fn test_sand_resize_basic_copy() {
         let mut se = SandEngine::new(20, 20);
@@ -590,6 +1084,22 @@ mod tests {
         assert_eq!(restored.grain_count, 1);
     }
 
+    #[test]
+    fn test_category_grains_counts_one_category() {
+        let mut se = SandEngine::new(10, 5);
+        let (none, work) = (CategoryId::new(0), CategoryId::new(1));
+        for _ in 0..3 {
+            se.spawn(none);
+        }
+        for _ in 0..5 {
+            se.spawn(work);
+        }
+
+        assert_eq!(se.category_grains(none), 3);
+        assert_eq!(se.category_grains(work), 5);
+        assert_eq!(se.category_grains(CategoryId::new(2)), 0);
+    }
+
     #[test]
     fn test_sand_state_restore_resizes_to_current_grid() {
         let mut small = SandEngine::new(20, 20);
@@ -607,6 +1117,38 @@ mod tests {
         assert_eq!(large.grain_count, 2);
     }
 
+    #[test]
+    fn test_compact_day_presses_the_pile_into_a_full_band() {
+        let mut se = SandEngine::new(10, 5);
+        let (work, rest) = (CategoryId::new(1), CategoryId::new(2));
+        for x in 0..20 {
+            se.grid[19][x] = Some(work);
+            se.grid[18][x] = Some(if x < 10 { work } else { rest });
+        }
+        se.grain_count = 40;
+
+        se.compact_day(None);
+        assert_eq!(se.grain_count, 0);
+        assert_eq!(se.grid.len(), 19);
+        let band = &se.layer_rows[0];
+        assert_eq!(band.iter().filter(|c| **c == Some(work)).count(), 15);
+        assert_eq!(band.iter().filter(|c| **c == Some(rest)).count(), 5);
+
+        se.grid[18][0] = Some(rest);
+        se.grain_count = 1;
+        let state = se.snapshot_state();
+        let mut restored = SandEngine::new(7, 5);
+        restored.restore_state(&state, &HashSet::from([work, rest]));
+        assert_eq!(restored.grid.len(), 19);
+        assert_eq!(restored.grain_count, 1);
+        assert!(restored.layer_rows[0].iter().all(Option::is_some));
+        assert_eq!(restored.layer_rows[0].len(), 14);
+
+        se.compact_day(None);
+        assert_eq!(se.grid.len(), 18);
+        assert!(se.layer_rows[0].iter().all(|c| *c == Some(rest)));
+    }
+
     #[test]
     fn test_clear_category_removes_only_requested_id() {
         let mut se = SandEngine::new(20, 20);
@@ -2,4 +2,6 @@ mod engine;
 mod resize;
 
 #[allow(unused_imports)]
-pub use engine::{SandEngine, SandState, SandStateGrain};
+pub use engine::{
+    SandEngine, SandLayer, SandState, SandStateGrain, SpawnStrategy, set_check_invariants,
+};
@@ -15,67 +15,98 @@ pub fn resize_grid(
     dot_width: usize,
     dot_height: usize,
 ) -> Vec<Vec<Option<CategoryId>>> {
-    let old_h = old_grid.len();
-    let old_w = old_grid.first().map_or(0, |row| row.len());
+    let mut grid = old_grid.to_vec();
+    resize_grid_in_place(&mut grid, new_w, new_h, dot_width, dot_height);
+    grid
+}
+
+pub fn resize_grid_in_place(
+    grid: &mut Vec<Vec<Option<CategoryId>>>,
+    new_w: usize,
+    new_h: usize,
+    dot_width: usize,
+    dot_height: usize,
+) {
+    let old_h = grid.len();
+    let old_w = grid.first().map_or(0, |row| row.len());
 
     if old_w == 0 || old_h == 0 {
-        return vec![vec![None; new_w]; new_h];
+        grid.clear();
+        reserve_geometric(grid, new_h);
+        grid.resize_with(new_h, || vec![None; new_w]);
+        return;
     }
 
     if old_w == new_w && old_h == new_h {
-        return old_grid.to_vec();
+        return;
     }
 
-    let mut new_grid = vec![vec![None; new_w]; new_h];
-
     let (x_src_start, x_src_end, x_dest_offset) = kept_window(old_w, new_w);
     let (y_src_start, y_src_end, y_dest_offset) = kept_window(old_h, new_h);
 
-    for (y_src, row) in old_grid
-        .iter()
-        .enumerate()
-        .take(y_src_end)
-        .skip(y_src_start)
-    {
-        for (x_src, cell) in row.iter().enumerate().take(x_src_end).skip(x_src_start) {
-            let x_dest = x_src - x_src_start + x_dest_offset;
-            let y_dest = y_src - y_src_start + y_dest_offset;
-            new_grid[y_dest][x_dest] = *cell;
-        }
-    }
+    let lost = classify_lost_grains(grid, x_src_start, x_src_end, y_src_start, y_src_end);
 
-    let lost = classify_lost_grains(old_grid, x_src_start, x_src_end, y_src_start, y_src_end);
+    grid.truncate(y_src_end);
+    grid.drain(..y_src_start);
+    for row in grid.iter_mut() {
+        shift_row(row, x_src_start, x_src_end, x_dest_offset, new_w);
+    }
+    if y_dest_offset > 0 || grid.len() < new_h {
+        reserve_geometric(grid, new_h);
+        grid.splice(0..0, (0..y_dest_offset).map(|_| vec![None; new_w]));
+        grid.resize_with(new_h, || vec![None; new_w]);
+    }
 
-    let new_cell_w = if dot_width == 0 { 0 } else { new_w / dot_width };
-    let new_cell_h = if dot_height == 0 {
-        0
-    } else {
-        new_h / dot_height
-    };
+    let new_cell_w = new_w.checked_div(dot_width).unwrap_or(0);
+    let new_cell_h = new_h.checked_div(dot_height).unwrap_or(0);
     let band_w = (new_cell_w / 40).clamp(2, 6);
     let band_h = (new_cell_h / 40).clamp(1, 3);
     let band_w_px = (band_w * dot_width).min(new_w);
     let band_h_px = (band_h * dot_height).min(new_h);
 
-    place_left_band(&mut new_grid, &lost.left, band_w_px);
-    place_right_band(&mut new_grid, &lost.right, band_w_px);
-    place_top_band(&mut new_grid, &lost.top, band_h_px);
-    place_bottom_band(&mut new_grid, &lost.bottom, band_h_px);
-
-    let left_capacity = band_w_px * new_h;
-    let right_capacity = band_w_px * new_h;
-    let top_capacity = band_h_px * new_w;
-    let bottom_capacity = band_h_px * new_w;
+    let left_placed = place_left_band(grid, &lost.left, band_w_px);
+    let right_placed = place_right_band(grid, &lost.right, band_w_px);
+    let top_placed = place_top_band(grid, &lost.top, band_h_px);
+    let bottom_placed = place_bottom_band(grid, &lost.bottom, band_h_px);
 
+    // Bands only take free cells, so whatever did not fit goes to overflow.
     let mut remaining = Vec::new();
-    remaining.extend(lost.left.iter().skip(left_capacity).copied());
-    remaining.extend(lost.right.iter().skip(right_capacity).copied());
-    remaining.extend(lost.top.iter().skip(top_capacity).copied());
-    remaining.extend(lost.bottom.iter().skip(bottom_capacity).copied());
+    remaining.extend(lost.left.iter().skip(left_placed).copied());
+    remaining.extend(lost.right.iter().skip(right_placed).copied());
+    remaining.extend(lost.top.iter().skip(top_placed).copied());
+    remaining.extend(lost.bottom.iter().skip(bottom_placed).copied());
 
-    place_overflow(&mut new_grid, &remaining);
+    place_overflow(grid, &remaining);
+}
 
-    new_grid
+fn reserve_geometric<T>(values: &mut Vec<T>, target_len: usize) {
+    if target_len > values.capacity() {
+        let target_capacity = target_len.max(values.capacity() * 2);
+        values.reserve_exact(target_capacity - values.len());
+    }
+}
+
+fn shift_row(
+    row: &mut Vec<Option<CategoryId>>,
+    src_start: usize,
+    src_end: usize,
+    dest_offset: usize,
+    new_w: usize,
+) {
+    let kept = src_end - src_start;
+    if src_start > 0 {
+        row.copy_within(src_start..src_end, 0);
+    }
+    row.truncate(kept);
+
+    if new_w > kept {
+        reserve_geometric(row, new_w);
+        row.resize(new_w, None);
+        if dest_offset > 0 {
+            row.copy_within(0..kept, dest_offset);
+            row[..dest_offset].fill(None);
+        }
+    }
 }
 
 fn kept_window(old_len: usize, new_len: usize) -> (usize, usize, usize) {
@@ -133,7 +164,11 @@ fn classify_lost_grains(
     lost
 }
 
-fn place_left_band(grid: &mut [Vec<Option<CategoryId>>], grains: &[CategoryId], band_w_px: usize) {
+fn place_left_band(
+    grid: &mut [Vec<Option<CategoryId>>],
+    grains: &[CategoryId],
+    band_w_px: usize,
+) -> usize {
     let mut iter = grains.iter();
 
     'outer: for row in grid.iter_mut().rev() {
@@ -147,9 +182,14 @@ fn place_left_band(grid: &mut [Vec<Option<CategoryId>>], grains: &[CategoryId],
             }
         }
     }
+    grains.len() - iter.len()
 }
 
-fn place_right_band(grid: &mut [Vec<Option<CategoryId>>], grains: &[CategoryId], band_w_px: usize) {
+fn place_right_band(
+    grid: &mut [Vec<Option<CategoryId>>],
+    grains: &[CategoryId],
+    band_w_px: usize,
+) -> usize {
     let w = grid.first().map_or(0, |row| row.len());
     let start = w.saturating_sub(band_w_px);
     let mut iter = grains.iter();
@@ -165,9 +205,14 @@ fn place_right_band(grid: &mut [Vec<Option<CategoryId>>], grains: &[CategoryId],
             }
         }
     }
+    grains.len() - iter.len()
 }
 
-fn place_top_band(grid: &mut [Vec<Option<CategoryId>>], grains: &[CategoryId], band_h_px: usize) {
+fn place_top_band(
+    grid: &mut [Vec<Option<CategoryId>>],
+    grains: &[CategoryId],
+    band_h_px: usize,
+) -> usize {
     let mut iter = grains.iter();
 
     'outer: for row in grid.iter_mut().take(band_h_px).rev() {
@@ -181,13 +226,14 @@ fn place_top_band(grid: &mut [Vec<Option<CategoryId>>], grains: &[CategoryId], b
             }
         }
     }
+    grains.len() - iter.len()
 }
 
 fn place_bottom_band(
     grid: &mut [Vec<Option<CategoryId>>],
     grains: &[CategoryId],
     band_h_px: usize,
-) {
+) -> usize {
     let h = grid.len();
     let start = h.saturating_sub(band_h_px);
     let mut iter = grains.iter();
@@ -203,6 +249,7 @@ fn place_bottom_band(
             }
         }
     }
+    grains.len() - iter.len()
 }
 
 fn place_overflow(grid: &mut [Vec<Option<CategoryId>>], grains: &[CategoryId]) {
@@ -220,7 +267,7 @@ fn place_overflow(grid: &mut [Vec<Option<CategoryId>>], grains: &[CategoryId]) {
 
 #[cfg(test)]
 mod tests {
-    use super::resize_grid;
+    use super::{resize_grid, resize_grid_in_place};
     use crate::domain::CategoryId;
 
     fn count_grains(grid: &[Vec<Option<CategoryId>>]) -> usize {
@@ -247,6 +294,20 @@ mod tests {
         assert!(resized.iter().any(|row| row[0].is_some()));
     }
 
+    #[test]
+    fn test_resize_grid_overflows_grains_that_miss_a_full_band() {
+        let mut old = vec![vec![None; 40]; 40];
+        for row in old.iter_mut().skip(20) {
+            for cell in row.iter_mut().skip(20) {
+                *cell = Some(CategoryId::new(2));
+            }
+        }
+
+        let resized = resize_grid(&old, 30, 30, 2, 4);
+
+        assert_eq!(count_grains(&resized), count_grains(&old));
+    }
+
     #[test]
     fn test_resize_grid_preserves_category_ids() {
         let mut old = vec![vec![None; 80]; 40];
@@ -270,4 +331,39 @@ mod tests {
 
         assert!(work_count > 0);
     }
+
+    #[test]
+    fn test_resize_grid_in_place_reuses_allocation_when_shrinking() {
+        let mut grid = vec![vec![None; 80]; 40];
+        for row in grid.iter_mut().skip(20).take(10) {
+            for cell in row.iter_mut().skip(20).take(40) {
+                *cell = Some(CategoryId::new(1));
+            }
+        }
+        let before = count_grains(&grid);
+        let outer_ptr = grid.as_ptr();
+
+        resize_grid_in_place(&mut grid, 60, 32, 2, 4);
+
+        assert_eq!(grid.as_ptr(), outer_ptr);
+        assert_eq!(grid.len(), 32);
+        assert!(grid.iter().all(|row| row.len() == 60));
+        assert_eq!(count_grains(&grid), before);
+        assert_eq!(grid[16][10], Some(CategoryId::new(1)));
+    }
+
+    #[test]
+    fn test_resize_grid_in_place_grows_and_centers_content() {
+        let mut grid = vec![vec![Some(CategoryId::new(2)); 4]; 4];
+
+        resize_grid_in_place(&mut grid, 8, 6, 2, 4);
+
+        assert_eq!(grid.len(), 6);
+        assert!(grid.iter().all(|row| row.len() == 8));
+        assert_eq!(count_grains(&grid), 16);
+        assert_eq!(grid[1][2], Some(CategoryId::new(2)));
+        assert_eq!(grid[0][0], None);
+        assert_eq!(grid[1][1], None);
+        assert_eq!(grid[4][5], Some(CategoryId::new(2)));
+    }
 }
@@ -0,0 +1,549 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::{cli, daemon, domain::ReportPeriod, storage};
+
+const MAX_BODY_BYTES: usize = 64 * 1024;
+const MAX_LINE_BYTES: usize = 8 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_SESSION_LIMIT: usize = 20;
+
+/// Loopback names a browser may put in `Host` or `Origin` for this server.
+const LOCAL_HOSTS: [&str; 3] = ["127.0.0.1", "localhost", "[::1]"];
+
+#[derive(Debug)]
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    /// Header names lowercased.
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    body: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody<'a> {
+    error: &'a str,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StopBody {
+    #[serde(default)]
+    at: Option<String>,
+    #[serde(default)]
+    discard: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct StartBody {
+    project: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    category: Option<String>,
+}
+
+impl Response {
+    fn json<T: Serialize>(status: u16, value: &T) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self { status, body },
+            Err(e) => Self::error(500, &e.to_string()),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        let body = serde_json::to_string(&ErrorBody { error: message })
+            .unwrap_or_else(|_| "{}".to_string());
+        Self { status, body }
+    }
+}
+
+/// Serves the API on `bind`. Only loopback addresses are allowed unless
+/// `allow_remote` is set, and then `token` must be given: every request has
+/// to carry it as `Authorization: Bearer <token>`.
+pub fn serve(
+    bind: &str,
+    port: u16,
+    allow_remote: bool,
+    token: Option<String>,
+) -> Result<(), String> {
+    let token = token.filter(|token| !token.trim().is_empty());
+    if !is_loopback_bind(bind, port)? {
+        if !allow_remote {
+            return Err(format!(
+                "Refusing to listen on non-loopback address {}; pass --allow-remote to expose the API",
+                bind
+            ));
+        }
+        if token.is_none() {
+            return Err(
+                "--allow-remote needs STRATA_SERVE_TOKEN set to the bearer token clients must send"
+                    .to_string(),
+            );
+        }
+    }
+
+    let listener = TcpListener::bind((bind, port))
+        .map_err(|e| format!("Failed to listen on {}:{}: {}", bind, port, e))?;
+    let addr = listener.local_addr().map_err(|e| e.to_string())?;
+    println!("Serving strata API on http://{}", addr);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = handle_connection(stream, addr.port(), token.as_deref()) {
+                    eprintln!("Request failed: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Connection failed: {}", e),
+        }
+    }
+    Ok(())
+}
+
+fn is_loopback_bind(bind: &str, port: u16) -> Result<bool, String> {
+    let addrs: Vec<_> = (bind, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Invalid bind address {}: {}", bind, e))?
+        .collect();
+    Ok(!addrs.is_empty() && addrs.iter().all(|addr| addr.ip().is_loopback()))
+}
+
+fn handle_connection(mut stream: TcpStream, port: u16, token: Option<&str>) -> Result<(), String> {
+    stream
+        .set_read_timeout(Some(READ_TIMEOUT))
+        .and_then(|_| stream.set_write_timeout(Some(WRITE_TIMEOUT)))
+        .map_err(|e| e.to_string())?;
+    let response = match read_request(&mut BufReader::new(&stream)) {
+        Ok(request) => check_source(&request, port, token).unwrap_or_else(|| route(&request)),
+        Err(e) => Response::error(400, &e),
+    };
+    write_response(&mut stream, &response)
+}
+
+/// Refuses requests without the bearer token when one is configured, and
+/// what a web page could send through the user's browser: requests under
+/// another host name (DNS rebinding; only checked without a token, since a
+/// remote client names the server by its own address), from a non-local
+/// origin, or POSTs that dodge the CORS preflight by not being JSON.
+fn check_source(request: &Request, port: u16, token: Option<&str>) -> Option<Response> {
+    if let Some(token) = token {
+        let presented = request
+            .headers
+            .get("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim);
+        if !presented.is_some_and(|presented| tokens_match(presented, token)) {
+            return Some(Response::error(401, "Missing or wrong bearer token"));
+        }
+    }
+    let local_host = token.is_some()
+        || request.headers.get("host").is_some_and(|host| {
+            LOCAL_HOSTS
+                .iter()
+                .any(|name| host.eq_ignore_ascii_case(&format!("{}:{}", name, port)))
+        });
+    if !local_host {
+        return Some(Response::error(
+            403,
+            &format!("Host must be localhost:{} or 127.0.0.1:{}", port, port),
+        ));
+    }
+    if let Some(origin) = request.headers.get("origin")
+        && !is_local_origin(origin)
+    {
+        return Some(Response::error(
+            403,
+            "Cross-origin requests are not allowed",
+        ));
+    }
+    let json = request.headers.get("content-type").is_some_and(|kind| {
+        kind.split(';')
+            .next()
+            .is_some_and(|kind| kind.trim().eq_ignore_ascii_case("application/json"))
+    });
+    if request.method == "POST" && !json {
+        return Some(Response::error(
+            415,
+            "Content-Type must be application/json",
+        ));
+    }
+    None
+}
+
+/// Compares every byte so the time taken doesn't give away how much of the
+/// token was right.
+fn tokens_match(presented: &str, token: &str) -> bool {
+    presented.len() == token.len()
+        && presented
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn is_local_origin(origin: &str) -> bool {
+    let Some(authority) = origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+    else {
+        return false;
+    };
+    let host = match authority.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => authority,
+    };
+    LOCAL_HOSTS
+        .iter()
+        .any(|name| host.eq_ignore_ascii_case(name))
+}
+
+/// One line of at most `MAX_LINE_BYTES`, so a client can't make the server
+/// buffer an endless header.
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> Result<usize, String> {
+    let read = Read::take(&mut *reader, MAX_LINE_BYTES as u64 + 1)
+        .read_line(line)
+        .map_err(|e| e.to_string())?;
+    if read > MAX_LINE_BYTES {
+        return Err("Request line too long".to_string());
+    }
+    Ok(read)
+}
+
+fn read_request(reader: &mut impl BufRead) -> Result<Request, String> {
+    let mut request_line = String::new();
+    read_line(reader, &mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err("Malformed request line".to_string());
+    };
+
+    let mut content_length = 0;
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        let read = read_line(reader, &mut line)?;
+        let line = line.trim_end();
+        if read == 0 || line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.trim().to_ascii_lowercase();
+        if name == "content-length" {
+            content_length = value
+                .trim()
+                .parse()
+                .map_err(|_| "Invalid Content-Length".to_string())?;
+        }
+        headers.insert(name, value.trim().to_string());
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err("Request body too large".to_string());
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, parse_query(query)),
+        None => (target, HashMap::new()),
+    };
+    Ok(Request {
+        method: method.to_ascii_uppercase(),
+        path: path.to_string(),
+        query,
+        headers,
+        body,
+    })
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match bytes
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn route(request: &Request) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/status") => match cli::status_output() {
+            Ok(output) => Response::json(200, &output),
+            Err(e) => Response::error(500, &e),
+        },
+        ("POST", "/start") => start(request),
+        ("POST", "/stop") => stop(request),
+        ("GET", "/report") => report(request),
+        ("GET", "/sessions") => sessions(request),
+        (_, "/status" | "/start" | "/stop" | "/report" | "/sessions") => {
+            Response::error(405, "Method not allowed")
+        }
+        _ => Response::error(404, "Not found"),
+    }
+}
+
+fn start(request: &Request) -> Response {
+    let body: StartBody = match serde_json::from_slice(&request.body) {
+        Ok(body) => body,
+        Err(e) => return Response::error(400, &format!("Invalid start body: {}", e)),
+    };
+    match cli::begin_session(
+        body.project,
+        body.description,
+        body.category.as_deref(),
+        Utc::now(),
+    ) {
+        Ok(output) => Response::json(200, &output),
+        Err(e) => Response::error(400, &e),
+    }
+}
+
+fn stop(request: &Request) -> Response {
+    let body: StopBody = if request.body.is_empty() {
+        StopBody::default()
+    } else {
+        match serde_json::from_slice(&request.body) {
+            Ok(body) => body,
+            Err(e) => return Response::error(400, &format!("Invalid stop body: {}", e)),
+        }
+    };
+    let end_time = match body.at.as_deref().map(cli::resolve_clock_time).transpose() {
+        Ok(end_time) => end_time,
+        Err(e) => return Response::error(400, &e),
+    };
+    let _lock = if daemon::is_running() {
+        None
+    } else {
+        match storage::acquire_instance_lock(&storage::get_instance_lock_path()) {
+            Ok(lock) => Some(lock),
+            Err(e) => return Response::error(409, &e),
+        }
+    };
+    match cli::end_active_session(end_time, body.discard) {
+        Ok(output) => Response::json(200, &output),
+        Err(e) => Response::error(409, &e),
+    }
+}
+
+fn report(request: &Request) -> Response {
+    let period = match request.query.get("period").map(String::as_str) {
+        None | Some("today") => ReportPeriod::Today,
+        Some("24h") => ReportPeriod::Last24Hours,
+        Some("week") => ReportPeriod::Week,
+        Some("month") => ReportPeriod::Month,
+        Some(other) => {
+            return Response::error(
+                400,
+                &format!(
+                    "Unknown period '{}', expected today, 24h, week or month",
+                    other
+                ),
+            );
+        }
+    };
+    Response::json(200, &cli::report_output(period, None))
+}
+
+fn count_param(request: &Request, name: &str, default: usize) -> Result<usize, Response> {
+    match request.query.get(name) {
+        Some(raw) => raw
+            .parse()
+            .map_err(|_| Response::error(400, &format!("Invalid {} '{}'", name, raw))),
+        None => Ok(default),
+    }
+}
+
+fn sessions(request: &Request) -> Response {
+    let limit = match count_param(request, "limit", DEFAULT_SESSION_LIMIT) {
+        Ok(limit) => limit,
+        Err(response) => return response,
+    };
+    let offset = match count_param(request, "offset", 0) {
+        Ok(offset) => offset,
+        Err(response) => return response,
+    };
+    let param = |name: &str| request.query.get(name).map(String::as_str);
+    let filter = cli::SessionFilter {
+        date: param("date"),
+        from: param("from"),
+        to: param("to"),
+        category: param("category"),
+        tag: param("tag"),
+        offset,
+        limit,
+    };
+    match cli::session_rows(&filter) {
+        Ok(rows) => Response::json(200, &rows),
+        Err(e) => Response::error(400, &e),
+    }
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        415 => "Unsupported Media Type",
+        _ => "Internal Server Error",
+    }
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> Result<(), String> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        status_text(response.status),
+        response.body.len()
+    );
+    stream
+        .write_all(head.as_bytes())
+        .and_then(|_| stream.write_all(response.body.as_bytes()))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request_parses_target_and_body() {
+        let raw = "POST /start?x=1 HTTP/1.1\r\nHost: localhost\r\nContent-Length: 17\r\n\r\n{\"project\":\"a b\"}";
+        let request = read_request(&mut raw.as_bytes()).unwrap();
+
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/start");
+        assert_eq!(request.query.get("x").map(String::as_str), Some("1"));
+        assert_eq!(request.body, b"{\"project\":\"a b\"}");
+    }
+
+    fn check_raw(raw: &str) -> Option<u16> {
+        let request = read_request(&mut raw.as_bytes()).unwrap();
+        check_source(&request, 7878, None).map(|response| response.status)
+    }
+
+    #[test]
+    fn test_check_source_rejects_foreign_origins_and_hosts() {
+        let foreign = "POST /stop HTTP/1.1\r\nHost: 127.0.0.1:7878\r\nOrigin: https://evil.example\r\nContent-Type: application/json\r\n\r\n";
+        assert_eq!(check_raw(foreign), Some(403));
+
+        let rebound = "GET /sessions HTTP/1.1\r\nHost: evil.example:7878\r\n\r\n";
+        assert_eq!(check_raw(rebound), Some(403));
+
+        let plain =
+            "POST /start HTTP/1.1\r\nHost: localhost:7878\r\nContent-Type: text/plain\r\n\r\n";
+        assert_eq!(check_raw(plain), Some(415));
+
+        let local = "POST /stop HTTP/1.1\r\nHost: localhost:7878\r\nOrigin: http://localhost:3000\r\nContent-Type: application/json; charset=utf-8\r\n\r\n";
+        assert_eq!(check_raw(local), None);
+        let curl = "GET /status HTTP/1.1\r\nHost: 127.0.0.1:7878\r\n\r\n";
+        assert_eq!(check_raw(curl), None);
+    }
+
+    #[test]
+    fn test_check_source_requires_the_bearer_token() {
+        let check = |auth: &str| {
+            let raw = format!(
+                "GET /status HTTP/1.1\r\nHost: 192.168.1.5:7878\r\n{}\r\n",
+                auth
+            );
+            let request = read_request(&mut raw.as_bytes()).unwrap();
+            check_source(&request, 7878, Some("s3cret")).map(|response| response.status)
+        };
+
+        assert_eq!(check(""), Some(401));
+        assert_eq!(check("Authorization: Bearer wrong\r\n"), Some(401));
+        assert_eq!(check("Authorization: Bearer s3cre\r\n"), Some(401));
+        assert_eq!(check("Authorization: Bearer s3cret\r\n"), None);
+    }
+
+    #[test]
+    fn test_serve_refuses_remote_binds_without_opt_in_and_token() {
+        assert!(is_loopback_bind("127.0.0.1", 0).unwrap());
+        assert!(is_loopback_bind("::1", 0).unwrap());
+        assert!(!is_loopback_bind("0.0.0.0", 0).unwrap());
+
+        let refused = serve("0.0.0.0", 0, false, Some("s3cret".to_string())).unwrap_err();
+        assert!(refused.contains("--allow-remote"));
+        let tokenless = serve("0.0.0.0", 0, true, Some(" ".to_string())).unwrap_err();
+        assert!(tokenless.contains("STRATA_SERVE_TOKEN"));
+    }
+
+    #[test]
+    fn test_read_request_rejects_oversized_body() {
+        let raw = format!(
+            "POST /start HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_BYTES + 1
+        );
+        assert!(read_request(&mut raw.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_read_request_rejects_overlong_header_lines() {
+        let raw = format!(
+            "GET /status HTTP/1.1\r\nX-Pad: {}\r\n\r\n",
+            "a".repeat(MAX_LINE_BYTES)
+        );
+        assert_eq!(
+            read_request(&mut raw.as_bytes()).unwrap_err(),
+            "Request line too long"
+        );
+    }
+
+    #[test]
+    fn test_parse_query_decodes_escapes() {
+        let query = parse_query("date=2024-01-02&name=deep+work%21&bad=%zz&flag");
+
+        assert_eq!(query["date"], "2024-01-02");
+        assert_eq!(query["name"], "deep work!");
+        assert_eq!(query["bad"], "%zz");
+        assert_eq!(query["flag"], "");
+    }
+}
@@ -1,11 +1,12 @@
 use std::{
-    collections::HashMap,
-    fs::{self, File},
-    io::Write,
+    collections::{BTreeMap, HashMap},
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
     path::{Path, PathBuf},
+    sync::OnceLock,
 };
 
-use chrono::Local;
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, Utc};
 use csv::{ReaderBuilder, StringRecord, WriterBuilder};
 use directories::ProjectDirs;
 use ratatui::style::Color;
@@ -13,9 +14,13 @@ use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use thiserror::Error;
 
 use crate::{
-    constants::COLORS,
-    domain::{Category, CategoryId, Session},
-    sand::SandState,
+    constants::{COLORS, TIME_SETTINGS},
+    domain::{
+        CarryOverRules, CarryReset, Category, CategoryId, GoalLedgerEntry, KeptOverlaps,
+        PeriodLabelStyle, Session, WeekStart, close_goal_weeks, color_hex, parse_color,
+    },
+    duration::parse_duration,
+    sand::{SandState, SpawnStrategy},
 };
 
 #[derive(Debug)]
@@ -30,8 +35,58 @@ pub struct LoadedSessions {
     pub next_session_id: usize,
 }
 
-const CATEGORIES_HEADER: [&str; 5] = ["id", "name", "description", "color_index", "karma_effect"];
-const SESSIONS_HEADER: [&str; 8] = [
+const CATEGORIES_HEADER: [&str; 7] = [
+    "id",
+    "name",
+    "description",
+    "color",
+    "karma_effect",
+    "default_description",
+    "order",
+];
+const PRE_HEX_CATEGORIES_HEADER: [&str; 7] = [
+    "id",
+    "name",
+    "description",
+    "color_index",
+    "karma_effect",
+    "default_description",
+    "order",
+];
+const PRE_ORDER_CATEGORIES_HEADER: [&str; 6] = [
+    "id",
+    "name",
+    "description",
+    "color_index",
+    "karma_effect",
+    "default_description",
+];
+const LEGACY_CATEGORIES_HEADER: [&str; 5] =
+    ["id", "name", "description", "color_index", "karma_effect"];
+const SESSIONS_HEADER: [&str; 10] = [
+    "id",
+    "date",
+    "category_id",
+    "category_name",
+    "description",
+    "start_time",
+    "end_time",
+    "elapsed_seconds",
+    "project",
+    "focus",
+];
+const PRE_FOCUS_SESSIONS_HEADER: [&str; 9] = [
+    "id",
+    "date",
+    "category_id",
+    "category_name",
+    "description",
+    "start_time",
+    "end_time",
+    "elapsed_seconds",
+    "project",
+];
+const LEGACY_SESSIONS_HEADER: [&str; 8] = [
     "id",
     "date",
     "category_id",
@@ -42,13 +97,28 @@ const SESSIONS_HEADER: [&str; 8] = [
     "elapsed_seconds",
 ];
 
+const GRAIN_LOG_HEADER: [&str; 4] = ["timestamp", "category_id", "x", "grid_width"];
+const GRAIN_LOG_MAX_BYTES: u64 = 4 * 1024 * 1024;
+const GRAIN_LOG_ROTATIONS: usize = 3;
+const DEFAULT_EXPORT_TEMPLATE: &str = "strata-{period}-{date}.{ext}";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrainEvent {
+    pub timestamp: DateTime<Utc>,
+    pub category_id: u64,
+    pub x: usize,
+    pub grid_width: usize,
+}
+
 #[derive(Debug, Error)]
 pub enum StorageError {
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
     #[error("CSV error: {0}")]
     Csv(#[from] csv::Error),
-    #[error("Invalid CSV schema for {file}: expected '{expected}', found '{found}'")]
+    #[error(
+        "Invalid CSV schema for {file}: expected '{expected}', found '{found}'; restore a backup or run `strata doctor --headers` once the columns line up"
+    )]
     InvalidCsvSchema {
         file: &'static str,
         expected: String,
@@ -64,6 +134,34 @@ pub struct CategoryTagsState {
 
 impl CategoryTagsState {
     pub const VERSION: u8 = 1;
+    pub const MAX_TAGS_PER_CATEGORY: usize = 24;
+
+    pub fn tags(&self, id: CategoryId) -> &[String] {
+        self.tags_by_category.get(&id.0).map_or(&[], Vec::as_slice)
+    }
+
+    /// Puts `tag` first, dropping an older copy and the oldest tags past
+    /// the limit. Blank tags are ignored.
+    pub fn add_tag(&mut self, id: CategoryId, tag: &str) -> bool {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return false;
+        }
+        let tags = self.tags_by_category.entry(id.0).or_default();
+        tags.retain(|existing| existing != tag);
+        tags.insert(0, tag.to_string());
+        tags.truncate(Self::MAX_TAGS_PER_CATEGORY);
+        true
+    }
+
+    pub fn remove_tag(&mut self, id: CategoryId, index: usize) -> Option<String> {
+        let tags = self.tags_by_category.get_mut(&id.0)?;
+        let removed = (index < tags.len()).then(|| tags.remove(index));
+        if tags.is_empty() {
+            self.tags_by_category.remove(&id.0);
+        }
+        removed
+    }
 }
 
 impl Default for CategoryTagsState {
@@ -75,338 +173,1967 @@ impl Default for CategoryTagsState {
     }
 }
 
-fn default_categories_loaded() -> LoadedCategories {
-    LoadedCategories {
-        categories: vec![Category {
-            id: CategoryId::new(0),
-            name: "none".to_string(),
-            color: Color::White,
-            description: String::new(),
-            karma_effect: 0,
-        }],
-        next_category_id: 1,
-    }
-}
-
-fn default_sessions_loaded() -> LoadedSessions {
-    LoadedSessions {
-        sessions: vec![],
-        next_session_id: 1,
-    }
+/// Named sections for the category modal and report subtotals. Categories
+/// without an entry stay ungrouped.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CategoryGroupsState {
+    pub version: u8,
+    pub group_by_category: HashMap<u64, String>,
+    pub collapsed: Vec<String>,
 }
 
-fn csv_header_matches(headers: &StringRecord, expected: &[&str]) -> bool {
-    headers.len() == expected.len()
-        && headers
-            .iter()
-            .zip(expected.iter())
-            .all(|(actual, expected)| actual == *expected)
-}
+impl CategoryGroupsState {
+    pub const VERSION: u8 = 1;
 
-fn csv_header_string(headers: &StringRecord) -> String {
-    headers.iter().collect::<Vec<_>>().join(",")
-}
+    pub fn group_of(&self, id: CategoryId) -> Option<&str> {
+        self.group_by_category.get(&id.0).map(String::as_str)
+    }
 
-pub fn load_categories_from_csv(path: &Path) -> LoadedCategories {
-    match try_load_categories_from_csv(path) {
-        Ok(loaded) => loaded,
-        Err(e) => {
-            eprintln!("Warning: Could not load categories file: {}", e);
-            default_categories_loaded()
+    /// An empty name takes the category out of its group.
+    pub fn set_group(&mut self, id: CategoryId, name: &str) {
+        let name = name.trim();
+        if name.is_empty() {
+            self.group_by_category.remove(&id.0);
+        } else {
+            self.group_by_category.insert(id.0, name.to_string());
         }
+        let groups = &self.group_by_category;
+        self.collapsed
+            .retain(|group| groups.values().any(|name| name == group));
     }
-}
 
-pub fn try_load_categories_from_csv(path: &Path) -> Result<LoadedCategories, StorageError> {
-    if !path.exists() {
-        return Ok(default_categories_loaded());
+    pub fn is_collapsed(&self, group: &str) -> bool {
+        self.collapsed.iter().any(|name| name == group)
     }
 
-    let mut reader = ReaderBuilder::new().has_headers(true).from_path(path)?;
-    let headers = reader.headers()?.clone();
-    if !csv_header_matches(&headers, &CATEGORIES_HEADER) {
-        return Err(StorageError::InvalidCsvSchema {
-            file: "categories.csv",
-            expected: CATEGORIES_HEADER.join(","),
-            found: csv_header_string(&headers),
-        });
+    pub fn set_collapsed(&mut self, group: &str, collapsed: bool) -> bool {
+        if self.is_collapsed(group) == collapsed {
+            return false;
+        }
+        if collapsed {
+            self.collapsed.push(group.to_string());
+        } else {
+            self.collapsed.retain(|name| name != group);
+        }
+        true
     }
 
-    let mut loaded = default_categories_loaded();
-
-    for record in reader.records() {
-        let record = record?;
-
-        let Some(id_raw) = record.get(0) else {
-            continue;
-        };
-        let id: u64 = match id_raw.parse() {
-            Ok(id) => id,
-            Err(_) => {
-                eprintln!("Warning: Invalid category ID '{}', skipping", id_raw);
-                continue;
+    /// Group names in the order their first category appears.
+    pub fn ordered_groups<'a>(
+        &self,
+        categories: impl IntoIterator<Item = &'a Category>,
+    ) -> Vec<String> {
+        let mut groups: Vec<String> = Vec::new();
+        for category in categories {
+            if let Some(group) = self.group_of(category.id)
+                && !groups.iter().any(|name| name == group)
+            {
+                groups.push(group.to_string());
             }
-        };
-
-        if id == 0 {
-            continue;
         }
+        groups
+    }
+}
 
-        let name = record.get(1).unwrap_or_default().trim().to_string();
-        if name.is_empty() || name.eq_ignore_ascii_case("none") {
-            continue;
+impl Default for CategoryGroupsState {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            group_by_category: HashMap::new(),
+            collapsed: Vec::new(),
         }
-
-        let description = record.get(2).unwrap_or_default().to_string();
-        let color_idx = record
-            .get(3)
-            .and_then(|value| value.parse::<usize>().ok())
-            .unwrap_or(0)
-            % COLORS.len();
-        let karma_effect = record
-            .get(4)
-            .and_then(|value| value.parse::<i8>().ok())
-            .unwrap_or(1);
-
-        loaded.categories.push(Category {
-            id: CategoryId::new(id),
-            name,
-            color: COLORS[color_idx],
-            description,
-            karma_effect,
-        });
-        loaded.next_category_id = loaded.next_category_id.max(id + 1);
     }
+}
 
-    Ok(loaded)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillingRatesState {
+    pub version: u8,
+    pub category_rates: HashMap<u64, f64>,
+    pub project_rates: HashMap<String, f64>,
 }
 
-pub fn load_sessions_from_csv(path: &Path, categories: &[Category]) -> LoadedSessions {
-    match try_load_sessions_from_csv(path, categories) {
-        Ok(loaded) => loaded,
-        Err(e) => {
-            eprintln!("Warning: Could not load sessions file: {}", e);
-            default_sessions_loaded()
+impl BillingRatesState {
+    pub const VERSION: u8 = 1;
+}
+
+impl Default for BillingRatesState {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            category_rates: HashMap::new(),
+            project_rates: HashMap::new(),
         }
     }
 }
 
-pub fn try_load_sessions_from_csv(
-    path: &Path,
-    categories: &[Category],
-) -> Result<LoadedSessions, StorageError> {
-    if !path.exists() {
-        return Ok(default_sessions_loaded());
-    }
+/// Feature usage counts, recorded only when `metrics` is enabled in the config.
+/// Keys are command and view names; session content is never stored.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UsageMetricsState {
+    pub version: u8,
+    pub since: Option<DateTime<Utc>>,
+    pub counts: BTreeMap<String, u64>,
+}
 
-    let category_by_id: HashMap<u64, CategoryId> = categories
-        .iter()
-        .map(|category| (category.id.0, category.id))
-        .collect();
+impl UsageMetricsState {
+    pub const VERSION: u8 = 1;
+}
 
-    let mut reader = ReaderBuilder::new().has_headers(true).from_path(path)?;
-    let headers = reader.headers()?.clone();
-    if !csv_header_matches(&headers, &SESSIONS_HEADER) {
-        return Err(StorageError::InvalidCsvSchema {
-            file: "time_log.csv",
-            expected: SESSIONS_HEADER.join(","),
-            found: csv_header_string(&headers),
-        });
+impl Default for UsageMetricsState {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            since: None,
+            counts: BTreeMap::new(),
+        }
     }
+}
 
-    let mut loaded = default_sessions_loaded();
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FocusContract {
+    pub category_id: u64,
+    pub started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
 
-    for record in reader.records() {
-        let record = record?;
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FocusContractRecord {
+    pub category_id: u64,
+    pub started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub broken_at: Option<DateTime<Utc>>,
+}
 
-        let Some(id_raw) = record.get(0) else {
-            continue;
-        };
-        let id: usize = match id_raw.parse() {
-            Ok(id) => id,
-            Err(_) => {
-                eprintln!("Warning: Invalid session ID '{}', skipping", id_raw);
-                continue;
-            }
-        };
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GoalLedgerRow {
+    pub week_start: String,
+    pub category_id: u64,
+    pub goal_seconds: usize,
+    pub actual_seconds: usize,
+    pub carried_in: i64,
+    pub carried_out: i64,
+}
 
-        let category_id = record
-            .get(2)
-            .and_then(|value| value.parse::<u64>().ok())
-            .and_then(|raw| category_by_id.get(&raw).copied())
-            .unwrap_or(CategoryId::new(0));
+/// Closed goal weeks. `open_week` is the first week not closed yet; it is
+/// set to the current week the first time carry-over runs.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct GoalLedgerState {
+    pub version: u8,
+    pub open_week: Option<String>,
+    pub weeks: Vec<GoalLedgerRow>,
+}
 
-        loaded.sessions.push(Session {
-            id,
-            date: record.get(1).unwrap_or_default().to_string(),
-            category_id,
-            description: record.get(4).unwrap_or_default().to_string(),
-            start_time: record.get(5).unwrap_or_default().to_string(),
-            end_time: record.get(6).unwrap_or_default().to_string(),
-            elapsed_seconds: record
-                .get(7)
-                .and_then(|value| value.parse::<usize>().ok())
-                .unwrap_or(0),
-        });
+impl GoalLedgerState {
+    pub const VERSION: u8 = 1;
 
-        loaded.next_session_id = loaded.next_session_id.max(id + 1);
+    pub fn entries(&self) -> Vec<GoalLedgerEntry> {
+        self.weeks
+            .iter()
+            .filter_map(|row| {
+                Some(GoalLedgerEntry {
+                    week_start: NaiveDate::parse_from_str(&row.week_start, "%Y-%m-%d").ok()?,
+                    category_id: CategoryId::new(row.category_id),
+                    goal_seconds: row.goal_seconds,
+                    actual_seconds: row.actual_seconds,
+                    carried_in: row.carried_in,
+                    carried_out: row.carried_out,
+                })
+            })
+            .collect()
     }
+}
 
-    Ok(loaded)
+impl Default for GoalLedgerState {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            open_week: None,
+            weeks: Vec::new(),
+        }
+    }
 }
 
-pub fn save_categories_to_csv(path: &Path, categories: &[Category]) -> Result<(), String> {
-    let mut writer = WriterBuilder::new().has_headers(false).from_writer(vec![]);
-    writer
-        .write_record(CATEGORIES_HEADER)
-        .map_err(|e| e.to_string())?;
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct FocusContractState {
+    pub version: u8,
+    pub active: Option<FocusContract>,
+    pub history: Vec<FocusContractRecord>,
+}
 
-    for category in categories {
-        if category.id.0 == 0 {
-            continue;
-        }
+impl FocusContractState {
+    pub const VERSION: u8 = 1;
 
-        let color_pos = COLORS
-            .iter()
-            .position(|&color| color == category.color)
-            .unwrap_or(0);
+    /// The contract still running at `now`.
+    pub fn active_at(&self, now: DateTime<Utc>) -> Option<FocusContract> {
+        self.active.filter(|contract| contract.ends_at > now)
+    }
 
-        writer
-            .write_record([
-                category.id.0.to_string(),
-                category.name.clone(),
-                category.description.clone(),
-                color_pos.to_string(),
-                category.karma_effect.to_string(),
-            ])
-            .map_err(|e| e.to_string())?;
+    pub fn start(&mut self, category_id: u64, now: DateTime<Utc>, seconds: usize) {
+        self.active = Some(FocusContract {
+            category_id,
+            started_at: now,
+            ends_at: now + chrono::Duration::seconds(seconds as i64),
+        });
     }
 
-    let bytes = writer.into_inner().map_err(|e| e.error().to_string())?;
-    let content = String::from_utf8_lossy(&bytes).to_string();
+    /// Moves the running contract into the history, broken at `broken_at`
+    /// or kept when that is `None`. Returns whether there was one.
+    pub fn finish(&mut self, broken_at: Option<DateTime<Utc>>) -> bool {
+        let Some(contract) = self.active.take() else {
+            return false;
+        };
+        self.history.push(FocusContractRecord {
+            category_id: contract.category_id,
+            started_at: contract.started_at,
+            ends_at: contract.ends_at,
+            broken_at,
+        });
+        true
+    }
 
-    atomic_write(path, &content)
+    /// Closes out a contract whose window passed by `now`, counting it as
+    /// kept.
+    pub fn expire(&mut self, now: DateTime<Utc>) -> bool {
+        self.active.is_some_and(|contract| contract.ends_at <= now) && self.finish(None)
+    }
 }
 
-pub fn save_sessions_to_csv(
-    path: &Path,
-    sessions: &[Session],
-    categories: &[Category],
-) -> Result<(), String> {
-    let mut writer = WriterBuilder::new().has_headers(false).from_writer(vec![]);
-    writer
-        .write_record(SESSIONS_HEADER)
-        .map_err(|e| e.to_string())?;
+impl Default for FocusContractState {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            active: None,
+            history: Vec::new(),
+        }
+    }
+}
 
-    for session in sessions {
-        let category_name = categories
-            .iter()
-            .find(|category| category.id == session.category_id)
-            .map(|category| category.name.as_str())
-            .unwrap_or("none");
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PlanItem {
+    pub category_id: u64,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub planned_seconds: Option<usize>,
+    /// Local clock time the block is scheduled to start, if any.
+    #[serde(default)]
+    pub starts_at: Option<NaiveTime>,
+    /// Tracked time frozen when the item was checked off.
+    #[serde(default)]
+    pub actual_seconds: Option<usize>,
+}
 
-        writer
-            .write_record([
-                session.id.to_string(),
-                session.date.clone(),
-                session.category_id.0.to_string(),
-                category_name.to_string(),
-                session.description.clone(),
-                session.start_time.clone(),
-                session.end_time.clone(),
-                session.elapsed_seconds.to_string(),
-            ])
-            .map_err(|e| e.to_string())?;
+impl PlanItem {
+    pub fn is_done(&self) -> bool {
+        self.actual_seconds.is_some()
     }
+}
 
-    let bytes = writer.into_inner().map_err(|e| e.error().to_string())?;
-    let content = String::from_utf8_lossy(&bytes).to_string();
+/// Items pinned for one operational day.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct PlanState {
+    pub version: u8,
+    pub day: Option<NaiveDate>,
+    pub items: Vec<PlanItem>,
+}
 
-    atomic_write(path, &content)
+impl PlanState {
+    pub const VERSION: u8 = 1;
+}
+
+impl Default for PlanState {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            day: None,
+            items: Vec::new(),
+        }
+    }
+}
+
+/// Overlapping session pairs kept with "keep both", so they aren't flagged
+/// again.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KeptOverlapsState {
+    pub version: u8,
+    pub pairs: KeptOverlaps,
+}
+
+impl KeptOverlapsState {
+    pub const VERSION: u8 = 1;
+}
+
+impl Default for KeptOverlapsState {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            pairs: KeptOverlaps::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CategoryBudgetsState {
+    pub version: u8,
+    pub daily_seconds_by_category: HashMap<u64, usize>,
+}
+
+impl CategoryBudgetsState {
+    pub const VERSION: u8 = 1;
+
+    /// Daily budgets double as goals; a week's goal is seven days' worth.
+    pub fn weekly_goals(&self) -> HashMap<CategoryId, usize> {
+        self.daily_seconds_by_category
+            .iter()
+            .map(|(id, seconds)| (CategoryId::new(*id), seconds * 7))
+            .collect()
+    }
+}
+
+impl Default for CategoryBudgetsState {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            daily_seconds_by_category: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct HooksConfig {
+    pub on_start: Option<String>,
+    pub on_stop: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub clear_sand: char,
+    pub reset_idle: char,
+    pub vim: bool,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            clear_sand: 'c',
+            reset_idle: 'x',
+            vim: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ActivityRule {
+    pub app: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    pub category: String,
+}
+
+impl ActivityRule {
+    pub fn matches(&self, app: &str, title: &str) -> bool {
+        let contains =
+            |haystack: &str, needle: &str| haystack.to_lowercase().contains(&needle.to_lowercase());
+        contains(app, &self.app)
+            && self
+                .title
+                .as_deref()
+                .is_none_or(|pattern| contains(title, pattern))
+    }
+}
+
+/// Colors are names or `#rrggbb`; anything left out keeps the default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub palette: Vec<String>,
+    pub border: Option<String>,
+    pub text: Option<String>,
+    pub muted: Option<String>,
+    pub karma_positive: Option<String>,
+    pub karma_negative: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PlanTemplate {
+    pub name: String,
+    pub category: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub planned: Option<String>,
+    #[serde(default)]
+    pub at: Option<String>,
+}
+
+/// Off unless `enabled`. `cap` is a duration; `reset` is `"never"`,
+/// `"month"` or `"year"`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct GoalCarryOverConfig {
+    pub enabled: bool,
+    pub credit: bool,
+    pub cap: Option<String>,
+    pub reset: Option<String>,
+}
+
+/// Which events send a desktop notification. `idle_none` is how long "none"
+/// may run before a reminder, e.g. `"30m"`; unset means never.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    pub goals: bool,
+    pub budgets: bool,
+    pub pomodoro: bool,
+    pub idle_none: Option<String>,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            goals: false,
+            budgets: false,
+            pomodoro: true,
+            idle_none: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ActivityWatchConfig {
+    pub rules: Vec<ActivityRule>,
+    pub merge_gap_secs: i64,
+    pub min_session_secs: i64,
+}
+
+impl Default for ActivityWatchConfig {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            merge_gap_secs: 300,
+            min_session_secs: 120,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ConfigState {
+    pub version: u8,
+    pub hooks: HooksConfig,
+    pub keys: KeyBindings,
+    pub activitywatch: ActivityWatchConfig,
+    pub prompt_focus_rating: bool,
+    pub daily_goal: Option<String>,
+    pub goal_carry_over: GoalCarryOverConfig,
+    pub week_start: Option<String>,
+    pub metrics: bool,
+    pub plan_templates: Vec<PlanTemplate>,
+    pub plan_alarm_minutes: u64,
+    pub min_session: Option<String>,
+    pub min_session_by_category: HashMap<String, String>,
+    pub switch_grace: Option<String>,
+    pub sand_spawn: Option<String>,
+    pub theme: Option<String>,
+    pub themes: BTreeMap<String, ThemeConfig>,
+    pub notifications: NotificationsConfig,
+    pub idle_prompt: Option<String>,
+    pub idle_stop: Option<String>,
+    pub sand_speed: Option<String>,
+    pub time_format: Option<String>,
+    pub autosave: Option<String>,
+}
+
+impl ConfigState {
+    pub const VERSION: u8 = 1;
+}
+
+impl Default for ConfigState {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            hooks: HooksConfig::default(),
+            keys: KeyBindings::default(),
+            activitywatch: ActivityWatchConfig::default(),
+            prompt_focus_rating: false,
+            daily_goal: None,
+            goal_carry_over: GoalCarryOverConfig::default(),
+            week_start: None,
+            metrics: false,
+            plan_templates: Vec::new(),
+            plan_alarm_minutes: 10,
+            min_session: None,
+            min_session_by_category: HashMap::new(),
+            switch_grace: None,
+            sand_spawn: None,
+            theme: None,
+            themes: BTreeMap::new(),
+            notifications: NotificationsConfig::default(),
+            idle_prompt: None,
+            idle_stop: None,
+            sand_speed: None,
+            time_format: None,
+            autosave: None,
+        }
+    }
+}
+
+fn default_categories_loaded() -> LoadedCategories {
+    LoadedCategories {
+        categories: vec![Category {
+            id: CategoryId::new(0),
+            name: "none".to_string(),
+            color: Color::White,
+            description: String::new(),
+            default_description: String::new(),
+            karma_effect: 0,
+        }],
+        next_category_id: 1,
+    }
+}
+
+fn default_sessions_loaded() -> LoadedSessions {
+    LoadedSessions {
+        sessions: vec![],
+        next_session_id: 1,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvHeaderStatus {
+    Missing,
+    Recognized,
+    /// An older schema; the next save writes the current one.
+    Legacy,
+    Positional,
+    Invalid,
+}
+
+const CATEGORIES_SCHEMAS: [&[&str]; 4] = [
+    &CATEGORIES_HEADER,
+    &PRE_HEX_CATEGORIES_HEADER,
+    &PRE_ORDER_CATEGORIES_HEADER,
+    &LEGACY_CATEGORIES_HEADER,
+];
+const SESSIONS_SCHEMAS: [&[&str]; 3] = [
+    &SESSIONS_HEADER,
+    &PRE_FOCUS_SESSIONS_HEADER,
+    &LEGACY_SESSIONS_HEADER,
+];
+
+fn normalize_header(value: &str) -> String {
+    value.trim_start_matches('\u{feff}').trim().to_lowercase()
+}
+
+fn csv_header_matches(headers: &StringRecord, expected: &[&str]) -> bool {
+    headers.len() == expected.len() && csv_header_starts_with(headers, expected)
+}
+
+fn csv_header_starts_with(headers: &StringRecord, expected: &[&str]) -> bool {
+    headers.len() >= expected.len()
+        && headers
+            .iter()
+            .zip(expected.iter())
+            .all(|(actual, expected)| normalize_header(actual) == *expected)
+}
+
+/// An older header as wide as the current one, with a newer release's
+/// columns after it.
+fn renamed_header_with_extras(headers: &StringRecord, schemas: &[&[&str]]) -> bool {
+    schemas
+        .iter()
+        .filter(|schema| schema.len() == schemas[0].len())
+        .any(|schema| csv_header_starts_with(headers, schema))
+}
+
+/// Spreadsheet apps sometimes translate header names; when the column count
+/// still matches a known schema the columns are read by position. Columns a
+/// newer release appended after the current schema are accepted as-is.
+fn classify_csv_header(headers: &StringRecord, schemas: &[&[&str]]) -> CsvHeaderStatus {
+    if csv_header_starts_with(headers, schemas[0]) {
+        CsvHeaderStatus::Recognized
+    } else if schemas
+        .iter()
+        .any(|schema| csv_header_matches(headers, schema))
+        || renamed_header_with_extras(headers, schemas)
+    {
+        CsvHeaderStatus::Legacy
+    } else if schemas.iter().any(|schema| headers.len() == schema.len()) {
+        CsvHeaderStatus::Positional
+    } else {
+        CsvHeaderStatus::Invalid
+    }
+}
+
+fn check_csv_header(
+    file: &'static str,
+    headers: &StringRecord,
+    schemas: &[&[&str]],
+) -> Result<(), StorageError> {
+    match classify_csv_header(headers, schemas) {
+        CsvHeaderStatus::Invalid => Err(StorageError::InvalidCsvSchema {
+            file,
+            expected: schemas[0].join(","),
+            found: csv_header_string(headers),
+        }),
+        CsvHeaderStatus::Positional => {
+            eprintln!(
+                "Warning: Unrecognized headers in {} ('{}'), reading columns by position; run `strata doctor --headers` to restore them",
+                file,
+                csv_header_string(headers)
+            );
+            Ok(())
+        }
+        CsvHeaderStatus::Missing | CsvHeaderStatus::Recognized | CsvHeaderStatus::Legacy => Ok(()),
+    }
+}
+
+fn csv_header_status(path: &Path, schemas: &[&[&str]]) -> Result<CsvHeaderStatus, StorageError> {
+    if !path.exists() {
+        return Ok(CsvHeaderStatus::Missing);
+    }
+    let mut reader = ReaderBuilder::new().has_headers(true).from_path(path)?;
+    let headers = reader.headers()?.clone();
+    Ok(classify_csv_header(&headers, schemas))
+}
+
+pub fn categories_header_status(path: &Path) -> Result<CsvHeaderStatus, StorageError> {
+    csv_header_status(path, &CATEGORIES_SCHEMAS)
+}
+
+pub fn sessions_header_status(path: &Path) -> Result<CsvHeaderStatus, StorageError> {
+    csv_header_status(path, &SESSIONS_SCHEMAS)
+}
+
+/// Current columns missing from the file; older schemas only ever append.
+fn csv_added_columns(path: &Path, schemas: &[&[&'static str]]) -> Vec<&'static str> {
+    let Ok(mut reader) = ReaderBuilder::new().has_headers(true).from_path(path) else {
+        return Vec::new();
+    };
+    let found = reader.headers().map_or(0, StringRecord::len);
+    schemas[0].get(found..).unwrap_or_default().to_vec()
+}
+
+pub fn categories_added_columns(path: &Path) -> Vec<&'static str> {
+    csv_added_columns(path, &CATEGORIES_SCHEMAS)
+}
+
+pub fn sessions_added_columns(path: &Path) -> Vec<&'static str> {
+    csv_added_columns(path, &SESSIONS_SCHEMAS)
+}
+
+fn csv_header_string(headers: &StringRecord) -> String {
+    headers.iter().collect::<Vec<_>>().join(",")
+}
+
+/// Trailing columns unknown to this version, keyed by row id, so saving
+/// writes them back instead of dropping data from a newer release.
+#[derive(Debug, Default)]
+struct ExtraColumns {
+    headers: Vec<String>,
+    values_by_id: HashMap<String, Vec<String>>,
+}
+
+impl ExtraColumns {
+    /// Reads past the current schema's width; an older header of that same
+    /// width still counts, since it is about to be rewritten.
+    fn read(path: &Path, schemas: &[&[&str]]) -> Self {
+        let schema = schemas[0];
+        let Ok(mut reader) = ReaderBuilder::new().has_headers(true).from_path(path) else {
+            return Self::default();
+        };
+        let Ok(headers) = reader.headers().cloned() else {
+            return Self::default();
+        };
+        if headers.len() <= schema.len() || !renamed_header_with_extras(&headers, schemas) {
+            return Self::default();
+        }
+
+        let values_by_id = reader
+            .records()
+            .filter_map(Result::ok)
+            .filter_map(|record| {
+                let id = record.get(0)?.to_string();
+                let extra = record.iter().skip(schema.len()).map(str::to_string);
+                Some((id, extra.collect()))
+            })
+            .collect();
+        Self {
+            headers: headers
+                .iter()
+                .skip(schema.len())
+                .map(str::to_string)
+                .collect(),
+            values_by_id,
+        }
+    }
+
+    fn header(&self, schema: &[&str]) -> Vec<String> {
+        schema
+            .iter()
+            .map(|column| column.to_string())
+            .chain(self.headers.iter().cloned())
+            .collect()
+    }
+
+    fn extend_record(&self, id: &str, record: &mut Vec<String>) {
+        let values = self.values_by_id.get(id).map_or(&[][..], Vec::as_slice);
+        record.extend(
+            (0..self.headers.len()).map(|index| values.get(index).cloned().unwrap_or_default()),
+        );
+    }
+}
+
+pub fn load_categories_from_csv(path: &Path) -> LoadedCategories {
+    match try_load_categories_from_csv(path) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("Warning: Could not load categories file: {}", e);
+            default_categories_loaded()
+        }
+    }
+}
+
+pub fn try_load_categories_from_csv(path: &Path) -> Result<LoadedCategories, StorageError> {
+    if !path.exists() {
+        return Ok(default_categories_loaded());
+    }
+
+    let mut reader = ReaderBuilder::new().has_headers(true).from_path(path)?;
+    let headers = reader.headers()?.clone();
+    check_csv_header("categories.csv", &headers, &CATEGORIES_SCHEMAS)?;
+
+    let mut loaded = default_categories_loaded();
+    let mut ordered = Vec::new();
+
+    for (row, record) in reader.records().enumerate() {
+        let record = record?;
+
+        let Some(id_raw) = record.get(0) else {
+            continue;
+        };
+        let id: u64 = match id_raw.parse() {
+            Ok(id) => id,
+            Err(_) => {
+                eprintln!("Warning: Invalid category ID '{}', skipping", id_raw);
+                continue;
+            }
+        };
+
+        if id == 0 {
+            continue;
+        }
+
+        let name = record.get(1).unwrap_or_default().trim().to_string();
+        if name.is_empty() || name.eq_ignore_ascii_case("none") {
+            continue;
+        }
+
+        let description = record.get(2).unwrap_or_default().to_string();
+        let color = record.get(3).and_then(parse_color).unwrap_or(COLORS[0]);
+        let karma_effect = record
+            .get(4)
+            .and_then(|value| value.parse::<i8>().ok())
+            .unwrap_or(1);
+        let default_description = record.get(5).unwrap_or_default().to_string();
+        // The explicit order wins over row order, so sorting the file
+        // elsewhere doesn't reshuffle the modal; ties fall back to the id.
+        let order = record
+            .get(6)
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .unwrap_or(row);
+
+        ordered.push((
+            order,
+            Category {
+                id: CategoryId::new(id),
+                name,
+                color,
+                description,
+                default_description,
+                karma_effect,
+            },
+        ));
+        loaded.next_category_id = loaded.next_category_id.max(id + 1);
+    }
+
+    ordered.sort_by_key(|(order, category)| (*order, category.id.0));
+    loaded
+        .categories
+        .extend(ordered.into_iter().map(|(_, category)| category));
+
+    Ok(loaded)
+}
+
+pub fn load_sessions_from_csv(path: &Path, categories: &[Category]) -> LoadedSessions {
+    match try_load_sessions_from_csv(path, categories) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("Warning: Could not load sessions file: {}", e);
+            default_sessions_loaded()
+        }
+    }
+}
+
+pub fn try_load_sessions_from_csv(
+    path: &Path,
+    categories: &[Category],
+) -> Result<LoadedSessions, StorageError> {
+    if !path.exists() {
+        return Ok(default_sessions_loaded());
+    }
+
+    let category_by_id: HashMap<u64, CategoryId> = categories
+        .iter()
+        .map(|category| (category.id.0, category.id))
+        .collect();
+
+    let mut reader = ReaderBuilder::new().has_headers(true).from_path(path)?;
+    let headers = reader.headers()?.clone();
+    check_csv_header("time_log.csv", &headers, &SESSIONS_SCHEMAS)?;
+
+    let mut loaded = default_sessions_loaded();
+
+    for record in reader.records() {
+        let record = record?;
+
+        let Some(id_raw) = record.get(0) else {
+            continue;
+        };
+        let id: usize = match id_raw.parse() {
+            Ok(id) => id,
+            Err(_) => {
+                eprintln!("Warning: Invalid session ID '{}', skipping", id_raw);
+                continue;
+            }
+        };
+
+        let category_id = record
+            .get(2)
+            .and_then(|value| value.parse::<u64>().ok())
+            .and_then(|raw| category_by_id.get(&raw).copied())
+            .unwrap_or(CategoryId::new(0));
+
+        loaded.sessions.push(Session {
+            id,
+            date: record.get(1).unwrap_or_default().to_string(),
+            category_id,
+            description: record.get(4).unwrap_or_default().to_string(),
+            project: record.get(8).unwrap_or_default().to_string(),
+            start_time: record.get(5).unwrap_or_default().to_string(),
+            end_time: record.get(6).unwrap_or_default().to_string(),
+            elapsed_seconds: record
+                .get(7)
+                .and_then(|value| value.parse::<usize>().ok())
+                .unwrap_or(0),
+            focus: record
+                .get(9)
+                .and_then(|value| value.parse::<u8>().ok())
+                .filter(|rating| (1..=5).contains(rating)),
+        });
+
+        loaded.next_session_id = loaded.next_session_id.max(id + 1);
+    }
+
+    Ok(loaded)
+}
+
+pub fn save_categories_to_csv(path: &Path, categories: &[Category]) -> Result<(), String> {
+    let extra = ExtraColumns::read(path, &CATEGORIES_SCHEMAS);
+    let mut writer = WriterBuilder::new().has_headers(false).from_writer(vec![]);
+    writer
+        .write_record(extra.header(&CATEGORIES_HEADER))
+        .map_err(|e| e.to_string())?;
+
+    for (order, category) in categories
+        .iter()
+        .filter(|category| category.id.0 != 0)
+        .enumerate()
+    {
+        let id = category.id.0.to_string();
+        let mut record = vec![
+            id.clone(),
+            category.name.clone(),
+            category.description.clone(),
+            color_hex(category.color),
+            category.karma_effect.to_string(),
+            category.default_description.clone(),
+            order.to_string(),
+        ];
+        extra.extend_record(&id, &mut record);
+        writer.write_record(record).map_err(|e| e.to_string())?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.error().to_string())?;
+    let content = String::from_utf8_lossy(&bytes).to_string();
+
+    atomic_write(path, &content)
+}
+
+fn session_record(session: &Session, categories: &[Category]) -> [String; 10] {
+    let category_name = categories
+        .iter()
+        .find(|category| category.id == session.category_id)
+        .map(|category| category.name.as_str())
+        .unwrap_or("none");
+
+    [
+        session.id.to_string(),
+        session.date.clone(),
+        session.category_id.0.to_string(),
+        category_name.to_string(),
+        session.description.clone(),
+        session.start_time.clone(),
+        session.end_time.clone(),
+        session.elapsed_seconds.to_string(),
+        session.project.clone(),
+        session
+            .focus
+            .map(|rating| rating.to_string())
+            .unwrap_or_default(),
+    ]
+}
+
+/// Appends removed sessions to the trash file, which uses the `time_log.csv`
+/// schema so rows can be pasted back.
+pub fn append_sessions_to_trash(
+    path: &Path,
+    sessions: &[Session],
+    categories: &[Category],
+) -> Result<(), String> {
+    let needs_header = prepare_trash_file(path)?;
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    let mut writer = WriterBuilder::new().has_headers(false).from_writer(file);
+    if needs_header {
+        writer
+            .write_record(SESSIONS_HEADER)
+            .map_err(|e| e.to_string())?;
+    }
+    for session in sessions {
+        writer
+            .write_record(session_record(session, categories))
+            .map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())
+}
+
+/// Brings an existing trash file to the current schema before rows are
+/// appended to it: one under an older `time_log.csv` header is rewritten
+/// with the newer columns left empty, and one with any other header is moved
+/// aside to `trash.N.csv`. Returns whether the file needs a header.
+fn prepare_trash_file(path: &Path) -> Result<bool, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(true),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let mut records = reader.records();
+    let header = match records.next() {
+        None => return Ok(true),
+        Some(header) => header.map_err(|e| e.to_string())?,
+    };
+    if csv_header_matches(&header, &SESSIONS_HEADER) {
+        return Ok(false);
+    }
+
+    if SESSIONS_SCHEMAS[1..]
+        .iter()
+        .any(|schema| csv_header_matches(&header, schema))
+    {
+        let mut writer = WriterBuilder::new().from_writer(vec![]);
+        writer
+            .write_record(SESSIONS_HEADER)
+            .map_err(|e| e.to_string())?;
+        for record in records {
+            let record = record.map_err(|e| e.to_string())?;
+            let mut fields: Vec<&str> = record.iter().collect();
+            fields.resize(SESSIONS_HEADER.len(), "");
+            writer.write_record(fields).map_err(|e| e.to_string())?;
+        }
+        let bytes = writer.into_inner().map_err(|e| e.error().to_string())?;
+        atomic_write(path, &String::from_utf8_lossy(&bytes))?;
+        return Ok(false);
+    }
+
+    let aside = (1..)
+        .map(|generation| rotated_csv_path(path, generation))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.with_extension("old.csv"));
+    fs::rename(path, aside).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Adds `new` to the sessions file under the instance lock, for a writer
+/// that doesn't hold the lock for its whole run. Ids carry on from the
+/// file's.
+pub fn append_sessions_locked(
+    path: &Path,
+    new: &[Session],
+    categories: &[Category],
+) -> Result<(), String> {
+    if new.is_empty() {
+        return Ok(());
+    }
+    let _lock = acquire_instance_lock(&get_instance_lock_path())?;
+    let mut sessions = load_sessions_from_csv(path, categories).sessions;
+    let first_id = sessions.iter().map(|s| s.id).max().unwrap_or(0) + 1;
+    sessions.extend(new.iter().zip(first_id..).map(|(session, id)| Session {
+        id,
+        ..session.clone()
+    }));
+    save_sessions_to_csv(path, &sessions, categories)
+}
+
+pub fn save_sessions_to_csv(
+    path: &Path,
+    sessions: &[Session],
+    categories: &[Category],
+) -> Result<(), String> {
+    let extra = ExtraColumns::read(path, &SESSIONS_SCHEMAS);
+    let mut writer = WriterBuilder::new().has_headers(false).from_writer(vec![]);
+    writer
+        .write_record(extra.header(&SESSIONS_HEADER))
+        .map_err(|e| e.to_string())?;
+
+    for session in sessions {
+        let mut record = session_record(session, categories).to_vec();
+        extra.extend_record(&session.id.to_string(), &mut record);
+        writer.write_record(record).map_err(|e| e.to_string())?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.error().to_string())?;
+    let content = String::from_utf8_lossy(&bytes).to_string();
+
+    atomic_write(path, &content)
+}
+
+static DATA_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+static ACTIVE_PROFILE: OnceLock<String> = OnceLock::new();
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProfilesState {
+    pub version: u8,
+    pub current: String,
+}
+
+impl ProfilesState {
+    pub const VERSION: u8 = 1;
+}
+
+impl Default for ProfilesState {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            current: DEFAULT_PROFILE.to_string(),
+        }
+    }
+}
+
+pub fn set_data_dir_override(path: PathBuf) {
+    let _ = DATA_DIR_OVERRIDE.set(path);
+}
+
+pub fn set_profile_override(name: String) {
+    let _ = ACTIVE_PROFILE.set(name);
+}
+
+fn data_dir_override() -> Option<PathBuf> {
+    pick_data_dir_override(DATA_DIR_OVERRIDE.get().cloned(), data_dir_from_env)
+}
+
+/// `--data-dir` wins over `STRATA_DATA_DIR`.
+fn pick_data_dir_override(
+    flag: Option<PathBuf>,
+    env: impl FnOnce() -> Option<PathBuf>,
+) -> Option<PathBuf> {
+    flag.or_else(env)
+}
+
+/// Data and state directories for an override, which keeps state in its
+/// `state` subdirectory, or else the profile's default locations.
+fn resolve_dirs(
+    override_dir: Option<PathBuf>,
+    default_data: impl FnOnce() -> PathBuf,
+    default_state: impl FnOnce() -> PathBuf,
+) -> (PathBuf, PathBuf) {
+    match override_dir {
+        Some(dir) => (dir.clone(), dir.join("state")),
+        None => (default_data(), default_state()),
+    }
+}
+
+pub fn data_dir_from_env() -> Option<PathBuf> {
+    std::env::var_os("STRATA_DATA_DIR")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+pub fn profile_from_env() -> Option<String> {
+    std::env::var("STRATA_PROFILE")
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+pub fn active_profile() -> String {
+    ACTIVE_PROFILE
+        .get_or_init(|| {
+            let name = profile_from_env()
+                .unwrap_or_else(|| load_profiles_state(&get_profiles_state_path()).current);
+            match validate_profile_name(&name) {
+                Ok(()) => name,
+                Err(e) => {
+                    eprintln!("Warning: {}, using the default profile", e);
+                    DEFAULT_PROFILE.to_string()
+                }
+            }
+        })
+        .clone()
+}
+
+fn ensure_dir(dir: PathBuf) -> PathBuf {
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+fn profile_dir(base: PathBuf) -> PathBuf {
+    named_profile_dir(base, &active_profile())
+}
+
+fn named_profile_dir(base: PathBuf, profile: &str) -> PathBuf {
+    if profile == DEFAULT_PROFILE {
+        base
+    } else {
+        base.join("profiles").join(profile)
+    }
+}
+
+/// A data directory override holds one dataset, so a profile can't apply on
+/// top of it; asking for both is an error rather than a silent choice.
+pub fn check_profile_with_data_dir(
+    data_dir: Option<&Path>,
+    profile: Option<&str>,
+) -> Result<(), String> {
+    match (data_dir, profile) {
+        (Some(dir), Some(profile)) => Err(format!(
+            "Profile '{}' can't be used with the data directory {}; drop one of \
+             --profile/STRATA_PROFILE or --data-dir/STRATA_DATA_DIR",
+            profile,
+            dir.display()
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn base_data_dir() -> PathBuf {
+    ProjectDirs::from("com", "strata", "strata")
+        .map(|proj_dirs| proj_dirs.data_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn base_state_dir() -> PathBuf {
+    ProjectDirs::from("com", "strata", "strata")
+        .and_then(|proj_dirs| proj_dirs.state_dir().map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn base_config_dir() -> PathBuf {
+    ProjectDirs::from("com", "strata", "strata")
+        .map(|proj_dirs| proj_dirs.config_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+pub fn get_data_dir() -> PathBuf {
+    let (data_dir, _) = resolve_dirs(
+        data_dir_override(),
+        || profile_dir(base_data_dir()),
+        PathBuf::new,
+    );
+    ensure_dir(data_dir)
+}
+
+/// A `time_log.csv` in `cwd` when the data directory is somewhere else,
+/// left from running strata against local files; both front-ends read
+/// `data_dir` and ignore it.
+pub fn stray_local_sessions(data_dir: &Path, cwd: &Path) -> Option<PathBuf> {
+    let local = cwd.join("time_log.csv");
+    if !local.is_file() {
+        return None;
+    }
+    let same_dir = match (fs::canonicalize(data_dir), fs::canonicalize(cwd)) {
+        (Ok(data_dir), Ok(cwd)) => data_dir == cwd,
+        _ => false,
+    };
+    (!same_dir).then_some(local)
+}
+
+pub fn get_state_dir() -> PathBuf {
+    let (_, state_dir) = resolve_dirs(data_dir_override(), PathBuf::new, || {
+        profile_dir(base_state_dir())
+    });
+    ensure_dir(state_dir)
+}
+
+pub fn get_config_dir() -> PathBuf {
+    ensure_dir(profile_dir(base_config_dir()))
+}
+
+pub fn get_profiles_state_path() -> PathBuf {
+    ensure_dir(base_config_dir()).join("profiles.json")
+}
+
+pub fn load_profiles_state(path: &Path) -> ProfilesState {
+    if !path.exists() {
+        return ProfilesState::default();
+    }
+
+    match read_json::<ProfilesState>(path) {
+        Ok(state) if state.version == ProfilesState::VERSION => state,
+        Ok(_) => {
+            eprintln!("Warning: Unsupported profiles version, using the default profile");
+            ProfilesState::default()
+        }
+        Err(e) => {
+            eprintln!("Warning: Could not load profiles: {}", e);
+            ProfilesState::default()
+        }
+    }
+}
+
+pub fn save_profiles_state(path: &Path, state: &ProfilesState) -> Result<(), String> {
+    write_json_atomic(path, state)
+}
+
+pub fn validate_profile_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid profile name '{}': use letters, digits, '-' or '_'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+pub fn list_profiles() -> Vec<String> {
+    profiles_in(&base_data_dir())
+}
+
+fn profiles_in(base: &Path) -> Vec<String> {
+    let mut profiles = vec![DEFAULT_PROFILE.to_string()];
+    if let Ok(entries) = fs::read_dir(base.join("profiles")) {
+        let mut named: Vec<String> = entries
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| validate_profile_name(name).is_ok() && name != DEFAULT_PROFILE)
+            .collect();
+        named.sort();
+        profiles.extend(named);
+    }
+    profiles
+}
+
+pub fn create_profile(name: &str) -> Result<(), String> {
+    create_profile_in(&base_data_dir(), name)
+}
+
+fn create_profile_in(base: &Path, name: &str) -> Result<(), String> {
+    validate_profile_name(name)?;
+    if profiles_in(base).iter().any(|profile| profile == name) {
+        return Err(format!("Profile '{}' already exists", name));
+    }
+    fs::create_dir_all(named_profile_dir(base.to_path_buf(), name)).map_err(|e| e.to_string())
+}
+
+/// Makes `name` the profile later runs use when none is given.
+pub fn switch_profile(name: &str) -> Result<(), String> {
+    switch_profile_in(&base_data_dir(), &get_profiles_state_path(), name)
+}
+
+fn switch_profile_in(base: &Path, state_path: &Path, name: &str) -> Result<(), String> {
+    if !profiles_in(base).iter().any(|profile| profile == name) {
+        return Err(format!(
+            "Unknown profile '{}'; create it with `strata profile create {}`",
+            name, name
+        ));
+    }
+    let mut state = load_profiles_state(state_path);
+    state.current = name.to_string();
+    save_profiles_state(state_path, &state)
+}
+
+pub fn get_config_path() -> PathBuf {
+    get_config_dir().join("config.json")
+}
+
+pub fn get_themes_dir() -> PathBuf {
+    get_config_dir().join("themes")
+}
+
+/// Themes from `<name>.json` files in `dir`, then the config's own `themes`,
+/// which win on a name clash.
+pub fn load_theme_configs(dir: &Path, config: &ConfigState) -> BTreeMap<String, ThemeConfig> {
+    let mut themes = BTreeMap::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            match read_json::<ThemeConfig>(&path) {
+                Ok(theme) => {
+                    themes.insert(name.to_string(), theme);
+                }
+                Err(e) => eprintln!("Warning: Could not load theme {}: {}", path.display(), e),
+            }
+        }
+    }
+    themes.extend(config.themes.clone());
+    themes
+}
+
+pub fn get_trash_path() -> PathBuf {
+    get_data_dir().join("trash.csv")
+}
+
+pub fn get_active_session_path() -> PathBuf {
+    get_state_dir().join("active_session.json")
+}
+
+pub fn get_sand_state_path() -> PathBuf {
+    get_state_dir().join("sand_state.json")
+}
+
+pub fn get_category_tags_path() -> PathBuf {
+    get_state_dir().join("category_tags.json")
+}
+
+pub fn get_category_groups_path() -> PathBuf {
+    get_data_dir().join("category_groups.json")
+}
+
+pub fn get_billing_rates_path() -> PathBuf {
+    get_data_dir().join("rates.json")
+}
+
+pub fn get_category_budgets_path() -> PathBuf {
+    get_data_dir().join("budgets.json")
+}
+
+pub fn get_kept_overlaps_path() -> PathBuf {
+    get_data_dir().join("kept_overlaps.json")
+}
+
+pub fn get_goal_ledger_path() -> PathBuf {
+    get_data_dir().join("goal_ledger.json")
+}
+
+pub fn get_focus_contract_path() -> PathBuf {
+    get_state_dir().join("focus_contract.json")
+}
+
+pub fn get_plan_path() -> PathBuf {
+    get_state_dir().join("plan.json")
+}
+
+pub fn get_usage_metrics_path() -> PathBuf {
+    get_state_dir().join("metrics.json")
+}
+
+pub fn get_instance_lock_path() -> PathBuf {
+    get_state_dir().join("strata.lock")
+}
+
+pub fn get_daemon_socket_path() -> PathBuf {
+    get_state_dir().join("strata.sock")
+}
+
+pub fn get_grain_log_path() -> PathBuf {
+    get_state_dir().join("grain_log.csv")
+}
+
+pub fn grain_log_enabled() -> bool {
+    std::env::var("STRATA_GRAIN_LOG")
+        .map(|value| matches!(value.trim(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+pub fn export_dir() -> Option<PathBuf> {
+    std::env::var_os("STRATA_EXPORT_DIR")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+pub fn export_filename_template() -> String {
+    std::env::var("STRATA_EXPORT_TEMPLATE")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EXPORT_TEMPLATE.to_string())
+}
+
+pub fn render_export_filename(template: &str, period: &str, date: &str, extension: &str) -> String {
+    template
+        .replace("{period}", period)
+        .replace("{date}", date)
+        .replace("{ext}", extension)
+        .replace(['/', '\\'], "_")
+}
+
+/// `notifications.budgets` in the config, or `STRATA_BUDGET_NOTIFY` set.
+pub fn budget_notifications_enabled(config: &NotificationsConfig) -> bool {
+    config.budgets
+        || std::env::var("STRATA_BUDGET_NOTIFY")
+            .map(|value| matches!(value.trim(), "1" | "true" | "yes" | "on"))
+            .unwrap_or(false)
+}
+
+pub fn idle_none_reminder_seconds(config: &NotificationsConfig) -> Option<usize> {
+    config
+        .idle_none
+        .as_deref()
+        .and_then(parse_duration)
+        .filter(|seconds| *seconds > 0)
+}
+
+/// Seconds of system idle time after which the TUI stops the running
+/// session; off unless `idle_stop` is set.
+pub fn idle_stop_seconds(config: &ConfigState) -> Option<u64> {
+    config
+        .idle_stop
+        .as_deref()
+        .and_then(parse_duration)
+        .filter(|seconds| *seconds > 0)
+        .map(|seconds| seconds as u64)
+}
+
+pub fn week_start_setting(config: &ConfigState) -> WeekStart {
+    match config.week_start.as_deref().map(str::trim) {
+        Some(value) if value.eq_ignore_ascii_case("monday") => WeekStart::Monday,
+        Some(value) if value.eq_ignore_ascii_case("sunday") => WeekStart::Sunday,
+        _ => WeekStart::Rolling,
+    }
+}
+
+pub fn carry_over_rules(config: &ConfigState) -> Option<CarryOverRules> {
+    let carry_over = &config.goal_carry_over;
+    if !carry_over.enabled {
+        return None;
+    }
+    Some(CarryOverRules {
+        credit: carry_over.credit,
+        cap_seconds: carry_over.cap.as_deref().and_then(parse_duration),
+        reset: match carry_over.reset.as_deref().map(str::trim) {
+            Some(value) if value.eq_ignore_ascii_case("month") => CarryReset::Month,
+            Some(value) if value.eq_ignore_ascii_case("year") => CarryReset::Year,
+            _ => CarryReset::Never,
+        },
+    })
+}
+
+pub fn spawn_strategy_setting(config: &ConfigState) -> SpawnStrategy {
+    match config.sand_spawn.as_deref().map(str::trim) {
+        Some(value) if value.eq_ignore_ascii_case("least-full") => SpawnStrategy::LeastFull,
+        Some(value) if value.eq_ignore_ascii_case("center") => SpawnStrategy::Center,
+        _ => SpawnStrategy::Scan,
+    }
+}
+
+/// `sand_speed` names and the milliseconds between gravity passes.
+pub const SAND_SPEEDS: [(&str, u64); 3] = [
+    ("slow", TIME_SETTINGS.physics_ms * 2),
+    ("normal", TIME_SETTINGS.physics_ms),
+    ("fast", TIME_SETTINGS.physics_ms / 2),
+];
+
+pub fn sand_speed_setting(config: &ConfigState) -> u64 {
+    let wanted = config.sand_speed.as_deref().map(str::trim);
+    SAND_SPEEDS
+        .iter()
+        .find(|(name, _)| wanted.is_some_and(|wanted| wanted.eq_ignore_ascii_case(name)))
+        .map_or(TIME_SETTINGS.physics_ms, |(_, ms)| *ms)
+}
+
+/// False when `time_format` is `"hh:mm"`, which drops the seconds.
+pub fn time_format_seconds(config: &ConfigState) -> bool {
+    !config
+        .time_format
+        .as_deref()
+        .is_some_and(|format| format.trim().eq_ignore_ascii_case("hh:mm"))
+}
+
+/// Seconds between the TUI's periodic saves; a minute unless `autosave`
+/// says otherwise.
+pub fn autosave_seconds(config: &ConfigState) -> usize {
+    config
+        .autosave
+        .as_deref()
+        .and_then(parse_duration)
+        .filter(|seconds| *seconds > 0)
+        .unwrap_or(60)
+}
+
+pub fn period_label_style() -> PeriodLabelStyle {
+    match std::env::var("STRATA_PERIOD_LABELS") {
+        Ok(value) if value.trim().eq_ignore_ascii_case("iso") => PeriodLabelStyle::Iso,
+        _ => PeriodLabelStyle::Range,
+    }
+}
+
+fn rotated_csv_path(path: &Path, generation: usize) -> PathBuf {
+    path.with_extension(format!("{}.csv", generation))
+}
+
+fn rotate_grain_log(path: &Path) -> Result<(), String> {
+    for generation in (1..GRAIN_LOG_ROTATIONS).rev() {
+        let from = rotated_csv_path(path, generation);
+        if from.exists() {
+            fs::rename(&from, rotated_csv_path(path, generation + 1)).map_err(|e| e.to_string())?;
+        }
+    }
+    fs::rename(path, rotated_csv_path(path, 1)).map_err(|e| e.to_string())
+}
+
+pub fn append_grain_event(path: &Path, event: &GrainEvent) -> Result<(), String> {
+    if fs::metadata(path).is_ok_and(|meta| meta.len() >= GRAIN_LOG_MAX_BYTES) {
+        rotate_grain_log(path)?;
+    }
+
+    let is_new = !path.exists();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+
+    if is_new {
+        writeln!(file, "{}", GRAIN_LOG_HEADER.join(",")).map_err(|e| e.to_string())?;
+    }
+    writeln!(
+        file,
+        "{},{},{},{}",
+        event.timestamp.to_rfc3339(),
+        event.category_id,
+        event.x,
+        event.grid_width
+    )
+    .map_err(|e| e.to_string())
+}
+
+pub fn load_grain_events(path: &Path) -> Vec<GrainEvent> {
+    let mut files: Vec<PathBuf> = (1..=GRAIN_LOG_ROTATIONS)
+        .rev()
+        .map(|generation| rotated_csv_path(path, generation))
+        .collect();
+    files.push(path.to_path_buf());
+
+    let mut events = Vec::new();
+    for file in files.iter().filter(|file| file.exists()) {
+        let Ok(mut reader) = ReaderBuilder::new().has_headers(true).from_path(file) else {
+            continue;
+        };
+        for record in reader.records().filter_map(|record| record.ok()) {
+            let timestamp = record
+                .get(0)
+                .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+                .map(|value| value.with_timezone(&Utc));
+            let category_id = record.get(1).and_then(|value| value.parse::<u64>().ok());
+            let x = record.get(2).and_then(|value| value.parse::<usize>().ok());
+            let grid_width = record.get(3).and_then(|value| value.parse::<usize>().ok());
+
+            if let (Some(timestamp), Some(category_id), Some(x), Some(grid_width)) =
+                (timestamp, category_id, x, grid_width)
+            {
+                events.push(GrainEvent {
+                    timestamp,
+                    category_id,
+                    x,
+                    grid_width,
+                });
+            }
+        }
+    }
+
+    events
+}
+
+pub fn load_sand_state(path: &Path) -> Option<SandState> {
+    if !path.exists() {
+        return None;
+    }
+
+    match read_json::<SandState>(path) {
+        Ok(state) if state.version == SandState::VERSION => Some(state),
+        Ok(_) => {
+            eprintln!("Warning: Unsupported sand state version, ignoring saved layout");
+            None
+        }
+        Err(e) => {
+            eprintln!("Warning: Could not load sand state: {}", e);
+            None
+        }
+    }
+}
+
+/// Saved on every autosave tick, so without a backup: the pile is a cache of
+/// the day's sessions, not data to restore.
+pub fn save_sand_state(path: &Path, state: &SandState) -> Result<(), String> {
+    write_without_backup(path, &json_keeping_unknown_keys(path, state)?)
+}
+
+pub fn load_category_tags(path: &Path) -> CategoryTagsState {
+    if !path.exists() {
+        return CategoryTagsState::default();
+    }
+
+    match read_json::<CategoryTagsState>(path) {
+        Ok(mut state) if state.version == CategoryTagsState::VERSION => {
+            for tags in state.tags_by_category.values_mut() {
+                tags.retain(|tag| !tag.trim().is_empty());
+            }
+            state
+        }
+        Ok(_) => {
+            eprintln!("Warning: Unsupported category tags version, ignoring saved tags");
+            CategoryTagsState::default()
+        }
+        Err(e) => {
+            eprintln!("Warning: Could not load category tags: {}", e);
+            CategoryTagsState::default()
+        }
+    }
+}
+
+pub fn save_category_tags(path: &Path, tags_state: &CategoryTagsState) -> Result<(), String> {
+    write_json_atomic(path, tags_state)
+}
+
+pub fn load_billing_rates(path: &Path) -> Result<BillingRatesState, String> {
+    if !path.exists() {
+        return Ok(BillingRatesState::default());
+    }
+
+    let state = read_json::<BillingRatesState>(path)?;
+    if state.version != BillingRatesState::VERSION {
+        return Err(format!(
+            "Unsupported billing rates version {} in {}",
+            state.version,
+            path.display()
+        ));
+    }
+    Ok(state)
+}
+
+pub fn save_billing_rates(path: &Path, rates: &BillingRatesState) -> Result<(), String> {
+    write_json_atomic(path, rates)
+}
+
+pub fn load_category_groups(path: &Path) -> CategoryGroupsState {
+    if !path.exists() {
+        return CategoryGroupsState::default();
+    }
+
+    match read_json::<CategoryGroupsState>(path) {
+        Ok(state) if state.version == CategoryGroupsState::VERSION => state,
+        Ok(_) => {
+            eprintln!("Warning: Unsupported category groups version, ignoring saved groups");
+            CategoryGroupsState::default()
+        }
+        Err(e) => {
+            eprintln!("Warning: Could not load category groups: {}", e);
+            CategoryGroupsState::default()
+        }
+    }
+}
+
+pub fn save_category_groups(path: &Path, groups: &CategoryGroupsState) -> Result<(), String> {
+    write_json_atomic(path, groups)
+}
+
+pub fn load_category_budgets(path: &Path) -> CategoryBudgetsState {
+    if !path.exists() {
+        return CategoryBudgetsState::default();
+    }
+
+    match read_json::<CategoryBudgetsState>(path) {
+        Ok(state) if state.version == CategoryBudgetsState::VERSION => state,
+        Ok(_) => {
+            eprintln!("Warning: Unsupported category budgets version, ignoring saved budgets");
+            CategoryBudgetsState::default()
+        }
+        Err(e) => {
+            eprintln!("Warning: Could not load category budgets: {}", e);
+            CategoryBudgetsState::default()
+        }
+    }
+}
+
+pub fn load_kept_overlaps(path: &Path) -> KeptOverlaps {
+    if !path.exists() {
+        return KeptOverlaps::new();
+    }
+
+    match read_json::<KeptOverlapsState>(path) {
+        Ok(state) if state.version == KeptOverlapsState::VERSION => state.pairs,
+        Ok(_) => {
+            eprintln!("Warning: Unsupported kept overlaps version, ignoring them");
+            KeptOverlaps::new()
+        }
+        Err(e) => {
+            eprintln!("Warning: Could not load kept overlaps: {}", e);
+            KeptOverlaps::new()
+        }
+    }
+}
+
+pub fn save_kept_overlaps(path: &Path, pairs: &KeptOverlaps) -> Result<(), String> {
+    write_json_atomic(
+        path,
+        &KeptOverlapsState {
+            pairs: pairs.clone(),
+            ..KeptOverlapsState::default()
+        },
+    )
+}
+
+pub fn save_category_budgets(path: &Path, budgets: &CategoryBudgetsState) -> Result<(), String> {
+    write_json_atomic(path, budgets)
+}
+
+pub fn load_goal_ledger(path: &Path) -> GoalLedgerState {
+    if !path.exists() {
+        return GoalLedgerState::default();
+    }
+
+    match read_json::<GoalLedgerState>(path) {
+        Ok(state) if state.version == GoalLedgerState::VERSION => state,
+        Ok(_) => {
+            eprintln!("Warning: Unsupported goal ledger version, ignoring saved ledger");
+            GoalLedgerState::default()
+        }
+        Err(e) => {
+            eprintln!("Warning: Could not load goal ledger: {}", e);
+            GoalLedgerState::default()
+        }
+    }
+}
+
+pub fn save_goal_ledger(path: &Path, ledger: &GoalLedgerState) -> Result<(), String> {
+    write_json_atomic(path, ledger)
+}
+
+/// Closes the weeks that ended since the last sync into the ledger at `path`
+/// and returns every closed week.
+pub fn sync_goal_ledger(
+    path: &Path,
+    sessions: &[Session],
+    weekly_goals: &HashMap<CategoryId, usize>,
+    rules: &CarryOverRules,
+    current_week: NaiveDate,
+) -> Result<Vec<GoalLedgerEntry>, String> {
+    let mut ledger = load_goal_ledger(path);
+    let mut entries = ledger.entries();
+    let open_week = ledger
+        .open_week
+        .as_deref()
+        .and_then(|week| NaiveDate::parse_from_str(week, "%Y-%m-%d").ok());
+    if open_week.is_some_and(|week| week >= current_week) {
+        return Ok(entries);
+    }
+
+    if let Some(open_week) = open_week {
+        let closed = close_goal_weeks(
+            &entries,
+            sessions,
+            weekly_goals,
+            open_week,
+            current_week,
+            rules,
+        );
+        ledger
+            .weeks
+            .extend(closed.iter().map(|entry| GoalLedgerRow {
+                week_start: entry.week_start.format("%Y-%m-%d").to_string(),
+                category_id: entry.category_id.0,
+                goal_seconds: entry.goal_seconds,
+                actual_seconds: entry.actual_seconds,
+                carried_in: entry.carried_in,
+                carried_out: entry.carried_out,
+            }));
+        entries.extend(closed);
+    }
+    ledger.open_week = Some(current_week.format("%Y-%m-%d").to_string());
+    save_goal_ledger(path, &ledger)?;
+    Ok(entries)
+}
+
+pub fn load_focus_contract(path: &Path) -> FocusContractState {
+    if !path.exists() {
+        return FocusContractState::default();
+    }
+
+    match read_json::<FocusContractState>(path) {
+        Ok(state) if state.version == FocusContractState::VERSION => state,
+        Ok(_) => {
+            eprintln!("Warning: Unsupported focus contract version, ignoring saved contract");
+            FocusContractState::default()
+        }
+        Err(e) => {
+            eprintln!("Warning: Could not load focus contract: {}", e);
+            FocusContractState::default()
+        }
+    }
+}
+
+pub fn save_focus_contract(path: &Path, state: &FocusContractState) -> Result<(), String> {
+    write_json_atomic(path, state)
+}
+
+/// Loads the plan for `day`; a plan saved on another day starts over empty.
+pub fn load_plan(path: &Path, day: NaiveDate) -> PlanState {
+    let fresh = PlanState {
+        day: Some(day),
+        ..PlanState::default()
+    };
+    if !path.exists() {
+        return fresh;
+    }
+
+    match read_json::<PlanState>(path) {
+        Ok(state) if state.version == PlanState::VERSION && state.day == Some(day) => state,
+        Ok(state) if state.version == PlanState::VERSION => fresh,
+        Ok(_) => {
+            eprintln!("Warning: Unsupported plan version, starting an empty plan");
+            fresh
+        }
+        Err(e) => {
+            eprintln!("Warning: Could not load plan: {}", e);
+            fresh
+        }
+    }
+}
+
+pub fn save_plan(path: &Path, state: &PlanState) -> Result<(), String> {
+    write_json_atomic(path, state)
 }
 
-pub fn get_data_dir() -> PathBuf {
-    if let Some(proj_dirs) = ProjectDirs::from("com", "strata", "strata") {
-        let data_dir = proj_dirs.data_dir().to_path_buf();
-        fs::create_dir_all(&data_dir).ok();
-        data_dir
-    } else {
-        PathBuf::from(".")
+pub fn load_usage_metrics(path: &Path) -> UsageMetricsState {
+    if !path.exists() {
+        return UsageMetricsState::default();
     }
-}
 
-pub fn get_state_dir() -> PathBuf {
-    if let Some(proj_dirs) = ProjectDirs::from("com", "strata", "strata")
-        && let Some(state_dir) = proj_dirs.state_dir()
-    {
-        let dir = state_dir.to_path_buf();
-        fs::create_dir_all(&dir).ok();
-        return dir;
+    match read_json::<UsageMetricsState>(path) {
+        Ok(state) if state.version == UsageMetricsState::VERSION => state,
+        Ok(_) => {
+            eprintln!("Warning: Unsupported usage metrics version, starting fresh");
+            UsageMetricsState::default()
+        }
+        Err(e) => {
+            eprintln!("Warning: Could not load usage metrics: {}", e);
+            UsageMetricsState::default()
+        }
     }
-    PathBuf::from(".")
 }
 
-pub fn get_active_session_path() -> PathBuf {
-    get_state_dir().join("active_session.json")
+pub fn save_usage_metrics(path: &Path, metrics: &UsageMetricsState) -> Result<(), String> {
+    write_json_atomic(path, metrics)
 }
 
-pub fn get_sand_state_path() -> PathBuf {
-    get_state_dir().join("sand_state.json")
+pub fn record_usage<'a>(events: impl IntoIterator<Item = &'a str>) -> Result<(), String> {
+    record_usage_at(&get_usage_metrics_path(), events)
 }
 
-pub fn get_category_tags_path() -> PathBuf {
-    get_state_dir().join("category_tags.json")
+pub fn record_usage_at<'a>(
+    path: &Path,
+    events: impl IntoIterator<Item = &'a str>,
+) -> Result<(), String> {
+    let mut metrics = load_usage_metrics(path);
+    metrics.since.get_or_insert_with(Utc::now);
+    for event in events {
+        *metrics.counts.entry(event.to_string()).or_insert(0) += 1;
+    }
+    save_usage_metrics(path, &metrics)
 }
 
-pub fn load_sand_state(path: &Path) -> Option<SandState> {
+pub fn load_config(path: &Path) -> ConfigState {
     if !path.exists() {
-        return None;
+        return ConfigState::default();
     }
 
-    match read_json::<SandState>(path) {
-        Ok(state) if state.version == SandState::VERSION => Some(state),
+    match read_json::<ConfigState>(path) {
+        Ok(state) if state.version == ConfigState::VERSION => state,
         Ok(_) => {
-            eprintln!("Warning: Unsupported sand state version, ignoring saved layout");
-            None
+            eprintln!("Warning: Unsupported config version, using defaults");
+            ConfigState::default()
         }
         Err(e) => {
-            eprintln!("Warning: Could not load sand state: {}", e);
-            None
+            eprintln!("Warning: Could not load config: {}", e);
+            ConfigState::default()
         }
     }
 }
 
-pub fn save_sand_state(path: &Path, state: &SandState) -> Result<(), String> {
-    write_json_atomic(path, state)
+/// Held for as long as the open file lives. The OS drops the advisory lock
+/// when the file closes, `process::exit` and crashes included, so a lock is
+/// never left behind and the file itself is never removed.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
 }
 
-pub fn load_category_tags(path: &Path) -> CategoryTagsState {
-    if !path.exists() {
-        return CategoryTagsState::default();
-    }
-
-    match read_json::<CategoryTagsState>(path) {
-        Ok(mut state) if state.version == CategoryTagsState::VERSION => {
-            for tags in state.tags_by_category.values_mut() {
-                tags.retain(|tag| !tag.trim().is_empty());
-            }
-            state
+pub fn acquire_instance_lock(path: &Path) -> Result<InstanceLock, String> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    match file.try_lock() {
+        Ok(()) => {
+            file.set_len(0).map_err(|e| e.to_string())?;
+            writeln!(file, "{}", std::process::id()).map_err(|e| e.to_string())?;
+            Ok(InstanceLock { _file: file })
         }
-        Ok(_) => {
-            eprintln!("Warning: Unsupported category tags version, ignoring saved tags");
-            CategoryTagsState::default()
+        Err(std::fs::TryLockError::WouldBlock) => {
+            // The holder may not have written its PID yet.
+            let holder = fs::read_to_string(path)
+                .ok()
+                .and_then(|content| content.trim().parse::<u32>().ok())
+                .map(|pid| format!(" (PID {})", pid))
+                .unwrap_or_default();
+            Err(format!(
+                "Another strata instance{} is writing data; wait for it to exit",
+                holder
+            ))
         }
-        Err(e) => {
-            eprintln!("Warning: Could not load category tags: {}", e);
-            CategoryTagsState::default()
+        Err(std::fs::TryLockError::Error(e)) => {
+            Err(format!("Could not lock {}: {}", path.display(), e))
         }
     }
 }
 
-pub fn save_category_tags(path: &Path, tags_state: &CategoryTagsState) -> Result<(), String> {
-    write_json_atomic(path, tags_state)
-}
-
 pub fn file_exists(path: &Path) -> bool {
     path.exists()
 }
@@ -416,8 +2143,46 @@ pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
     serde_json::from_str(&content).map_err(|e| e.to_string())
 }
 
+/// Top-level keys already in the file that `value` doesn't know about are
+/// kept, so a newer release's fields survive a save by an older one.
 pub fn write_json_atomic<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
-    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    atomic_write(path, &json_keeping_unknown_keys(path, value)?)
+}
+
+/// `value` as pretty JSON, with top-level keys a newer release wrote to
+/// `path` carried over.
+fn json_keeping_unknown_keys<T: Serialize>(path: &Path, value: &T) -> Result<String, String> {
+    let mut json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    if let Ok(serde_json::Value::Object(mut fields)) = serde_json::from_str(&json)
+        && let Ok(serde_json::Value::Object(existing)) = read_json::<serde_json::Value>(path)
+        && existing.keys().any(|key| !fields.contains_key(key))
+    {
+        for (key, field) in existing {
+            fields.entry(key).or_insert(field);
+        }
+        json = serde_json::to_string_pretty(&fields).map_err(|e| e.to_string())?;
+    }
+    Ok(json)
+}
+
+/// Sets top-level string settings in `config.json` and leaves everything
+/// else in the file as written. A missing file starts from the current
+/// version; one that doesn't parse is left alone.
+pub fn update_config(path: &Path, settings: &BTreeMap<String, String>) -> Result<(), String> {
+    let mut fields = if path.exists() {
+        match read_json::<serde_json::Value>(path)? {
+            serde_json::Value::Object(fields) => fields,
+            _ => return Err("config is not a JSON object".to_string()),
+        }
+    } else {
+        let mut fields = serde_json::Map::new();
+        fields.insert("version".to_string(), ConfigState::VERSION.into());
+        fields
+    };
+    for (key, value) in settings {
+        fields.insert(key.clone(), value.clone().into());
+    }
+    let json = serde_json::to_string_pretty(&fields).map_err(|e| e.to_string())?;
     atomic_write(path, &json)
 }
 
@@ -468,18 +2233,127 @@ pub fn create_backup(path: &Path) -> Result<(), String> {
     Ok(())
 }
 
+#[derive(Debug)]
+pub struct UndoCandidate {
+    pub backup_path: PathBuf,
+    pub timestamp: String,
+    superseded: Vec<PathBuf>,
+}
+
+fn backup_timestamp(path: &Path, backup_path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_string_lossy().to_string();
+    let backup_name = backup_path.file_name()?.to_string_lossy().to_string();
+    backup_name
+        .strip_prefix(&format!("{}.", file_name))
+        .map(ToString::to_string)
+}
+
+pub fn list_backups(path: &Path) -> Vec<PathBuf> {
+    let backup_dir = path.parent().unwrap_or(Path::new(".")).join("backups");
+    let Ok(entries) = fs::read_dir(&backup_dir) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<(String, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter_map(|backup| backup_timestamp(path, &backup).map(|stamp| (stamp, backup)))
+        .collect();
+    backups.sort_by(|a, b| b.0.cmp(&a.0));
+    backups.into_iter().map(|(_, backup)| backup).collect()
+}
+
+pub fn find_undo_candidate(path: &Path) -> Option<UndoCandidate> {
+    let current = fs::read(path).unwrap_or_default();
+    let mut superseded = Vec::new();
+
+    for backup_path in list_backups(path) {
+        let Ok(content) = fs::read(&backup_path) else {
+            continue;
+        };
+        if content == current {
+            superseded.push(backup_path);
+            continue;
+        }
+
+        let timestamp = backup_timestamp(path, &backup_path).unwrap_or_default();
+        return Some(UndoCandidate {
+            backup_path,
+            timestamp,
+            superseded,
+        });
+    }
+
+    None
+}
+
+pub fn apply_undo(path: &Path, candidate: &UndoCandidate) -> Result<(), String> {
+    let content = fs::read_to_string(&candidate.backup_path).map_err(|e| e.to_string())?;
+    write_without_backup(path, &content)?;
+
+    for backup_path in candidate
+        .superseded
+        .iter()
+        .chain(std::iter::once(&candidate.backup_path))
+    {
+        let _ = fs::remove_file(backup_path);
+    }
+
+    Ok(())
+}
+
+/// Replaces `path` with `content`, keeping a backup of what was there. An
+/// unchanged file is left alone, so periodic saves don't rotate the last
+/// real change out of the backups `strata undo` restores from.
 pub fn atomic_write(path: &Path, content: &str) -> Result<(), String> {
     if path.exists() {
+        if fs::read(path).is_ok_and(|current| current == content.as_bytes()) {
+            return Ok(());
+        }
         create_backup(path)?;
     }
 
-    let tmp_path = path.with_extension("tmp");
-    let mut tmp_file = File::create(&tmp_path).map_err(|e| e.to_string())?;
+    write_without_backup(path, content)
+}
+
+/// Writes a uniquely named temp file next to `path` and renames it over
+/// `path`, so a crash leaves either the old or the new content.
+fn write_without_backup(path: &Path, content: &str) -> Result<(), String> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut tmp_file = tempfile::Builder::new()
+        .prefix(&format!(".{}.", file_name))
+        .suffix(".tmp")
+        .tempfile_in(dir)
+        .map_err(|e| e.to_string())?;
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(tmp_file.path(), metadata.permissions()).map_err(|e| e.to_string())?;
+    }
     tmp_file
         .write_all(content.as_bytes())
         .map_err(|e| e.to_string())?;
-    tmp_file.sync_all().map_err(|e| e.to_string())?;
-    fs::rename(&tmp_path, path).map_err(|e| e.to_string())?;
+    tmp_file.as_file().sync_all().map_err(|e| e.to_string())?;
+    tmp_file.persist(path).map_err(|e| e.error.to_string())?;
+    sync_dir(dir)
+}
+
+/// Makes the rename itself durable.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> Result<(), String> {
+    File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .map_err(|e| e.to_string())
+}
+
+/// Windows can't open a directory to sync it.
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> Result<(), String> {
     Ok(())
 }
 
@@ -487,6 +2361,7 @@ pub fn atomic_write(path: &Path, content: &str) -> Result<(), String> {
 mod tests {
     use std::{fs, path::PathBuf, time::SystemTime};
 
+    use chrono::TimeZone;
     use serde::{Deserialize, Serialize};
 
     use super::*;
@@ -499,6 +2374,82 @@ mod tests {
         PathBuf::from(format!("/tmp/{}_{}.{}", prefix, now, extension))
     }
 
+    #[test]
+    fn test_render_export_filename_fills_placeholders() {
+        assert_eq!(
+            render_export_filename(DEFAULT_EXPORT_TEMPLATE, "2026-W09", "2026-02-25", "json"),
+            "strata-2026-W09-2026-02-25.json"
+        );
+        assert_eq!(
+            render_export_filename("{date}/{period}.{ext}", "all", "2026-02-25", "ics"),
+            "2026-02-25_all.ics"
+        );
+    }
+
+    #[test]
+    fn test_instance_lock_rejects_live_holder_and_replaces_stale_one() {
+        let path = unique_path("strata_lock", "lock");
+
+        let lock = acquire_instance_lock(&path).unwrap();
+        let err = acquire_instance_lock(&path).unwrap_err();
+        assert!(err.contains(&format!("PID {}", std::process::id())));
+
+        // A holder that hasn't written its PID yet still holds the lock.
+        fs::write(&path, "").unwrap();
+        assert!(acquire_instance_lock(&path).is_err());
+        drop(lock);
+
+        fs::write(&path, format!("{}\n", u32::MAX)).unwrap();
+        let lock = acquire_instance_lock(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().trim(),
+            std::process::id().to_string()
+        );
+        drop(lock);
+    }
+
+    #[test]
+    fn test_focus_contract_start_break_and_completion() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let mut state = FocusContractState::default();
+        state.start(3, now, 50 * 60);
+
+        let contract = state.active_at(now).unwrap();
+        assert_eq!(contract.category_id, 3);
+        assert_eq!(contract.ends_at, now + chrono::Duration::minutes(50));
+        assert!(!state.expire(now + chrono::Duration::minutes(49)));
+
+        let broken_at = now + chrono::Duration::minutes(10);
+        assert!(state.finish(Some(broken_at)));
+        assert!(state.active.is_none());
+        assert_eq!(state.history[0].broken_at, Some(broken_at));
+        assert!(!state.finish(None));
+
+        state.start(3, now, 60);
+        let end = now + chrono::Duration::seconds(60);
+        assert!(state.active_at(end).is_none());
+        assert!(state.expire(end));
+        assert_eq!(state.history.len(), 2);
+        assert_eq!(state.history[1].broken_at, None);
+    }
+
+    #[test]
+    fn test_focus_contract_reloads_from_disk() {
+        let path = unique_path("strata_focus_contract", "json");
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let mut state = FocusContractState::default();
+        state.start(2, now, 60);
+        state.finish(Some(now));
+        state.start(3, now, 25 * 60);
+
+        save_focus_contract(&path, &state).unwrap();
+        assert_eq!(load_focus_contract(&path), state);
+
+        fs::write(&path, r#"{"version": 99, "active": null, "history": []}"#).unwrap();
+        assert_eq!(load_focus_contract(&path), FocusContractState::default());
+        let _ = fs::remove_file(&path);
+    }
+
     #[test]
     fn test_categories_round_trip() {
         let path = unique_path("strata_categories_roundtrip", "csv");
@@ -508,6 +2459,7 @@ mod tests {
                 name: "none".to_string(),
                 color: Color::White,
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
             Category {
@@ -515,6 +2467,7 @@ mod tests {
                 name: "Work".to_string(),
                 color: COLORS[0],
                 description: "focus, deep work".to_string(),
+                default_description: "standup".to_string(),
                 karma_effect: 1,
             },
         ];
@@ -526,6 +2479,32 @@ mod tests {
         assert_eq!(loaded.categories[1].id, CategoryId::new(1));
         assert_eq!(loaded.categories[1].name, "Work");
         assert_eq!(loaded.categories[1].description, "focus, deep work");
+        assert_eq!(loaded.categories[1].default_description, "standup");
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_load_categories_accepts_legacy_header() {
+        let path = unique_path("strata_categories_legacy", "csv");
+        fs::write(
+            &path,
+            "id,name,description,color_index,karma_effect\n1,Work,,0,-1\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            categories_header_status(&path).unwrap(),
+            CsvHeaderStatus::Legacy
+        );
+        assert_eq!(
+            categories_added_columns(&path),
+            vec!["default_description", "order"]
+        );
+        let loaded = try_load_categories_from_csv(&path).expect("legacy schema loads");
+        assert_eq!(loaded.categories.len(), 2);
+        assert_eq!(loaded.categories[1].karma_effect, -1);
+        assert_eq!(loaded.categories[1].default_description, "");
 
         fs::remove_file(path).ok();
     }
@@ -539,6 +2518,7 @@ mod tests {
                 name: "none".to_string(),
                 color: Color::White,
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
             Category {
@@ -546,6 +2526,7 @@ mod tests {
                 name: "DeepWork".to_string(),
                 color: COLORS[1],
                 description: String::new(),
+                default_description: String::new(),
                 karma_effect: 1,
             },
         ];
@@ -554,9 +2535,11 @@ mod tests {
             date: "2026-02-25".to_string(),
             category_id: CategoryId::new(2),
             description: "plan, review".to_string(),
+            project: "strata".to_string(),
             start_time: "10:00:00".to_string(),
             end_time: "11:00:00".to_string(),
             elapsed_seconds: 3600,
+            focus: Some(4),
         }];
 
         save_sessions_to_csv(&path, &sessions, &categories).unwrap();
@@ -567,10 +2550,195 @@ mod tests {
         assert_eq!(loaded.sessions[0].category_id, CategoryId::new(2));
         assert_eq!(loaded.sessions[0].elapsed_seconds, 3600);
         assert_eq!(loaded.sessions[0].description, "plan, review");
+        assert_eq!(loaded.sessions[0].project, "strata");
+        assert_eq!(loaded.sessions[0].focus, Some(4));
 
         fs::remove_file(path).ok();
     }
 
+    #[test]
+    fn test_trash_under_an_older_header_is_migrated_before_appending() {
+        let path = unique_path("strata_trash_migrate", "csv");
+        fs::write(
+            &path,
+            format!(
+                "{}\n1,2026-02-24,0,none,idle,09:00:00,09:30:00,1800\n",
+                LEGACY_SESSIONS_HEADER.join(",")
+            ),
+        )
+        .unwrap();
+        let categories = vec![Category {
+            id: CategoryId::new(0),
+            name: "none".to_string(),
+            color: Color::White,
+            description: String::new(),
+            default_description: String::new(),
+            karma_effect: 1,
+        }];
+        let removed = Session {
+            id: 2,
+            date: "2026-02-25".to_string(),
+            category_id: CategoryId::new(0),
+            description: "break".to_string(),
+            project: "strata".to_string(),
+            start_time: "10:00:00".to_string(),
+            end_time: "10:15:00".to_string(),
+            elapsed_seconds: 900,
+            focus: Some(3),
+        };
+
+        append_sessions_to_trash(&path, &[removed], &categories).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], SESSIONS_HEADER.join(","));
+        assert_eq!(
+            lines[1],
+            "1,2026-02-24,0,none,idle,09:00:00,09:30:00,1800,,"
+        );
+        assert_eq!(lines.len(), 3);
+        let loaded = load_sessions_from_csv(&path, &categories);
+        assert_eq!(loaded.sessions.len(), 2);
+        assert_eq!(loaded.sessions[1].focus, Some(3));
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_trash_with_an_unknown_header_is_moved_aside() {
+        let path = unique_path("strata_trash_rotate", "csv");
+        fs::write(&path, "when,what\nyesterday,something\n").unwrap();
+
+        append_sessions_to_trash(&path, &[], &[]).unwrap();
+
+        let aside = rotated_csv_path(&path, 1);
+        assert_eq!(
+            fs::read_to_string(&aside).unwrap(),
+            "when,what\nyesterday,something\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().trim_end(),
+            SESSIONS_HEADER.join(",")
+        );
+
+        fs::remove_file(&path).ok();
+        fs::remove_file(&aside).ok();
+    }
+
+    #[test]
+    fn test_sand_state_saves_leave_no_backups() {
+        let dir = unique_path("strata_sand_save", "d");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sand_state.json");
+        let mut engine = crate::sand::SandEngine::new(10, 10);
+        for _ in 0..3 {
+            engine.spawn(CategoryId::new(1));
+            save_sand_state(&path, &engine.snapshot_state()).unwrap();
+        }
+
+        assert!(list_backups(&path).is_empty());
+        assert_eq!(
+            load_sand_state(&path).map(|state| state.grains.len()),
+            Some(3)
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_undo_survives_repeated_saves_of_unchanged_content() {
+        let dir = unique_path("strata_undo_autosave", "d");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("time_log.csv");
+        atomic_write(&path, "before\n").unwrap();
+        atomic_write(&path, "after\n").unwrap();
+        for _ in 0..12 {
+            atomic_write(&path, "after\n").unwrap();
+        }
+        assert_eq!(list_backups(&path).len(), 1);
+
+        let candidate = find_undo_candidate(&path).expect("the change is still undoable");
+        apply_undo(&path, &candidate).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "before\n");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_profiles_are_created_listed_and_switched_under_a_base_dir() {
+        let base = unique_path("strata_profiles", "d");
+        let state_path = base.join("profiles.json");
+
+        assert_eq!(profiles_in(&base), [DEFAULT_PROFILE]);
+        create_profile_in(&base, "work").unwrap();
+        create_profile_in(&base, "client-a").unwrap();
+        assert!(base.join("profiles").join("work").is_dir());
+        assert_eq!(profiles_in(&base), ["default", "client-a", "work"]);
+        assert!(create_profile_in(&base, "work").is_err());
+        assert!(create_profile_in(&base, "../escape").is_err());
+
+        assert!(switch_profile_in(&base, &state_path, "missing").is_err());
+        assert_eq!(load_profiles_state(&state_path).current, DEFAULT_PROFILE);
+        switch_profile_in(&base, &state_path, "work").unwrap();
+        assert_eq!(load_profiles_state(&state_path).current, "work");
+
+        assert_eq!(
+            named_profile_dir(base.clone(), "work"),
+            base.join("profiles").join("work")
+        );
+        assert_eq!(named_profile_dir(base.clone(), DEFAULT_PROFILE), base);
+
+        fs::remove_dir_all(&base).ok();
+    }
+
+    #[test]
+    fn test_data_dir_flag_beats_env_which_beats_the_default() {
+        let flag = PathBuf::from("/tmp/strata-flag");
+        let env = PathBuf::from("/tmp/strata-env");
+
+        assert_eq!(
+            pick_data_dir_override(Some(flag.clone()), || Some(env.clone())),
+            Some(flag.clone())
+        );
+        assert_eq!(
+            pick_data_dir_override(None, || Some(env.clone())),
+            Some(env.clone())
+        );
+        assert_eq!(pick_data_dir_override(None, || None), None);
+
+        let defaults = || {
+            resolve_dirs(
+                pick_data_dir_override(None, || None),
+                || PathBuf::from("/home/me/.local/share/strata"),
+                || PathBuf::from("/home/me/.local/state/strata"),
+            )
+        };
+        assert_eq!(
+            defaults(),
+            (
+                PathBuf::from("/home/me/.local/share/strata"),
+                PathBuf::from("/home/me/.local/state/strata")
+            )
+        );
+
+        let (data_dir, state_dir) = resolve_dirs(
+            pick_data_dir_override(Some(flag.clone()), || Some(env.clone())),
+            || unreachable!("the default data dir isn't consulted"),
+            || unreachable!("the default state dir isn't consulted"),
+        );
+        assert_eq!(data_dir, flag);
+        assert_eq!(state_dir, flag.join("state"));
+    }
+
+    #[test]
+    fn test_a_profile_is_rejected_with_a_data_dir_override() {
+        let dir = Path::new("/tmp/strata-data");
+        assert!(check_profile_with_data_dir(Some(dir), None).is_ok());
+        assert!(check_profile_with_data_dir(None, Some("work")).is_ok());
+        let err = check_profile_with_data_dir(Some(dir), Some("work")).unwrap_err();
+        assert!(err.contains("'work'") && err.contains("/tmp/strata-data"));
+    }
+
     #[derive(Debug, Serialize, Deserialize, PartialEq)]
     struct TestJsonValue {
         name: String,
@@ -612,6 +2780,11 @@ mod tests {
                     category_id: 0,
                 },
             ],
+            day: NaiveDate::from_ymd_opt(2026, 3, 2),
+            layers: vec![crate::sand::SandLayer {
+                day: NaiveDate::from_ymd_opt(2026, 3, 1),
+                counts: vec![(3, 40), (0, 5)],
+            }],
         };
 
         save_sand_state(&path, &state).unwrap();
@@ -622,6 +2795,31 @@ mod tests {
         assert!(!path.exists());
     }
 
+    #[test]
+    fn test_grain_log_append_rotate_and_load() {
+        let dir = unique_path("strata_grain_log", "d");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("grain_log.csv");
+        let event = GrainEvent {
+            timestamp: Utc::now(),
+            category_id: 2,
+            x: 17,
+            grid_width: 160,
+        };
+
+        append_grain_event(&path, &event).unwrap();
+        rotate_grain_log(&path).unwrap();
+        append_grain_event(&path, &event).unwrap();
+
+        assert!(rotated_csv_path(&path, 1).exists());
+        let loaded = load_grain_events(&path);
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].x, 17);
+        assert_eq!(loaded[1].category_id, 2);
+
+        fs::remove_dir_all(dir).ok();
+    }
+
     #[test]
     fn test_category_tags_round_trip() {
         let path = unique_path("strata_category_tags_roundtrip", "json");
@@ -637,10 +2835,101 @@ mod tests {
         let loaded = load_category_tags(&path);
         assert_eq!(loaded, state);
 
+        let mut state = loaded;
+        let id = CategoryId::new(2);
+        assert!(state.add_tag(id, " deep work "));
+        assert!(!state.add_tag(id, "  "));
+        assert_eq!(state.tags(id), ["deep work", "focus"]);
+        assert_eq!(state.remove_tag(id, 1).as_deref(), Some("focus"));
+        assert_eq!(state.remove_tag(id, 1), None);
+        assert_eq!(state.remove_tag(id, 0).as_deref(), Some("deep work"));
+        assert!(!state.tags_by_category.contains_key(&2));
+        assert!(state.tags(id).is_empty());
+
         delete_file_if_exists(&path).unwrap();
         assert!(!path.exists());
     }
 
+    #[test]
+    fn test_category_groups_order_collapse_and_round_trip() {
+        let path = unique_path("strata_category_groups_roundtrip", "json");
+        let category = |id: u64, name: &str| Category {
+            id: CategoryId::new(id),
+            name: name.to_string(),
+            color: Color::Reset,
+            description: String::new(),
+            default_description: String::new(),
+            karma_effect: 1,
+        };
+        let categories = vec![
+            category(0, "none"),
+            category(1, "Gym"),
+            category(2, "Code"),
+            category(3, "Run"),
+        ];
+        let mut state = CategoryGroupsState::default();
+        state.set_group(CategoryId::new(1), " Health ");
+        state.set_group(CategoryId::new(2), "Work");
+        state.set_group(CategoryId::new(3), "Health");
+        assert_eq!(state.ordered_groups(&categories), vec!["Health", "Work"]);
+        assert_eq!(state.group_of(CategoryId::new(1)), Some("Health"));
+
+        assert!(state.set_collapsed("Work", true));
+        assert!(!state.set_collapsed("Work", true));
+        save_category_groups(&path, &state).unwrap();
+        assert_eq!(load_category_groups(&path), state);
+
+        state.set_group(CategoryId::new(2), "");
+        assert_eq!(state.group_of(CategoryId::new(2)), None);
+        assert!(!state.is_collapsed("Work"));
+
+        delete_file_if_exists(&path).unwrap();
+    }
+
+    #[test]
+    fn test_plan_round_trip_and_day_rollover() {
+        let path = unique_path("strata_plan_roundtrip", "json");
+        let day = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let mut state = load_plan(&path, day);
+        assert_eq!(state.day, Some(day));
+        state.items.push(PlanItem {
+            category_id: 1,
+            description: "write spec".to_string(),
+            planned_seconds: Some(1500),
+            starts_at: NaiveTime::from_hms_opt(9, 30, 0),
+            actual_seconds: None,
+        });
+
+        save_plan(&path, &state).unwrap();
+        assert_eq!(load_plan(&path, day), state);
+        assert!(load_plan(&path, day.succ_opt().unwrap()).items.is_empty());
+
+        delete_file_if_exists(&path).unwrap();
+    }
+
+    #[test]
+    fn test_undo_restores_latest_differing_backup() {
+        let dir = unique_path("strata_undo", "d");
+        fs::create_dir_all(dir.join("backups")).unwrap();
+        let path = dir.join("time_log.csv");
+        fs::write(&path, "current").unwrap();
+        fs::write(dir.join("backups/time_log.csv.20260101_000000"), "older").unwrap();
+        fs::write(dir.join("backups/time_log.csv.20260102_000000"), "previous").unwrap();
+        fs::write(dir.join("backups/time_log.csv.20260103_000000"), "current").unwrap();
+
+        let candidate = find_undo_candidate(&path).expect("candidate");
+        assert_eq!(candidate.timestamp, "20260102_000000");
+
+        apply_undo(&path, &candidate).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "previous");
+        assert_eq!(list_backups(&path).len(), 1);
+
+        let candidate = find_undo_candidate(&path).expect("older candidate");
+        assert_eq!(candidate.timestamp, "20260101_000000");
+
+        fs::remove_dir_all(dir).ok();
+    }
+
     #[test]
     fn test_try_load_categories_invalid_schema_returns_error() {
         let path = unique_path("strata_categories_invalid_schema", "csv");
@@ -652,6 +2941,206 @@ mod tests {
         fs::remove_file(path).ok();
     }
 
+    #[test]
+    fn test_load_categories_tolerates_bom_case_and_translated_headers() {
+        let path = unique_path("strata_categories_localized", "csv");
+        fs::write(
+            &path,
+            "\u{feff}ID, Name ,description,Color,karma_effect,default_description,Order\n1,Work,,0,1,,0\n",
+        )
+        .unwrap();
+        assert_eq!(
+            categories_header_status(&path).unwrap(),
+            CsvHeaderStatus::Recognized
+        );
+        assert_eq!(
+            try_load_categories_from_csv(&path)
+                .unwrap()
+                .categories
+                .len(),
+            2
+        );
+
+        fs::write(
+            &path,
+            "Kennung,Name,Beschreibung,Farbe,Karma\n1,Arbeit,,0,-1\n",
+        )
+        .unwrap();
+        assert_eq!(
+            categories_header_status(&path).unwrap(),
+            CsvHeaderStatus::Positional
+        );
+        let loaded = try_load_categories_from_csv(&path).expect("positional schema loads");
+        assert_eq!(loaded.categories[1].name, "Arbeit");
+        assert_eq!(loaded.categories[1].karma_effect, -1);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_category_order_column_overrides_row_order() {
+        let path = unique_path("strata_categories_order", "csv");
+        fs::write(
+            &path,
+            "id,name,description,color_index,karma_effect,default_description,order\n\
+             1,Alpha,,0,1,,2\n\
+             2,Beta,,1,1,,0\n\
+             3,Gamma,,2,1,,1\n\
+             4,Delta,,3,1,,1\n",
+        )
+        .unwrap();
+
+        let categories = try_load_categories_from_csv(&path).unwrap().categories;
+        let names: Vec<&str> = categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["none", "Beta", "Gamma", "Delta", "Alpha"]);
+
+        save_categories_to_csv(&path, &categories).unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(
+            saved.contains("2,Beta,,#80ff00,1,,0\n3,Gamma,,#ffff00,1,,1\n4,Delta,,#ffcc00,1,,2\n1,Alpha,,#00b050,1,,3\n")
+        );
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_category_colors_round_trip_as_hex_and_read_old_indexes() {
+        let path = unique_path("strata_categories_hex", "csv");
+        fs::write(
+            &path,
+            "id,name,description,color_index,karma_effect,default_description,order\n\
+             1,Work,,2,1,,0\n\
+             2,Play,,#123abc,1,,1\n",
+        )
+        .unwrap();
+        assert_eq!(
+            categories_header_status(&path).unwrap(),
+            CsvHeaderStatus::Legacy
+        );
+
+        let categories = try_load_categories_from_csv(&path).unwrap().categories;
+        assert_eq!(categories[1].color, COLORS[2]);
+        assert_eq!(categories[2].color, Color::Rgb(0x12, 0x3a, 0xbc));
+
+        save_categories_to_csv(&path, &categories).unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(saved.starts_with("id,name,description,color,"));
+        assert!(saved.contains("1,Work,,#ffff00,1,,0\n2,Play,,#123abc,1,,1\n"));
+        assert_eq!(
+            try_load_categories_from_csv(&path).unwrap().categories,
+            categories
+        );
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_unknown_trailing_columns_survive_a_save() {
+        let categories_path = unique_path("strata_categories_future_columns", "csv");
+        let sessions_path = unique_path("strata_sessions_future_columns", "csv");
+        fs::write(
+            &categories_path,
+            "id,name,description,color_index,karma_effect,default_description,order,icon\n1,Work,,0,1,,0,laptop\n",
+        )
+        .unwrap();
+        fs::write(
+            &sessions_path,
+            "id,date,category_id,category_name,description,start_time,end_time,elapsed_seconds,project,focus,billable,ticket\n\
+             1,2026-02-25,1,Work,a,09:00:00,10:00:00,3600,,,yes,T-1\n\
+             2,2026-02-25,1,Work,b,10:00:00,11:00:00,3600,,,no,\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            sessions_header_status(&sessions_path).unwrap(),
+            CsvHeaderStatus::Recognized
+        );
+        let mut categories = try_load_categories_from_csv(&categories_path)
+            .unwrap()
+            .categories;
+        let mut sessions = try_load_sessions_from_csv(&sessions_path, &categories)
+            .unwrap()
+            .sessions;
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].description, "a");
+
+        categories[1].name = "Deep work".to_string();
+        sessions[0].description = "edited".to_string();
+        let mut new_session = sessions[1].clone();
+        new_session.id = 3;
+        sessions.remove(1);
+        sessions.push(new_session);
+        save_categories_to_csv(&categories_path, &categories).unwrap();
+        save_sessions_to_csv(&sessions_path, &sessions, &categories).unwrap();
+
+        let saved_categories = fs::read_to_string(&categories_path).unwrap();
+        assert!(saved_categories.starts_with(
+            "id,name,description,color,karma_effect,default_description,order,icon\n"
+        ));
+        assert!(saved_categories.contains("1,Deep work,,#00b050,1,,0,laptop\n"));
+
+        let saved_sessions = fs::read_to_string(&sessions_path).unwrap();
+        let lines: Vec<&str> = saved_sessions.lines().collect();
+        assert!(lines[0].ends_with(",focus,billable,ticket"));
+        assert!(
+            lines[1].starts_with("1,")
+                && lines[1].ends_with(",edited,09:00:00,10:00:00,3600,,,yes,T-1")
+        );
+        assert!(lines[2].starts_with("3,") && lines[2].ends_with(",,,,"));
+
+        fs::remove_file(categories_path).ok();
+        fs::remove_file(sessions_path).ok();
+    }
+
+    #[test]
+    fn test_atomic_writes_to_files_sharing_a_stem_leave_no_temp_files() {
+        let dir = unique_path("strata_atomic_write", "d");
+        fs::create_dir_all(&dir).unwrap();
+        let json = dir.join("plan.json");
+        let csv = dir.join("plan.csv");
+        let archive = dir.join("plan.tar.gz");
+
+        write_without_backup(&json, "{}").unwrap();
+        write_without_backup(&csv, "a,b\n").unwrap();
+        write_without_backup(&archive, "packed").unwrap();
+        write_without_backup(&archive, "repacked").unwrap();
+
+        assert_eq!(fs::read_to_string(&json).unwrap(), "{}");
+        assert_eq!(fs::read_to_string(&csv).unwrap(), "a,b\n");
+        assert_eq!(fs::read_to_string(&archive).unwrap(), "repacked");
+        let mut names: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["plan.csv", "plan.json", "plan.tar.gz"]);
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_write_json_keeps_unknown_top_level_fields() {
+        let path = unique_path("strata_json_future_fields", "json");
+        fs::write(
+            &path,
+            r#"{"version":1,"day":null,"items":[],"shared_with":["a"]}"#,
+        )
+        .unwrap();
+
+        let state = PlanState {
+            day: NaiveDate::from_ymd_opt(2026, 2, 25),
+            ..PlanState::default()
+        };
+        write_json_atomic(&path, &state).unwrap();
+
+        let saved: serde_json::Value = read_json(&path).unwrap();
+        assert_eq!(saved["day"], "2026-02-25");
+        assert_eq!(saved["shared_with"][0], "a");
+        assert_eq!(read_json::<PlanState>(&path).unwrap(), state);
+
+        fs::remove_file(path).ok();
+    }
+
     #[test]
     fn test_try_load_sessions_invalid_schema_returns_error() {
         let path = unique_path("strata_sessions_invalid_schema", "csv");
@@ -664,4 +3153,169 @@ mod tests {
 
         fs::remove_file(path).ok();
     }
+
+    #[test]
+    fn test_load_theme_configs_reads_files_and_lets_config_win() {
+        let dir = unique_path("strata_themes", "d");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("night.json"),
+            r##"{"palette":["#112233"],"text":"gray"}"##,
+        )
+        .unwrap();
+        fs::write(dir.join("dusk.json"), r#"{"border":"blue"}"#).unwrap();
+        fs::write(dir.join("notes.txt"), "not a theme").unwrap();
+
+        let mut config = ConfigState::default();
+        config.themes.insert(
+            "dusk".to_string(),
+            ThemeConfig {
+                border: Some("red".to_string()),
+                ..ThemeConfig::default()
+            },
+        );
+
+        let themes = load_theme_configs(&dir, &config);
+        assert_eq!(themes.keys().collect::<Vec<_>>(), ["dusk", "night"]);
+        assert_eq!(themes["night"].palette, ["#112233"]);
+        assert_eq!(themes["dusk"].border.as_deref(), Some("red"));
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_notifications_config_defaults_and_idle_none_duration() {
+        let path = unique_path("strata_notifications_config", "json");
+        fs::write(
+            &path,
+            r#"{"version":1,"notifications":{"goals":true,"idle_none":"30m"}}"#,
+        )
+        .unwrap();
+
+        let notifications = load_config(&path).notifications;
+        assert!(notifications.goals);
+        assert!(!notifications.budgets);
+        assert!(notifications.pomodoro);
+        assert_eq!(idle_none_reminder_seconds(&notifications), Some(1800));
+        assert_eq!(
+            idle_none_reminder_seconds(&NotificationsConfig::default()),
+            None
+        );
+
+        delete_file_if_exists(&path).unwrap();
+    }
+
+    #[test]
+    fn test_idle_stop_is_off_unless_configured() {
+        let path = unique_path("strata_idle_stop_config", "json");
+        fs::write(&path, r#"{"version":1,"idle_stop":"10m"}"#).unwrap();
+
+        assert_eq!(idle_stop_seconds(&load_config(&path)), Some(600));
+        assert_eq!(idle_stop_seconds(&ConfigState::default()), None);
+        let off = ConfigState {
+            idle_stop: Some("0".to_string()),
+            ..ConfigState::default()
+        };
+        assert_eq!(idle_stop_seconds(&off), None);
+
+        delete_file_if_exists(&path).unwrap();
+    }
+
+    #[test]
+    fn test_update_config_sets_keys_and_keeps_the_rest() {
+        let path = unique_path("strata_update_config", "json");
+        let edits = BTreeMap::from([("theme".to_string(), "pastel".to_string())]);
+        update_config(&path, &edits).unwrap();
+        assert_eq!(load_config(&path).theme.as_deref(), Some("pastel"));
+
+        fs::write(
+            &path,
+            r#"{"version":1,"week_start":"sunday","future":{"x":1}}"#,
+        )
+        .unwrap();
+        let edits = BTreeMap::from([
+            ("sand_speed".to_string(), "fast".to_string()),
+            ("time_format".to_string(), "hh:mm".to_string()),
+            ("autosave".to_string(), "5m".to_string()),
+        ]);
+        update_config(&path, &edits).unwrap();
+        let config = load_config(&path);
+        assert_eq!(config.week_start.as_deref(), Some("sunday"));
+        assert_eq!(sand_speed_setting(&config), TIME_SETTINGS.physics_ms / 2);
+        assert!(!time_format_seconds(&config));
+        assert_eq!(autosave_seconds(&config), 300);
+        let raw: serde_json::Value = read_json(&path).unwrap();
+        assert_eq!(raw["future"]["x"], 1);
+
+        fs::write(&path, "{not json").unwrap();
+        assert!(update_config(&path, &edits).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{not json");
+
+        let defaults = ConfigState::default();
+        assert_eq!(sand_speed_setting(&defaults), TIME_SETTINGS.physics_ms);
+        assert!(time_format_seconds(&defaults));
+        assert_eq!(autosave_seconds(&defaults), 60);
+
+        delete_file_if_exists(&path).unwrap();
+    }
+
+    #[test]
+    fn test_stray_local_sessions_only_outside_the_data_dir() {
+        let cwd = unique_path("strata_stray_cwd", "d");
+        let data_dir = unique_path("strata_stray_data", "d");
+        fs::create_dir_all(&cwd).unwrap();
+        fs::create_dir_all(&data_dir).unwrap();
+        assert_eq!(stray_local_sessions(&data_dir, &cwd), None);
+
+        fs::write(cwd.join("time_log.csv"), "").unwrap();
+        assert_eq!(
+            stray_local_sessions(&data_dir, &cwd),
+            Some(cwd.join("time_log.csv"))
+        );
+        assert_eq!(stray_local_sessions(&cwd, &cwd), None);
+        assert_eq!(stray_local_sessions(&cwd.join("."), &cwd), None);
+
+        fs::remove_dir_all(&cwd).unwrap();
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[test]
+    fn test_sync_goal_ledger_starts_at_the_current_week_then_closes_past_ones() {
+        let path = unique_path("strata_goal_ledger", "json");
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let sessions = vec![Session {
+            id: 1,
+            date: "2026-03-03".to_string(),
+            category_id: CategoryId::new(1),
+            description: String::new(),
+            project: String::new(),
+            start_time: "09:00:00".to_string(),
+            end_time: "10:00:00".to_string(),
+            elapsed_seconds: 3600,
+            focus: None,
+        }];
+        let goals = HashMap::from([(CategoryId::new(1), 3 * 3600)]);
+        let rules = CarryOverRules::default();
+
+        let entries = sync_goal_ledger(&path, &sessions, &goals, &rules, day(2)).unwrap();
+        assert!(entries.is_empty());
+        assert_eq!(
+            load_goal_ledger(&path).open_week.as_deref(),
+            Some("2026-03-02")
+        );
+
+        let entries = sync_goal_ledger(&path, &sessions, &goals, &rules, day(16)).unwrap();
+        let carried: Vec<(NaiveDate, i64)> = entries
+            .iter()
+            .map(|entry| (entry.week_start, entry.carried_out))
+            .collect();
+        assert_eq!(carried, vec![(day(2), 2 * 3600), (day(9), 5 * 3600)]);
+        assert_eq!(load_goal_ledger(&path).entries(), entries);
+        assert_eq!(
+            sync_goal_ledger(&path, &[], &goals, &rules, day(16)).unwrap(),
+            entries
+        );
+
+        fs::remove_file(path).ok();
+    }
 }